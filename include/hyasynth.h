@@ -6,6 +6,7 @@
 #ifndef HYASYNTH_H
 #define HYASYNTH_H
 
+#include <stddef.h>
 #include <stdint.h>
 #include <stdbool.h>
 
@@ -43,6 +44,58 @@ typedef struct {
     bool running;
 } HyasynthReadback;
 
+/// Outcome of a graph mutation.
+typedef enum {
+    HYA_RESULT_OK = 0,
+    /// A node ID doesn't exist in the graph.
+    HYA_RESULT_NODE_NOT_FOUND = 1,
+    /// A port isn't declared by the node's type.
+    HYA_RESULT_INVALID_PORT = 2,
+    /// The connection would feed a node's output back into itself.
+    HYA_RESULT_CYCLE_DETECTED = 3,
+    /// The session handle is NULL.
+    HYA_RESULT_NULL_HANDLE = 4,
+} HyaResult;
+
+/// Node type info for UI display.
+/// name and category are owned by the caller; free each with hya_string_free.
+typedef struct {
+    uint32_t type_id;
+    const char* name;
+    const char* category;
+    uint32_t num_inputs;
+    uint32_t num_outputs;
+    uint32_t num_params;
+} HyaNodeTypeInfo;
+
+/// Parameter info for UI controls.
+/// name is owned by the caller; free it with hya_string_free.
+typedef struct {
+    uint32_t id;
+    const char* name;
+    float min_value;
+    float max_value;
+    float default_value;
+} HyaParamInfo;
+
+/// One connection between two node ports.
+typedef struct {
+    uint32_t source_node;
+    uint32_t source_port;
+    uint32_t dest_node;
+    uint32_t dest_port;
+} HyaConnection;
+
+/// One problem found when validating the graph.
+/// kind: 0 = missing output, 1 = node_id can't reach the output,
+/// 2 = connection from node_id to dest_node uses a missing node.
+/// Unused IDs are UINT32_MAX.
+typedef struct {
+    uint32_t kind;
+    uint32_t node_id;
+    uint32_t dest_node;
+} HyaGraphIssue;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Node Type Constants
 // ═══════════════════════════════════════════════════════════════════════════
@@ -55,24 +108,37 @@ extern const uint32_t NODE_TRIANGLE_OSC;
 
 // Envelopes
 extern const uint32_t NODE_ADSR_ENV;
+extern const uint32_t NODE_DAHDSR_ENV;
+extern const uint32_t NODE_FILTER_ENV;
 
 // Effects
 extern const uint32_t NODE_GAIN;
 extern const uint32_t NODE_PAN;
 extern const uint32_t NODE_DELAY;
 extern const uint32_t NODE_REVERB;
+extern const uint32_t NODE_DISTORTION;
+extern const uint32_t NODE_LIMITER;
+extern const uint32_t NODE_EQ;
+extern const uint32_t NODE_FLANGER;
+extern const uint32_t NODE_WIDTH;
+extern const uint32_t NODE_RINGMOD;
 
 // Filters
 extern const uint32_t NODE_LOWPASS;
 extern const uint32_t NODE_HIGHPASS;
 extern const uint32_t NODE_BANDPASS;
 extern const uint32_t NODE_NOTCH;
+extern const uint32_t NODE_LADDER;
+extern const uint32_t NODE_ALLPASS;
+extern const uint32_t NODE_COMB;
 
 // Modulators
 extern const uint32_t NODE_LFO;
 
 // Utility
 extern const uint32_t NODE_OUTPUT;
+extern const uint32_t NODE_SLEW;
+extern const uint32_t NODE_DC_BLOCKER;
 
 // ═══════════════════════════════════════════════════════════════════════════
 // Parameter ID Constants
@@ -87,24 +153,47 @@ extern const uint32_t PARAM_ATTACK;
 extern const uint32_t PARAM_DECAY;
 extern const uint32_t PARAM_SUSTAIN;
 extern const uint32_t PARAM_RELEASE;
+extern const uint32_t PARAM_CURVE;
+extern const uint32_t PARAM_ENV_DELAY;
+extern const uint32_t PARAM_HOLD;
+extern const uint32_t PARAM_VELOCITY;
+extern const uint32_t PARAM_ENV_AMOUNT;
 
 // Gain/mixer params
 extern const uint32_t PARAM_GAIN;
 extern const uint32_t PARAM_PAN;
+extern const uint32_t PARAM_AUTO_GAIN;
 
 // Filter params
 extern const uint32_t PARAM_CUTOFF;
 extern const uint32_t PARAM_RESONANCE;
+extern const uint32_t PARAM_KEYTRACK;
 
 // LFO params
 extern const uint32_t PARAM_RATE;
 extern const uint32_t PARAM_DEPTH;
+extern const uint32_t PARAM_PHASE_OFFSET;
+extern const uint32_t PARAM_RETRIGGER;
 
 // Effect params
 extern const uint32_t PARAM_TIME;
 extern const uint32_t PARAM_FEEDBACK;
 extern const uint32_t PARAM_MIX;
+extern const uint32_t PARAM_SYNC_ON;
+extern const uint32_t PARAM_SYNC_DIV;
+extern const uint32_t PARAM_PINGPONG;
+extern const uint32_t PARAM_DELAY_DAMPING;
 extern const uint32_t PARAM_DAMPING;
+extern const uint32_t PARAM_PREDELAY;
+extern const uint32_t PARAM_WIDTH;
+extern const uint32_t PARAM_FREEZE;
+extern const uint32_t PARAM_DRIVE;
+extern const uint32_t PARAM_SHAPE;
+extern const uint32_t PARAM_CEILING;
+extern const uint32_t PARAM_FLANGER_FEEDBACK;
+extern const uint32_t PARAM_RINGMOD_MODE;
+extern const uint32_t PARAM_RISE;
+extern const uint32_t PARAM_FALL;
 
 // ═══════════════════════════════════════════════════════════════════════════
 // Registry Functions
@@ -120,6 +209,24 @@ void registry_destroy(HyasynthRegistry* registry);
 /// Get the number of registered node types.
 uint32_t registry_count(const HyasynthRegistry* registry);
 
+/// Get info for the node type at index (0..registry_count), ordered by type ID.
+/// Returns false if index is out of range. On success the strings in
+/// out_info must be freed with hya_string_free.
+bool registry_get_node_type(const HyasynthRegistry* registry, uint32_t index, HyaNodeTypeInfo* out_info);
+
+/// Get info for parameter param_index (0..num_params) of a node type.
+/// Returns false if the type or parameter doesn't exist. On success
+/// out_info->name must be freed with hya_string_free.
+bool registry_get_param(
+    const HyasynthRegistry* registry,
+    uint32_t type_id,
+    uint32_t param_index,
+    HyaParamInfo* out_info
+);
+
+/// Free a string returned by the engine. NULL is ignored.
+void hya_string_free(const char* s);
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Configuration
 // ═══════════════════════════════════════════════════════════════════════════
@@ -167,6 +274,28 @@ HyasynthSession* session_create_with_config(
     HyasynthEngine** out_engine
 );
 
+/// Serialize a session to a JSON byte buffer for saving.
+/// On success *out_ptr and *out_len describe a buffer owned by the caller,
+/// which must be released with hya_bytes_free. Audio pool entries keep their
+/// metadata but not their samples.
+/// @return false if serialization fails.
+bool session_serialize(const HyasynthSession* session, uint8_t** out_ptr, size_t* out_len);
+
+/// Create a session and engine pair from a buffer written by session_serialize.
+/// Works like session_create_with_config (config may be NULL for defaults).
+/// The engine is asked to recompile, so the first engine_process_commands
+/// returns true.
+/// @return The session handle, or NULL if the buffer isn't a valid project.
+HyasynthSession* session_deserialize(
+    const uint8_t* bytes,
+    size_t len,
+    const HyasynthConfig* config,
+    HyasynthEngine** out_engine
+);
+
+/// Free a buffer returned by session_serialize. NULL is ignored.
+void hya_bytes_free(uint8_t* ptr, size_t len);
+
 /// Destroy a session handle.
 void session_destroy(HyasynthSession* session);
 
@@ -187,11 +316,14 @@ uint32_t session_add_node(
 );
 
 /// Remove a node from the graph.
-void session_remove_node(HyasynthSession* session, uint32_t node_id);
+HyaResult session_remove_node(HyasynthSession* session, uint32_t node_id);
 
 /// Connect two nodes.
-void session_connect(
+/// Ports are checked against the node types in registry; pass NULL to skip
+/// port validation.
+HyaResult session_connect(
     HyasynthSession* session,
+    const HyasynthRegistry* registry,
     uint32_t source_node,
     uint32_t source_port,
     uint32_t dest_node,
@@ -199,7 +331,7 @@ void session_connect(
 );
 
 /// Disconnect two nodes.
-void session_disconnect(
+HyaResult session_disconnect(
     HyasynthSession* session,
     uint32_t source_node,
     uint32_t source_port,
@@ -207,8 +339,26 @@ void session_disconnect(
     uint32_t dest_port
 );
 
+/// Modulate a node parameter from another node's output.
+/// `depth` is in the destination parameter's units per unit of source signal.
+HyaResult session_add_mod_route(
+    HyasynthSession* session,
+    uint32_t source_node,
+    uint32_t dest_node,
+    uint32_t param_id,
+    float depth
+);
+
+/// Remove a modulation route.
+void session_remove_mod_route(
+    HyasynthSession* session,
+    uint32_t source_node,
+    uint32_t dest_node,
+    uint32_t param_id
+);
+
 /// Set the output node.
-void session_set_output(HyasynthSession* session, uint32_t node_id);
+HyaResult session_set_output(HyasynthSession* session, uint32_t node_id);
 
 /// Clear the entire graph.
 void session_clear_graph(HyasynthSession* session);
@@ -225,6 +375,33 @@ void session_set_param(
     float value
 );
 
+/// Clamp parameter values set from now on to the ranges in `registry`.
+/// Sessions start with the standard nodes' ranges.
+void session_set_param_ranges(HyasynthSession* session, const HyasynthRegistry* registry);
+
+/// Get a parameter's current value.
+/// Parameters never set fall back to the registry default. Returns NaN if the
+/// node doesn't exist, or if the parameter was never set and isn't known to
+/// registry (which may be NULL to skip the fallback).
+float session_get_param(
+    const HyasynthSession* session,
+    const HyasynthRegistry* registry,
+    uint32_t node_id,
+    uint32_t param_id
+);
+
+/// Scale a parameter by note velocity (0.0 = off, 1.0 = fully proportional).
+void session_set_velocity_sensitivity(
+    HyasynthSession* session,
+    uint32_t node_id,
+    uint32_t param_id,
+    float amount
+);
+
+/// Pass a node's input straight to its output without unwiring it, or
+/// restore it. Bypassed sources fall silent.
+void session_set_node_bypass(HyasynthSession* session, uint32_t node_id, bool bypassed);
+
 /// Begin a parameter gesture (for automation recording).
 void session_begin_gesture(
     HyasynthSession* session,
@@ -252,9 +429,32 @@ void session_stop(HyasynthSession* session);
 /// Set tempo in BPM.
 void session_set_tempo(HyasynthSession* session, double bpm);
 
+/// Glide from the current tempo to target_bpm over over_beats beats.
+void session_ramp_tempo(HyasynthSession* session, double target_bpm, double over_beats);
+
 /// Seek to a position in beats.
+///
+/// Realtime rendering doesn't play clips (the host sends their notes), so
+/// only the transport moves; clips follow a seek in offline renders.
 void session_seek(HyasynthSession* session, double beat);
 
+/// Loop playback between two beat positions.
+void session_set_loop(HyasynthSession* session, bool enabled, double start_beat, double end_beat);
+
+/// Click on every beat, accenting the first beat of each bar.
+void session_set_metronome(HyasynthSession* session, bool enabled, uint32_t beats_per_bar);
+
+/// Tune note frequencies so A4 sounds at `a4` Hz (440 by default).
+void session_set_tuning(HyasynthSession* session, double a4);
+
+/// Load a microtonal tuning table from a list of cents, one scale degree
+/// per line with the period last, starting on `root_note`.
+/// Returns false if the list can't be parsed.
+bool session_load_tuning_cents(HyasynthSession* session, const char* text, uint8_t root_note);
+
+/// Return to equal temperament.
+void session_clear_tuning_table(HyasynthSession* session);
+
 // ═══════════════════════════════════════════════════════════════════════════
 // MIDI
 // ═══════════════════════════════════════════════════════════════════════════
@@ -265,6 +465,63 @@ void session_note_on(HyasynthSession* session, uint8_t note, float velocity);
 /// Send a MIDI note off.
 void session_note_off(HyasynthSession* session, uint8_t note);
 
+/// Set the channel pitch bend in semitones (applies to all voices).
+void session_pitch_bend(HyasynthSession* session, float semitones);
+
+/// Switch between polyphonic (false) and monophonic (true) voice allocation.
+void session_set_mono(HyasynthSession* session, bool mono);
+
+/// Choose how notes are assigned to voices.
+///
+/// mode: 0 = poly, 1 = mono, 2 = legato. Unknown modes are ignored.
+void session_set_voice_mode(HyasynthSession* session, uint32_t mode);
+
+/// Set the portamento time in seconds between mono notes.
+void session_set_glide_time(HyasynthSession* session, float seconds);
+
+/// Choose which voice is stolen when polyphony is exhausted.
+/// mode: 0 = oldest, 1 = quietest, 2 = lowest note, 3 = skip new note.
+/// Unknown modes are ignored.
+void session_set_voice_steal_mode(HyasynthSession* session, uint32_t mode);
+
+/// Configure unison: voices per note, detune of the outer voices in cents,
+/// and stereo spread (0..1).
+void session_set_unison(HyasynthSession* session, uint32_t voices, float detune_cents, float spread);
+
+/// Pan voices by note number, low notes left and high notes right (0..1).
+void session_set_voice_pan_spread(HyasynthSession* session, float spread);
+
+/// Arpeggiate held notes into target_node, or play them directly when
+/// enabled is false.
+/// mode: 0 = up, 1 = down, 2 = up-down, 3 = random. rate is the step length
+/// in beats and gate the fraction of a step each note sounds for.
+void session_set_arpeggiator(
+    HyasynthSession* session,
+    bool enabled,
+    uint32_t mode,
+    double rate,
+    uint8_t octaves,
+    float gate,
+    uint32_t target_node
+);
+
+/// Send a MIDI control change (value normalized to 0..1).
+/// Mapped CCs are forwarded as parameter changes; unmapped CCs are ignored.
+void session_control_change(HyasynthSession* session, uint8_t cc, float value);
+
+/// Route a CC number to the full range of a node parameter.
+/// Returns false if the node or parameter doesn't exist.
+bool session_map_control_change(
+    HyasynthSession* session,
+    const HyasynthRegistry* registry,
+    uint8_t cc,
+    uint32_t node_id,
+    uint32_t param_id
+);
+
+/// Remove the mapping for a CC number.
+void session_unmap_control_change(HyasynthSession* session, uint8_t cc);
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Readback (UI polling)
 // ═══════════════════════════════════════════════════════════════════════════
@@ -272,6 +529,15 @@ void session_note_off(HyasynthSession* session, uint8_t note);
 /// Get the current engine readback state.
 HyasynthReadback session_get_readback(const HyasynthSession* session);
 
+/// Get the notes held by the engine's voices, ascending.
+/// Writes up to `max_notes` notes to `out_notes` and returns how many are
+/// held, which may be more than were written.
+uint32_t session_get_active_notes(
+    const HyasynthSession* session,
+    uint8_t* out_notes,
+    uint32_t max_notes
+);
+
 /// Check if the transport is playing.
 bool session_is_playing(const HyasynthSession* session);
 
@@ -284,6 +550,29 @@ uint32_t session_node_count(const HyasynthSession* session);
 /// Get the output node ID, or UINT32_MAX if not set.
 uint32_t session_get_output_node(const HyasynthSession* session);
 
+/// Get a node's type ID, or UINT32_MAX if the node doesn't exist.
+uint32_t session_get_node_type(const HyasynthSession* session, uint32_t node_id);
+
+/// Get the number of connections in the graph.
+uint32_t session_connection_count(const HyasynthSession* session);
+
+/// Get the connection at index (0..session_connection_count).
+/// Returns false if index is out of range.
+bool session_get_connection(
+    const HyasynthSession* session,
+    uint32_t index,
+    HyaConnection* out_connection
+);
+
+/// Check the graph for problems without compiling it.
+/// Writes up to `max_issues` issues to `out_issues` and returns how many
+/// were found, which may be more than were written.
+uint32_t session_validate_graph(
+    const HyasynthSession* session,
+    HyaGraphIssue* out_issues,
+    uint32_t max_issues
+);
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Engine (Audio Thread)
 // ═══════════════════════════════════════════════════════════════════════════
@@ -330,6 +619,18 @@ void engine_render_interleaved(
     float* output
 );
 
+/// Capture input from the host callback for the take being recorded.
+/// Input format: [L0, R0, L1, R1, L2, R2, ...]
+///
+/// @param engine The engine handle
+/// @param input Pointer to interleaved input (must hold `frames * 2` floats)
+/// @param frames Number of frames of input
+void engine_push_input(
+    HyasynthEngine* engine,
+    const float* input,
+    uint32_t frames
+);
+
 /// Check if the engine is currently playing.
 bool engine_is_playing(const HyasynthEngine* engine);
 
@@ -392,6 +693,32 @@ void session_add_note_to_clip(
 /// Clear all notes from a clip.
 void session_clear_clip(HyasynthSession* session, uint32_t clip_id);
 
+/// Move a clip's note starts toward a grid of `grid` beats.
+/// strength: 0 = untouched, 1 = exactly on the grid.
+void session_quantize_clip(
+    HyasynthSession* session,
+    uint32_t clip_id,
+    double grid,
+    float strength
+);
+
+/// Transpose a clip's notes on playback, in semitones.
+void session_set_clip_transpose(HyasynthSession* session, uint32_t clip_id, int8_t semitones);
+
+/// Scale a clip's note velocities on playback (1.0 = unchanged).
+void session_set_clip_velocity_scale(HyasynthSession* session, uint32_t clip_id, float scale);
+
+/// Randomly delay a clip's notes by up to timing beats and move their
+/// velocities by up to velocity on playback. The same seed always gives the
+/// same offsets.
+void session_set_clip_humanize(
+    HyasynthSession* session,
+    uint32_t clip_id,
+    double timing,
+    float velocity,
+    uint32_t seed
+);
+
 /// Get the number of notes in a clip.
 uint32_t session_get_clip_note_count(const HyasynthSession* session, uint32_t clip_id);
 
@@ -438,6 +765,17 @@ uint32_t session_create_clip_from_audio(
 /// Get the number of audio entries in the pool.
 uint32_t session_get_audio_pool_count(const HyasynthSession* session);
 
+/// Start recording input onto an armed track at the playhead.
+/// Returns false if the track isn't armed or a take is already running.
+bool session_start_recording(HyasynthSession* session, uint32_t track_id);
+
+/// Stop recording; the take is committed by `session_poll_recording`.
+void session_stop_recording(HyasynthSession* session);
+
+/// Commit a finished take to the audio pool as a clip on its track.
+/// Returns the clip ID, or UINT32_MAX if no take has come back yet.
+uint32_t session_poll_recording(HyasynthSession* session);
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Tracks
 // ═══════════════════════════════════════════════════════════════════════════
@@ -460,9 +798,26 @@ void session_set_track_mute(HyasynthSession* session, uint32_t track_id, bool mu
 /// Set track solo.
 void session_set_track_solo(HyasynthSession* session, uint32_t track_id, bool solo);
 
+/// Arm a track for recording, or disarm it.
+void session_set_track_armed(HyasynthSession* session, uint32_t track_id, bool armed);
+
 /// Set track target node.
 void session_set_track_target(HyasynthSession* session, uint32_t track_id, uint32_t node_id);
 
+/// Route notes from low_note to high_note on a track to their own target,
+/// shifted by transpose semitones. Overlapping zones layer.
+void session_add_track_key_zone(HyasynthSession* session, uint32_t track_id, uint8_t low_note,
+                                uint8_t high_note, uint32_t target_node, int8_t transpose);
+
+/// Remove a track's key zones.
+void session_clear_track_key_zones(HyasynthSession* session, uint32_t track_id);
+
+/// Play a note through a track's key zones.
+void session_track_note_on(HyasynthSession* session, uint32_t track_id, uint8_t note, float velocity);
+
+/// Release a note played with session_track_note_on.
+void session_track_note_off(HyasynthSession* session, uint32_t track_id, uint8_t note);
+
 /// Get the number of tracks.
 uint32_t session_get_track_count(const HyasynthSession* session);
 
@@ -488,6 +843,14 @@ void session_stop_clip(HyasynthSession* session, uint32_t track_id);
 /// Stop all clips.
 void session_stop_all_clips(HyasynthSession* session);
 
+/// Set the grid that clip and scene launches wait for.
+/// quantization: 0 = off, 1 = beat, 2 = half bar, 3 = bar, 4 = two bars,
+/// 5 = four bars. Unknown values are ignored.
+void session_set_launch_quantization(HyasynthSession* session, uint32_t quantization);
+
+/// Set the swing applied to off-beat 8th notes in clips (0..1).
+void session_set_swing(HyasynthSession* session, float amount);
+
 /// Get the number of scenes.
 uint32_t session_get_scene_count(const HyasynthSession* session);
 
@@ -0,0 +1,343 @@
+//! Arpeggiator: turns held chords into rhythmic note patterns.
+//!
+//! While the arpeggiator is on, the engine collects live note-ons into a
+//! held set instead of starting voices. The scheduler owns musical time, so
+//! it runs the arpeggiator over each block and places the resulting notes
+//! alongside the block's other events, stepping on a tempo-synced grid.
+
+use crate::event::MusicalEvent;
+use crate::state::NodeId;
+use crate::voice_allocator::mask_notes;
+
+/// Longest pattern: every MIDI note over four octaves, up then down.
+const MAX_PATTERN_LEN: usize = 128 * 4 * 2;
+
+/// Order the held notes are played in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArpMode {
+    /// Lowest to highest.
+    #[default]
+    Up,
+    /// Highest to lowest.
+    Down,
+    /// Up then back down, without repeating the end notes.
+    UpDown,
+    /// A random held note each step.
+    Random,
+}
+
+impl ArpMode {
+    /// Look up a mode by its binding index (0 = up, 1 = down, 2 = up-down,
+    /// 3 = random).
+    pub fn from_index(index: u32) -> Option<Self> {
+        match index {
+            0 => Some(Self::Up),
+            1 => Some(Self::Down),
+            2 => Some(Self::UpDown),
+            3 => Some(Self::Random),
+            _ => None,
+        }
+    }
+}
+
+/// How the arpeggiator plays the held notes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArpSettings {
+    /// Order of the notes.
+    pub mode: ArpMode,
+
+    /// Length of each step in beats (0.25 = 16th notes).
+    pub rate: f64,
+
+    /// Octaves the pattern spans, repeating the held notes an octave up.
+    pub octaves: u8,
+
+    /// Fraction of each step a note sounds for (0..1).
+    pub gate: f32,
+
+    /// The node the arpeggiated notes play.
+    pub target_node: NodeId,
+}
+
+impl ArpSettings {
+    pub fn new(target_node: NodeId) -> Self {
+        Self {
+            mode: ArpMode::Up,
+            rate: 0.25,
+            octaves: 1,
+            gate: 0.5,
+            target_node,
+        }
+    }
+
+    /// Settings with the step, range and gate clamped to usable values.
+    pub fn clamped(self) -> Self {
+        Self {
+            rate: self.rate.max(1.0 / 64.0),
+            octaves: self.octaves.clamp(1, 4),
+            gate: self.gate.clamp(0.01, 1.0),
+            ..self
+        }
+    }
+}
+
+/// Steps through the held notes on a beat grid.
+#[derive(Debug, Clone)]
+pub struct Arpeggiator {
+    settings: Option<ArpSettings>,
+
+    /// Held notes, one bit per MIDI note.
+    held: [u64; 2],
+
+    /// Velocity the notes play at.
+    velocity: f32,
+
+    /// Notes of one pass through the pattern.
+    pattern: Vec<u8>,
+
+    /// Index into `pattern` of the next step.
+    step: usize,
+
+    /// Note still sounding and the beat it ends on.
+    sounding: Option<(NodeId, u8, f64)>,
+
+    /// State of the generator picking notes in random mode.
+    seed: u32,
+}
+
+impl Default for Arpeggiator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Arpeggiator {
+    pub fn new() -> Self {
+        Self {
+            settings: None,
+            held: [0; 2],
+            velocity: 1.0,
+            pattern: Vec::with_capacity(MAX_PATTERN_LEN),
+            step: 0,
+            sounding: None,
+            seed: 0x2545_f491,
+        }
+    }
+
+    /// Turn the arpeggiator on with `settings`, or off with `None`.
+    pub fn set_settings(&mut self, settings: Option<ArpSettings>) {
+        let settings = settings.map(ArpSettings::clamped);
+        if settings != self.settings {
+            self.settings = settings;
+            self.rebuild_pattern();
+        }
+    }
+
+    /// Replace the held notes (one bit per MIDI note) and their velocity.
+    ///
+    /// The pattern restarts when notes are pressed after all were released.
+    pub fn set_held(&mut self, held: [u64; 2], velocity: f32) {
+        self.velocity = velocity.clamp(0.0, 1.0);
+        if held == self.held {
+            return;
+        }
+        if self.held == [0; 2] {
+            self.step = 0;
+        }
+        self.held = held;
+        self.rebuild_pattern();
+    }
+
+    /// Runs on the audio thread, so the pattern stays within the capacity
+    /// reserved in `new`.
+    fn rebuild_pattern(&mut self) {
+        self.pattern.clear();
+        let Some(settings) = self.settings else {
+            return;
+        };
+        for octave in 0..settings.octaves {
+            for note in mask_notes(self.held) {
+                let note = note as u16 + octave as u16 * 12;
+                if note < 128 {
+                    self.pattern.push(note as u8);
+                }
+            }
+        }
+        match settings.mode {
+            ArpMode::Up | ArpMode::Random => {}
+            ArpMode::Down => self.pattern.reverse(),
+            ArpMode::UpDown => {
+                let len = self.pattern.len();
+                if len > 2 {
+                    for i in (1..len - 1).rev() {
+                        self.pattern.push(self.pattern[i]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The note for the next step.
+    fn next_note(&mut self, mode: ArpMode) -> u8 {
+        let index = match mode {
+            ArpMode::Random => {
+                self.seed ^= self.seed << 13;
+                self.seed ^= self.seed >> 17;
+                self.seed ^= self.seed << 5;
+                self.seed as usize % self.pattern.len()
+            }
+            _ => self.step % self.pattern.len(),
+        };
+        self.step = (self.step + 1) % self.pattern.len();
+        self.pattern[index]
+    }
+
+    /// Append the notes that start or end in `start_beat..end_beat`.
+    ///
+    /// Steps fall on multiples of the rate. A note still sounding when the
+    /// held set empties or the arpeggiator turns off is released at
+    /// `start_beat`.
+    pub fn generate_events(&mut self, start_beat: f64, end_beat: f64, out: &mut Vec<MusicalEvent>) {
+        if let Some((node_id, note, off)) = self.sounding
+            && (off < end_beat || self.pattern.is_empty())
+        {
+            out.push(MusicalEvent::NoteOffTarget {
+                beat: if off < end_beat {
+                    off.max(start_beat)
+                } else {
+                    start_beat
+                },
+                node_id,
+                note,
+            });
+            self.sounding = None;
+        }
+
+        let Some(settings) = self.settings else {
+            return;
+        };
+        if self.pattern.is_empty() {
+            return;
+        }
+
+        let mut beat = (start_beat / settings.rate).ceil() * settings.rate;
+        while beat < end_beat {
+            if let Some((node_id, note, _)) = self.sounding.take() {
+                out.push(MusicalEvent::NoteOffTarget {
+                    beat,
+                    node_id,
+                    note,
+                });
+            }
+
+            let note = self.next_note(settings.mode);
+            out.push(MusicalEvent::NoteOnTarget {
+                beat,
+                node_id: settings.target_node,
+                note,
+                velocity: self.velocity,
+            });
+
+            let off = beat + settings.rate * settings.gate as f64;
+            if off < end_beat {
+                out.push(MusicalEvent::NoteOffTarget {
+                    beat: off,
+                    node_id: settings.target_node,
+                    note,
+                });
+            } else {
+                self.sounding = Some((settings.target_node, note, off));
+            }
+            beat += settings.rate;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(notes: &[u8]) -> [u64; 2] {
+        let mut mask = [0u64; 2];
+        for &note in notes {
+            mask[note as usize / 64] |= 1 << (note % 64);
+        }
+        mask
+    }
+
+    #[test]
+    fn test_largest_pattern_fits_reserved_capacity() {
+        let mut arp = Arpeggiator::new();
+        arp.set_settings(Some(ArpSettings {
+            mode: ArpMode::UpDown,
+            octaves: 4,
+            ..ArpSettings::new(7)
+        }));
+        let capacity = arp.pattern.capacity();
+
+        arp.set_held([u64::MAX; 2], 1.0);
+        assert!(arp.pattern.len() > 128);
+        assert_eq!(arp.pattern.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_arpeggiates_held_chord_upwards() {
+        let mut arp = Arpeggiator::new();
+        arp.set_settings(Some(ArpSettings {
+            rate: 0.5,
+            gate: 0.5,
+            ..ArpSettings::new(7)
+        }));
+        arp.set_held(chord(&[67, 60, 64]), 0.9);
+
+        // Two beats in uneven blocks, so notes straddle block edges
+        let mut events = Vec::new();
+        let mut beat = 0.0;
+        while beat < 2.0 {
+            arp.generate_events(beat, beat + 0.3, &mut events);
+            beat += 0.3;
+        }
+
+        let note_ons: Vec<(f64, u8)> = events
+            .iter()
+            .filter_map(|e| match *e {
+                MusicalEvent::NoteOnTarget {
+                    beat,
+                    node_id: 7,
+                    note,
+                    ..
+                } => Some((beat, note)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            note_ons,
+            vec![(0.0, 60), (0.5, 64), (1.0, 67), (1.5, 60), (2.0, 64)]
+        );
+
+        // Every note is released a quarter beat later, before the next step
+        let note_offs: Vec<(f64, u8)> = events
+            .iter()
+            .filter_map(|e| match *e {
+                MusicalEvent::NoteOffTarget { beat, note, .. } => Some((beat, note)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            note_offs,
+            vec![(0.25, 60), (0.75, 64), (1.25, 67), (1.75, 60)]
+        );
+
+        // Releasing the chord stops the pattern and the sounding note
+        arp.set_held([0; 2], 0.9);
+        events.clear();
+        arp.generate_events(2.1, 3.0, &mut events);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            MusicalEvent::NoteOffTarget { note: 64, .. }
+        ));
+    }
+}
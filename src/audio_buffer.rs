@@ -1,12 +1,58 @@
+/// Read-only view of an upstream node's output.
+///
+/// Inputs are shared slices, so a node can never write through one of them
+/// or hold one that overlaps its own output.
+#[derive(Debug, Clone, Copy)]
+pub struct InputBuffer<'a> {
+    pub channels: usize,
+    pub frames: usize,
+    pub data: &'a [f32], // planar: [ch0_all_frames][ch1_all_frames]...
+}
+
+impl<'a> InputBuffer<'a> {
+    /// Create a new InputBuffer wrapping existing data.
+    #[inline]
+    pub fn new(data: &'a [f32], channels: usize) -> Self {
+        let frames = data.len() / channels;
+        Self {
+            channels,
+            frames,
+            data,
+        }
+    }
+
+    #[inline]
+    pub fn channel(&self, ch: usize) -> &'a [f32] {
+        let start = ch * self.frames;
+        &self.data[start..start + self.frames]
+    }
+
+    /// Left and right channels; a mono input is read on both sides.
+    #[inline]
+    pub fn stereo(&self) -> (&'a [f32], &'a [f32]) {
+        (
+            self.channel(0),
+            self.channel(1.min(self.channels.saturating_sub(1))),
+        )
+    }
+
+    /// Get direct access to the raw planar sample data.
+    #[inline]
+    pub fn samples(&self) -> &'a [f32] {
+        self.data
+    }
+}
+
+/// Mutable view of the buffer a node writes its output to.
 #[derive(Debug)]
-pub struct AudioBuffer<'a> {
+pub struct OutputBuffer<'a> {
     pub channels: usize,
     pub frames: usize,
     pub data: &'a mut [f32], // planar: [ch0_all_frames][ch1_all_frames]...
 }
 
-impl<'a> AudioBuffer<'a> {
-    /// Create a new AudioBuffer wrapping existing data.
+impl<'a> OutputBuffer<'a> {
+    /// Create a new OutputBuffer wrapping existing data.
     #[inline]
     pub fn new(data: &'a mut [f32], channels: usize) -> Self {
         let frames = data.len() / channels;
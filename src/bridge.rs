@@ -22,16 +22,25 @@
 //! engine.process_plan(&plan);
 //! ```
 
+use std::collections::HashMap;
 use std::sync::{
     Arc,
-    atomic::{AtomicBool, AtomicU64, Ordering},
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
     mpsc::{self, Receiver, Sender, TryRecvError},
 };
 
-use crate::engine::Engine;
+use crate::arpeggiator::ArpSettings;
+use crate::engine::{Engine, INPUT_CHANNELS};
 use crate::execution_plan::ExecutionPlan;
 use crate::graph::Graph;
-use crate::state::{Command, CommandResult, EngineReadback, NodeId, NodeTypeId, Session};
+use crate::node_factory::NodeRegistry;
+use crate::nodes::register_standard_nodes;
+use crate::voice_allocator::{VoiceMode, VoiceStealMode, notes_in_mask};
+use crate::state::{
+    CcMapping, Command, CommandResult, ConnectionDef, EngineReadback, GraphIssue, KeyZone, NodeId,
+    NodePreset, NodeTypeId, ParamId, ParamInfo, PresetError, Session, TuningError, TuningTable, UndoHistory, UndoStep,
+    invert_command, redo_command,
+};
 
 /// Handle for the UI thread to communicate with the engine.
 ///
@@ -47,8 +56,20 @@ pub struct SessionHandle {
     /// Channel to receive command results.
     result_rx: Receiver<CommandResult>,
 
+    /// Sender for results found on the UI side, such as clamped values.
+    local_result_tx: Sender<CommandResult>,
+
     /// Shared readback state (updated by engine, read by UI).
     readback: Arc<SharedReadback>,
+
+    /// Undo/redo history of graph edits.
+    history: UndoHistory,
+
+    /// Channel to receive finished recording takes.
+    take_rx: Receiver<Vec<f32>>,
+
+    /// Declared range and default of each node type's parameters.
+    param_infos: HashMap<(NodeTypeId, ParamId), ParamInfo>,
 }
 
 /// Handle for the audio thread containing the engine and communication channels.
@@ -67,6 +88,9 @@ pub struct EngineHandle {
 
     /// Shared readback state (written by engine).
     readback: Arc<SharedReadback>,
+
+    /// Channel to send finished recording takes to UI.
+    take_tx: Sender<Vec<f32>>,
 }
 
 /// Lock-free shared state for engine -> UI readback.
@@ -77,8 +101,13 @@ struct SharedReadback {
     /// Beat position stored as f64 bits (no AtomicF64 in std)
     beat_position_bits: AtomicU64,
     active_voices: AtomicU64,
+    /// Held notes as a 128-bit mask
+    active_notes: [AtomicU64; 2],
     running: AtomicBool,
-    // Peak meters would use AtomicU32 with f32::to_bits/from_bits
+    /// Output peak meters stored as f32 bits (left, right)
+    peak_bits: [AtomicU32; 2],
+    /// Smoothed CPU load stored as f32 bits
+    cpu_load_bits: AtomicU32,
 }
 
 impl SharedReadback {
@@ -87,32 +116,52 @@ impl SharedReadback {
             sample_position: AtomicU64::new(0),
             beat_position_bits: AtomicU64::new(0.0_f64.to_bits()),
             active_voices: AtomicU64::new(0),
+            active_notes: [AtomicU64::new(0), AtomicU64::new(0)],
             running: AtomicBool::new(false),
+            peak_bits: [AtomicU32::new(0), AtomicU32::new(0)],
+            cpu_load_bits: AtomicU32::new(0),
         }
     }
 }
 
+/// Time for the peak meters to fall by a factor of e after the signal stops.
+const PEAK_DECAY_SECONDS: f64 = 0.3;
+
+/// Time constant for smoothing the CPU load readback.
+const CPU_LOAD_SMOOTHING_SECONDS: f64 = 0.5;
+
 /// Create a linked pair of handles for UI and Engine communication.
 ///
 /// The `engine` parameter is the audio engine that will be owned by the
-/// `EngineHandle` and run on the audio thread.
+/// `EngineHandle` and run on the audio thread. Parameter values are
+/// clamped to the standard nodes' ranges until `set_param_ranges` is
+/// given another registry.
 pub fn create_bridge(session: Session, engine: Engine) -> (SessionHandle, EngineHandle) {
     let (cmd_tx, cmd_rx) = mpsc::channel();
     let (result_tx, result_rx) = mpsc::channel();
+    let (take_tx, take_rx) = mpsc::channel();
     let readback = Arc::new(SharedReadback::new());
 
-    let session_handle = SessionHandle {
+    let mut session_handle = SessionHandle {
         session,
         command_tx: cmd_tx,
         result_rx,
+        local_result_tx: result_tx.clone(),
         readback: Arc::clone(&readback),
+        history: UndoHistory::new(),
+        take_rx,
+        param_infos: HashMap::new(),
     };
+    let mut registry = NodeRegistry::new();
+    register_standard_nodes(&mut registry);
+    session_handle.set_param_ranges(&registry);
 
     let engine_handle = EngineHandle {
         engine,
         command_rx: cmd_rx,
         result_tx,
         readback,
+        take_tx,
     };
 
     (session_handle, engine_handle)
@@ -137,13 +186,113 @@ impl SessionHandle {
     ///
     /// Also updates local session state for immediate UI feedback.
     pub fn send(&mut self, cmd: Command) {
+        // CCs reach the engine as parameter changes on their mapped target.
+        // Controller moves are performance, not edits, so they stay out of
+        // the undo history.
+        if let Command::ControlChange { cc, value } = cmd {
+            if let Some((node_id, param_id, value)) = self.session.control_change(cc, value)
+                && let Some(value) = self.check_param(node_id, param_id, value)
+            {
+                let cmd = Command::SetParam {
+                    node_id,
+                    param_id,
+                    value,
+                };
+                self.apply_to_session(&cmd);
+                let _ = self.command_tx.send(cmd);
+            }
+            return;
+        }
+
+        // Keep parameter values finite and inside their declared range
+        let cmd = match cmd {
+            Command::SetParam {
+                node_id,
+                param_id,
+                value,
+            } => {
+                let Some(value) = self.check_param(node_id, param_id, value) else {
+                    return;
+                };
+                Command::SetParam {
+                    node_id,
+                    param_id,
+                    value,
+                }
+            }
+            cmd => cmd,
+        };
+
+        // Work out how to revert graph edits before they apply
+        let inverse = invert_command(&self.session.graph, &cmd, |type_id, param_id| {
+            self.param_infos
+                .get(&(type_id, param_id))
+                .map(|info| info.default)
+        });
+        let added = self.session.graph.next_node_id();
+
         // Apply to local state first (optimistic update)
         self.apply_to_session(&cmd);
 
+        if let Some(undo) = inverse {
+            let redo = vec![redo_command(&self.session.graph, &cmd, added)];
+            self.history.record(UndoStep { redo, undo });
+        }
+        match cmd {
+            Command::BeginParamGesture { node_id, param_id } => {
+                self.history.begin_gesture(node_id, param_id)
+            }
+            Command::EndParamGesture { node_id, param_id } => {
+                self.history.end_gesture(node_id, param_id)
+            }
+            Command::ClearGraph => self.history.clear(),
+            _ => {}
+        }
+
         // Send to engine
         let _ = self.command_tx.send(cmd);
     }
 
+    /// Revert the most recent graph edit.
+    ///
+    /// Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(step) = self.history.take_undo() else {
+            return false;
+        };
+        self.replay(step.undo);
+        true
+    }
+
+    /// Re-apply the most recently undone graph edit.
+    ///
+    /// Returns false if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(step) = self.history.take_redo() else {
+            return false;
+        };
+        self.replay(step.redo);
+        true
+    }
+
+    /// Whether `undo` would do anything.
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    /// Whether `redo` would do anything.
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Apply and send history commands without recording them again.
+    fn replay(&mut self, commands: Vec<Command>) {
+        for cmd in commands {
+            self.apply_to_session(&cmd);
+            let _ = self.command_tx.send(cmd);
+        }
+    }
+
     /// Apply a command to the local session state.
     ///
     /// This provides immediate feedback before the engine processes it.
@@ -161,6 +310,20 @@ impl SessionHandle {
             Command::RemoveNode { node_id } => {
                 self.session.graph.remove_node(*node_id);
             }
+            Command::RestoreNode {
+                node,
+                connections,
+                mod_routes,
+            } => {
+                let graph = &mut self.session.graph;
+                graph.restore_node(node.clone());
+                for c in connections {
+                    graph.connect(c.source_node, c.source_port, c.dest_node, c.dest_port);
+                }
+                for r in mod_routes {
+                    graph.add_mod_route(r.source_node, r.dest_node, r.param_id, r.depth);
+                }
+            }
             Command::Connect {
                 source_node,
                 source_port,
@@ -184,6 +347,37 @@ impl SessionHandle {
             Command::SetOutputNode { node_id } => {
                 self.session.graph.output_node = Some(*node_id);
             }
+            Command::SetVelocitySensitivity {
+                node_id,
+                param_id,
+                amount,
+            } => {
+                self.session
+                    .graph
+                    .set_velocity_sensitivity(*node_id, *param_id, *amount);
+            }
+            Command::SetNodeBypass { node_id, bypassed } => {
+                self.session.graph.set_bypass(*node_id, *bypassed);
+            }
+            Command::AddModRoute {
+                source_node,
+                dest_node,
+                param_id,
+                depth,
+            } => {
+                self.session
+                    .graph
+                    .add_mod_route(*source_node, *dest_node, *param_id, *depth);
+            }
+            Command::RemoveModRoute {
+                source_node,
+                dest_node,
+                param_id,
+            } => {
+                self.session
+                    .graph
+                    .remove_mod_route(*source_node, *dest_node, *param_id);
+            }
             Command::MoveNode { node_id, position } => {
                 if let Some(node) = self.session.graph.get_node_mut(*node_id) {
                     node.position = *position;
@@ -199,12 +393,38 @@ impl SessionHandle {
             Command::SetTempo { bpm } => {
                 self.session.transport.bpm = *bpm;
             }
+            Command::RampTempo { target_bpm, .. } => {
+                self.session.transport.bpm = *target_bpm;
+            }
             Command::Play => {
                 self.session.transport.playing = true;
             }
             Command::Stop => {
                 self.session.transport.playing = false;
             }
+            Command::SetLoop {
+                enabled,
+                start,
+                end,
+            } => {
+                let transport = &mut self.session.transport;
+                transport.loop_enabled = *enabled;
+                transport.loop_start = *start;
+                transport.loop_end = *end;
+            }
+            Command::SetMetronome {
+                enabled,
+                beats_per_bar,
+            } => {
+                self.session.metronome_enabled = *enabled;
+                self.session.transport.beats_per_bar = (*beats_per_bar).max(1);
+            }
+            Command::SetTuning { a4 } => {
+                self.session.transport.tuning = *a4;
+            }
+            Command::SetTuningTable { table } => {
+                self.session.tuning_table = table.clone();
+            }
             Command::ClearGraph => {
                 self.session.graph = Default::default();
             }
@@ -252,6 +472,37 @@ impl SessionHandle {
                     clip.looping = *looping;
                 }
             }
+            Command::SetClipTranspose { clip_id, semitones } => {
+                if let Some(clip) = self.session.arrangement.get_clip_mut(*clip_id) {
+                    clip.transpose = *semitones;
+                }
+            }
+            Command::SetClipVelocityScale { clip_id, scale } => {
+                if let Some(clip) = self.session.arrangement.get_clip_mut(*clip_id) {
+                    clip.velocity_scale = scale.max(0.0);
+                }
+            }
+            Command::SetClipHumanize {
+                clip_id,
+                timing,
+                velocity,
+                seed,
+            } => {
+                if let Some(clip) = self.session.arrangement.get_clip_mut(*clip_id) {
+                    clip.humanize_timing = timing.max(0.0);
+                    clip.humanize_velocity = velocity.clamp(0.0, 1.0);
+                    clip.humanize_seed = *seed;
+                }
+            }
+            Command::QuantizeClip {
+                clip_id,
+                grid,
+                strength,
+            } => {
+                if let Some(clip) = self.session.arrangement.get_clip_mut(*clip_id) {
+                    clip.quantize(*grid, *strength);
+                }
+            }
 
             // ═══════════════════════════════════════════════════════════════
             // Track commands
@@ -286,6 +537,29 @@ impl SessionHandle {
                     .arrangement
                     .set_track_target(*track_id, *node_id);
             }
+            Command::AddTrackKeyZone { track_id, zone } => {
+                self.session.arrangement.add_track_key_zone(*track_id, *zone);
+            }
+            Command::ClearTrackKeyZones { track_id } => {
+                self.session.arrangement.clear_track_key_zones(*track_id);
+            }
+            Command::SetTrackParent { track_id, parent } => {
+                self.session
+                    .arrangement
+                    .set_track_parent(*track_id, *parent);
+            }
+            Command::SetTrackSend {
+                track_id,
+                aux_id,
+                level,
+            } => {
+                self.session
+                    .arrangement
+                    .set_track_send(*track_id, *aux_id, *level);
+            }
+            Command::SetAuxEffect { aux_id, node_id } => {
+                self.session.arrangement.set_aux_effect(*aux_id, *node_id);
+            }
             Command::SetClipSlot {
                 track_id,
                 scene_index,
@@ -339,6 +613,16 @@ impl SessionHandle {
                     .remove_clip_placement(*track_id, *start_beat);
             }
 
+            // ═══════════════════════════════════════════════════════════════
+            // Recording commands
+            // ═══════════════════════════════════════════════════════════════
+            Command::StartRecording { track_id } => {
+                let start_beat = self.readback().beat_position;
+                self.session
+                    .arrangement
+                    .start_recording(*track_id, start_beat);
+            }
+
             // ═══════════════════════════════════════════════════════════════
             // Compilation commands
             // ═══════════════════════════════════════════════════════════════
@@ -352,13 +636,46 @@ impl SessionHandle {
             Command::SyncAllTrackParams => {
                 // Sync computed on-demand.
             }
+            Command::SetVoiceMode { mode } => {
+                self.session.voice_mode = *mode;
+            }
+            Command::SetGlideTime { seconds } => {
+                self.session.glide_time = seconds.max(0.0);
+            }
+            Command::SetVoiceStealMode { mode } => {
+                self.session.voice_steal_mode = *mode;
+            }
+            Command::SetUnison {
+                voices,
+                detune_cents,
+                spread,
+            } => {
+                self.session.unison_voices = (*voices).max(1);
+                self.session.unison_detune = detune_cents.max(0.0);
+                self.session.unison_spread = spread.clamp(0.0, 1.0);
+            }
+            Command::SetVoicePanSpread { spread } => {
+                self.session.voice_pan_spread = spread.clamp(0.0, 1.0);
+            }
+            Command::SetArpeggiator { settings } => {
+                self.session.arpeggiator = *settings;
+            }
+            Command::MapControlChange { cc, mapping } => {
+                self.session.cc_mappings.insert(*cc, *mapping);
+            }
+            Command::UnmapControlChange { cc } => {
+                self.session.cc_mappings.remove(cc);
+            }
 
             // Commands that don't affect session state directly
             Command::BeginParamGesture { .. }
             | Command::EndParamGesture { .. }
             | Command::Seek { .. }
+            | Command::StopRecording
             | Command::NoteOn { .. }
             | Command::NoteOff { .. }
+            | Command::PitchBend { .. }
+            | Command::ControlChange { .. }
             | Command::LoadConnections { .. } => {}
         }
     }
@@ -378,30 +695,38 @@ impl SessionHandle {
 
     /// Get the current engine readback state.
     ///
-    /// Note: `cpu_load` and `output_peaks` are not yet implemented.
-    /// They require additional atomic storage in SharedReadback.
+    /// `cpu_load` stays at 0 on wasm, which has no monotonic clock in std.
     pub fn readback(&self) -> EngineReadback {
         EngineReadback {
             sample_position: self.readback.sample_position.load(Ordering::Relaxed),
             beat_position: f64::from_bits(self.readback.beat_position_bits.load(Ordering::Relaxed)),
-            cpu_load: 0.0,
+            cpu_load: f32::from_bits(self.readback.cpu_load_bits.load(Ordering::Relaxed)),
             active_voices: self.readback.active_voices.load(Ordering::Relaxed) as usize,
-            output_peaks: [0.0, 0.0],
+            output_peaks: [
+                f32::from_bits(self.readback.peak_bits[0].load(Ordering::Relaxed)),
+                f32::from_bits(self.readback.peak_bits[1].load(Ordering::Relaxed)),
+            ],
             running: self.readback.running.load(Ordering::Relaxed),
         }
     }
 
+    /// Notes held by the engine's voices, ascending, as of the last
+    /// `EngineHandle::sync_readback`.
+    pub fn active_notes(&self) -> Vec<u8> {
+        notes_in_mask([
+            self.readback.active_notes[0].load(Ordering::Relaxed),
+            self.readback.active_notes[1].load(Ordering::Relaxed),
+        ])
+    }
+
     // ───────────────────────────────────────────────────────────────
     // Convenience methods
     // ───────────────────────────────────────────────────────────────
 
     /// Add a node to the graph.
     pub fn add_node(&mut self, type_id: NodeTypeId, x: f32, y: f32) -> NodeId {
-        let id = self.session.graph.add_node(type_id);
-        if let Some(node) = self.session.graph.get_node_mut(id) {
-            node.position = (x, y);
-        }
-        let _ = self.command_tx.send(Command::AddNode {
+        let id = self.session.graph.next_node_id();
+        self.send(Command::AddNode {
             type_id,
             position: (x, y),
         });
@@ -414,12 +739,82 @@ impl SessionHandle {
     }
 
     /// Set a parameter value.
-    pub fn set_param(&mut self, node_id: NodeId, param_id: u32, value: f32) {
+    ///
+    /// Returns the value applied, which is clamped to the parameter's
+    /// declared range. Non-finite values are rejected, leaving the current
+    /// value in place.
+    pub fn set_param(&mut self, node_id: NodeId, param_id: u32, value: f32) -> f32 {
+        let Some(value) = self.check_param(node_id, param_id, value) else {
+            return self.param_value(node_id, param_id).unwrap_or(f32::NAN);
+        };
         self.send(Command::SetParam {
             node_id,
             param_id,
             value,
         });
+        value
+    }
+
+    /// Clamp parameter values to the ranges `registry` declares.
+    ///
+    /// Nodes clamp some parameters themselves but not all, so without this
+    /// out-of-range values would reach them unchanged. Sessions start with
+    /// the standard nodes' ranges.
+    pub fn set_param_ranges(&mut self, registry: &NodeRegistry) {
+        self.param_infos = registry
+            .iter()
+            .flat_map(|info| {
+                info.parameters
+                    .iter()
+                    .map(|param| ((info.type_id, param.id), param.clone()))
+            })
+            .collect();
+    }
+
+    /// A parameter's current value, falling back to its declared default.
+    fn param_value(&self, node_id: NodeId, param_id: ParamId) -> Option<f32> {
+        let node = self.session.graph.get_node(node_id)?;
+        node.param_values.get(&param_id).copied().or_else(|| {
+            self.param_infos
+                .get(&(node.type_id, param_id))
+                .map(|info| info.default)
+        })
+    }
+
+    /// A parameter value clamped to its declared range, if known, or `None`
+    /// if it isn't finite.
+    ///
+    /// Rejected and clamped values are reported through `poll_results`.
+    fn check_param(&self, node_id: NodeId, param_id: ParamId, value: f32) -> Option<f32> {
+        if !value.is_finite() {
+            let _ = self.local_result_tx.send(CommandResult::Error {
+                message: format!(
+                    "Value {} for parameter {} of node {} is not finite",
+                    value, param_id, node_id
+                ),
+            });
+            return None;
+        }
+        let info = self
+            .session
+            .graph
+            .get_node(node_id)
+            .and_then(|node| self.param_infos.get(&(node.type_id, param_id)));
+        let clamped = info.map_or(value, |info| info.clamp(value));
+        if clamped != value {
+            let _ = self.local_result_tx.send(CommandResult::ParamClamped {
+                node_id,
+                param_id,
+                requested: value,
+                value: clamped,
+            });
+        }
+        Some(clamped)
+    }
+
+    /// Bypass a node without unwiring it, or restore it.
+    pub fn set_node_bypass(&mut self, node_id: NodeId, bypassed: bool) {
+        self.send(Command::SetNodeBypass { node_id, bypassed });
     }
 
     /// Start playback.
@@ -432,6 +827,49 @@ impl SessionHandle {
         self.send(Command::Stop);
     }
 
+    /// Glide from the current tempo to `target_bpm` over `over_beats` beats.
+    pub fn ramp_tempo(&mut self, target_bpm: f64, over_beats: f64) {
+        self.send(Command::RampTempo {
+            target_bpm,
+            over_beats,
+        });
+    }
+
+    /// Loop playback between `start` and `end` beats while `enabled`.
+    pub fn set_loop(&mut self, enabled: bool, start: f64, end: f64) {
+        self.send(Command::SetLoop {
+            enabled,
+            start,
+            end,
+        });
+    }
+
+    /// Turn the metronome on or off, accenting every `beats_per_bar` beats.
+    pub fn set_metronome(&mut self, enabled: bool, beats_per_bar: u32) {
+        self.send(Command::SetMetronome {
+            enabled,
+            beats_per_bar,
+        });
+    }
+
+    /// Tune note frequencies so A4 sounds at `a4` Hz.
+    pub fn set_tuning(&mut self, a4: f64) {
+        self.send(Command::SetTuning { a4 });
+    }
+
+    /// Map notes through a microtonal tuning table, or back to equal
+    /// temperament with `None`.
+    pub fn set_tuning_table(&mut self, table: Option<TuningTable>) {
+        self.send(Command::SetTuningTable { table });
+    }
+
+    /// Load a tuning table from a list of cents, one scale degree per line.
+    pub fn load_tuning_cents(&mut self, text: &str, root_note: u8) -> Result<(), TuningError> {
+        let table = TuningTable::from_cents_list(text, root_note)?;
+        self.set_tuning_table(Some(table));
+        Ok(())
+    }
+
     /// Send a MIDI note on.
     pub fn note_on(&mut self, note: u8, velocity: f32) {
         self.send(Command::NoteOn { note, velocity });
@@ -442,6 +880,66 @@ impl SessionHandle {
         self.send(Command::NoteOff { note });
     }
 
+    /// Set the channel pitch bend in semitones.
+    pub fn pitch_bend(&mut self, semitones: f32) {
+        self.send(Command::PitchBend { semitones });
+    }
+
+    /// Switch between polyphonic and monophonic voice allocation.
+    pub fn set_voice_mode(&mut self, mode: VoiceMode) {
+        self.send(Command::SetVoiceMode { mode });
+    }
+
+    /// Set the portamento time in seconds between mono notes.
+    pub fn set_glide_time(&mut self, seconds: f32) {
+        self.send(Command::SetGlideTime { seconds });
+    }
+
+    /// Choose which voice is stolen when polyphony is exhausted.
+    pub fn set_voice_steal_mode(&mut self, mode: VoiceStealMode) {
+        self.send(Command::SetVoiceStealMode { mode });
+    }
+
+    /// Configure unison voices per note, detune (cents) and stereo spread (0..1).
+    pub fn set_unison(&mut self, voices: usize, detune_cents: f32, spread: f32) {
+        self.send(Command::SetUnison {
+            voices,
+            detune_cents,
+            spread,
+        });
+    }
+
+    /// Pan voices by note number, low notes left and high notes right (0..1).
+    pub fn set_voice_pan_spread(&mut self, spread: f32) {
+        self.send(Command::SetVoicePanSpread { spread });
+    }
+
+    /// Arpeggiate held notes with `settings`, or play them directly with
+    /// `None`.
+    pub fn set_arpeggiator(&mut self, settings: Option<ArpSettings>) {
+        self.send(Command::SetArpeggiator {
+            settings: settings.map(ArpSettings::clamped),
+        });
+    }
+
+    /// Send a MIDI control change (value normalized to 0..1).
+    pub fn control_change(&mut self, cc: u8, value: f32) {
+        self.send(Command::ControlChange { cc, value });
+    }
+
+    /// Route a CC number to the full range of a node parameter.
+    pub fn map_control_change(&mut self, cc: u8, node_id: NodeId, param: &ParamInfo) {
+        self.send(Command::MapControlChange {
+            cc,
+            mapping: CcMapping::new(node_id, param),
+        });
+    }
+
+    /// Remove the mapping for a CC number.
+    pub fn unmap_control_change(&mut self, cc: u8) {
+        self.send(Command::UnmapControlChange { cc });
+    }
+
     // ───────────────────────────────────────────────────────────────
     // Runtime graph methods
     // ───────────────────────────────────────────────────────────────
@@ -461,6 +959,57 @@ impl SessionHandle {
         self.send(Command::RecompileGraph);
     }
 
+    /// Bring a freshly created engine up to date with the whole session.
+    ///
+    /// Used after loading a saved project: the transport and voice settings
+    /// are re-sent and the graph is recompiled. Nothing is recorded for undo.
+    pub fn sync_engine(&mut self) {
+        let session = &self.session;
+        let transport = &session.transport;
+        let commands = [
+            Command::SetTempo { bpm: transport.bpm },
+            Command::SetLoop {
+                enabled: transport.loop_enabled,
+                start: transport.loop_start,
+                end: transport.loop_end,
+            },
+            Command::SetMetronome {
+                enabled: session.metronome_enabled,
+                beats_per_bar: transport.beats_per_bar,
+            },
+            Command::SetTuning {
+                a4: transport.tuning,
+            },
+            Command::SetTuningTable {
+                table: session.tuning_table.clone(),
+            },
+            Command::SetVoiceMode {
+                mode: session.voice_mode,
+            },
+            Command::SetGlideTime {
+                seconds: session.glide_time,
+            },
+            Command::SetVoiceStealMode {
+                mode: session.voice_steal_mode,
+            },
+            Command::SetUnison {
+                voices: session.unison_voices,
+                detune_cents: session.unison_detune,
+                spread: session.unison_spread,
+            },
+            Command::SetVoicePanSpread {
+                spread: session.voice_pan_spread,
+            },
+            Command::SetArpeggiator {
+                settings: session.arpeggiator,
+            },
+            Command::RecompileGraph,
+        ];
+        for cmd in commands {
+            let _ = self.command_tx.send(cmd);
+        }
+    }
+
     /// Get parameter updates for a specific track.
     ///
     /// Returns (node_id, param_id, value) tuples for the track's mixer nodes.
@@ -535,12 +1084,251 @@ impl SessionHandle {
         self.sync_track(track_id);
     }
 
+    /// Set track solo (with automatic parameter sync).
+    ///
+    /// Soloing changes which other tracks are audible, so every track is
+    /// re-synced.
+    pub fn set_track_solo(&mut self, track_id: crate::state::TrackId, solo: bool) {
+        self.send(Command::SetTrackSolo { track_id, solo });
+        self.sync_all_tracks();
+    }
+
+    /// Arm a track for recording, or disarm it.
+    pub fn set_track_armed(&mut self, track_id: crate::state::TrackId, armed: bool) {
+        self.send(Command::SetTrackArmed { track_id, armed });
+    }
+
     /// Set track target node (the instrument this track routes MIDI to).
     pub fn set_track_target(&mut self, track_id: crate::state::TrackId, node_id: Option<u32>) {
         self.send(Command::SetTrackTarget { track_id, node_id });
         // Routing change requires recompilation
         self.send(Command::RecompileGraph);
     }
+
+    /// Route a note range on a track to its own target, transposed.
+    ///
+    /// Overlapping zones layer; a track with no zones plays every note on
+    /// its target.
+    pub fn add_track_key_zone(&mut self, track_id: crate::state::TrackId, zone: KeyZone) {
+        self.send(Command::AddTrackKeyZone { track_id, zone });
+        // The zone's target is mixed into the track
+        self.send(Command::RecompileGraph);
+    }
+
+    /// Remove a track's key zones.
+    pub fn clear_track_key_zones(&mut self, track_id: crate::state::TrackId) {
+        self.send(Command::ClearTrackKeyZones { track_id });
+        self.send(Command::RecompileGraph);
+    }
+
+    /// Play a note through a track's key zones.
+    pub fn track_note_on(&mut self, track_id: crate::state::TrackId, note: u8, velocity: f32) {
+        for note in self.track_notes(track_id, note) {
+            self.send(Command::NoteOn { note, velocity });
+        }
+    }
+
+    /// Release a note played with `track_note_on`.
+    pub fn track_note_off(&mut self, track_id: crate::state::TrackId, note: u8) {
+        for note in self.track_notes(track_id, note) {
+            self.send(Command::NoteOff { note });
+        }
+    }
+
+    /// Notes a note played on a track sounds.
+    ///
+    /// Voices are shared by every instrument, so layers reaching the same
+    /// note sound it once.
+    fn track_notes(&self, track_id: crate::state::TrackId, note: u8) -> Vec<u8> {
+        let Some(track) = self.session.arrangement.get_track(track_id) else {
+            return Vec::new();
+        };
+        let mut notes: Vec<u8> = Vec::new();
+        for (_, played) in track.note_targets(note) {
+            if !notes.contains(&played) {
+                notes.push(played);
+            }
+        }
+        notes
+    }
+
+    /// Route a track into a group track, or back to the master bus with `None`.
+    pub fn set_track_parent(
+        &mut self,
+        track_id: crate::state::TrackId,
+        parent: Option<crate::state::TrackId>,
+    ) {
+        self.send(Command::SetTrackParent { track_id, parent });
+        // Routing change requires recompilation
+        self.send(Command::RecompileGraph);
+    }
+
+    /// Set a track's send level to an aux return bus (0.0 removes the send).
+    pub fn set_track_send(&mut self, track_id: crate::state::TrackId, aux_id: u32, level: f32) {
+        let had_send = self
+            .session
+            .arrangement
+            .get_track(track_id)
+            .is_some_and(|t| t.send_level(aux_id) > 0.0);
+        self.send(Command::SetTrackSend {
+            track_id,
+            aux_id,
+            level,
+        });
+        let has_send = self
+            .session
+            .arrangement
+            .get_track(track_id)
+            .is_some_and(|t| t.send_level(aux_id) > 0.0);
+
+        if had_send && has_send {
+            self.sync_track(track_id);
+        } else if had_send != has_send {
+            // Adding or removing a send changes routing
+            self.send(Command::RecompileGraph);
+        }
+    }
+
+    /// Insert an effect node on an aux return bus, or clear it with `None`.
+    pub fn set_aux_effect(&mut self, aux_id: u32, node_id: Option<NodeId>) {
+        self.send(Command::SetAuxEffect { aux_id, node_id });
+        self.send(Command::RecompileGraph);
+    }
+
+    // ───────────────────────────────────────────────────────────────
+    // Graph queries
+    // ───────────────────────────────────────────────────────────────
+
+    /// All connections in the graph, in the order they were made.
+    pub fn iter_connections(&self) -> Vec<ConnectionDef> {
+        self.session.graph.connections.clone()
+    }
+
+    /// Problems with the graph, checked without compiling it.
+    pub fn validate_graph(&self) -> Vec<GraphIssue> {
+        self.session.graph.validate()
+    }
+
+    /// Type of a node, or None if it doesn't exist.
+    pub fn node_type_of(&self, node_id: NodeId) -> Option<NodeTypeId> {
+        self.session
+            .graph
+            .get_node(node_id)
+            .map(|node| node.type_id)
+    }
+
+    // ───────────────────────────────────────────────────────────────
+    // Preset convenience methods
+    // ───────────────────────────────────────────────────────────────
+
+    /// Snapshot a node's parameter values as a preset.
+    pub fn capture_preset(&self, node_id: NodeId) -> Option<NodePreset> {
+        self.session.graph.get_node(node_id).map(NodePreset::capture)
+    }
+
+    /// Apply a preset to a node of the same type.
+    ///
+    /// Parameters the preset doesn't list return to their defaults, so the
+    /// node ends up as the preset was captured. Undo reverts the whole
+    /// preset in one step.
+    pub fn apply_preset(
+        &mut self,
+        node_id: NodeId,
+        preset: &NodePreset,
+    ) -> Result<(), PresetError> {
+        let node = self
+            .session
+            .graph
+            .get_node(node_id)
+            .ok_or(PresetError::NodeNotFound { node_id })?;
+        if node.type_id != preset.type_id {
+            return Err(PresetError::TypeMismatch {
+                node_id,
+                expected: preset.type_id,
+                found: node.type_id,
+            });
+        }
+
+        let type_id = node.type_id;
+        let mut values: Vec<(ParamId, f32)> = self
+            .param_infos
+            .iter()
+            .filter(|((t, p), _)| *t == type_id && !preset.param_values.contains_key(p))
+            .map(|(&(_, param_id), info)| (param_id, info.default))
+            .chain(preset.param_values.iter().map(|(&p, &v)| (p, v)))
+            .collect();
+        values.sort_by_key(|&(param_id, _)| param_id);
+
+        self.history.begin_group();
+        for (param_id, value) in values {
+            if self.param_value(node_id, param_id) != Some(value) {
+                self.set_param(node_id, param_id, value);
+            }
+        }
+        self.history.end_group();
+        Ok(())
+    }
+
+    // ───────────────────────────────────────────────────────────────
+    // Audio pool convenience methods
+    // ───────────────────────────────────────────────────────────────
+
+    /// Decode a WAV file into the audio pool.
+    ///
+    /// The audio keeps the file's native sample rate; playback resamples
+    /// using the rate recorded in the pool entry.
+    pub fn add_wav_to_pool(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+        name: impl Into<String>,
+    ) -> std::io::Result<crate::state::AudioPoolId> {
+        let (sample_rate, channels, samples) = crate::io::wav::import_wav(path)?;
+        Ok(self.session.arrangement.add_audio_to_pool(
+            name,
+            sample_rate as f64,
+            channels as usize,
+            samples,
+        ))
+    }
+
+    // ───────────────────────────────────────────────────────────────
+    // Recording convenience methods
+    // ───────────────────────────────────────────────────────────────
+
+    /// Start recording input onto an armed track at the playhead.
+    ///
+    /// Returns false if the track isn't armed or a take is already running.
+    pub fn start_recording(&mut self, track_id: crate::state::TrackId) -> bool {
+        let armed = self
+            .session
+            .arrangement
+            .get_track(track_id)
+            .is_some_and(|t| t.armed);
+        if !armed || self.session.arrangement.recording_track().is_some() {
+            return false;
+        }
+        self.send(Command::StartRecording { track_id });
+        true
+    }
+
+    /// Stop recording; the take is committed by `poll_recording`.
+    pub fn stop_recording(&mut self) {
+        self.send(Command::StopRecording);
+    }
+
+    /// Commit a take the engine has finished recording.
+    ///
+    /// The take goes into the audio pool as a clip on the recorded track.
+    /// Returns the clip, or `None` if no take has come back yet.
+    pub fn poll_recording(&mut self) -> Option<crate::state::ClipId> {
+        let samples = self.take_rx.try_recv().ok()?;
+        self.session.arrangement.stop_recording(
+            self.session.sample_rate,
+            INPUT_CHANNELS,
+            samples,
+            self.session.transport.bpm,
+        )
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -562,10 +1350,56 @@ impl EngineHandle {
         while let Ok(cmd) = self.command_rx.try_recv() {
             needs_recompile |= !self.engine.process_command(&cmd);
         }
+        if let Some(take) = self.engine.take_recording() {
+            let _ = self.take_tx.send(take);
+        }
 
         needs_recompile
     }
 
+    /// Take the most recent seek target requested via `Command::Seek`.
+    #[inline]
+    pub fn take_pending_seek(&mut self) -> Option<f64> {
+        self.engine.take_pending_seek()
+    }
+
+    /// Take the most recent tempo change requested via `Command::SetTempo`
+    /// or `Command::RampTempo`, as `(target_bpm, over_beats)`.
+    #[inline]
+    pub fn take_pending_tempo(&mut self) -> Option<(f64, f64)> {
+        self.engine.take_pending_tempo()
+    }
+
+    /// The loop region requested via `Command::SetLoop`, if enabled.
+    #[inline]
+    pub fn loop_region(&self) -> Option<(f64, f64)> {
+        self.engine.loop_region()
+    }
+
+    /// Metronome beats per bar requested via `Command::SetMetronome`, if on.
+    #[inline]
+    pub fn metronome(&self) -> Option<u32> {
+        self.engine.metronome()
+    }
+
+    /// Arpeggiator settings requested via `Command::SetArpeggiator`, if on.
+    #[inline]
+    pub fn arpeggiator(&self) -> Option<ArpSettings> {
+        self.engine.arpeggiator()
+    }
+
+    /// Notes held for the arpeggiator and the velocity of the last one.
+    #[inline]
+    pub fn held_notes(&self) -> ([u64; 2], f32) {
+        self.engine.held_notes()
+    }
+
+    /// Capture a block of interleaved input for the take being recorded.
+    #[inline]
+    pub fn push_input(&mut self, input: &[f32]) {
+        self.engine.push_input(input);
+    }
+
     /// Try to receive a single command (non-blocking).
     pub fn try_recv(&self) -> Option<Command> {
         self.command_rx.try_recv().ok()
@@ -582,10 +1416,35 @@ impl EngineHandle {
 
     /// Execute a precompiled execution plan.
     ///
-    /// Call this once per audio block from the audio callback.
+    /// Call this once per audio block from the audio callback. Also
+    /// measures how long the block took for the CPU load readback.
     #[inline]
     pub fn process_plan(&mut self, plan: &ExecutionPlan) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = std::time::Instant::now();
+
         self.engine.process_plan(plan);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_cpu_load(start.elapsed().as_secs_f64(), plan);
+    }
+
+    /// Fold one block's processing time into the smoothed CPU load.
+    ///
+    /// Load is processing time over the block's real-time duration,
+    /// capped at 1 (the block took as long as it lasts, or longer).
+    fn update_cpu_load(&self, elapsed_seconds: f64, plan: &ExecutionPlan) {
+        if plan.block_frames == 0 || plan.sample_rate <= 0.0 {
+            return;
+        }
+        let block_seconds = plan.block_frames as f64 / plan.sample_rate;
+        let load = (elapsed_seconds / block_seconds).min(1.0) as f32;
+        let coeff = 1.0 - (-block_seconds / CPU_LOAD_SMOOTHING_SECONDS).exp() as f32;
+
+        let slot = &self.readback.cpu_load_bits;
+        let previous = f32::from_bits(slot.load(Ordering::Relaxed));
+        let smoothed = previous + (load - previous) * coeff;
+        slot.store(smoothed.to_bits(), Ordering::Relaxed);
     }
 
     /// Get the output buffer after processing.
@@ -594,6 +1453,12 @@ impl EngineHandle {
         self.engine.output_buffer(frames)
     }
 
+    /// Channel count of the output buffer (0 when there is no output node).
+    #[inline]
+    pub fn output_channels(&self) -> usize {
+        self.engine.output_channels()
+    }
+
     /// Reset the engine (on transport stop/seek).
     pub fn reset(&mut self) {
         self.engine.reset();
@@ -666,6 +1531,36 @@ impl EngineHandle {
             .store(count as u64, Ordering::Relaxed);
     }
 
+    /// Update the output peak meters from the last processed block.
+    ///
+    /// Call once per processed chunk, after `process_plan`. Peaks rise
+    /// instantly and fall exponentially with a `PEAK_DECAY_SECONDS` time constant.
+    pub fn update_output_peaks(&self, frames: usize) {
+        let sample_rate = self.engine.graph().sample_rate;
+        let decay = if sample_rate > 0.0 {
+            (-(frames as f64) / (PEAK_DECAY_SECONDS * sample_rate)).exp() as f32
+        } else {
+            0.0
+        };
+
+        let mut block_peaks = [0.0f32; 2];
+        if let Some(output) = self.engine.output_buffer(frames) {
+            let channels = self.engine.output_channels();
+            for (ch, peak) in block_peaks.iter_mut().enumerate() {
+                let start = (ch % channels) * frames;
+                if let Some(samples) = output.get(start..start + frames) {
+                    *peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+                }
+            }
+        }
+
+        for (slot, block_peak) in self.readback.peak_bits.iter().zip(block_peaks) {
+            let previous = f32::from_bits(slot.load(Ordering::Relaxed));
+            let peak = block_peak.max(previous * decay);
+            slot.store(peak.to_bits(), Ordering::Relaxed);
+        }
+    }
+
     /// Sync readback state from engine.
     ///
     /// Call this at the end of each audio block to update UI-visible state.
@@ -673,6 +1568,14 @@ impl EngineHandle {
         self.readback
             .active_voices
             .store(self.engine.active_voices() as u64, Ordering::Relaxed);
+        for (slot, bits) in self
+            .readback
+            .active_notes
+            .iter()
+            .zip(self.engine.active_note_mask())
+        {
+            slot.store(bits, Ordering::Relaxed);
+        }
         self.readback
             .running
             .store(self.engine.is_playing(), Ordering::Relaxed);
@@ -683,3 +1586,549 @@ impl EngineHandle {
         self.readback.running.store(running, Ordering::Relaxed);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution_plan::ExecutionPlan;
+    use crate::plan_handoff::PlanHandoff;
+    use crate::scheduler::Scheduler;
+    use crate::voice_allocator::VoiceAllocator;
+
+    #[test]
+    fn test_beat_position_readback() {
+        let sample_rate = 48_000.0;
+        let mut graph = Graph::new(256, 4);
+        graph.prepare(sample_rate);
+        let engine = Engine::new(graph, VoiceAllocator::new(4));
+        let (session, engine) = create_bridge(Session::new("Readback"), engine);
+
+        let mut scheduler = Scheduler::new(sample_rate);
+        let mut handoff = PlanHandoff::new(
+            ExecutionPlan::new(sample_rate),
+            ExecutionPlan::new(sample_rate),
+        );
+
+        for _ in 0..10 {
+            scheduler.compile_block(&mut handoff, 256, &[]);
+            engine.update_sample_position(scheduler.sample_position());
+            engine.update_beat_position(scheduler.beat_position());
+        }
+
+        let readback = session.readback();
+        assert_eq!(readback.sample_position, 2560);
+        assert!((readback.beat_position - scheduler.beat_position()).abs() < 1e-9);
+        // 2560 samples at 120 BPM / 48 kHz = 0.1066.. beats
+        assert!((readback.beat_position - 2560.0 / 24_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_active_notes_readback() {
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, mut engine) = create_bridge(Session::new("Keys"), engine);
+
+        session.note_on(60, 0.8);
+        session.note_on(64, 0.8);
+        engine.process_commands();
+        assert!(session.active_notes().is_empty());
+        engine.sync_readback();
+        assert_eq!(session.active_notes(), [60, 64]);
+
+        session.note_off(60);
+        engine.process_commands();
+        engine.sync_readback();
+        assert_eq!(session.active_notes(), [64]);
+    }
+
+    #[test]
+    fn test_output_peak_readback() {
+        use crate::node_factory::NodeRegistry;
+        use crate::nodes::{node_types, params, register_standard_nodes};
+
+        let sample_rate = 48_000.0;
+        let block = 256;
+        let engine = Engine::new(Graph::new(block, 4), VoiceAllocator::new(4));
+        let (mut session, mut engine) = create_bridge(Session::new("Meters"), engine);
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        let osc = session.add_node(node_types::SINE_OSC, 0.0, 0.0);
+        let gain = session.add_node(node_types::GAIN, 0.0, 0.0);
+        let out = session.add_node(node_types::OUTPUT, 0.0, 0.0);
+        for (src, dst) in [(osc, gain), (gain, out)] {
+            session.send(Command::Connect {
+                source_node: src,
+                source_port: 0,
+                dest_node: dst,
+                dest_port: 0,
+            });
+        }
+        session.send(Command::SetOutputNode { node_id: out });
+        session.set_param(gain, params::GAIN, -6.0206); // 0.5 linear
+
+        let mut graph =
+            crate::compile::compile(&session.session().graph, &registry, block, 4).unwrap();
+        graph.prepare(sample_rate);
+        engine.swap_graph(graph);
+        engine.process_commands();
+
+        let mut scheduler = Scheduler::new(sample_rate);
+        let mut handoff = PlanHandoff::new(
+            ExecutionPlan::new(sample_rate),
+            ExecutionPlan::new(sample_rate),
+        );
+
+        session.note_on(69, 1.0);
+        for _ in 0..20 {
+            engine.process_commands();
+            scheduler.compile_block(&mut handoff, block, &[]);
+            engine.process_plan(handoff.read_plan());
+            engine.update_output_peaks(block);
+        }
+
+        let peaks = session.readback().output_peaks;
+        for peak in peaks {
+            assert!((peak - 0.5).abs() < 0.02, "peak {peak} != 0.5");
+        }
+
+        // Meters fall back towards zero once the output goes silent
+        engine.reset();
+        for _ in 0..20 {
+            scheduler.compile_block(&mut handoff, block, &[]);
+            engine.process_plan(handoff.read_plan());
+            engine.update_output_peaks(block);
+        }
+        let decayed = session.readback().output_peaks;
+        assert!(decayed[0] < peaks[0] * 0.8);
+    }
+
+    #[test]
+    fn test_cpu_load_readback() {
+        use crate::node_factory::NodeRegistry;
+        use crate::nodes::{node_types, register_standard_nodes};
+
+        let sample_rate = 48_000.0;
+        let block = 256;
+        let engine = Engine::new(Graph::new(block, 16), VoiceAllocator::new(16));
+        let (mut session, mut engine) = create_bridge(Session::new("Load"), engine);
+        assert_eq!(session.readback().cpu_load, 0.0);
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        // Sixteen voices through a ladder filter into a reverb
+        let osc = session.add_node(node_types::SAW_OSC, 0.0, 0.0);
+        let filter = session.add_node(node_types::LADDER, 0.0, 0.0);
+        let reverb = session.add_node(node_types::REVERB, 0.0, 0.0);
+        let out = session.add_node(node_types::OUTPUT, 0.0, 0.0);
+        for (src, dst) in [(osc, filter), (filter, reverb), (reverb, out)] {
+            session.send(Command::Connect {
+                source_node: src,
+                source_port: 0,
+                dest_node: dst,
+                dest_port: 0,
+            });
+        }
+        session.send(Command::SetOutputNode { node_id: out });
+
+        let mut graph =
+            crate::compile::compile(&session.session().graph, &registry, block, 16).unwrap();
+        graph.prepare(sample_rate);
+        engine.swap_graph(graph);
+
+        let mut scheduler = Scheduler::new(sample_rate);
+        let mut handoff = PlanHandoff::new(
+            ExecutionPlan::new(sample_rate),
+            ExecutionPlan::new(sample_rate),
+        );
+
+        for note in 40..56 {
+            session.note_on(note, 1.0);
+        }
+        for _ in 0..50 {
+            engine.process_commands();
+            scheduler.compile_block(&mut handoff, block, &[]);
+            engine.process_plan(handoff.read_plan());
+        }
+
+        let load = session.readback().cpu_load;
+        assert!(load > 0.0 && load <= 1.0, "cpu load {load}");
+    }
+
+    #[test]
+    fn test_control_change_maps_to_param_range() {
+        use crate::node_factory::NodeRegistry;
+        use crate::nodes::{node_types, params, register_standard_nodes};
+
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, engine) = create_bridge(Session::new("CC"), engine);
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+        let info = registry
+            .get_info(node_types::GAIN)
+            .and_then(|t| t.find_param(params::GAIN))
+            .unwrap()
+            .clone();
+
+        let gain = session.add_node(node_types::GAIN, 0.0, 0.0);
+        session.map_control_change(1, gain, &info);
+        while engine.command_rx.try_recv().is_ok() {}
+
+        let gain_value = |session: &SessionHandle| {
+            session.session().graph.get_node(gain).unwrap().param_values[&params::GAIN]
+        };
+
+        // Mod wheel at 75% lands three quarters of the way through -60..12 dB
+        session.control_change(1, 0.75);
+        assert!((gain_value(&session) - (-6.0)).abs() < 1e-4);
+        match engine.command_rx.try_recv() {
+            Ok(Command::SetParam {
+                node_id,
+                param_id,
+                value,
+            }) => {
+                assert_eq!((node_id, param_id), (gain, params::GAIN));
+                assert!((value - (-6.0)).abs() < 1e-4);
+            }
+            other => panic!("expected SetParam, got {other:?}"),
+        }
+
+        // Out-of-range CC values stay inside the declared range
+        session.control_change(1, 1.5);
+        assert_eq!(gain_value(&session), info.max);
+        session.control_change(1, -1.0);
+        assert_eq!(gain_value(&session), info.min);
+        while engine.command_rx.try_recv().is_ok() {}
+
+        // Unmapped CCs are dropped
+        session.control_change(7, 0.5);
+        session.unmap_control_change(1);
+        session.control_change(1, 0.5);
+        assert_eq!(gain_value(&session), info.min);
+        assert!(matches!(
+            engine.command_rx.try_recv(),
+            Ok(Command::UnmapControlChange { cc: 1 })
+        ));
+        assert!(engine.command_rx.try_recv().is_err());
+
+        // Controller moves aren't recorded, so undo goes straight to the
+        // node's creation
+        assert!(session.undo());
+        assert!(session.session().graph.get_node(gain).is_none());
+        assert!(!session.can_undo());
+    }
+
+    #[test]
+    fn test_wav_round_trip_into_pool() {
+        use crate::io::wav::{WavFormat, export_wav_as};
+
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, _engine) = create_bridge(Session::new("Import"), engine);
+
+        // Stereo at 44.1 kHz, while the session runs at 48 kHz
+        let samples: Vec<f32> = (0..2 * 441)
+            .map(|i| ((i / 2) as f32 * 0.07).sin() * if i % 2 == 0 { 0.8 } else { -0.3 })
+            .collect();
+        let path = std::env::temp_dir().join(format!("hyasynth-import-{}.wav", std::process::id()));
+
+        for (format, tolerance) in [(WavFormat::Float32, 0.0), (WavFormat::Pcm16, 1e-4)] {
+            export_wav_as(&path, &samples, 2, 44_100, format).unwrap();
+            let id = session.add_wav_to_pool(&path, "loop.wav").unwrap();
+
+            let entry = session.session().arrangement.get_audio(id).unwrap();
+            assert_eq!(entry.name, "loop.wav");
+            assert_eq!(entry.sample_rate, 44_100.0);
+            assert_eq!(entry.channels, 2);
+            assert_eq!(entry.frames, 441);
+            for (a, b) in entry.samples.iter().zip(&samples) {
+                assert!((a - b).abs() <= tolerance, "{a} != {b}");
+            }
+        }
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(session.add_wav_to_pool(&path, "missing.wav").is_err());
+    }
+
+    #[test]
+    fn test_recording_commits_take_to_pool() {
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, mut engine) = create_bridge(Session::new("Record"), engine);
+        let track = session.create_track("Vocals");
+
+        // Input before a take starts is dropped, and unarmed tracks refuse
+        engine.push_input(&[0.5; 2 * 64]);
+        assert!(!session.start_recording(track));
+
+        session.set_track_armed(track, true);
+        assert!(session.start_recording(track));
+        assert!(!session.start_recording(track));
+        engine.process_commands();
+
+        let block: Vec<f32> = (0..2 * 256).map(|i| (i as f32 * 0.01).sin()).collect();
+        for _ in 0..10 {
+            engine.push_input(&block);
+        }
+        assert_eq!(session.poll_recording(), None);
+
+        session.stop_recording();
+        engine.process_commands();
+        engine.push_input(&block);
+        let clip_id = session.poll_recording().unwrap();
+
+        let arrangement = &session.session().arrangement;
+        assert_eq!(arrangement.recording_track(), None);
+        let entry = arrangement.audio_pool.iter().next().unwrap();
+        assert_eq!(entry.name, "Vocals Take");
+        assert_eq!(entry.channels, 2);
+        assert_eq!(entry.frames, 10 * 256);
+        assert_eq!(&entry.samples[..block.len()], &block[..]);
+
+        // 2560 frames at 120 BPM / 48 kHz
+        let clip = arrangement.get_clip(clip_id).unwrap();
+        assert!((clip.length - 2560.0 / 24_000.0).abs() < 1e-9);
+        let placements = &arrangement.timeline[&track];
+        assert_eq!(placements.len(), 1);
+        assert_eq!(placements[0].clip_id, clip_id);
+    }
+
+    #[test]
+    fn test_set_param_clamps_to_declared_range() {
+        use crate::nodes::{node_types, params};
+
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, engine) = create_bridge(Session::new("Ranges"), engine);
+        let osc = session.add_node(node_types::SINE_OSC, 0.0, 0.0);
+
+        // A custom registry without the oscillator lets the value through
+        session.set_param_ranges(&NodeRegistry::new());
+        assert_eq!(session.set_param(osc, params::FREQ, 50_000.0), 50_000.0);
+
+        // New sessions clamp to the standard nodes' ranges
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+        session.set_param_ranges(&registry);
+        assert_eq!(session.set_param(osc, params::FREQ, 50_000.0), 20_000.0);
+        assert_eq!(session.set_param(osc, params::FREQ, 1.0), 20.0);
+        session.send(Command::SetParam {
+            node_id: osc,
+            param_id: params::FREQ,
+            value: 50_000.0,
+        });
+
+        let node = session.session().graph.get_node(osc).unwrap();
+        assert_eq!(node.param_values[&params::FREQ], 20_000.0);
+        let sent: Vec<f32> = std::iter::from_fn(|| engine.try_recv())
+            .filter_map(|cmd| match cmd {
+                Command::SetParam { value, .. } => Some(value),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sent, [50_000.0, 20_000.0, 20.0, 20_000.0]);
+
+        // Clamping is reported, and non-finite values never reach the node
+        let results = session.poll_results();
+        assert!(matches!(
+            results[0],
+            CommandResult::ParamClamped {
+                requested: 50_000.0,
+                value: 20_000.0,
+                ..
+            }
+        ));
+        assert_eq!(session.set_param(osc, params::FREQ, f32::NAN), 20_000.0);
+        assert!(engine.try_recv().is_none());
+        assert!(matches!(
+            session.poll_results()[..],
+            [CommandResult::Error { .. }]
+        ));
+
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, _engine) = create_bridge(Session::new("Defaults"), engine);
+        let osc = session.add_node(node_types::SINE_OSC, 0.0, 0.0);
+        assert_eq!(session.set_param(osc, params::FREQ, 50_000.0), 20_000.0);
+    }
+
+    #[test]
+    fn test_preset_capture_and_apply() {
+        use crate::nodes::{node_types, params};
+
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, _engine) = create_bridge(Session::new("Presets"), engine);
+
+        let env = session.add_node(node_types::ADSR_ENV, 0.0, 0.0);
+        session.set_param(env, params::ATTACK, 0.25);
+        session.set_param(env, params::DECAY, 0.4);
+        session.set_param(env, params::SUSTAIN, 0.6);
+        session.set_param(env, params::RELEASE, 1.5);
+        let preset = session.capture_preset(env).unwrap();
+        assert_eq!(preset.type_id, node_types::ADSR_ENV);
+
+        let fresh = session.add_node(node_types::ADSR_ENV, 0.0, 0.0);
+        session.apply_preset(fresh, &preset).unwrap();
+        let graph = &session.session().graph;
+        assert_eq!(
+            graph.get_node(fresh).unwrap().param_values,
+            graph.get_node(env).unwrap().param_values
+        );
+
+        // Presets only apply to nodes of the type they came from
+        let osc = session.add_node(node_types::SINE_OSC, 0.0, 0.0);
+        assert_eq!(
+            session.apply_preset(osc, &preset),
+            Err(PresetError::TypeMismatch {
+                node_id: osc,
+                expected: node_types::ADSR_ENV,
+                found: node_types::SINE_OSC,
+            })
+        );
+        assert!(session.session().graph.get_node(osc).unwrap().param_values.is_empty());
+        assert_eq!(
+            session.apply_preset(999, &preset),
+            Err(PresetError::NodeNotFound { node_id: 999 })
+        );
+    }
+
+    #[test]
+    fn test_apply_preset_resets_unlisted_params_in_one_undo_step() {
+        use crate::nodes::{node_types, params};
+
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, _engine) = create_bridge(Session::new("Presets"), engine);
+
+        let source = session.add_node(node_types::ADSR_ENV, 0.0, 0.0);
+        session.set_param(source, params::ATTACK, 0.25);
+        let preset = session.capture_preset(source).unwrap();
+
+        let tweaked = session.add_node(node_types::ADSR_ENV, 0.0, 0.0);
+        session.set_param(tweaked, params::ATTACK, 1.0);
+        session.set_param(tweaked, params::DECAY, 2.0);
+        let default_decay = session.param_infos[&(node_types::ADSR_ENV, params::DECAY)].default;
+
+        session.apply_preset(tweaked, &preset).unwrap();
+        assert_eq!(session.param_value(tweaked, params::ATTACK), Some(0.25));
+        assert_eq!(session.param_value(tweaked, params::DECAY), Some(default_decay));
+
+        assert!(session.undo());
+        assert_eq!(session.param_value(tweaked, params::ATTACK), Some(1.0));
+        assert_eq!(session.param_value(tweaked, params::DECAY), Some(2.0));
+    }
+
+    #[test]
+    fn test_undo_redo_graph_edits() {
+        use crate::compile::compile;
+        use crate::node_factory::NodeRegistry;
+        use crate::nodes::{node_types, params, register_standard_nodes};
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, mut engine) = create_bridge(Session::new("Undo"), engine);
+
+        let out = session.add_node(node_types::OUTPUT, 0.0, 0.0);
+        session.send(Command::SetOutputNode { node_id: out });
+        let compiled_nodes = |session: &SessionHandle| {
+            compile(&session.session().graph, &registry, 256, 4)
+                .unwrap()
+                .id_to_index
+                .len()
+        };
+        let before = compiled_nodes(&session);
+        engine.process_commands();
+
+        let osc = session.add_node(node_types::SINE_OSC, 10.0, 20.0);
+        session.send(Command::Connect {
+            source_node: osc,
+            source_port: 0,
+            dest_node: out,
+            dest_port: 0,
+        });
+        assert_eq!(session.session().graph.nodes.len(), 2);
+
+        // Undo the connection, then the node
+        assert!(session.undo());
+        assert!(session.session().graph.connections.is_empty());
+        assert!(session.undo());
+        assert_eq!(session.session().graph.nodes.len(), 1);
+        assert_eq!(compiled_nodes(&session), before);
+        // The engine is told to recompile
+        assert!(engine.process_commands());
+
+        // Redo brings the node back under the same ID
+        assert!(session.redo());
+        assert!(session.redo());
+        assert!(!session.can_redo());
+        let graph = &session.session().graph;
+        assert_eq!(graph.get_node(osc).unwrap().position, (10.0, 20.0));
+        assert!(graph.is_connected(osc, 0, out, 0));
+        assert_eq!(compiled_nodes(&session), before + 1);
+
+        // A parameter drag is one step back to the value before it
+        session.set_param(osc, params::FREQ, 220.0);
+        session.send(Command::BeginParamGesture {
+            node_id: osc,
+            param_id: params::FREQ,
+        });
+        for freq in [300.0, 400.0, 500.0] {
+            session.set_param(osc, params::FREQ, freq);
+        }
+        session.send(Command::EndParamGesture {
+            node_id: osc,
+            param_id: params::FREQ,
+        });
+        let freq = |session: &SessionHandle| {
+            session.session().graph.get_node(osc).unwrap().param_values[&params::FREQ]
+        };
+        assert!(session.undo());
+        assert_eq!(freq(&session), 220.0);
+        assert!(session.redo());
+        assert_eq!(freq(&session), 500.0);
+
+        // Undoing the first set returns the parameter to its default with
+        // a plain parameter change, so the engine needn't recompile
+        engine.process_commands();
+        assert!(session.undo());
+        assert!(session.undo());
+        assert_eq!(freq(&session), 440.0);
+        assert!(!engine.process_commands());
+    }
+
+    #[test]
+    fn test_graph_introspection() {
+        use crate::nodes::node_types;
+
+        let engine = Engine::new(Graph::new(256, 4), VoiceAllocator::new(4));
+        let (mut session, _engine) = create_bridge(Session::new("Patch"), engine);
+
+        let osc = session.add_node(node_types::SAW_OSC, 0.0, 0.0);
+        let filter = session.add_node(node_types::LOWPASS, 100.0, 0.0);
+        let out = session.add_node(node_types::OUTPUT, 200.0, 0.0);
+        let wired = [(osc, 0, filter, 0), (filter, 0, out, 0), (osc, 0, out, 1)];
+        for (source_node, source_port, dest_node, dest_port) in wired {
+            session.send(Command::Connect {
+                source_node,
+                source_port,
+                dest_node,
+                dest_port,
+            });
+        }
+
+        let edges: Vec<_> = session
+            .iter_connections()
+            .iter()
+            .map(|c| (c.source_node, c.source_port, c.dest_node, c.dest_port))
+            .collect();
+        assert_eq!(edges, wired);
+
+        assert_eq!(session.node_type_of(osc), Some(node_types::SAW_OSC));
+        assert_eq!(session.node_type_of(filter), Some(node_types::LOWPASS));
+        assert_eq!(session.node_type_of(out), Some(node_types::OUTPUT));
+        assert_eq!(session.node_type_of(out + 1), None);
+
+        session.remove_node(filter);
+        assert_eq!(session.iter_connections().len(), 1);
+        assert_eq!(session.node_type_of(filter), None);
+    }
+}
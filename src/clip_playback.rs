@@ -8,13 +8,21 @@
 // - Track which clips are playing on which tracks
 // - Generate note events from MIDI content in clips
 // - Generate audio playback events from audio regions in clips
+// - Sample automation lanes into parameter changes
 // - Handle clip looping
+// - Hold quantized launches until their grid line
+// - Apply swing to off-beat notes
 // - Track active notes for proper note-off generation
+// - Release outgoing notes and fade outgoing audio when clips switch
 
 use std::collections::HashMap;
 
 use crate::event::MusicalEvent;
-use crate::state::{Arrangement, AudioPool, ClipDef, ClipId, NoteDef, TrackId};
+use crate::state::{
+    Arrangement, AudioPool, AudioPoolId, ClipDef, ClipId, LaunchQuantization, NoteDef, TrackDef,
+    TrackId,
+};
+use crate::util::convert::beats_to_samples;
 
 /// Unique identifier for an active note (for tracking note-offs).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -33,6 +41,23 @@ struct ActiveNoteState {
     end_beat: f64,
 }
 
+/// Audio region still playing, so it can be stopped if its clip is.
+#[derive(Debug, Clone, Copy)]
+struct ActiveAudioState {
+    track_id: TrackId,
+    node_id: u32,
+    audio_id: AudioPoolId,
+    /// The beat position when the region finishes on its own.
+    end_beat: f64,
+}
+
+/// Something an outgoing clip left playing, waiting to be released.
+#[derive(Debug, Clone, Copy)]
+enum Release {
+    Note { node_id: u32, note: u8 },
+    Audio { node_id: u32, audio_id: AudioPoolId },
+}
+
 /// State for an actively playing clip on a track.
 #[derive(Debug, Clone)]
 struct PlayingClip {
@@ -55,6 +80,107 @@ impl PlayingClip {
     }
 }
 
+/// Swing grid in beats (8th notes).
+const SWING_SUBDIVISION: f64 = 0.5;
+
+/// How far a note at `position` (in clip beats) is delayed by `swing`.
+///
+/// Only notes sitting exactly on an off-beat subdivision move, by up to
+/// half a subdivision.
+fn swing_delay(position: f64, swing: f32) -> f64 {
+    if swing <= 0.0 {
+        return 0.0;
+    }
+    let step = position / SWING_SUBDIVISION;
+    let nearest = step.round();
+    if (step - nearest).abs() > 1e-6 || (nearest as i64).rem_euclid(2) == 0 {
+        return 0.0;
+    }
+    swing.min(1.0) as f64 * SWING_SUBDIVISION * 0.5
+}
+
+/// Random value in 0..1 for humanizing a note, fixed by the clip's seed.
+///
+/// Hashes the note itself rather than drawing from a running generator, so
+/// a note gets the same offset on every pass whatever the block sizes.
+fn humanize_random(seed: u32, note: &NoteDef, salt: u32) -> f64 {
+    let mut x = seed as u64 ^ note.start.to_bits() ^ ((note.note as u64) << 56) ^ salt as u64;
+    // splitmix64 finalizer
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// How a clip's notes are played: the arrangement's swing plus the clip's
+/// humanize, transpose and velocity scale.
+#[derive(Debug, Clone, Copy)]
+struct PlaybackSettings {
+    swing: f32,
+    humanize_timing: f64,
+    humanize_velocity: f32,
+    humanize_seed: u32,
+    transpose: i8,
+    velocity_scale: f32,
+}
+
+impl PlaybackSettings {
+    fn new(clip: &ClipDef, swing: f32) -> Self {
+        Self {
+            swing,
+            humanize_timing: clip.humanize_timing.max(0.0),
+            humanize_velocity: clip.humanize_velocity.max(0.0),
+            humanize_seed: clip.humanize_seed,
+            transpose: clip.transpose,
+            velocity_scale: clip.velocity_scale,
+        }
+    }
+
+    /// Where `note` starts in a clip of `length` beats once swung and
+    /// humanized. Both only delay notes, so a note never moves back into a
+    /// range that has already played; one pushed past the end stays put.
+    fn note_start(&self, note: &NoteDef, length: f64) -> f64 {
+        let humanize = self.humanize_timing * humanize_random(self.humanize_seed, note, 0);
+        let start = note.start + swing_delay(note.start, self.swing) + humanize;
+        if start < length { start } else { note.start }
+    }
+
+    /// `note` as it plays, transposed, with its velocity scaled and
+    /// humanized.
+    fn played(&self, note: &NoteDef) -> NoteDef {
+        let jitter = humanize_random(self.humanize_seed, note, 1) as f32 * 2.0 - 1.0;
+        let velocity = (note.velocity * self.velocity_scale).clamp(0.0, 1.0);
+        NoteDef {
+            note: (note.note as i16 + self.transpose as i16).clamp(0, 127) as u8,
+            velocity: (velocity + jitter * self.humanize_velocity).clamp(0.0, 1.0),
+            ..*note
+        }
+    }
+}
+
+/// Where a playing clip is over the range being generated.
+#[derive(Debug, Clone, Copy)]
+struct ClipWindow {
+    track_id: TrackId,
+    clip_id: ClipId,
+    /// Clip position at the start of the range.
+    clip_start: f64,
+    /// Clip position at the end of the range (unwrapped).
+    clip_end: f64,
+    /// Timeline beat the range starts on.
+    start_beat: f64,
+}
+
+/// A clip launch or stop waiting for its quantized start.
+#[derive(Debug, Clone, Copy)]
+struct PendingLaunch {
+    track_id: TrackId,
+    /// Clip to start, or None to stop the track.
+    clip_id: Option<ClipId>,
+    /// Beat position at which the switch happens.
+    beat: f64,
+}
+
 /// Clip playback engine.
 ///
 /// Maintains state about which clips are playing and generates events.
@@ -62,12 +188,25 @@ pub struct ClipPlayback {
     /// Currently playing clips (track_id -> PlayingClip).
     playing: HashMap<TrackId, PlayingClip>,
 
+    /// Launches and stops waiting for their quantized start.
+    pending: Vec<PendingLaunch>,
+
     /// Active notes that need note-off events.
     active_notes: Vec<ActiveNoteState>,
 
+    /// Audio regions still playing.
+    active_audio: Vec<ActiveAudioState>,
+
+    /// Notes and audio of clips switched out, sent as note-offs and
+    /// audio stops with the next events.
+    releases: Vec<Release>,
+
     /// Sample rate (for audio calculations).
     sample_rate: f64,
 
+    /// Bar length for launch quantization, from the transport's meter.
+    beats_per_bar: f64,
+
     /// Scratch buffer for generated events.
     event_buffer: Vec<MusicalEvent>,
 }
@@ -76,12 +215,21 @@ impl ClipPlayback {
     pub fn new(sample_rate: f64) -> Self {
         Self {
             playing: HashMap::new(),
+            pending: Vec::new(),
             active_notes: Vec::with_capacity(32),
+            active_audio: Vec::new(),
+            releases: Vec::new(),
             sample_rate,
+            beats_per_bar: 4.0,
             event_buffer: Vec::with_capacity(64),
         }
     }
 
+    /// Set the bar length launch quantization snaps to (4 in 4/4, 3 in 3/4).
+    pub fn set_beats_per_bar(&mut self, beats_per_bar: f64) {
+        self.beats_per_bar = beats_per_bar.max(1.0);
+    }
+
     /// Start playing a clip on a track.
     pub fn start_clip(&mut self, clip_id: ClipId, track_id: TrackId, current_beat: f64) {
         // Stop any currently playing clip on this track
@@ -93,35 +241,206 @@ impl ClipPlayback {
     }
 
     /// Stop the clip playing on a track.
+    ///
+    /// Its notes are released and its audio faded out at the start of the
+    /// next generated range.
     pub fn stop_track(&mut self, track_id: TrackId, _current_beat: f64) {
-        // Remove the playing clip
-        if let Some(playing) = self.playing.remove(&track_id) {
-            // Remove active notes from this clip
-            self.active_notes
-                .retain(|n| !(n.key.track_id == track_id && n.key.clip_id == playing.clip_id));
+        if self.playing.remove(&track_id).is_some() {
+            self.release_track(track_id);
         }
     }
 
     /// Stop all clips.
     pub fn stop_all(&mut self) {
         self.playing.clear();
+        self.pending.clear();
+        self.active_notes.clear();
+        self.active_audio.clear();
+        self.releases.clear();
+    }
+
+    /// Queue the notes and audio still playing on a track for release.
+    fn release_track(&mut self, track_id: TrackId) {
+        let releases = &mut self.releases;
+        self.active_notes.retain(|n| {
+            if n.key.track_id != track_id {
+                return true;
+            }
+            releases.push(Release::Note {
+                node_id: n.key.target_node,
+                note: n.key.note,
+            });
+            false
+        });
+        self.active_audio.retain(|a| {
+            if a.track_id != track_id {
+                return true;
+            }
+            releases.push(Release::Audio {
+                node_id: a.node_id,
+                audio_id: a.audio_id,
+            });
+            false
+        });
+    }
+
+    /// Send the queued releases at `beat`.
+    fn push_releases(&mut self, beat: f64) {
+        for release in self.releases.drain(..) {
+            self.event_buffer.push(match release {
+                Release::Note { node_id, note } => MusicalEvent::NoteOffTarget {
+                    beat,
+                    node_id,
+                    note,
+                },
+                Release::Audio { node_id, audio_id } => MusicalEvent::AudioStop {
+                    beat,
+                    node_id,
+                    audio_id,
+                },
+            });
+        }
+    }
+
+    /// Number of launches/stops waiting for their quantized start.
+    pub fn pending_launch_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Switch a track to `clip_id` (or stop it) at the next grid line.
+    ///
+    /// Launches that land on the current beat happen immediately.
+    fn request_launch(
+        &mut self,
+        track_id: TrackId,
+        clip_id: Option<ClipId>,
+        quantization: LaunchQuantization,
+        current_beat: f64,
+    ) {
+        let beat = quantization.next_boundary(current_beat, self.beats_per_bar);
+        if beat <= current_beat {
+            self.pending.retain(|p| p.track_id != track_id);
+            match clip_id {
+                Some(clip_id) => self.start_clip(clip_id, track_id, current_beat),
+                None => self.stop_track(track_id, current_beat),
+            }
+            return;
+        }
+
+        match self.pending.iter_mut().find(|p| p.track_id == track_id) {
+            // Re-requesting the same switch keeps its original start
+            Some(pending) if pending.clip_id == clip_id => {}
+            Some(pending) => {
+                *pending = PendingLaunch {
+                    track_id,
+                    clip_id,
+                    beat,
+                }
+            }
+            None => self.pending.push(PendingLaunch {
+                track_id,
+                clip_id,
+                beat,
+            }),
+        }
+    }
+
+    /// Perform the pending switches due at or before `beat`.
+    ///
+    /// Notes still sounding from the outgoing clip are released, and its
+    /// audio faded out, at the switch point.
+    fn apply_pending(&mut self, beat: f64) {
+        let mut i = 0;
+        while i < self.pending.len() {
+            let launch = self.pending[i];
+            if launch.beat > beat {
+                i += 1;
+                continue;
+            }
+            self.pending.swap_remove(i);
+
+            self.release_track(launch.track_id);
+            self.push_releases(launch.beat);
+
+            match launch.clip_id {
+                Some(clip_id) => {
+                    let clip = PlayingClip::new(clip_id, launch.track_id, launch.beat);
+                    self.playing.insert(launch.track_id, clip);
+                }
+                None => {
+                    self.playing.remove(&launch.track_id);
+                }
+            }
+        }
+    }
+
+    /// Re-sync playing clips to a new transport position.
+    ///
+    /// Pending note-offs from the old position are discarded (the engine
+    /// releases all voices on seek), and each clip's playhead is moved to
+    /// where it would be at `beat` had it kept playing since launch.
+    pub fn seek(&mut self, beat: f64, arrangement: &Arrangement) {
         self.active_notes.clear();
+        self.active_audio.clear();
+        self.releases.clear();
+
+        for playing in self.playing.values_mut() {
+            let mut position = (beat - playing.start_beat).max(0.0);
+
+            if let Some(clip) = arrangement.get_clip(playing.clip_id)
+                && clip.looping
+                && clip.length > 0.0
+            {
+                position %= clip.length;
+            }
+
+            playing.clip_position = position;
+        }
+    }
+
+    /// Jump playing clips back to the loop start when the transport wraps.
+    ///
+    /// Notes and audio still playing at the loop end are released at
+    /// `loop_start`, ahead of any that retrigger there.
+    ///
+    /// # Returns
+    /// The note-off and audio stop events (valid until next call).
+    pub fn wrap_loop(&mut self, loop_start: f64, arrangement: &Arrangement) -> &[MusicalEvent] {
+        self.event_buffer.clear();
+        for state in &self.active_notes {
+            self.event_buffer.push(MusicalEvent::NoteOffTarget {
+                beat: loop_start,
+                node_id: state.key.target_node,
+                note: state.key.note,
+            });
+        }
+        for audio in &self.active_audio {
+            self.event_buffer.push(MusicalEvent::AudioStop {
+                beat: loop_start,
+                node_id: audio.node_id,
+                audio_id: audio.audio_id,
+            });
+        }
+        self.seek(loop_start, arrangement);
+        &self.event_buffer
     }
 
     /// Sync playing clips with arrangement state.
     ///
     /// This updates internal state to match which clips are marked as playing
-    /// in the arrangement.
+    /// in the arrangement. With launch quantization on, changes are queued
+    /// and the current clips keep playing until the next grid line.
     pub fn sync_with_arrangement(&mut self, arrangement: &Arrangement, current_beat: f64) {
+        let quantization = arrangement.launch_quantization;
+
         // Find clips that should start
-        for (track_id, clip_id) in &arrangement.playing_clips {
-            if !self.playing.contains_key(track_id) {
-                self.start_clip(*clip_id, *track_id, current_beat);
-            } else if let Some(playing) = self.playing.get(track_id) {
-                // Check if a different clip should be playing
-                if playing.clip_id != *clip_id {
-                    self.start_clip(*clip_id, *track_id, current_beat);
-                }
+        for (&track_id, &clip_id) in &arrangement.playing_clips {
+            let current = self.playing.get(&track_id).map(|p| p.clip_id);
+            if current != Some(clip_id) {
+                self.request_launch(track_id, Some(clip_id), quantization, current_beat);
+            } else {
+                // Back to the clip already playing: drop any queued switch
+                self.pending.retain(|p| p.track_id != track_id);
             }
         }
 
@@ -134,8 +453,14 @@ impl ClipPlayback {
             .collect();
 
         for track_id in tracks_to_stop {
-            self.stop_track(track_id, current_beat);
+            self.request_launch(track_id, None, quantization, current_beat);
         }
+
+        // Queued launches on tracks that were stopped before they started
+        self.pending.retain(|p| {
+            arrangement.playing_clips.contains_key(&p.track_id)
+                || self.playing.contains_key(&p.track_id)
+        });
     }
 
     /// Generate events for a time range.
@@ -159,6 +484,36 @@ impl ClipPlayback {
     ) -> &[MusicalEvent] {
         self.event_buffer.clear();
 
+        // Clips switched since the last call go quiet before anything new
+        // starts
+        self.push_releases(start_beat);
+
+        // Split the range at queued launches so each switch lands exactly
+        // on its grid line
+        let mut segment_start = start_beat;
+        loop {
+            self.apply_pending(segment_start);
+            let segment_end = self.pending.iter().map(|p| p.beat).fold(end_beat, f64::min);
+
+            self.generate_range(arrangement, segment_start, segment_end, bpm);
+
+            if segment_end >= end_beat {
+                break;
+            }
+            segment_start = segment_end;
+        }
+
+        &self.event_buffer
+    }
+
+    /// Append the events for one stretch of time with no clip switches.
+    fn generate_range(
+        &mut self,
+        arrangement: &Arrangement,
+        start_beat: f64,
+        end_beat: f64,
+        bpm: f64,
+    ) {
         let beat_duration = end_beat - start_beat;
 
         // Collect track IDs to process (to avoid borrow conflicts)
@@ -183,27 +538,30 @@ impl ClipPlayback {
                 continue;
             }
 
-            // Get target node for this track
-            let Some(target_node) = track.target_node else {
+            // Skip tracks with nowhere to send their events
+            if track.target_node.is_none() && track.key_zones.is_empty() {
                 continue;
-            };
+            }
 
             // Capture values needed for event generation
-            let clip_id = playing.clip_id;
-            let clip_position = playing.clip_position;
+            let window = ClipWindow {
+                track_id,
+                clip_id: playing.clip_id,
+                clip_start: playing.clip_position,
+                clip_end: playing.clip_position + beat_duration,
+                start_beat,
+            };
             let clip_length = clip.length;
             let clip_looping = clip.looping;
+            let settings = PlaybackSettings::new(clip, arrangement.swing);
 
             // Generate events from this clip
             self.generate_clip_events_inline(
-                track_id,
-                clip_id,
-                clip_position,
+                &window,
                 clip,
-                target_node,
+                track,
                 &arrangement.audio_pool,
-                start_beat,
-                end_beat,
+                &settings,
                 bpm,
             );
 
@@ -221,24 +579,21 @@ impl ClipPlayback {
         // Generate note-offs for notes that end in this range
         self.generate_note_offs(start_beat, end_beat);
 
-        &self.event_buffer
+        // Regions that finish on their own need no stop
+        self.active_audio.retain(|a| a.end_beat > end_beat);
     }
 
     /// Generate events from a single clip (inline version to avoid borrow issues).
     fn generate_clip_events_inline(
         &mut self,
-        track_id: TrackId,
-        clip_id: ClipId,
-        clip_position: f64,
+        window: &ClipWindow,
         clip: &ClipDef,
-        target_node: u32,
+        track: &TrackDef,
         audio_pool: &AudioPool,
-        start_beat: f64,
-        end_beat: f64,
+        settings: &PlaybackSettings,
         bpm: f64,
     ) {
-        let clip_start = clip_position;
-        let clip_end = clip_position + (end_beat - start_beat);
+        let clip_start = window.clip_start;
 
         // Handle non-looping clips that have ended
         if !clip.looping && clip_start >= clip.length {
@@ -247,49 +602,64 @@ impl ClipPlayback {
 
         // Generate note events
         for note_def in clip.notes() {
-            self.generate_note_event_inline(
-                track_id,
-                clip_id,
-                clip_position,
-                note_def,
-                target_node,
-                clip,
-                clip_start,
-                clip_end,
-                start_beat,
-            );
+            self.generate_note_event_inline(window, note_def, track, clip, settings);
         }
 
-        // Generate audio events
-        for audio_def in clip.audio_regions() {
-            self.generate_audio_event_inline(
-                audio_def,
-                target_node,
-                audio_pool,
-                clip,
-                clip_start,
-                clip_end,
-                start_beat,
-                bpm,
-            );
+        // Generate audio events; key zones only split notes, so audio
+        // always plays on the track's target
+        if let Some(target_node) = track.target_node {
+            for audio_def in clip.audio_regions() {
+                self.generate_audio_event_inline(
+                    window,
+                    audio_def,
+                    target_node,
+                    audio_pool,
+                    clip,
+                    bpm,
+                );
+            }
+        }
+
+        // Sample automation at the start of the range; the scheduler applies
+        // it at the slice boundary, so lanes move in block-sized steps
+        let lane_position = if clip.looping {
+            clip_start % clip.length
+        } else {
+            clip_start
+        };
+        for lane in &clip.automation {
+            if let Some(value) = lane.value_at(lane_position) {
+                self.event_buffer.push(MusicalEvent::ParamChange {
+                    beat: window.start_beat,
+                    node_id: lane.node_id,
+                    param_id: lane.param_id,
+                    value,
+                });
+            }
         }
     }
 
     /// Generate a note event if it falls within the time range.
     fn generate_note_event_inline(
         &mut self,
-        track_id: TrackId,
-        clip_id: ClipId,
-        _clip_position: f64,
+        window: &ClipWindow,
         note: &NoteDef,
-        target_node: u32,
+        track: &TrackDef,
         clip: &ClipDef,
-        clip_start: f64,
-        clip_end: f64,
-        block_start_beat: f64,
+        settings: &PlaybackSettings,
     ) {
-        // Check if note starts in this range
-        let note_start = note.start;
+        let ClipWindow {
+            track_id,
+            clip_id,
+            clip_start,
+            clip_end,
+            start_beat: block_start_beat,
+        } = *window;
+
+        // Check if note starts in this range. Swing and humanizing move the
+        // whole note, so its note-off (scheduled from the note-on beat) stays
+        // in step
+        let note_start = settings.note_start(note, clip.length);
 
         // Handle looping: check if note should trigger
         let should_trigger = if clip.looping {
@@ -323,40 +693,57 @@ impl ClipPlayback {
 
             let absolute_beat = block_start_beat + offset_in_block;
 
-            // Generate note-on
-            self.event_buffer.push(MusicalEvent::NoteOnTarget {
-                beat: absolute_beat,
-                node_id: target_node,
-                note: note.note,
-                velocity: note.velocity,
-            });
+            // Looping clips cut notes off at the loop end, so a note is never
+            // still held when the next pass retriggers it
+            let duration = if clip.looping {
+                note.duration.min(clip.length - note_start)
+            } else {
+                note.duration
+            };
+            let end_beat = absolute_beat + duration;
+
+            let note = settings.played(note);
+            for (target_node, played) in track.note_targets(note.note) {
+                // Generate note-on
+                self.event_buffer.push(MusicalEvent::NoteOnTarget {
+                    beat: absolute_beat,
+                    node_id: target_node,
+                    note: played,
+                    velocity: note.velocity,
+                });
 
-            // Track this note for note-off generation
-            let end_beat = absolute_beat + note.duration;
-            self.active_notes.push(ActiveNoteState {
-                key: ActiveNote {
-                    track_id,
-                    clip_id,
-                    target_node,
-                    note: note.note,
-                },
-                end_beat,
-            });
+                // Track this note for note-off generation
+                self.active_notes.push(ActiveNoteState {
+                    key: ActiveNote {
+                        track_id,
+                        clip_id,
+                        target_node,
+                        note: played,
+                    },
+                    end_beat,
+                });
+            }
         }
     }
 
     /// Generate an audio playback event if it falls within the time range.
     fn generate_audio_event_inline(
         &mut self,
+        window: &ClipWindow,
         audio_def: &crate::state::AudioRegionDef,
         target_node: u32,
         audio_pool: &AudioPool,
         clip: &ClipDef,
-        clip_start: f64,
-        clip_end: f64,
-        block_start_beat: f64,
         bpm: f64,
     ) {
+        let ClipWindow {
+            track_id,
+            clip_start,
+            clip_end,
+            start_beat: block_start_beat,
+            ..
+        } = *window;
+
         // Get audio info
         let Some(audio_entry) = audio_pool.get(audio_def.audio_id) else {
             return;
@@ -392,13 +779,14 @@ impl ClipPlayback {
 
             let absolute_beat = block_start_beat + offset_in_block;
 
-            // Convert beats to samples
-            let beat_to_seconds = 60.0 / bpm;
-            let source_offset_seconds = audio_def.source_offset * beat_to_seconds;
-            let duration_seconds = audio_def.duration * beat_to_seconds;
+            // Varispeed reads the source faster (or slower) over the same
+            // stretch of timeline
+            let speed = 2f64.powf(audio_def.pitch_shift as f64 / 12.0);
 
-            let start_sample = (source_offset_seconds * audio_entry.sample_rate) as u64;
-            let duration_samples = (duration_seconds * audio_entry.sample_rate) as u64;
+            let source_rate = audio_entry.sample_rate;
+            let start_sample = beats_to_samples(audio_def.source_offset, bpm, source_rate) as u64;
+            let duration_samples =
+                (beats_to_samples(audio_def.duration, bpm, source_rate) * speed) as u64;
 
             self.event_buffer.push(MusicalEvent::AudioStart {
                 beat: absolute_beat,
@@ -407,20 +795,30 @@ impl ClipPlayback {
                 start_sample,
                 duration_samples,
                 gain: audio_def.gain,
+                pitch_shift: audio_def.pitch_shift,
+            });
+            self.active_audio.push(ActiveAudioState {
+                track_id,
+                node_id: target_node,
+                audio_id: audio_def.audio_id,
+                end_beat: absolute_beat + audio_def.duration,
             });
         }
     }
 
-    /// Generate note-off events for notes that end in this range.
+    /// Generate note-off events for notes that end before `end_beat`.
+    ///
+    /// Notes that were due before `start_beat` (a gap between ranges) are
+    /// released at its start instead of being left to hang.
     fn generate_note_offs(&mut self, start_beat: f64, end_beat: f64) {
-        // Partition: notes ending in this range vs notes to keep
+        // Partition: notes ending by this range vs notes to keep
         let mut i = 0;
         while i < self.active_notes.len() {
             let state = &self.active_notes[i];
-            if state.end_beat >= start_beat && state.end_beat < end_beat {
+            if state.end_beat < end_beat {
                 // Generate note-off at the correct beat
                 self.event_buffer.push(MusicalEvent::NoteOffTarget {
-                    beat: state.end_beat,
+                    beat: state.end_beat.max(start_beat),
                     node_id: state.key.target_node,
                     note: state.key.note,
                 });
@@ -463,7 +861,9 @@ impl ClipPlayback {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state::{ClipDef, NoteDef};
+    use crate::state::{
+        AudioRegionDef, AutomationLane, ClipDef, KeyZone, LaunchQuantization, NoteDef,
+    };
 
     fn make_test_arrangement() -> Arrangement {
         let mut arr = Arrangement::new();
@@ -514,4 +914,500 @@ mod tests {
 
         assert!(!note_ons.is_empty(), "Should generate note-on events");
     }
+
+    #[test]
+    fn test_seek_flushes_stale_note_offs() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Seek Track");
+        arr.set_track_target(track_id, Some(100));
+        let clip_id = arr.create_clip("Seek Clip", 8.0);
+        if let Some(clip) = arr.get_clip_mut(clip_id) {
+            clip.looping = false;
+            clip.add_note(NoteDef::new(0.0, 2.0, 60, 0.8)); // ends at beat 2
+            clip.add_note(NoteDef::new(4.0, 1.0, 67, 0.8));
+        }
+        arr.launch_clip(track_id, clip_id);
+
+        let mut playback = ClipPlayback::new(48000.0);
+        playback.sync_with_arrangement(&arr, 0.0);
+        playback.generate_events(&arr, 0.0, 1.0, 120.0);
+        assert_eq!(playback.active_note_count(), 1);
+
+        // Seek past the note's start; its pending note-off at beat 2 is stale
+        playback.seek(1.5, &arr);
+        assert_eq!(playback.active_note_count(), 0);
+
+        let events = playback.generate_events(&arr, 1.5, 2.5, 120.0);
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e, MusicalEvent::NoteOffTarget { note: 60, .. })),
+            "stale note-off fired after seek"
+        );
+
+        // The clip playhead followed the seek, so the note at clip beat 4
+        // fires at absolute beat 4.
+        let events = playback.generate_events(&arr, 2.5, 4.5, 120.0);
+        let note_on_beat = events.iter().find_map(|e| match e {
+            MusicalEvent::NoteOnTarget { beat, note: 67, .. } => Some(*beat),
+            _ => None,
+        });
+        assert_eq!(note_on_beat, Some(4.0));
+    }
+
+    #[test]
+    fn test_automation_lane_ramps_param() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Automated");
+        arr.set_track_target(track_id, Some(100));
+        let clip_id = arr.create_clip("Ramp", 8.0);
+        if let Some(clip) = arr.get_clip_mut(clip_id) {
+            clip.looping = false;
+            clip.add_automation(
+                AutomationLane::new(7, 0)
+                    .with_point(0.0, 0.0)
+                    .with_point(4.0, 1.0),
+            );
+        }
+        arr.launch_clip(track_id, clip_id);
+
+        let mut playback = ClipPlayback::new(48000.0);
+        playback.sync_with_arrangement(&arr, 0.0);
+
+        let mut values = Vec::new();
+        for block in 0..12 {
+            let start = block as f64 * 0.5;
+            for event in playback.generate_events(&arr, start, start + 0.5, 120.0) {
+                if let MusicalEvent::ParamChange {
+                    beat,
+                    node_id,
+                    param_id,
+                    value,
+                } = *event
+                {
+                    assert_eq!((beat, node_id, param_id), (start, 7, 0));
+                    values.push(value);
+                }
+            }
+        }
+
+        // One change per block: a rising ramp that holds once it reaches 1
+        assert_eq!(values.len(), 12);
+        assert_eq!(values[0], 0.0);
+        assert!(values[..9].windows(2).all(|w| w[1] > w[0]), "{values:?}");
+        assert!((values[4] - 0.5).abs() < 1e-6);
+        assert!(values[8..].iter().all(|&v| v == 1.0));
+    }
+
+    #[test]
+    fn test_launch_quantization_waits_for_downbeat() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Quantized");
+        arr.set_track_target(track_id, Some(100));
+        let verse = arr.create_clip("Verse", 1.0);
+        let chorus = arr.create_clip("Chorus", 4.0);
+        arr.add_note_to_clip(verse, NoteDef::new(0.0, 0.25, 60, 0.8));
+        arr.add_note_to_clip(chorus, NoteDef::new(0.0, 1.0, 72, 0.8));
+
+        // The first launch at beat 0 is already on the grid
+        arr.launch_quantization = LaunchQuantization::Bar;
+        arr.launch_clip(track_id, verse);
+        let mut playback = ClipPlayback::new(48000.0);
+        playback.sync_with_arrangement(&arr, 0.0);
+        assert_eq!(playback.pending_launch_count(), 0);
+
+        let mut note_ons = Vec::new();
+        let mut collect = |events: &[MusicalEvent]| {
+            for event in events {
+                if let MusicalEvent::NoteOnTarget { beat, note, .. } = *event {
+                    note_ons.push((beat, note));
+                }
+            }
+        };
+
+        // Launch the chorus mid-bar; the verse keeps looping until beat 4
+        for block in 0..4 {
+            let beat = block as f64 * 0.375;
+            collect(playback.generate_events(&arr, beat, beat + 0.375, 120.0));
+        }
+        arr.launch_clip(track_id, chorus);
+        playback.sync_with_arrangement(&arr, 1.5);
+        assert_eq!(playback.pending_launch_count(), 1);
+
+        let mut beat = 1.5;
+        while beat < 6.0 {
+            collect(playback.generate_events(&arr, beat, beat + 0.375, 120.0));
+            beat += 0.375;
+        }
+
+        assert_eq!(playback.pending_launch_count(), 0);
+        assert_eq!(
+            note_ons,
+            vec![(0.0, 60), (1.0, 60), (2.0, 60), (3.0, 60), (4.0, 72)]
+        );
+    }
+
+    #[test]
+    fn test_launch_quantization_follows_meter() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Waltz");
+        arr.set_track_target(track_id, Some(100));
+        let clip = arr.create_clip("Waltz", 3.0);
+        arr.add_note_to_clip(clip, NoteDef::new(0.0, 0.5, 60, 0.8));
+        arr.launch_quantization = LaunchQuantization::Bar;
+
+        // In 3/4, a launch at beat 1.5 waits for the bar line at beat 3
+        let mut playback = ClipPlayback::new(48000.0);
+        playback.set_beats_per_bar(3.0);
+        arr.launch_clip(track_id, clip);
+        playback.sync_with_arrangement(&arr, 1.5);
+        assert_eq!(playback.pending_launch_count(), 1);
+
+        let mut note_ons = Vec::new();
+        let mut beat = 1.5;
+        while beat < 6.0 {
+            for event in playback.generate_events(&arr, beat, beat + 0.5, 120.0) {
+                if let MusicalEvent::NoteOnTarget { beat, .. } = *event {
+                    note_ons.push(beat);
+                }
+            }
+            beat += 0.5;
+        }
+        assert_eq!(note_ons, vec![3.0]);
+    }
+
+    #[test]
+    fn test_loop_wrap_stops_audio_before_retrigger() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Loop");
+        arr.set_track_target(track_id, Some(100));
+        let audio_id = arr.add_audio_to_pool("Pad", 48000.0, 1, vec![0.5; 384000]);
+        let clip = arr.create_clip("Pad", 8.0);
+        arr.add_audio_to_clip(clip, AudioRegionDef::new(0.0, 8.0, audio_id));
+
+        arr.launch_clip(track_id, clip);
+        let mut playback = ClipPlayback::new(48000.0);
+        playback.sync_with_arrangement(&arr, 0.0);
+        playback.generate_events(&arr, 0.0, 4.0, 120.0);
+
+        // The transport loops over beats 0..4, inside the 8-beat region
+        let events = playback.wrap_loop(0.0, &arr);
+        assert!(matches!(
+            events,
+            [MusicalEvent::AudioStop {
+                beat: 0.0,
+                node_id: 100,
+                audio_id: id,
+            }] if *id == audio_id
+        ));
+        let events = playback.generate_events(&arr, 0.0, 1.0, 120.0);
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, MusicalEvent::AudioStart { .. }))
+        );
+    }
+
+    #[test]
+    fn test_relaunch_stops_outgoing_audio_and_notes() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Relaunch");
+        arr.set_track_target(track_id, Some(100));
+        let audio_id = arr.add_audio_to_pool("Loop", 48000.0, 1, vec![0.5; 96000]);
+        let verse = arr.create_clip("Verse", 8.0);
+        let chorus = arr.create_clip("Chorus", 8.0);
+        arr.add_audio_to_clip(verse, AudioRegionDef::new(0.0, 4.0, audio_id));
+        arr.add_note_to_clip(verse, NoteDef::new(0.0, 4.0, 60, 0.8));
+        arr.add_note_to_clip(chorus, NoteDef::new(0.0, 1.0, 72, 0.8));
+
+        arr.launch_clip(track_id, verse);
+        let mut playback = ClipPlayback::new(48000.0);
+        playback.sync_with_arrangement(&arr, 0.0);
+        let events = playback.generate_events(&arr, 0.0, 1.0, 120.0);
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, MusicalEvent::AudioStart { .. }))
+        );
+
+        // Switch clips halfway through the verse's audio region
+        arr.launch_clip(track_id, chorus);
+        playback.sync_with_arrangement(&arr, 1.0);
+        let events = playback.generate_events(&arr, 1.0, 2.0, 120.0).to_vec();
+
+        // The outgoing region and note stop at the switch, ahead of the
+        // chorus starting
+        let stop = events.iter().position(|e| {
+            matches!(
+                *e,
+                MusicalEvent::AudioStop {
+                    beat,
+                    node_id: 100,
+                    audio_id: id,
+                } if beat == 1.0 && id == audio_id
+            )
+        });
+        let note_off = events.iter().position(|e| {
+            matches!(
+                *e,
+                MusicalEvent::NoteOffTarget { beat, note: 60, .. } if beat == 1.0
+            )
+        });
+        let note_on = events
+            .iter()
+            .position(|e| matches!(*e, MusicalEvent::NoteOnTarget { note: 72, .. }));
+        assert!(stop.unwrap() < note_on.unwrap());
+        assert!(note_off.unwrap() < note_on.unwrap());
+
+        // Nothing is left to release later
+        let events = playback.generate_events(&arr, 2.0, 6.0, 120.0);
+        assert!(!events.iter().any(|e| matches!(
+            e,
+            MusicalEvent::AudioStop { .. } | MusicalEvent::NoteOffTarget { note: 60, .. }
+        )));
+    }
+
+    #[test]
+    fn test_swing_delays_off_beats() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Hats");
+        arr.set_track_target(track_id, Some(100));
+        let clip_id = arr.create_clip("Eighths", 2.0);
+        for i in 0..4 {
+            arr.add_note_to_clip(clip_id, NoteDef::new(i as f64 * 0.5, 0.25, 42, 0.8));
+        }
+        arr.swing = 0.5;
+        arr.launch_clip(track_id, clip_id);
+
+        let mut playback = ClipPlayback::new(48000.0);
+        playback.sync_with_arrangement(&arr, 0.0);
+
+        let mut note_ons = Vec::new();
+        let mut note_offs = Vec::new();
+        for block in 0..16 {
+            let beat = block as f64 * 0.125;
+            for event in playback.generate_events(&arr, beat, beat + 0.125, 120.0) {
+                match *event {
+                    MusicalEvent::NoteOnTarget { beat, .. } => note_ons.push(beat),
+                    MusicalEvent::NoteOffTarget { beat, .. } => note_offs.push(beat),
+                    _ => {}
+                }
+            }
+        }
+
+        // Down-beats stay put; off-beats move an 8th of a beat late
+        let expected = [0.0, 0.625, 1.0, 1.625];
+        assert_eq!(note_ons.len(), expected.len(), "{note_ons:?}");
+        for (got, want) in note_ons.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-9, "{note_ons:?}");
+        }
+
+        // Every note keeps its length
+        assert_eq!(note_offs.len(), expected.len());
+        for (off, want) in note_offs.iter().zip(expected) {
+            assert!((off - (want + 0.25)).abs() < 1e-9, "{note_offs:?}");
+        }
+    }
+
+    #[test]
+    fn test_looping_note_past_loop_end_does_not_hang() {
+        use crate::voice_allocator::VoiceAllocator;
+
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Loop");
+        arr.set_track_target(track_id, Some(100));
+        let clip_id = arr.create_clip("Overhang", 4.0);
+        arr.add_note_to_clip(clip_id, NoteDef::new(0.0, 1.0, 64, 0.8));
+        arr.add_note_to_clip(clip_id, NoteDef::new(3.0, 2.0, 60, 0.8)); // runs past the loop end
+        arr.launch_clip(track_id, clip_id);
+
+        let mut playback = ClipPlayback::new(48000.0);
+        playback.sync_with_arrangement(&arr, 0.0);
+        let mut voices = VoiceAllocator::new(8);
+        let held = |voices: &VoiceAllocator, note: u8| {
+            voices
+                .active_voices()
+                .filter(|v| v.gate && v.note == note)
+                .count()
+        };
+
+        // Two passes through the loop, in blocks that straddle the wrap
+        let mut beat = 0.0;
+        while beat < 8.25 {
+            let mut events = playback
+                .generate_events(&arr, beat, beat + 0.375, 120.0)
+                .to_vec();
+            events.sort_by(|a, b| a.beat().total_cmp(&b.beat()));
+            for event in events {
+                match event {
+                    MusicalEvent::NoteOnTarget { note, velocity, .. } => {
+                        voices.note_on(note, velocity);
+                    }
+                    MusicalEvent::NoteOffTarget { note, .. } => voices.note_off(note),
+                    _ => {}
+                }
+            }
+            beat += 0.375;
+
+            // The overhanging note is released by the loop end each pass
+            assert!(held(&voices, 60) <= 1, "note 60 stacked at beat {beat}");
+        }
+
+        // Past the second loop end only the downbeat note is still held
+        assert_eq!(held(&voices, 60), 0);
+        assert_eq!(held(&voices, 64), 1);
+        assert_eq!(playback.active_note_count(), 1);
+    }
+
+    #[test]
+    fn test_key_zones_split_notes_between_targets() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Split");
+        let bass = KeyZone {
+            low_note: 0,
+            high_note: 47,
+            target_node: 10,
+            transpose: -12,
+        };
+        let lead = KeyZone {
+            low_note: 48,
+            high_note: 127,
+            target_node: 20,
+            transpose: 0,
+        };
+        arr.add_track_key_zone(track_id, bass);
+        arr.add_track_key_zone(track_id, lead);
+
+        let clip_id = arr.create_clip("Split Clip", 4.0);
+        if let Some(clip) = arr.get_clip_mut(clip_id) {
+            clip.add_note(NoteDef::new(0.0, 1.0, 40, 0.8));
+            clip.add_note(NoteDef::new(0.0, 1.0, 72, 0.8));
+        }
+        arr.launch_clip(track_id, clip_id);
+
+        let mut playback = ClipPlayback::new(48000.0);
+        playback.sync_with_arrangement(&arr, 0.0);
+        let mut note_ons: Vec<(u32, u8)> = playback
+            .generate_events(&arr, 0.0, 0.5, 120.0)
+            .iter()
+            .filter_map(|e| match *e {
+                MusicalEvent::NoteOnTarget { node_id, note, .. } => Some((node_id, note)),
+                _ => None,
+            })
+            .collect();
+        note_ons.sort();
+
+        // The low note plays the bass an octave down, the high one the lead
+        assert_eq!(note_ons, vec![(10, 28), (20, 72)]);
+
+        // An overlapping zone layers a second target under the lead
+        arr.add_track_key_zone(
+            track_id,
+            KeyZone {
+                low_note: 60,
+                high_note: 127,
+                target_node: 30,
+                transpose: 12,
+            },
+        );
+        let track = arr.get_track(track_id).unwrap();
+        assert_eq!(track.note_targets(72), vec![(20, 72), (30, 84)]);
+        assert_eq!(track.note_targets(40), vec![(10, 28)]);
+    }
+
+    #[test]
+    fn test_clip_transpose_and_velocity_scale() {
+        let note_ons = |arr: &Arrangement| {
+            let mut playback = ClipPlayback::new(48000.0);
+            playback.sync_with_arrangement(arr, 0.0);
+            playback
+                .generate_events(arr, 0.0, 3.5, 120.0)
+                .iter()
+                .filter_map(|e| match *e {
+                    MusicalEvent::NoteOnTarget {
+                        beat,
+                        note,
+                        velocity,
+                        ..
+                    } => Some((beat, note, velocity)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut arr = make_test_arrangement();
+        let original = note_ons(&arr);
+        assert_eq!(original.len(), 3);
+
+        let clip = arr.clips.values_mut().next().unwrap();
+        clip.transpose = 12;
+        clip.velocity_scale = 1.5;
+        let shifted = note_ons(&arr);
+
+        // Same timing, an octave up, and velocity scaled but clamped
+        assert_eq!(shifted.len(), original.len());
+        for (before, after) in original.iter().zip(&shifted) {
+            assert_eq!(after.0, before.0);
+            assert_eq!(after.1, before.1 + 12);
+            assert!((after.2 - (before.2 * 1.5).min(1.0)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_humanize_perturbs_notes_within_window() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Humanized");
+        arr.set_track_target(track_id, Some(100));
+        let clip_id = arr.create_clip("Humanized Clip", 4.0);
+        if let Some(clip) = arr.get_clip_mut(clip_id) {
+            clip.looping = false;
+            for step in 0..8 {
+                clip.add_note(NoteDef::new(step as f64 * 0.5, 0.25, 60 + step, 0.5));
+            }
+            clip.humanize_timing = 0.05;
+            clip.humanize_velocity = 0.1;
+            clip.humanize_seed = 42;
+        }
+        arr.launch_clip(track_id, clip_id);
+
+        let play = |arr: &Arrangement| {
+            let mut playback = ClipPlayback::new(48000.0);
+            playback.sync_with_arrangement(arr, 0.0);
+            let mut note_ons = Vec::new();
+            let mut beat = 0.0;
+            while beat < 4.0 {
+                for event in playback.generate_events(arr, beat, beat + 0.1, 120.0) {
+                    if let MusicalEvent::NoteOnTarget {
+                        beat,
+                        note,
+                        velocity,
+                        ..
+                    } = *event
+                    {
+                        note_ons.push((beat, note, velocity));
+                    }
+                }
+                beat += 0.1;
+            }
+            note_ons
+        };
+
+        let note_ons = play(&arr);
+        assert_eq!(note_ons.len(), 8);
+        for (step, &(beat, note, velocity)) in note_ons.iter().enumerate() {
+            // Notes keep their order and are only ever delayed, within the window
+            assert_eq!(note, 60 + step as u8);
+            let offset = beat - step as f64 * 0.5;
+            assert!((0.0..0.05).contains(&offset), "offset {offset}");
+            assert!((velocity - 0.5).abs() <= 0.1 + 1e-6);
+        }
+        assert!(
+            note_ons
+                .iter()
+                .any(|&(beat, ..)| beat.fract() != 0.0 && beat.fract() != 0.5)
+        );
+
+        // The same seed always plays the same
+        assert_eq!(play(&arr), note_ons);
+    }
 }
@@ -6,8 +6,10 @@
 use std::collections::HashMap;
 
 use crate::graph::Graph;
+use crate::modulation::{ModRoute, ModScale, VelocityRoute};
 use crate::node_factory::NodeRegistry;
-use crate::state::{GraphDef, NodeId};
+use crate::nodes::node_types;
+use crate::state::{GraphDef, NodeDef, NodeId};
 
 /// Error during graph compilation.
 #[derive(Debug)]
@@ -17,6 +19,9 @@ pub enum CompileError {
 
     /// A connection references a non-existent node.
     InvalidConnection { source: NodeId, dest: NodeId },
+
+    /// Connections form a feedback loop through these nodes.
+    CycleDetected { nodes: Vec<NodeId> },
 }
 
 impl std::fmt::Display for CompileError {
@@ -28,6 +33,9 @@ impl std::fmt::Display for CompileError {
             CompileError::InvalidConnection { source, dest } => {
                 write!(f, "Invalid connection from {} to {}", source, dest)
             }
+            CompileError::CycleDetected { nodes } => {
+                write!(f, "Graph contains a cycle through nodes {:?}", nodes)
+            }
         }
     }
 }
@@ -42,8 +50,18 @@ pub type CompileResult<T> = Result<T, CompileError>;
 /// This function:
 /// 1. Creates node instances using the registry's factories
 /// 2. Applies parameter values from the definition
-/// 3. Wires up connections
-/// 4. Sets the output node
+/// 3. Wires up connections, rejecting feedback cycles
+/// 4. Compiles modulation routes and velocity sensitivity
+/// 5. Sets the output node
+///
+/// Connections between ports of different widths are allowed: each input
+/// port receives the channel count its `PortInfo` declares. A stereo source
+/// on a mono port is averaged down to mono, and a mono source on a stereo
+/// port is copied to both channels.
+///
+/// Only nodes contributing to the output, through connections or by
+/// modulating a node that does, are instantiated; the rest would be
+/// processed for nothing. Without an output node every node is kept.
 ///
 /// The returned Graph is ready to be prepared and processed.
 pub fn compile(
@@ -57,8 +75,17 @@ pub fn compile(
     // Map from NodeDef ID -> runtime Graph index
     let mut id_to_index: HashMap<NodeId, usize> = HashMap::new();
 
-    // Sort nodes by ID for deterministic ordering
-    let mut node_ids: Vec<NodeId> = def.nodes.keys().copied().collect();
+    // Sort nodes by ID for deterministic ordering, dropping dead nodes
+    let live = def
+        .output_node
+        .filter(|id| def.nodes.contains_key(id))
+        .map(|output| def.contributing_nodes(output));
+    let mut node_ids: Vec<NodeId> = def
+        .nodes
+        .keys()
+        .copied()
+        .filter(|id| live.as_ref().is_none_or(|live| live.contains(id)))
+        .collect();
     node_ids.sort();
 
     // Create all nodes
@@ -75,6 +102,14 @@ pub fn compile(
 
         let idx = graph.add_node(factory);
         id_to_index.insert(node_id, idx);
+        graph.set_bypass(idx, node_def.bypassed);
+
+        // Declared input widths, so mismatched sources get mixed to fit
+        if let Some(info) = registry.get_info(node_def.type_id) {
+            for port in &info.inputs {
+                graph.set_port_channels(idx, port.id as usize, port.channels);
+            }
+        }
 
         // Apply parameter values
         for (&param_id, &value) in &node_def.param_values {
@@ -83,14 +118,25 @@ pub fn compile(
     }
 
     // Wire up connections
-    // Note: Current Graph only tracks node->node, not port->port
-    // We deduplicate connections to the same dest node
-    let mut connected: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    // Note: Every node has a single output buffer. A node declaring several
+    // output ports writes one port per channel, so its source port picks a
+    // channel; otherwise the source port is ignored.
+    // We deduplicate connections to the same dest port
+    let mut connected: HashMap<(NodeId, u32), Vec<(NodeId, u32)>> = HashMap::new();
+
+    // Pruned nodes are still in the definition; only missing ones are errors
+    let pruned = |id| def.nodes.contains_key(&id) && !id_to_index.contains_key(&id);
 
     for conn in &def.connections {
-        let sources = connected.entry(conn.dest_node).or_default();
-        if !sources.contains(&conn.source_node) {
-            sources.push(conn.source_node);
+        if pruned(conn.source_node) || pruned(conn.dest_node) {
+            continue;
+        }
+
+        let sources = connected
+            .entry((conn.dest_node, conn.dest_port))
+            .or_default();
+        if !sources.contains(&(conn.source_node, conn.source_port)) {
+            sources.push((conn.source_node, conn.source_port));
 
             let src_idx =
                 id_to_index
@@ -108,7 +154,85 @@ pub fn compile(
                         dest: conn.dest_node,
                     })?;
 
-            graph.connect(*src_idx, *dst_idx);
+            let multi_output = def
+                .get_node(conn.source_node)
+                .and_then(|node| registry.get_info(node.type_id))
+                .is_some_and(|info| info.outputs.len() > 1);
+            if multi_output {
+                graph.connect_output(
+                    *src_idx,
+                    conn.source_port as usize,
+                    *dst_idx,
+                    conn.dest_port as usize,
+                );
+            } else {
+                graph.connect_port(*src_idx, *dst_idx, conn.dest_port as usize);
+            }
+        }
+    }
+
+    // Reject feedback loops (graph indices follow sorted node_ids order)
+    let cyclic = graph.cyclic_nodes();
+    if !cyclic.is_empty() {
+        return Err(CompileError::CycleDetected {
+            nodes: cyclic.into_iter().map(|idx| node_ids[idx]).collect(),
+        });
+    }
+
+    // Compile modulation routes, starting from the destination's current value
+    for route in &def.mod_routes {
+        if pruned(route.dest_node) {
+            continue;
+        }
+        let invalid = CompileError::InvalidConnection {
+            source: route.source_node,
+            dest: route.dest_node,
+        };
+        let (Some(&source), Some(&dest)) = (
+            id_to_index.get(&route.source_node),
+            id_to_index.get(&route.dest_node),
+        ) else {
+            return Err(invalid);
+        };
+
+        // Filter envelopes sweep their destination in octaves
+        let scale = match def.nodes.get(&route.source_node) {
+            Some(node) if node.type_id == node_types::FILTER_ENV => ModScale::Octaves,
+            _ => ModScale::Linear,
+        };
+
+        graph.add_mod_route(ModRoute {
+            source,
+            dest,
+            param_id: route.param_id,
+            depth: route.depth,
+            scale,
+            base: base_param_value(&def.nodes[&route.dest_node], route.param_id, registry),
+        });
+    }
+
+    for &node_id in &node_ids {
+        let node_def = &def.nodes[&node_id];
+        for (&param_id, &amount) in &node_def.velocity_sensitivity {
+            graph.add_velocity_route(VelocityRoute {
+                node: id_to_index[&node_id],
+                param_id,
+                amount,
+                base: base_param_value(node_def, param_id, registry),
+            });
+        }
+
+        // Params with a smoothing time glide to runtime changes
+        let Some(info) = registry.get_info(node_def.type_id) else {
+            continue;
+        };
+        for param in info.parameters.iter().filter(|p| p.smooth_ms > 0.0) {
+            graph.add_param_smoother(
+                id_to_index[&node_id],
+                param.id,
+                param.smooth_ms / 1000.0,
+                base_param_value(node_def, param.id, registry),
+            );
         }
     }
 
@@ -128,13 +252,31 @@ pub fn compile(
     Ok(graph)
 }
 
+/// A node's configured parameter value, falling back to the type's default.
+fn base_param_value(node_def: &NodeDef, param_id: u32, registry: &NodeRegistry) -> f32 {
+    node_def
+        .param_values
+        .get(&param_id)
+        .copied()
+        .or_else(|| {
+            registry
+                .get_info(node_def.type_id)
+                .and_then(|info| info.find_param(param_id))
+                .map(|p| p.default)
+        })
+        .unwrap_or(0.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::audio_buffer::AudioBuffer;
+    use crate::audio_buffer::{InputBuffer, OutputBuffer};
     use crate::node::{Node, Polyphony, ProcessContext};
     use crate::node_factory::SimpleNodeFactory;
-    use crate::state::NodeTypeInfo;
+    use crate::state::{NodeTypeInfo, PortInfo};
+    use crate::voice_allocator::VoiceAllocator;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     // Test node that just outputs silence
     struct TestNode;
@@ -145,8 +287,8 @@ mod tests {
         fn process(
             &mut self,
             _ctx: &ProcessContext,
-            _inputs: &[&AudioBuffer],
-            _output: &mut AudioBuffer,
+            _inputs: &[InputBuffer],
+            _output: &mut OutputBuffer,
         ) -> bool {
             true
         }
@@ -190,4 +332,955 @@ mod tests {
         assert_eq!(graph.nodes.len(), 2);
         assert_eq!(graph.nodes[1].inputs.len(), 1);
     }
+
+    // Test node that outputs a constant (set via param 0)
+    struct ConstNode(f32);
+
+    impl Node for ConstNode {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            _ctx: &ProcessContext,
+            _inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            output.channel_mut(0).fill(self.0);
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn set_param(&mut self, _: u32, value: f32) {
+            self.0 = value;
+        }
+    }
+
+    // Test node that encodes its port inputs as `ports * 100 + in0 + 10 * in1`
+    struct PortProbe;
+
+    impl Node for PortProbe {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            ctx: &ProcessContext,
+            inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            let port = |p: usize| inputs.get(p).map_or(0.0, |b| b.channel(0)[0]);
+            let value = inputs.len() as f32 * 100.0 + port(0) + 10.0 * port(1);
+            output.channel_mut(0)[..ctx.frames].fill(value);
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn set_param(&mut self, _: u32, _: f32) {}
+    }
+
+    #[test]
+    fn test_compile_routes_by_dest_port() {
+        const CONST_NODE: u32 = 1;
+        const PROBE_NODE: u32 = 2;
+
+        let mut def = GraphDef::new();
+        let a = def.add_node(CONST_NODE);
+        let b = def.add_node(CONST_NODE);
+        let c = def.add_node(CONST_NODE);
+        let probe = def.add_node(PROBE_NODE);
+        def.set_param(a, 0, 1.0);
+        def.set_param(b, 0, 2.0);
+        def.set_param(c, 0, 3.0);
+        def.connect(a, 0, probe, 0);
+        def.connect(b, 0, probe, 1);
+        def.output_node = Some(probe);
+
+        let mut registry = NodeRegistry::new();
+        registry.register(
+            NodeTypeInfo::new(CONST_NODE, "Const", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ConstNode(0.0)), Polyphony::Global),
+        );
+        registry.register(
+            NodeTypeInfo::new(PROBE_NODE, "Probe", "Test"),
+            SimpleNodeFactory::new(|| Box::new(PortProbe), Polyphony::Global),
+        );
+
+        let render = |def: &GraphDef| {
+            let mut graph = compile(def, &registry, 64, 1).unwrap();
+            graph.prepare(48_000.0);
+            graph.process(64, 0, 120.0, &VoiceAllocator::new(1));
+            graph.output_buffer(64).unwrap()[0]
+        };
+
+        // Two ports, each with its own source
+        assert_eq!(render(&def), 200.0 + 1.0 + 20.0);
+
+        // A second source on port 1 is summed with the first
+        def.connect(c, 0, probe, 1);
+        assert_eq!(render(&def), 200.0 + 1.0 + 50.0);
+    }
+
+    #[test]
+    fn test_bypassed_gain_passes_signal() {
+        use crate::nodes::{node_types, params, register_standard_nodes};
+
+        const CONST_NODE: u32 = 900;
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+        registry.register(
+            NodeTypeInfo::new(CONST_NODE, "Const", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ConstNode(0.0)), Polyphony::Global).channels(1),
+        );
+
+        let mut def = GraphDef::new();
+        let src = def.add_node(CONST_NODE);
+        let gain = def.add_node(node_types::GAIN);
+        def.set_param(src, 0, 0.5);
+        def.set_param(gain, params::GAIN, f32::NEG_INFINITY);
+        def.connect(src, 0, gain, 0);
+        def.output_node = Some(gain);
+
+        let render = |def: &GraphDef| {
+            let mut graph = compile(def, &registry, 64, 1).unwrap();
+            graph.prepare(48_000.0);
+            graph.process(64, 0, 120.0, &VoiceAllocator::new(1));
+            graph.output_buffer(64).unwrap().to_vec()
+        };
+
+        // Muted while in the signal path
+        assert!(render(&def).iter().all(|&s| s == 0.0));
+
+        // Bypassed, the mono source reaches both channels untouched
+        def.set_bypass(gain, true);
+        assert!(render(&def).iter().all(|&s| s == 0.5));
+
+        // Bypassing a source silences it
+        def.set_bypass(src, true);
+        assert!(render(&def).iter().all(|&s| s == 0.0));
+    }
+
+    // Test node that outputs 0.8 on the left and 0.2 on the right
+    struct StereoConst;
+
+    impl Node for StereoConst {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            _ctx: &ProcessContext,
+            _inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            output.channel_mut(0).fill(0.8);
+            output.channel_mut(1).fill(0.2);
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            2
+        }
+
+        fn set_param(&mut self, _: u32, _: f32) {}
+    }
+
+    // Test node that encodes its input as `channels * 100 + first + 10 * last`
+    struct ChannelProbe;
+
+    impl Node for ChannelProbe {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            _ctx: &ProcessContext,
+            inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            let input = inputs[0];
+            let first = input.channel(0)[0];
+            let last = input.channel(input.channels - 1)[0];
+            output
+                .samples_mut()
+                .fill(input.channels as f32 * 100.0 + first + 10.0 * last);
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn set_param(&mut self, _: u32, _: f32) {}
+    }
+
+    #[test]
+    fn test_compile_mixes_to_port_width() {
+        const MONO_CONST: u32 = 1;
+        const STEREO_CONST: u32 = 2;
+        const MONO_PROBE: u32 = 3;
+        const STEREO_PROBE: u32 = 4;
+        const ANY_PROBE: u32 = 5;
+
+        let mut registry = NodeRegistry::new();
+        registry.register(
+            NodeTypeInfo::new(MONO_CONST, "Mono", "Test")
+                .with_output(PortInfo::audio_output(0, "Out")),
+            SimpleNodeFactory::new(|| Box::new(ConstNode(0.3)), Polyphony::Global).channels(1),
+        );
+        registry.register(
+            NodeTypeInfo::new(STEREO_CONST, "Stereo", "Test")
+                .with_output(PortInfo::audio_output(0, "Out").stereo()),
+            SimpleNodeFactory::new(|| Box::new(StereoConst), Polyphony::Global),
+        );
+        registry.register(
+            NodeTypeInfo::new(MONO_PROBE, "Mono Probe", "Test")
+                .with_input(PortInfo::audio_input(0, "In")),
+            SimpleNodeFactory::new(|| Box::new(ChannelProbe), Polyphony::Global).channels(1),
+        );
+        registry.register(
+            NodeTypeInfo::new(STEREO_PROBE, "Stereo Probe", "Test")
+                .with_input(PortInfo::audio_input(0, "In").stereo()),
+            SimpleNodeFactory::new(|| Box::new(ChannelProbe), Polyphony::Global).channels(1),
+        );
+        // No declared ports, so sources arrive as they are
+        registry.register(
+            NodeTypeInfo::new(ANY_PROBE, "Any Probe", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ChannelProbe), Polyphony::Global).channels(1),
+        );
+
+        let render = |source: u32, probe: u32| {
+            let mut def = GraphDef::new();
+            let src = def.add_node(source);
+            let dst = def.add_node(probe);
+            def.connect(src, 0, dst, 0);
+            def.output_node = Some(dst);
+            let mut graph = compile(&def, &registry, 64, 1).unwrap();
+            graph.prepare(48_000.0);
+            graph.process(64, 0, 120.0, &VoiceAllocator::new(1));
+            graph.output_buffer(64).unwrap()[0]
+        };
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+
+        // Stereo into mono is averaged down
+        assert!(close(render(STEREO_CONST, MONO_PROBE), 100.0 + 0.5 + 5.0));
+        // Mono into stereo is copied to both channels
+        assert!(close(render(MONO_CONST, STEREO_PROBE), 200.0 + 0.3 + 3.0));
+        // Matching widths pass straight through
+        assert!(close(render(STEREO_CONST, STEREO_PROBE), 200.0 + 0.8 + 2.0));
+        assert!(close(render(STEREO_CONST, ANY_PROBE), 200.0 + 0.8 + 2.0));
+    }
+
+    // Test node that outputs 1, 2 and 3 on three separate output ports
+    struct MultiOutConst;
+
+    impl Node for MultiOutConst {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            _ctx: &ProcessContext,
+            _inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            for ch in 0..3 {
+                output.channel_mut(ch).fill(ch as f32 + 1.0);
+            }
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            3
+        }
+
+        fn set_param(&mut self, _: u32, _: f32) {}
+    }
+
+    #[test]
+    fn test_compile_routes_by_source_port() {
+        const MULTI_NODE: u32 = 1;
+        const PROBE_NODE: u32 = 2;
+
+        let mut registry = NodeRegistry::new();
+        registry.register(
+            NodeTypeInfo::new(MULTI_NODE, "Multi", "Test")
+                .with_output(PortInfo::audio_output(0, "A"))
+                .with_output(PortInfo::audio_output(1, "B"))
+                .with_output(PortInfo::audio_output(2, "C")),
+            SimpleNodeFactory::new(|| Box::new(MultiOutConst), Polyphony::Global).channels(3),
+        );
+        registry.register(
+            NodeTypeInfo::new(PROBE_NODE, "Probe", "Test"),
+            SimpleNodeFactory::new(|| Box::new(PortProbe), Polyphony::Global),
+        );
+
+        let mut def = GraphDef::new();
+        let src = def.add_node(MULTI_NODE);
+        let probe = def.add_node(PROBE_NODE);
+        def.connect(src, 2, probe, 0);
+        def.connect(src, 1, probe, 1);
+        def.output_node = Some(probe);
+
+        let render = |def: &GraphDef| {
+            let mut graph = compile(def, &registry, 64, 1).unwrap();
+            graph.prepare(48_000.0);
+            graph.process(64, 0, 120.0, &VoiceAllocator::new(1));
+            graph.output_buffer(64).unwrap()[0]
+        };
+
+        // Each port receives only the output it was wired from
+        assert_eq!(render(&def), 200.0 + 3.0 + 20.0);
+
+        // Two outputs of the same node on one port are summed
+        def.connect(src, 0, probe, 1);
+        assert_eq!(render(&def), 200.0 + 3.0 + 30.0);
+    }
+
+    // Test node that counts how many blocks it has processed
+    struct CountingNode(Arc<AtomicUsize>);
+
+    impl Node for CountingNode {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            _ctx: &ProcessContext,
+            _inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            output.channel_mut(0).fill(0.1);
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn set_param(&mut self, _: u32, _: f32) {}
+    }
+
+    #[test]
+    fn test_compile_prunes_nodes_off_the_output_path() {
+        const CONNECTED: u32 = 1;
+        const ORPHAN: u32 = 2;
+        const MODULATOR: u32 = 3;
+        const OUTPUT: u32 = 4;
+
+        let counters: Vec<Arc<AtomicUsize>> = (0..3).map(|_| Arc::default()).collect();
+        let mut registry = NodeRegistry::new();
+        for (type_id, counter) in [CONNECTED, ORPHAN, MODULATOR].into_iter().zip(&counters) {
+            let counter = counter.clone();
+            registry.register(
+                NodeTypeInfo::new(type_id, "Counting", "Test"),
+                SimpleNodeFactory::new(
+                    move || Box::new(CountingNode(counter.clone())),
+                    Polyphony::Global,
+                )
+                .channels(1),
+            );
+        }
+        registry.register(
+            NodeTypeInfo::new(OUTPUT, "Probe", "Test"),
+            SimpleNodeFactory::new(|| Box::new(PortProbe), Polyphony::Global),
+        );
+
+        let mut def = GraphDef::new();
+        let osc = def.add_node(CONNECTED);
+        let orphan = def.add_node(ORPHAN);
+        let lfo = def.add_node(MODULATOR);
+        let out = def.add_node(OUTPUT);
+        def.connect(osc, 0, out, 0);
+        def.add_mod_route(lfo, osc, 0, 1.0);
+        def.output_node = Some(out);
+
+        let mut graph = compile(&def, &registry, 64, 1).unwrap();
+        assert_eq!(graph.nodes.len(), 3);
+        assert!(!graph.id_to_index.contains_key(&orphan));
+
+        graph.prepare(48_000.0);
+        graph.process(64, 0, 120.0, &VoiceAllocator::new(1));
+        let counts: Vec<usize> = counters.iter().map(|c| c.load(Ordering::Relaxed)).collect();
+        // The modulation source is kept, the orphan never runs
+        assert_eq!(counts, [1, 0, 1]);
+    }
+
+    #[test]
+    fn test_compile_rejects_cycle() {
+        const TEST_NODE: u32 = 1;
+
+        let mut def = GraphDef::new();
+        let a = def.add_node(TEST_NODE);
+        let b = def.add_node(TEST_NODE);
+        let out = def.add_node(TEST_NODE);
+        def.connect(a, 0, b, 0);
+        def.connect(b, 0, a, 0);
+        def.connect(b, 0, out, 0);
+        def.output_node = Some(out);
+
+        let mut registry = NodeRegistry::new();
+        registry.register(
+            NodeTypeInfo::new(TEST_NODE, "Test", "Test"),
+            SimpleNodeFactory::new(|| Box::new(TestNode), Polyphony::Global),
+        );
+
+        match compile(&def, &registry, 512, 8) {
+            Err(CompileError::CycleDetected { mut nodes }) => {
+                nodes.sort();
+                assert_eq!(nodes, vec![a, b]);
+            }
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("cyclic graph compiled"),
+        }
+    }
+
+    // Test node that outputs a fixed-frequency sine
+    struct ToneNode {
+        freq: f32,
+        phase: f32,
+        sample_rate: f32,
+    }
+
+    impl Node for ToneNode {
+        fn prepare(&mut self, sample_rate: f64, _: usize) {
+            self.sample_rate = sample_rate as f32;
+        }
+
+        fn process(
+            &mut self,
+            ctx: &ProcessContext,
+            _inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            for sample in output.channel_mut(0).iter_mut().take(ctx.frames) {
+                *sample = (self.phase * std::f32::consts::TAU).sin();
+                self.phase = (self.phase + self.freq / self.sample_rate).fract();
+            }
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn set_param(&mut self, _: u32, _: f32) {}
+    }
+
+    #[test]
+    fn test_mod_route_lfo_to_cutoff() {
+        use crate::nodes::{FilterType, SvfFilter, node_types, params, register_standard_nodes};
+
+        const TONE_NODE: u32 = 900;
+        const GLOBAL_LOWPASS: u32 = 901;
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+        registry.register(
+            NodeTypeInfo::new(TONE_NODE, "Tone", "Test"),
+            SimpleNodeFactory::new(
+                || {
+                    Box::new(ToneNode {
+                        freq: 4_000.0,
+                        phase: 0.0,
+                        sample_rate: 48_000.0,
+                    })
+                },
+                Polyphony::Global,
+            ),
+        );
+        registry.register(
+            NodeTypeInfo::new(GLOBAL_LOWPASS, "Lowpass", "Test"),
+            SimpleNodeFactory::new(
+                || Box::new(SvfFilter::new(FilterType::Lowpass)),
+                Polyphony::Global,
+            ),
+        );
+
+        let mut def = GraphDef::new();
+        let tone = def.add_node(TONE_NODE);
+        let lfo = def.add_node(node_types::LFO);
+        let filter = def.add_node(GLOBAL_LOWPASS);
+        def.connect(tone, 0, filter, 0);
+        def.set_param(lfo, params::RATE, 2.0);
+        def.set_param(filter, params::CUTOFF, 3_000.0);
+        def.add_mod_route(lfo, filter, params::CUTOFF, 2_900.0);
+        def.output_node = Some(filter);
+
+        let mut graph = compile(&def, &registry, 256, 1).unwrap();
+        assert_eq!(graph.mod_routes.len(), 1);
+        assert_eq!(graph.mod_routes[0].base, 3_000.0);
+        graph.prepare(48_000.0);
+
+        // The 4 kHz tone passes near the top of the sweep and is strongly
+        // attenuated near the bottom, so block levels must swing widely.
+        let voices = VoiceAllocator::new(1);
+        let mut levels = Vec::new();
+        for block in 0..187 {
+            graph.process(256, block * 256, 120.0, &voices);
+            let out = graph.output_buffer(256).unwrap();
+            levels.push(out.iter().map(|s| s * s).sum::<f32>().sqrt());
+        }
+        let max = levels.iter().cloned().fold(0.0, f32::max);
+        let min = levels.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(max > 10.0 * min, "cutoff did not oscillate: {min}..{max}");
+    }
+
+    // Test node that outputs its param 0 value
+    struct ParamEcho(f32);
+
+    impl Node for ParamEcho {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            ctx: &ProcessContext,
+            _inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            output.channel_mut(0)[..ctx.frames].fill(self.0);
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn set_param(&mut self, _: u32, value: f32) {
+            self.0 = value;
+        }
+    }
+
+    #[test]
+    fn test_voice_chain_ending_in_filter_sounds() {
+        use crate::nodes::register_standard_nodes;
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        for filter_type in [node_types::LOWPASS, node_types::LADDER] {
+            let mut def = GraphDef::new();
+            let osc = def.add_node(node_types::SAW_OSC);
+            let env = def.add_node(node_types::ADSR_ENV);
+            let filter = def.add_node(filter_type);
+            let out = def.add_node(node_types::OUTPUT);
+            def.connect(osc, 0, env, 0);
+            def.connect(env, 0, filter, 0);
+            def.connect(filter, 0, out, 0);
+            def.output_node = Some(out);
+
+            let mut graph = compile(&def, &registry, 256, 4).unwrap();
+            graph.prepare(48_000.0);
+            let mut voices = VoiceAllocator::new(4);
+            voices.note_on(48, 1.0);
+
+            let mut peak = 0.0f32;
+            for block in 0..20u64 {
+                graph.process(256, block * 256, 120.0, &voices);
+                voices.clear_triggers();
+                let output = graph.output_buffer(256).unwrap();
+                peak = output.iter().fold(peak, |p, s| p.max(s.abs()));
+            }
+            assert!(
+                peak > 0.05,
+                "filter {filter_type} silenced the voice: {peak}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mod_routes_to_one_param_add_up() {
+        use crate::nodes::{params, register_standard_nodes};
+
+        const ECHO_NODE: u32 = 900;
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+        registry.register(
+            NodeTypeInfo::new(ECHO_NODE, "Echo", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ParamEcho(0.0)), Polyphony::Global),
+        );
+
+        // Two square LFOs start high, at 1.0 and 0.5
+        let mut def = GraphDef::new();
+        let echo = def.add_node(ECHO_NODE);
+        let full = def.add_node(node_types::LFO);
+        let half = def.add_node(node_types::LFO);
+        for lfo in [full, half] {
+            def.set_param(lfo, params::WAVEFORM, 3.0);
+            def.set_param(lfo, params::RATE, 0.01);
+        }
+        def.set_param(half, params::DEPTH, 0.5);
+        def.set_param(echo, params::CUTOFF, 1_000.0);
+        def.add_mod_route(full, echo, params::CUTOFF, 100.0);
+        def.add_mod_route(half, echo, params::CUTOFF, 300.0);
+        def.output_node = Some(echo);
+
+        let mut graph = compile(&def, &registry, 64, 1).unwrap();
+        graph.prepare(48_000.0);
+        let voices = VoiceAllocator::new(1);
+        for _ in 0..2 {
+            graph.process(64, 0, 120.0, &voices);
+        }
+
+        let cutoff = graph.output_buffer(64).unwrap()[0];
+        assert!((cutoff - 1_250.0).abs() < 1e-3, "{cutoff}");
+    }
+
+    #[test]
+    fn test_velocity_sensitivity_scales_param() {
+        const ECHO_NODE: u32 = 1;
+        const CUTOFF: u32 = 0;
+
+        let mut def = GraphDef::new();
+        let echo = def.add_node(ECHO_NODE);
+        def.set_param(echo, CUTOFF, 2_000.0);
+        def.set_velocity_sensitivity(echo, CUTOFF, 1.0);
+        def.output_node = Some(echo);
+
+        let mut registry = NodeRegistry::new();
+        registry.register(
+            NodeTypeInfo::new(ECHO_NODE, "Echo", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ParamEcho(0.0)), Polyphony::PerVoice),
+        );
+
+        let cutoff_at = |velocity: f32| {
+            let mut graph = compile(&def, &registry, 64, 1).unwrap();
+            graph.prepare(48_000.0);
+            let mut voices = VoiceAllocator::new(1);
+            voices.note_on(60, velocity);
+            graph.process(64, 0, 120.0, &voices);
+            graph.output_buffer(64).unwrap()[0]
+        };
+
+        let soft = cutoff_at(0.3);
+        let hard = cutoff_at(1.0);
+        assert!((hard - 2_000.0).abs() < 1e-3);
+        assert!((soft / hard - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_smoothed_param_glides_to_target() {
+        use crate::state::ParamInfo;
+
+        const INSTANT_NODE: u32 = 1;
+        const SMOOTH_NODE: u32 = 2;
+        const CUTOFF: u32 = 0;
+
+        let mut registry = NodeRegistry::new();
+        for (type_id, smooth_ms) in [(INSTANT_NODE, 0.0), (SMOOTH_NODE, 20.0)] {
+            registry.register(
+                NodeTypeInfo::new(type_id, "Echo", "Test").with_param(
+                    ParamInfo::new(CUTOFF, "Cutoff")
+                        .range(20.0, 20_000.0)
+                        .default(1_000.0)
+                        .smooth_ms(smooth_ms),
+                ),
+                SimpleNodeFactory::new(|| Box::new(ParamEcho(0.0)), Polyphony::Global),
+            );
+        }
+
+        // Cutoff after each 64-frame block following a runtime change
+        let glide = |type_id: u32| {
+            let mut def = GraphDef::new();
+            let echo = def.add_node(type_id);
+            def.set_param(echo, CUTOFF, 1_000.0);
+            def.output_node = Some(echo);
+
+            let mut graph = compile(&def, &registry, 64, 1).unwrap();
+            graph.prepare(48_000.0);
+            let voices = VoiceAllocator::new(1);
+            graph.process(64, 0, 120.0, &voices);
+            assert_eq!(graph.output_buffer(64).unwrap()[0], 1_000.0);
+
+            graph.set_param_by_id(echo, CUTOFF, 5_000.0);
+            (0..60)
+                .map(|_| {
+                    graph.process(64, 0, 120.0, &voices);
+                    graph.output_buffer(64).unwrap()[0]
+                })
+                .collect::<Vec<f32>>()
+        };
+
+        // Instant params jump on the next block
+        assert!(glide(INSTANT_NODE).iter().all(|&v| v == 5_000.0));
+
+        // Smoothed ones rise steadily, covering 1 - 1/e of the change in
+        // 20 ms (15 blocks) and settling on the target
+        let smoothed = glide(SMOOTH_NODE);
+        assert!(smoothed[0] > 1_000.0 && smoothed[0] < 1_500.0);
+        assert!(smoothed.windows(2).all(|w| w[1] >= w[0]));
+        let expected = 5_000.0 - 4_000.0 * (-1.0f32).exp();
+        assert!((smoothed[14] - expected).abs() < 1.0, "{}", smoothed[14]);
+        assert!((smoothed[59] - 5_000.0).abs() < 100.0);
+    }
+
+    #[test]
+    fn test_mod_route_envelope_to_cutoff() {
+        use crate::nodes::{node_types, params, register_standard_nodes};
+
+        const ECHO_NODE: u32 = 902;
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+        registry.register(
+            NodeTypeInfo::new(ECHO_NODE, "Echo", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ParamEcho(0.0)), Polyphony::PerVoice),
+        );
+
+        // 20 ms linear attack, decay to half, 20 ms release
+        let mut def = GraphDef::new();
+        let env = def.add_node(node_types::ADSR_ENV);
+        let echo = def.add_node(ECHO_NODE);
+        def.set_param(env, params::ATTACK, 0.02);
+        def.set_param(env, params::DECAY, 0.02);
+        def.set_param(env, params::SUSTAIN, 0.5);
+        def.set_param(env, params::RELEASE, 0.02);
+        def.set_param(env, params::CURVE, 0.0);
+        def.set_param(echo, params::CUTOFF, 500.0);
+        def.add_mod_route(env, echo, params::CUTOFF, 4_000.0);
+        def.output_node = Some(echo);
+
+        let mut graph = compile(&def, &registry, 64, 1).unwrap();
+        graph.prepare(48_000.0);
+
+        // 15 blocks of attack, 15 of decay, hold, then release at block 45
+        let mut voices = VoiceAllocator::new(1);
+        voices.note_on(60, 1.0);
+        let mut cutoffs = Vec::new();
+        for block in 0..90 {
+            if block == 45 {
+                voices.note_off(60);
+            }
+            graph.process(64, block * 64, 120.0, &voices);
+            voices.clear_triggers();
+            cutoffs.push(graph.output_buffer(64).unwrap()[0]);
+        }
+
+        // Sweeps up from the base during the attack
+        assert!(cutoffs[0] < 600.0, "{cutoffs:?}");
+        assert!(cutoffs[..15].windows(2).all(|w| w[1] > w[0]), "{cutoffs:?}");
+        // Peaks at base + depth, settles at the sustain level
+        let peak = cutoffs.iter().cloned().fold(0.0, f32::max);
+        assert!((peak - 4_500.0).abs() < 300.0, "{cutoffs:?}");
+        assert!((cutoffs[40] - 2_500.0).abs() < 1.0, "{cutoffs:?}");
+        // Falls back to the base after the release
+        assert!(cutoffs[50] < 2_500.0 && cutoffs[50] > 500.0, "{cutoffs:?}");
+        assert!((cutoffs[80] - 500.0).abs() < 1.0, "{cutoffs:?}");
+    }
+
+    #[test]
+    fn test_filter_envelope_sweeps_cutoff_down() {
+        use crate::modulation::ModScale;
+        use crate::nodes::{node_types, params, register_standard_nodes};
+
+        const ECHO_NODE: u32 = 902;
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+        registry.register(
+            NodeTypeInfo::new(ECHO_NODE, "Echo", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ParamEcho(0.0)), Polyphony::PerVoice),
+        );
+
+        // Near-instant attack, then a 250 ms linear decay to nothing, three
+        // octaves above a 250 Hz cutoff
+        let mut def = GraphDef::new();
+        let echo = def.add_node(ECHO_NODE);
+        let env = def.add_node(node_types::FILTER_ENV);
+        def.set_param(env, params::ATTACK, 0.001);
+        def.set_param(env, params::DECAY, 0.25);
+        def.set_param(env, params::SUSTAIN, 0.0);
+        def.set_param(env, params::CURVE, 0.0);
+        def.set_param(env, params::ENV_AMOUNT, 36.0);
+        def.set_param(echo, params::CUTOFF, 250.0);
+        def.add_mod_route(env, echo, params::CUTOFF, 1.0);
+        def.output_node = Some(echo);
+
+        let mut graph = compile(&def, &registry, 64, 1).unwrap();
+        assert_eq!(graph.mod_routes[0].scale, ModScale::Octaves);
+        graph.prepare(48_000.0);
+
+        let mut voices = VoiceAllocator::new(1);
+        voices.note_on(60, 1.0);
+        let mut cutoffs = Vec::new();
+        for block in 0..200 {
+            graph.process(64, block * 64, 120.0, &voices);
+            voices.clear_triggers();
+            cutoffs.push(graph.output_buffer(64).unwrap()[0]);
+        }
+
+        // Falls from three octaves up for as long as the note decays...
+        assert!((cutoffs[1] - 2_000.0).abs() < 50.0, "{cutoffs:?}");
+        assert!(
+            cutoffs[1..188].windows(2).all(|w| w[1] < w[0]),
+            "{cutoffs:?}"
+        );
+        // ...evenly in pitch: halfway through it is an octave and a half up
+        let halfway = 250.0 * 2.0f32.powf(1.5);
+        assert!((cutoffs[95] - halfway).abs() < 15.0, "{cutoffs:?}");
+        // ...and settles on the base once the sweep is over
+        assert!((cutoffs[199] - 250.0).abs() < 1.0, "{cutoffs:?}");
+    }
+
+    // Test node that outputs a single sample of 0.5, then silence
+    struct ImpulseNode(bool);
+
+    impl Node for ImpulseNode {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            _ctx: &ProcessContext,
+            _inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            if !self.0 {
+                output.channel_mut(0)[0] = 0.5;
+                self.0 = true;
+            }
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn set_param(&mut self, _: u32, _: f32) {}
+    }
+
+    #[test]
+    fn test_latency_compensation_aligns_parallel_paths() {
+        use crate::nodes::{node_types, register_standard_nodes};
+
+        const IMPULSE_NODE: u32 = 903;
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+        registry.register(
+            NodeTypeInfo::new(IMPULSE_NODE, "Impulse", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ImpulseNode(false)), Polyphony::Global).channels(1),
+        );
+
+        // One path through the lookahead limiter, one straight to the mixer
+        let mut def = GraphDef::new();
+        let impulse = def.add_node(IMPULSE_NODE);
+        let limiter = def.add_node(node_types::LIMITER);
+        let mixer = def.add_node(node_types::MIXER);
+        def.connect(impulse, 0, limiter, 0);
+        def.connect(limiter, 0, mixer, 0);
+        def.connect(impulse, 0, mixer, 0);
+        def.output_node = Some(mixer);
+
+        let mut graph = compile(&def, &registry, 64, 1).unwrap();
+        graph.prepare(48_000.0);
+        let latency = graph.latency_samples();
+        assert_eq!(latency, 240); // 5 ms at 48 kHz
+
+        let voices = VoiceAllocator::new(1);
+        let mut left = Vec::new();
+        for block in 0..8 {
+            graph.process(64, block * 64, 120.0, &voices);
+            left.extend_from_slice(&graph.output_buffer(64).unwrap()[..64]);
+        }
+
+        // Both halves of the transient arrive together, after the latency
+        for (i, &sample) in left.iter().enumerate() {
+            let expected = if i == latency { 1.0 } else { 0.0 };
+            assert!((sample - expected).abs() < 1e-6, "sample {i} = {sample}");
+        }
+    }
+
+    // Test node that flags any input sharing memory with its output
+    struct AliasProbe(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl Node for AliasProbe {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            _ctx: &ProcessContext,
+            inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            let out = output.samples().as_ptr_range();
+            for input in inputs {
+                let data = input.samples().as_ptr_range();
+                if data.start < out.end && out.start < data.end {
+                    self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                }
+            }
+            output.samples_mut().fill(1.0);
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn set_param(&mut self, _: u32, _: f32) {}
+    }
+
+    #[test]
+    fn test_inputs_never_alias_output() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const CONST_NODE: u32 = 1;
+        const VOICE_CONST_NODE: u32 = 2;
+        const PROBE_NODE: u32 = 3;
+        const VOICE_PROBE_NODE: u32 = 4;
+
+        let overlaps = Arc::new(AtomicUsize::new(0));
+        let mut registry = NodeRegistry::new();
+        registry.register(
+            NodeTypeInfo::new(CONST_NODE, "Const", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ConstNode(0.5)), Polyphony::Global),
+        );
+        registry.register(
+            NodeTypeInfo::new(VOICE_CONST_NODE, "Voice Const", "Test"),
+            SimpleNodeFactory::new(|| Box::new(ConstNode(0.5)), Polyphony::PerVoice),
+        );
+        let probe_overlaps = overlaps.clone();
+        registry.register(
+            NodeTypeInfo::new(PROBE_NODE, "Probe", "Test"),
+            SimpleNodeFactory::new(
+                move || Box::new(AliasProbe(probe_overlaps.clone())),
+                Polyphony::Global,
+            ),
+        );
+        let voice_overlaps = overlaps.clone();
+        registry.register(
+            NodeTypeInfo::new(VOICE_PROBE_NODE, "Voice Probe", "Test"),
+            SimpleNodeFactory::new(
+                move || Box::new(AliasProbe(voice_overlaps.clone())),
+                Polyphony::PerVoice,
+            ),
+        );
+
+        // Direct, per-voice, voice-mixed and port-mixed inputs
+        let mut def = GraphDef::new();
+        let global = def.add_node(CONST_NODE);
+        let voice = def.add_node(VOICE_CONST_NODE);
+        let voice_probe = def.add_node(VOICE_PROBE_NODE);
+        let probe = def.add_node(PROBE_NODE);
+        def.connect(voice, 0, voice_probe, 0);
+        def.connect(global, 0, voice_probe, 1);
+        def.connect(voice_probe, 0, probe, 0);
+        def.connect(global, 0, probe, 0);
+        def.connect(global, 0, probe, 1);
+        def.output_node = Some(probe);
+
+        let mut graph = compile(&def, &registry, 64, 2).unwrap();
+        graph.prepare(48_000.0);
+        let mut voices = VoiceAllocator::new(2);
+        voices.note_on(60, 1.0);
+        voices.note_on(64, 1.0);
+        graph.process(64, 0, 120.0, &voices);
+
+        assert_eq!(graph.output_buffer(64).unwrap()[0], 1.0);
+        assert_eq!(overlaps.load(Ordering::Relaxed), 0);
+    }
 }
@@ -4,12 +4,18 @@
 //! and handles commands from the UI thread. All operations are designed to be
 //! deterministic, allocation-free, and lock-free for real-time safety.
 
+use crate::arpeggiator::ArpSettings;
 use crate::event::Event;
 use crate::execution_plan::{ExecutionPlan, SlicePlan};
 use crate::graph::Graph;
-use crate::state::Command;
+use crate::metronome::Metronome;
+use crate::node::DEFAULT_TUNING;
+use crate::state::{Command, NOTE_COUNT, TuningTable};
 use crate::voice_allocator::VoiceAllocator;
 
+/// Channels of the interleaved input pushed with `Engine::push_input`.
+pub const INPUT_CHANNELS: usize = 2;
+
 /// Real-time audio engine.
 ///
 /// This struct runs exclusively on the audio thread.
@@ -30,17 +36,81 @@ pub struct Engine {
 
     /// Current tempo in BPM
     bpm: f64,
+
+    /// Seek target awaiting pickup by the scheduler
+    pending_seek: Option<f64>,
+
+    /// Tempo change `(target_bpm, over_beats)` awaiting pickup by the scheduler
+    pending_tempo: Option<(f64, f64)>,
+
+    /// Loop region in beats, mirrored into the scheduler each block
+    loop_region: Option<(f64, f64)>,
+
+    /// Metronome beats per bar, mirrored into the scheduler each block
+    metronome: Option<u32>,
+
+    /// Reference pitch of A4 in Hz, kept across graph swaps
+    tuning: f64,
+
+    /// Note ratios from the session's tuning table, kept across graph swaps
+    note_ratios: Option<[f32; NOTE_COUNT]>,
+
+    /// Interleaved input captured since `Command::StartRecording`
+    recording: Option<Vec<f32>>,
+
+    /// Take finished by `Command::StopRecording`, awaiting pickup
+    finished_take: Option<Vec<f32>>,
+
+    /// Arpeggiator settings, mirrored into the scheduler each block
+    arpeggiator: Option<ArpSettings>,
+
+    /// Notes held for the arpeggiator, one bit per MIDI note
+    held_notes: [u64; 2],
+
+    /// Velocity of the last note added to `held_notes`
+    held_velocity: f32,
+
+    /// Click generator mixed over the graph output
+    click: Metronome,
+
+    /// Planar output of the last block, assembled from its slices
+    output: Vec<f32>,
+
+    /// Channel count of the graph's output node
+    output_channels: usize,
 }
 
 impl Engine {
     pub fn new(graph: Graph, voices: VoiceAllocator) -> Self {
-        Self {
+        let mut engine = Self {
             graph,
             voices,
             sample_pos: 0,
             playing: false,
             bpm: 120.0,
-        }
+            pending_seek: None,
+            pending_tempo: None,
+            loop_region: None,
+            metronome: None,
+            tuning: DEFAULT_TUNING,
+            note_ratios: None,
+            recording: None,
+            finished_take: None,
+            arpeggiator: None,
+            held_notes: [0; 2],
+            held_velocity: 1.0,
+            click: Metronome::new(48_000.0),
+            output: Vec::new(),
+            output_channels: 0,
+        };
+        engine.size_output();
+        engine
+    }
+
+    /// Size the block output buffer for the current graph.
+    fn size_output(&mut self) {
+        self.output_channels = self.graph.output_buffer(1).map_or(0, |b| b.len());
+        self.output = vec![0.0; self.output_channels * self.graph.max_block];
     }
 
     /// Check if the engine is currently playing.
@@ -55,15 +125,96 @@ impl Engine {
         self.bpm
     }
 
+    /// Take the most recent seek target, if a seek was requested.
+    ///
+    /// The scheduler owns musical time, so the render loop must forward this
+    /// to `Scheduler::seek` before compiling the next block.
+    #[inline]
+    pub fn take_pending_seek(&mut self) -> Option<f64> {
+        self.pending_seek.take()
+    }
+
+    /// Take the most recent tempo change as `(target_bpm, over_beats)`.
+    ///
+    /// Instant changes have `over_beats == 0`. The render loop forwards this
+    /// to `Scheduler::ramp_tempo` before compiling the next block.
+    #[inline]
+    pub fn take_pending_tempo(&mut self) -> Option<(f64, f64)> {
+        self.pending_tempo.take()
+    }
+
+    /// The loop region in beats, if looping is enabled.
+    ///
+    /// Like seeks, loops are applied by the scheduler; the render loop
+    /// forwards this to `Scheduler::set_loop` before compiling each block.
+    #[inline]
+    pub fn loop_region(&self) -> Option<(f64, f64)> {
+        self.loop_region
+    }
+
+    /// Capture a block of interleaved input from the host callback.
+    ///
+    /// Frames carry `INPUT_CHANNELS` samples each and are ignored unless a
+    /// take is recording. The take grows as input arrives, which makes
+    /// recording the one place the audio thread allocates.
+    pub fn push_input(&mut self, input: &[f32]) {
+        if let Some(take) = &mut self.recording {
+            take.extend_from_slice(input);
+        }
+    }
+
+    /// Take the input recorded before the last `Command::StopRecording`.
+    #[inline]
+    pub fn take_recording(&mut self) -> Option<Vec<f32>> {
+        self.finished_take.take()
+    }
+
+    /// Beats per bar while the metronome is enabled.
+    ///
+    /// The scheduler places the clicks; the render loop forwards this to
+    /// `Scheduler::set_metronome` before compiling each block.
+    #[inline]
+    pub fn metronome(&self) -> Option<u32> {
+        self.metronome
+    }
+
+    /// Arpeggiator settings while it is on.
+    ///
+    /// Live notes are held rather than played while the arpeggiator is on;
+    /// the render loop forwards this and `held_notes` to
+    /// `Scheduler::set_arpeggiator`, which plays the pattern.
+    #[inline]
+    pub fn arpeggiator(&self) -> Option<ArpSettings> {
+        self.arpeggiator
+    }
+
+    /// Notes held for the arpeggiator (one bit per MIDI note) and the
+    /// velocity of the last one pressed.
+    #[inline]
+    pub fn held_notes(&self) -> ([u64; 2], f32) {
+        (self.held_notes, self.held_velocity)
+    }
+
     /// Execute a precompiled execution plan.
     ///
     /// Called once per audio block from the audio callback.
     /// It must not allocate or block.
     pub fn process_plan(&mut self, plan: &ExecutionPlan) {
         self.sample_pos = plan.block_start_sample;
+        self.click.set_sample_rate(plan.sample_rate);
 
         for slice in &plan.slices {
             self.process_slice(slice, plan);
+            self.copy_slice_output(slice, plan.block_frames);
+            if self.click.is_active() {
+                self.click.mix_into(
+                    &mut self.output,
+                    self.output_channels,
+                    plan.block_frames,
+                    slice.frame_offset,
+                    slice.frame_count,
+                );
+            }
         }
 
         // Clear one-shot voice triggers at block end, after processing.
@@ -84,18 +235,40 @@ impl Engine {
         self.graph
             .process(slice.frame_count, slice_start, plan.bpm, &self.voices);
 
+        for (voice_id, level) in self.graph.voice_levels() {
+            self.voices.set_level(voice_id, level);
+        }
+
         // Deactivate voices that finished their envelope release
         for voice_id in self.graph.drain_finished_voices() {
             self.voices.deactivate(voice_id);
         }
     }
 
+    /// Copy a slice's output into place in the block output.
+    ///
+    /// Every slice renders from the start of the graph's buffers, so without
+    /// this only the last slice of a block would be visible.
+    #[inline]
+    fn copy_slice_output(&mut self, slice: &SlicePlan, block_frames: usize) {
+        let frames = slice.frame_count;
+        let Some(rendered) = self.graph.output_buffer(frames) else {
+            return;
+        };
+        for ch in 0..self.output_channels {
+            let dst = ch * block_frames + slice.frame_offset;
+            self.output[dst..dst + frames]
+                .copy_from_slice(&rendered[ch * frames..(ch + 1) * frames]);
+        }
+    }
+
     /// Apply a musical event immediately.
     #[inline]
     fn apply_event(&mut self, event: &Event) {
         match event {
             Event::NoteOn { note, velocity } => {
                 self.voices.note_on(*note, *velocity);
+                self.graph.retrigger();
             }
 
             Event::NoteOff { note } => {
@@ -112,6 +285,7 @@ impl Engine {
                 // voice allocation per-instrument in the future.
                 let _ = node_id;
                 self.voices.note_on(*note, *velocity);
+                self.graph.retrigger();
             }
 
             Event::NoteOffTarget { node_id, note } => {
@@ -119,6 +293,10 @@ impl Engine {
                 self.voices.note_off(*note);
             }
 
+            Event::PitchBend { semitones } => {
+                self.voices.set_pitch_bend(*semitones);
+            }
+
             Event::ParamChange {
                 node_id,
                 param_id,
@@ -133,6 +311,7 @@ impl Engine {
                 start_sample,
                 duration_samples,
                 gain,
+                pitch_shift,
             } => {
                 self.graph.start_audio_by_id(
                     *node_id,
@@ -140,23 +319,40 @@ impl Engine {
                     *start_sample,
                     *duration_samples,
                     *gain,
+                    *pitch_shift,
                 );
             }
 
             Event::AudioStop { node_id, audio_id } => {
                 self.graph.stop_audio_by_id(*node_id, *audio_id);
             }
+
+            Event::Click { accent } => {
+                self.click.trigger(*accent);
+            }
         }
     }
 
     /// Reset the engine (on transport stop/seek)
     pub fn reset(&mut self) {
         self.graph.reset();
+        self.voices.reset();
+        self.click.reset();
+        self.output.fill(0.0);
     }
 
-    /// Get the output buffer after processing
+    /// Channel count of `output_buffer` (0 when there is no output node)
+    #[inline]
+    pub fn output_channels(&self) -> usize {
+        self.output_channels
+    }
+
+    /// Get the output buffer after processing (planar, `frames` per channel)
     pub fn output_buffer(&self, frames: usize) -> Option<&[f32]> {
-        self.graph.output_buffer(frames)
+        if self.output_channels == 0 {
+            return None;
+        }
+        self.output.get(..self.output_channels * frames)
     }
 
     /// Get active voice count
@@ -164,6 +360,11 @@ impl Engine {
         self.voices.active_count()
     }
 
+    /// Bitmask of held notes (see `VoiceAllocator::active_note_mask`)
+    pub fn active_note_mask(&self) -> [u64; 2] {
+        self.voices.active_note_mask()
+    }
+
     // ═══════════════════════════════════════════════════════════════════
     // Command Processing
     // ═══════════════════════════════════════════════════════════════════
@@ -190,6 +391,11 @@ impl Engine {
                 true
             }
 
+            Command::SetNodeBypass { node_id, bypassed } => {
+                self.graph.set_bypass_by_id(*node_id, *bypassed);
+                true
+            }
+
             Command::BeginParamGesture { .. } | Command::EndParamGesture { .. } => {
                 // Gestures are for automation recording, not RT processing
                 true
@@ -211,21 +417,77 @@ impl Engine {
 
             Command::SetTempo { bpm } => {
                 self.bpm = *bpm;
+                self.pending_tempo = Some((*bpm, 0.0));
+                true
+            }
+
+            Command::RampTempo {
+                target_bpm,
+                over_beats,
+            } => {
+                self.bpm = *target_bpm;
+                self.pending_tempo = Some((*target_bpm, *over_beats));
                 true
             }
 
-            Command::Seek { beat: _ } => {
-                // Seek requires coordination with the scheduler.
-                // Reset the engine state; the scheduler handles position.
+            Command::Seek { beat } => {
+                // Silence voices/envelopes here; the scheduler picks up the
+                // new position via take_pending_seek().
                 self.reset();
+                self.pending_seek = Some(*beat);
+                true
+            }
+
+            Command::SetLoop {
+                enabled,
+                start,
+                end,
+            } => {
+                self.loop_region = (*enabled && end > start).then_some((*start, *end));
+                true
+            }
+
+            Command::SetMetronome {
+                enabled,
+                beats_per_bar,
+            } => {
+                self.metronome = enabled.then_some((*beats_per_bar).max(1));
+                true
+            }
+
+            Command::SetTuning { a4 } => {
+                self.tuning = *a4;
+                self.graph.set_tuning(*a4);
+                true
+            }
+
+            Command::SetTuningTable { table } => {
+                self.note_ratios = table.as_ref().map(TuningTable::note_ratios);
+                self.graph.set_note_ratios(self.note_ratios);
                 true
             }
 
             // ═══════════════════════════════════════════════════════════
             // MIDI - RT safe
             // ═══════════════════════════════════════════════════════════
+            Command::NoteOn { note, velocity } if self.arpeggiator.is_some() => {
+                if *note < 128 {
+                    self.held_notes[*note as usize / 64] |= 1 << (note % 64);
+                    self.held_velocity = *velocity;
+                }
+                true
+            }
+
             Command::NoteOn { note, velocity } => {
                 self.voices.note_on(*note, *velocity);
+                self.graph.retrigger();
+                true
+            }
+
+            Command::NoteOff { note } if self.arpeggiator.is_some() => {
+                if *note < 128 {
+                    self.held_notes[*note as usize / 64] &= !(1 << (note % 64));
+                }
                 true
             }
 
@@ -234,15 +496,56 @@ impl Engine {
                 true
             }
 
+            Command::SetArpeggiator { settings } => {
+                if settings.is_none() {
+                    self.held_notes = [0; 2];
+                }
+                self.arpeggiator = *settings;
+                true
+            }
+
+            Command::PitchBend { semitones } => {
+                self.voices.set_pitch_bend(*semitones);
+                true
+            }
+            Command::SetVoiceMode { mode } => {
+                self.voices.set_mode(*mode);
+                true
+            }
+            Command::SetGlideTime { seconds } => {
+                self.voices.set_glide_time(*seconds);
+                true
+            }
+            Command::SetVoiceStealMode { mode } => {
+                self.voices.set_steal_mode(*mode);
+                true
+            }
+            Command::SetUnison {
+                voices,
+                detune_cents,
+                spread,
+            } => {
+                self.voices.set_unison(*voices, *detune_cents, *spread);
+                true
+            }
+            Command::SetVoicePanSpread { spread } => {
+                self.voices.set_pan_spread(*spread);
+                true
+            }
+
             // ═══════════════════════════════════════════════════════════
             // Graph structure - NOT RT safe, requires recompilation
             // ═══════════════════════════════════════════════════════════
             Command::AddNode { .. }
             | Command::AddNodeDef { .. }
             | Command::RemoveNode { .. }
+            | Command::RestoreNode { .. }
             | Command::Connect { .. }
             | Command::Disconnect { .. }
             | Command::SetOutputNode { .. }
+            | Command::AddModRoute { .. }
+            | Command::SetVelocitySensitivity { .. }
+            | Command::RemoveModRoute { .. }
             | Command::ClearGraph
             | Command::LoadConnections { .. }
             | Command::RecompileGraph => {
@@ -267,7 +570,11 @@ impl Engine {
             | Command::RemoveNoteFromClip { .. }
             | Command::ClearClip { .. }
             | Command::SetClipLength { .. }
-            | Command::SetClipLooping { .. } => true,
+            | Command::SetClipLooping { .. }
+            | Command::SetClipTranspose { .. }
+            | Command::SetClipVelocityScale { .. }
+            | Command::SetClipHumanize { .. }
+            | Command::QuantizeClip { .. } => true,
 
             // Track commands - handled by session state
             Command::CreateTrack { .. }
@@ -278,6 +585,11 @@ impl Engine {
             | Command::SetTrackSolo { .. }
             | Command::SetTrackArmed { .. }
             | Command::SetTrackTarget { .. }
+            | Command::AddTrackKeyZone { .. }
+            | Command::ClearTrackKeyZones { .. }
+            | Command::SetTrackParent { .. }
+            | Command::SetTrackSend { .. }
+            | Command::SetAuxEffect { .. }
             | Command::SetClipSlot { .. } => true,
 
             // Scene commands - handled by session state
@@ -291,8 +603,23 @@ impl Engine {
             // Timeline commands - handled by session state
             Command::ScheduleClip { .. } | Command::RemoveClipPlacement { .. } => true,
 
+            // Recording - the session places the take once it comes back
+            Command::StartRecording { .. } => {
+                self.recording = Some(Vec::new());
+                true
+            }
+            Command::StopRecording => {
+                self.finished_take = self.recording.take();
+                true
+            }
+
             // Compilation commands - sync handled elsewhere
             Command::SyncTrackParams { .. } | Command::SyncAllTrackParams => true,
+
+            // CC routing - the bridge translates CCs into SetParam
+            Command::ControlChange { .. }
+            | Command::MapControlChange { .. }
+            | Command::UnmapControlChange { .. } => true,
         }
     }
 
@@ -302,6 +629,9 @@ impl Engine {
     /// The new graph should already be prepared (call `graph.prepare(sample_rate)`).
     pub fn swap_graph(&mut self, new_graph: Graph) {
         self.graph = new_graph;
+        self.graph.set_tuning(self.tuning);
+        self.graph.set_note_ratios(self.note_ratios);
+        self.size_output();
     }
 
     /// Get a reference to the current graph.
@@ -314,3 +644,172 @@ impl Engine {
         &mut self.graph
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::audio_buffer::{InputBuffer, OutputBuffer};
+    use crate::execution_plan::ExecutionPlan;
+    use crate::node::{Node, Polyphony, ProcessContext};
+    use crate::node_factory::SimpleNodeFactory;
+    use crate::nodes::{AdsrEnvelope, AudioPlayerNode, SharedAudioData, SineOsc, params};
+
+    /// Pass-through that counts the voices it processes and, like a
+    /// filter, never reports silence.
+    struct CountingNode(Arc<AtomicUsize>);
+
+    impl Node for CountingNode {
+        fn prepare(&mut self, _: f64, _: usize) {}
+
+        fn process(
+            &mut self,
+            _ctx: &ProcessContext,
+            inputs: &[InputBuffer],
+            output: &mut OutputBuffer,
+        ) -> bool {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            if let Some(input) = inputs.first() {
+                output.channel_mut(0).copy_from_slice(input.channel(0));
+            }
+            false
+        }
+
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn set_param(&mut self, _: u32, _: f32) {}
+    }
+
+    #[test]
+    fn test_realtime_note_commands_drive_voices() {
+        let mut graph = Graph::new(128, 4);
+        graph.prepare(48_000.0);
+        let mut engine = Engine::new(graph, VoiceAllocator::new(4));
+
+        assert!(engine.process_command(&Command::NoteOn {
+            note: 60,
+            velocity: 0.9,
+        }));
+        let mut plan = ExecutionPlan::new(48_000.0);
+        plan.block_frames = 128;
+        plan.slices.push(SlicePlan::new(0, 128));
+        engine.process_plan(&plan);
+
+        assert_eq!(engine.active_voices(), 1);
+        let voice = engine.voices.active_voices().next().unwrap();
+        assert_eq!(voice.note, 60);
+        assert!(voice.gate);
+
+        engine.process_command(&Command::NoteOff { note: 60 });
+        let voice = engine.voices.active_voices().next().unwrap();
+        assert!(!voice.gate && voice.release);
+    }
+
+    #[test]
+    fn test_released_voice_frees_itself() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut graph = Graph::new(128, 4);
+        let osc = graph.add_node(
+            &SimpleNodeFactory::new(|| Box::new(SineOsc::new()), Polyphony::PerVoice).channels(1),
+        );
+        let env = graph.add_node(
+            &SimpleNodeFactory::new(|| Box::new(AdsrEnvelope::new()), Polyphony::PerVoice)
+                .channels(1),
+        );
+        let counter = Arc::clone(&calls);
+        let filter = graph.add_node(
+            &SimpleNodeFactory::new(
+                move || Box::new(CountingNode(Arc::clone(&counter))),
+                Polyphony::PerVoice,
+            )
+            .channels(1),
+        );
+        graph.connect(osc, env);
+        graph.connect(env, filter);
+        graph.output_node = filter;
+        graph.set_param(env, params::RELEASE, 0.01);
+        graph.prepare(48_000.0);
+        let mut engine = Engine::new(graph, VoiceAllocator::new(4));
+
+        let mut plan = ExecutionPlan::new(48_000.0);
+        plan.block_frames = 128;
+        plan.slices.push(SlicePlan::new(0, 128));
+
+        engine.process_command(&Command::NoteOn {
+            note: 60,
+            velocity: 0.9,
+        });
+        for _ in 0..10 {
+            engine.process_plan(&plan);
+        }
+        assert_eq!(engine.active_voices(), 1);
+
+        // A 10 ms release lasts under four blocks, plus one silent block
+        engine.process_command(&Command::NoteOff { note: 60 });
+        for _ in 0..6 {
+            engine.process_plan(&plan);
+        }
+        assert_eq!(engine.active_voices(), 0);
+
+        // The freed voice is no longer processed
+        let processed = calls.load(Ordering::Relaxed);
+        for _ in 0..10 {
+            engine.process_plan(&plan);
+        }
+        assert_eq!(calls.load(Ordering::Relaxed), processed);
+    }
+
+    #[test]
+    fn test_audio_start_lands_on_its_slice_offset() {
+        const PLAYER_ID: u32 = 7;
+        let block = 256;
+        let offset = 100;
+
+        let mut graph = Graph::new(block, 1);
+        let player = graph.add_node(&SimpleNodeFactory::new(
+            || Box::new(AudioPlayerNode::new(2)),
+            Polyphony::Global,
+        ));
+        graph.output_node = player;
+        graph.id_to_index.insert(PLAYER_ID, player);
+        graph.load_audio(
+            player,
+            SharedAudioData {
+                id: 1,
+                sample_rate: 48_000.0,
+                channels: 1,
+                frames: 48_000,
+                samples: Arc::new(vec![0.5; 48_000]),
+            },
+        );
+        graph.prepare(48_000.0);
+        let mut engine = Engine::new(graph, VoiceAllocator::new(1));
+
+        // The scheduler splits the block at the event, which starts its slice
+        let mut plan = ExecutionPlan::new(48_000.0);
+        plan.block_frames = block;
+        plan.slices.push(SlicePlan::new(0, offset));
+        plan.push_slice(offset, block - offset)
+            .events
+            .push(Event::AudioStart {
+                node_id: PLAYER_ID,
+                audio_id: 1,
+                start_sample: 0,
+                duration_samples: 48_000,
+                gain: 1.0,
+                pitch_shift: 0.0,
+            });
+        engine.process_plan(&plan);
+
+        let output = engine.output_buffer(block).unwrap();
+        for channel in output.chunks(block) {
+            let first = channel.iter().position(|&s| s != 0.0);
+            assert_eq!(first, Some(offset));
+            assert!(channel[offset..].iter().all(|&s| s == 0.5));
+        }
+    }
+}
@@ -29,6 +29,9 @@ pub enum MusicalEvent {
     /// Note off targeted to a specific node.
     NoteOffTarget { beat: f64, node_id: u32, note: u8 },
 
+    /// Channel pitch bend (semitones) applied to all voices.
+    PitchBend { beat: f64, semitones: f32 },
+
     /// Parameter change.
     ParamChange {
         beat: f64,
@@ -50,6 +53,8 @@ pub enum MusicalEvent {
         duration_samples: u64,
         /// Gain level.
         gain: f32,
+        /// Varispeed pitch shift in semitones.
+        pitch_shift: f32,
     },
 
     /// Stop audio region playback.
@@ -58,6 +63,9 @@ pub enum MusicalEvent {
         node_id: u32,
         audio_id: AudioPoolId,
     },
+
+    /// Metronome click (accented on the first beat of a bar).
+    Click { beat: f64, accent: bool },
 }
 
 impl MusicalEvent {
@@ -67,9 +75,11 @@ impl MusicalEvent {
             MusicalEvent::NoteOff { beat, .. } => *beat,
             MusicalEvent::NoteOnTarget { beat, .. } => *beat,
             MusicalEvent::NoteOffTarget { beat, .. } => *beat,
+            MusicalEvent::PitchBend { beat, .. } => *beat,
             MusicalEvent::ParamChange { beat, .. } => *beat,
             MusicalEvent::AudioStart { beat, .. } => *beat,
             MusicalEvent::AudioStop { beat, .. } => *beat,
+            MusicalEvent::Click { beat, .. } => *beat,
         }
     }
 }
@@ -102,6 +112,9 @@ pub enum Event {
     /// Note off targeted to a specific node.
     NoteOffTarget { node_id: u32, note: u8 },
 
+    /// Channel pitch bend (semitones).
+    PitchBend { semitones: f32 },
+
     /// Parameter change.
     ParamChange {
         node_id: u32,
@@ -121,8 +134,13 @@ pub enum Event {
         duration_samples: u64,
         /// Gain level.
         gain: f32,
+        /// Varispeed pitch shift in semitones.
+        pitch_shift: f32,
     },
 
     /// Stop audio playback.
     AudioStop { node_id: u32, audio_id: AudioPoolId },
+
+    /// Trigger a metronome click.
+    Click { accent: bool },
 }
@@ -30,6 +30,9 @@ pub struct ExecutionPlan {
 
     /// Event-free slices (for sample-accurate event timing)
     pub slices: Vec<SlicePlan>,
+
+    /// Slices from earlier blocks kept for reuse, in reverse order
+    spare_slices: Vec<SlicePlan>,
 }
 
 impl ExecutionPlan {
@@ -40,7 +43,31 @@ impl ExecutionPlan {
             bpm: 120.0,
             sample_rate,
             slices: Vec::with_capacity(16), // Pre-allocate for typical case
+            spare_slices: Vec::with_capacity(16),
+        }
+    }
+
+    /// Remove all slices, keeping them and their event storage for reuse.
+    pub fn clear_slices(&mut self) {
+        for slice in &mut self.slices {
+            slice.events.clear();
         }
+        // Reversed so the next block gets the same slice at each index
+        self.spare_slices.extend(self.slices.drain(..).rev());
+    }
+
+    /// Append an empty slice, reusing one cleared earlier when available.
+    pub fn push_slice(&mut self, frame_offset: usize, frame_count: usize) -> &mut SlicePlan {
+        let slice = match self.spare_slices.pop() {
+            Some(mut slice) => {
+                slice.frame_offset = frame_offset;
+                slice.frame_count = frame_count;
+                slice
+            }
+            None => SlicePlan::new(frame_offset, frame_count),
+        };
+        self.slices.push(slice);
+        self.slices.last_mut().unwrap()
     }
 }
 
@@ -8,7 +8,7 @@
 //! - String parameters must be valid UTF-8 (Swift strings are always valid)
 //! - Caller must call the corresponding _destroy function for each _create
 
-use std::ffi::{CStr, c_char, c_void};
+use std::ffi::{CStr, CString, c_char, c_void};
 
 use crate::bridge::{EngineHandle, SessionHandle, create_bridge};
 use crate::engine::Engine;
@@ -18,7 +18,7 @@ use crate::node_factory::NodeRegistry;
 use crate::nodes::register_standard_nodes;
 use crate::plan_handoff::PlanHandoff;
 use crate::scheduler::Scheduler;
-use crate::state::{EngineReadback, Session};
+use crate::state::{EngineReadback, GraphIssue, KeyZone, Session};
 use crate::voice_allocator::VoiceAllocator;
 
 use log::{LevelFilter, error, info};
@@ -102,7 +102,25 @@ impl From<EngineReadback> for HyasynthReadback {
     }
 }
 
+/// Outcome of a graph mutation.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HyaResult {
+    Ok = 0,
+    /// A node ID doesn't exist in the graph.
+    NodeNotFound = 1,
+    /// A port isn't declared by the node's type.
+    InvalidPort = 2,
+    /// The connection would feed a node's output back into itself.
+    CycleDetected = 3,
+    /// The session handle is NULL.
+    NullHandle = 4,
+}
+
 /// Node type info for UI display.
+///
+/// `name` and `category` are owned by the caller and must each be released
+/// with `hya_string_free`.
 #[repr(C)]
 pub struct HyaNodeTypeInfo {
     pub type_id: u32,
@@ -114,6 +132,8 @@ pub struct HyaNodeTypeInfo {
 }
 
 /// Parameter info for UI controls.
+///
+/// `name` is owned by the caller and must be released with `hya_string_free`.
 #[repr(C)]
 pub struct HyaParamInfo {
     pub id: u32,
@@ -123,6 +143,42 @@ pub struct HyaParamInfo {
     pub default_value: f32,
 }
 
+/// One connection between two node ports.
+#[repr(C)]
+pub struct HyaConnection {
+    pub source_node: u32,
+    pub source_port: u32,
+    pub dest_node: u32,
+    pub dest_port: u32,
+}
+
+/// One problem found when validating the graph.
+///
+/// `kind` is 0 for a missing output, 1 for a node that can't reach the
+/// output (`node_id`), and 2 for a connection from `node_id` to
+/// `dest_node` where either node is missing. Unused IDs are u32::MAX.
+#[repr(C)]
+pub struct HyaGraphIssue {
+    pub kind: u32,
+    pub node_id: u32,
+    pub dest_node: u32,
+}
+
+impl From<GraphIssue> for HyaGraphIssue {
+    fn from(issue: GraphIssue) -> Self {
+        let (kind, node_id, dest_node) = match issue {
+            GraphIssue::MissingOutput => (0, u32::MAX, u32::MAX),
+            GraphIssue::UnreachableNode { node_id } => (1, node_id, u32::MAX),
+            GraphIssue::DanglingConnection { source, dest } => (2, source, dest),
+        };
+        Self {
+            kind,
+            node_id,
+            dest_node,
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Registry Functions
 // ═══════════════════════════════════════════════════════════════════════════
@@ -157,6 +213,93 @@ pub unsafe extern "C" fn registry_count(registry: *const HyasynthRegistry) -> u3
     unsafe { (*registry).inner.iter().count() as u32 }
 }
 
+/// Get info for the node type at `index` (0..registry_count), ordered by
+/// type ID.
+///
+/// Returns false and leaves `out_info` untouched if `index` is out of range.
+/// On success the strings in `out_info` must be freed with `hya_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn registry_get_node_type(
+    registry: *const HyasynthRegistry,
+    index: u32,
+    out_info: *mut HyaNodeTypeInfo,
+) -> bool {
+    if registry.is_null() || out_info.is_null() {
+        return false;
+    }
+    let registry = unsafe { &(*registry).inner };
+    let mut types: Vec<_> = registry.iter().collect();
+    types.sort_unstable_by_key(|info| info.type_id);
+    let Some(info) = types.get(index as usize) else {
+        return false;
+    };
+
+    unsafe {
+        *out_info = HyaNodeTypeInfo {
+            type_id: info.type_id,
+            name: to_c_string(&info.name),
+            category: to_c_string(&info.category),
+            num_inputs: info.inputs.len() as u32,
+            num_outputs: info.outputs.len() as u32,
+            num_params: info.parameters.len() as u32,
+        }
+    };
+    true
+}
+
+/// Get info for parameter `param_index` (0..num_params) of a node type.
+///
+/// Returns false and leaves `out_info` untouched if the type or parameter
+/// doesn't exist. On success `out_info.name` must be freed with
+/// `hya_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn registry_get_param(
+    registry: *const HyasynthRegistry,
+    type_id: u32,
+    param_index: u32,
+    out_info: *mut HyaParamInfo,
+) -> bool {
+    if registry.is_null() || out_info.is_null() {
+        return false;
+    }
+    let registry = unsafe { &(*registry).inner };
+    let Some(param) = registry
+        .get_info(type_id)
+        .and_then(|info| info.parameters.get(param_index as usize))
+    else {
+        return false;
+    };
+
+    unsafe {
+        *out_info = HyaParamInfo {
+            id: param.id,
+            name: to_c_string(&param.name),
+            min_value: param.min,
+            max_value: param.max,
+            default_value: param.default,
+        }
+    };
+    true
+}
+
+/// Free a string returned by this module.
+///
+/// # Safety
+/// `s` must be NULL or a string returned by this module that hasn't been
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hya_string_free(s: *const c_char) {
+    if !s.is_null() {
+        unsafe { drop(CString::from_raw(s as *mut c_char)) };
+    }
+}
+
+/// Copy `s` into a caller-owned C string (interior NULs are dropped).
+fn to_c_string(s: &str) -> *const c_char {
+    let bytes: Vec<u8> = s.bytes().filter(|&b| b != 0).collect();
+    CString::new(bytes).unwrap_or_default().into_raw()
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Session/Engine Creation
 // ═══════════════════════════════════════════════════════════════════════════
@@ -237,8 +380,18 @@ pub unsafe extern "C" fn session_create_with_config(
         unsafe { std::ptr::read(config) }
     };
 
-    let session = Session::new(name_str);
+    unsafe { create_handles(Session::new(name_str), &cfg, out_engine) }
+}
 
+/// Build the session and engine handles around `session`.
+///
+/// # Safety
+/// `out_engine` must be NULL or a valid pointer to store the engine handle.
+unsafe fn create_handles(
+    session: Session,
+    cfg: &HyasynthConfig,
+    out_engine: *mut *mut HyasynthEngine,
+) -> *mut HyasynthSession {
     // Create graph and engine with configured parameters
     let mut graph = Graph::new(cfg.max_block_size as usize, cfg.max_voices as usize);
     graph.prepare(cfg.sample_rate);
@@ -270,6 +423,94 @@ pub unsafe extern "C" fn session_create_with_config(
     }))
 }
 
+/// Serialize a session to a JSON byte buffer for saving.
+///
+/// On success `*out_ptr` and `*out_len` describe a buffer owned by the
+/// caller, which must be released with `hya_bytes_free`. Audio pool
+/// entries keep their metadata but not their samples.
+///
+/// Returns false if serialization fails.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_serialize(
+    session: *const HyasynthSession,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> bool {
+    if session.is_null() || out_ptr.is_null() || out_len.is_null() {
+        return false;
+    }
+    let json = match unsafe { (*session).inner.session() }.to_json() {
+        Ok(json) => json,
+        Err(e) => {
+            error!("session_serialize: {}", e);
+            return false;
+        }
+    };
+    let bytes = json.into_bytes().into_boxed_slice();
+    unsafe {
+        *out_len = bytes.len();
+        *out_ptr = Box::into_raw(bytes) as *mut u8;
+    }
+    true
+}
+
+/// Create a session and engine pair from a buffer written by
+/// `session_serialize`.
+///
+/// Works like `session_create_with_config` (`config` may be NULL for
+/// defaults). The engine is sent the saved transport and voice settings and
+/// asked to recompile, so the first `engine_process_commands` returns true.
+///
+/// Returns NULL, leaving `out_engine` untouched, if the buffer isn't a
+/// valid project.
+///
+/// # Safety
+/// `bytes` must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_deserialize(
+    bytes: *const u8,
+    len: usize,
+    config: *const HyasynthConfig,
+    out_engine: *mut *mut HyasynthEngine,
+) -> *mut HyasynthSession {
+    if bytes.is_null() {
+        return std::ptr::null_mut();
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(bytes, len) };
+    let session = match std::str::from_utf8(bytes)
+        .map_err(|e| e.to_string())
+        .and_then(|json| Session::from_json(json).map_err(|e| e.to_string()))
+    {
+        Ok(session) => session,
+        Err(e) => {
+            error!("session_deserialize: {}", e);
+            return std::ptr::null_mut();
+        }
+    };
+
+    let cfg = if config.is_null() {
+        HyasynthConfig::default()
+    } else {
+        unsafe { std::ptr::read(config) }
+    };
+
+    let handle = unsafe { create_handles(session, &cfg, out_engine) };
+    unsafe { (*handle).inner.sync_engine() };
+    handle
+}
+
+/// Free a buffer returned by `session_serialize`.
+///
+/// # Safety
+/// `ptr` and `len` must come from the same `session_serialize` call, and
+/// the buffer must not have been freed yet. NULL is ignored.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hya_bytes_free(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        unsafe { drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len))) };
+    }
+}
+
 /// Destroy a session handle.
 ///
 /// # Safety
@@ -314,34 +555,70 @@ pub unsafe extern "C" fn session_add_node(
 
 /// Remove a node from the graph.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn session_remove_node(session: *mut HyasynthSession, node_id: u32) {
+pub unsafe extern "C" fn session_remove_node(
+    session: *mut HyasynthSession,
+    node_id: u32,
+) -> HyaResult {
     if session.is_null() {
-        return;
+        return HyaResult::NullHandle;
+    }
+    let session = unsafe { &mut (*session).inner };
+    if session.session().graph.get_node(node_id).is_none() {
+        return HyaResult::NodeNotFound;
     }
-    unsafe { (*session).inner.remove_node(node_id) };
+    session.remove_node(node_id);
+    HyaResult::Ok
 }
 
 /// Connect two nodes.
+///
+/// Ports are checked against the node types in `registry`; pass NULL to
+/// skip port validation.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn session_connect(
     session: *mut HyasynthSession,
+    registry: *const HyasynthRegistry,
     source_node: u32,
     source_port: u32,
     dest_node: u32,
     dest_port: u32,
-) {
+) -> HyaResult {
     if session.is_null() {
-        return;
+        return HyaResult::NullHandle;
     }
-    use crate::state::Command;
-    unsafe {
-        (*session).inner.send(Command::Connect {
-            source_node,
-            source_port,
-            dest_node,
-            dest_port,
-        })
+    let session = unsafe { &mut (*session).inner };
+    let graph = &session.session().graph;
+    let (Some(source), Some(dest)) = (graph.get_node(source_node), graph.get_node(dest_node))
+    else {
+        return HyaResult::NodeNotFound;
     };
+
+    if let Some(registry) = unsafe { registry.as_ref() } {
+        let has_output = registry
+            .inner
+            .get_info(source.type_id)
+            .is_some_and(|info| info.outputs.iter().any(|p| p.id == source_port));
+        let has_input = registry
+            .inner
+            .get_info(dest.type_id)
+            .is_some_and(|info| info.inputs.iter().any(|p| p.id == dest_port));
+        if !has_output || !has_input {
+            return HyaResult::InvalidPort;
+        }
+    }
+
+    if graph.reaches(dest_node, source_node) {
+        return HyaResult::CycleDetected;
+    }
+
+    use crate::state::Command;
+    session.send(Command::Connect {
+        source_node,
+        source_port,
+        dest_node,
+        dest_port,
+    });
+    HyaResult::Ok
 }
 
 /// Disconnect two nodes.
@@ -352,29 +629,91 @@ pub unsafe extern "C" fn session_disconnect(
     source_port: u32,
     dest_node: u32,
     dest_port: u32,
+) -> HyaResult {
+    if session.is_null() {
+        return HyaResult::NullHandle;
+    }
+    let session = unsafe { &mut (*session).inner };
+    let graph = &session.session().graph;
+    if graph.get_node(source_node).is_none() || graph.get_node(dest_node).is_none() {
+        return HyaResult::NodeNotFound;
+    }
+    use crate::state::Command;
+    session.send(Command::Disconnect {
+        source_node,
+        source_port,
+        dest_node,
+        dest_port,
+    });
+    HyaResult::Ok
+}
+
+/// Modulate a node parameter from another node's output.
+///
+/// `depth` is in the destination parameter's units per unit of source signal.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_add_mod_route(
+    session: *mut HyasynthSession,
+    source_node: u32,
+    dest_node: u32,
+    param_id: u32,
+    depth: f32,
+) -> HyaResult {
+    if session.is_null() {
+        return HyaResult::NullHandle;
+    }
+    let session = unsafe { &mut (*session).inner };
+    let graph = &session.session().graph;
+    if graph.get_node(source_node).is_none() || graph.get_node(dest_node).is_none() {
+        return HyaResult::NodeNotFound;
+    }
+    use crate::state::Command;
+    session.send(Command::AddModRoute {
+        source_node,
+        dest_node,
+        param_id,
+        depth,
+    });
+    HyaResult::Ok
+}
+
+/// Remove a modulation route.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_remove_mod_route(
+    session: *mut HyasynthSession,
+    source_node: u32,
+    dest_node: u32,
+    param_id: u32,
 ) {
     if session.is_null() {
         return;
     }
     use crate::state::Command;
     unsafe {
-        (*session).inner.send(Command::Disconnect {
+        (*session).inner.send(Command::RemoveModRoute {
             source_node,
-            source_port,
             dest_node,
-            dest_port,
+            param_id,
         })
     };
 }
 
 /// Set the output node.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn session_set_output(session: *mut HyasynthSession, node_id: u32) {
+pub unsafe extern "C" fn session_set_output(
+    session: *mut HyasynthSession,
+    node_id: u32,
+) -> HyaResult {
     if session.is_null() {
-        return;
+        return HyaResult::NullHandle;
+    }
+    let session = unsafe { &mut (*session).inner };
+    if session.session().graph.get_node(node_id).is_none() {
+        return HyaResult::NodeNotFound;
     }
     use crate::state::Command;
-    unsafe { (*session).inner.send(Command::SetOutputNode { node_id }) };
+    session.send(Command::SetOutputNode { node_id });
+    HyaResult::Ok
 }
 
 /// Clear the entire graph.
@@ -409,6 +748,87 @@ pub unsafe extern "C" fn session_set_param(
     unsafe { (*session).inner.set_param(node_id, param_id, value) };
 }
 
+/// Clamp parameter values set from now on to the ranges in `registry`.
+///
+/// Sessions start with the standard nodes' ranges.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_param_ranges(
+    session: *mut HyasynthSession,
+    registry: *const HyasynthRegistry,
+) {
+    if session.is_null() || registry.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.set_param_ranges(&(*registry).inner) };
+}
+
+/// Get a parameter's current value.
+///
+/// Parameters never set fall back to the registry default. Returns NaN if
+/// the node doesn't exist, or if the parameter was never set and isn't
+/// known to `registry` (which may be NULL to skip the fallback).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_get_param(
+    session: *const HyasynthSession,
+    registry: *const HyasynthRegistry,
+    node_id: u32,
+    param_id: u32,
+) -> f32 {
+    if session.is_null() {
+        return f32::NAN;
+    }
+    let session = unsafe { (*session).inner.session() };
+    let Some(node) = session.graph.get_node(node_id) else {
+        return f32::NAN;
+    };
+    let registry = unsafe { registry.as_ref() }.map(|r| &r.inner);
+    node.param_values
+        .get(&param_id)
+        .copied()
+        .or_else(|| {
+            registry
+                .and_then(|r| r.get_info(node.type_id))
+                .and_then(|info| info.find_param(param_id))
+                .map(|param| param.default)
+        })
+        .unwrap_or(f32::NAN)
+}
+
+/// Scale a parameter by note velocity (0.0 = off, 1.0 = fully proportional).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_velocity_sensitivity(
+    session: *mut HyasynthSession,
+    node_id: u32,
+    param_id: u32,
+    amount: f32,
+) {
+    if session.is_null() {
+        return;
+    }
+    use crate::state::Command;
+    unsafe {
+        (*session).inner.send(Command::SetVelocitySensitivity {
+            node_id,
+            param_id,
+            amount,
+        })
+    };
+}
+
+/// Pass a node's input straight to its output without unwiring it, or
+/// restore it. Bypassed sources fall silent.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_node_bypass(
+    session: *mut HyasynthSession,
+    node_id: u32,
+    bypassed: bool,
+) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.set_node_bypass(node_id, bypassed) };
+}
+
 /// Begin a parameter gesture (for automation recording).
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn session_begin_gesture(
@@ -477,7 +897,23 @@ pub unsafe extern "C" fn session_set_tempo(session: *mut HyasynthSession, bpm: f
     unsafe { (*session).inner.send(Command::SetTempo { bpm }) };
 }
 
+/// Glide from the current tempo to `target_bpm` over `over_beats` beats.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_ramp_tempo(
+    session: *mut HyasynthSession,
+    target_bpm: f64,
+    over_beats: f64,
+) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.ramp_tempo(target_bpm, over_beats) };
+}
+
 /// Seek to a position in beats.
+///
+/// Realtime rendering doesn't play clips (the host sends their notes), so
+/// only the transport moves; clips follow a seek in offline renders.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn session_seek(session: *mut HyasynthSession, beat: f64) {
     if session.is_null() {
@@ -487,6 +923,72 @@ pub unsafe extern "C" fn session_seek(session: *mut HyasynthSession, beat: f64)
     unsafe { (*session).inner.send(Command::Seek { beat }) };
 }
 
+/// Loop playback between two beat positions.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_loop(
+    session: *mut HyasynthSession,
+    enabled: bool,
+    start_beat: f64,
+    end_beat: f64,
+) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.set_loop(enabled, start_beat, end_beat) };
+}
+
+/// Click on every beat, accenting the first beat of each bar.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_metronome(
+    session: *mut HyasynthSession,
+    enabled: bool,
+    beats_per_bar: u32,
+) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.set_metronome(enabled, beats_per_bar) };
+}
+
+/// Tune note frequencies so A4 sounds at `a4` Hz (440 by default).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_tuning(session: *mut HyasynthSession, a4: f64) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.set_tuning(a4) };
+}
+
+/// Load a microtonal tuning table from a list of cents, one scale degree
+/// per line with the period last, starting on `root_note`.
+/// Returns false if the list can't be parsed.
+///
+/// # Safety
+/// `text` must be a valid null-terminated UTF-8 string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_load_tuning_cents(
+    session: *mut HyasynthSession,
+    text: *const c_char,
+    root_note: u8,
+) -> bool {
+    if session.is_null() || text.is_null() {
+        return false;
+    }
+    let Ok(text) = (unsafe { CStr::from_ptr(text) }).to_str() else {
+        return false;
+    };
+    unsafe { (*session).inner.load_tuning_cents(text, root_note).is_ok() }
+}
+
+/// Return to equal temperament.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_clear_tuning_table(session: *mut HyasynthSession) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.set_tuning_table(None) };
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Session - MIDI
 // ═══════════════════════════════════════════════════════════════════════════
@@ -509,6 +1011,175 @@ pub unsafe extern "C" fn session_note_off(session: *mut HyasynthSession, note: u
     unsafe { (*session).inner.note_off(note) };
 }
 
+/// Set the channel pitch bend in semitones (applies to all voices).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_pitch_bend(session: *mut HyasynthSession, semitones: f32) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.pitch_bend(semitones) };
+}
+
+/// Switch between polyphonic (false) and monophonic (true) voice allocation.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_mono(session: *mut HyasynthSession, mono: bool) {
+    if session.is_null() {
+        return;
+    }
+    let mode = if mono {
+        crate::VoiceMode::Mono
+    } else {
+        crate::VoiceMode::Poly
+    };
+    unsafe { (*session).inner.set_voice_mode(mode) };
+}
+
+/// Choose how notes are assigned to voices.
+///
+/// `mode`: 0 = poly, 1 = mono, 2 = legato. Unknown modes are ignored.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_voice_mode(session: *mut HyasynthSession, mode: u32) {
+    if session.is_null() {
+        return;
+    }
+    if let Some(mode) = crate::VoiceMode::from_index(mode) {
+        unsafe { (*session).inner.set_voice_mode(mode) };
+    }
+}
+
+/// Set the portamento time in seconds between mono notes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_glide_time(session: *mut HyasynthSession, seconds: f32) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.set_glide_time(seconds) };
+}
+
+/// Choose which voice is stolen when polyphony is exhausted.
+///
+/// `mode`: 0 = oldest, 1 = quietest, 2 = lowest note, 3 = skip new note.
+/// Unknown modes are ignored.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_voice_steal_mode(session: *mut HyasynthSession, mode: u32) {
+    if session.is_null() {
+        return;
+    }
+    if let Some(mode) = crate::VoiceStealMode::from_index(mode) {
+        unsafe { (*session).inner.set_voice_steal_mode(mode) };
+    }
+}
+
+/// Configure unison: voices per note, detune of the outer voices in cents,
+/// and stereo spread (0..1).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_unison(
+    session: *mut HyasynthSession,
+    voices: u32,
+    detune_cents: f32,
+    spread: f32,
+) {
+    if session.is_null() {
+        return;
+    }
+    unsafe {
+        (*session)
+            .inner
+            .set_unison(voices as usize, detune_cents, spread)
+    };
+}
+
+/// Pan voices by note number, low notes left and high notes right (0..1).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_voice_pan_spread(session: *mut HyasynthSession, spread: f32) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.set_voice_pan_spread(spread) };
+}
+
+/// Arpeggiate held notes into `target_node`, or play them directly when
+/// `enabled` is false.
+///
+/// `mode`: 0 = up, 1 = down, 2 = up-down, 3 = random. `rate` is the step
+/// length in beats and `gate` the fraction of a step each note sounds for.
+/// Unknown modes are ignored.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_arpeggiator(
+    session: *mut HyasynthSession,
+    enabled: bool,
+    mode: u32,
+    rate: f64,
+    octaves: u8,
+    gate: f32,
+    target_node: u32,
+) {
+    if session.is_null() {
+        return;
+    }
+    let Some(mode) = crate::ArpMode::from_index(mode) else {
+        return;
+    };
+    let settings = enabled.then_some(crate::ArpSettings {
+        mode,
+        rate,
+        octaves,
+        gate,
+        target_node,
+    });
+    unsafe { (*session).inner.set_arpeggiator(settings) };
+}
+
+/// Send a MIDI control change (value normalized to 0..1).
+///
+/// Mapped CCs are forwarded as parameter changes; unmapped CCs are ignored.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_control_change(session: *mut HyasynthSession, cc: u8, value: f32) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.control_change(cc, value) };
+}
+
+/// Route a CC number to the full range of a node parameter.
+///
+/// Returns false if the node or parameter doesn't exist.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_map_control_change(
+    session: *mut HyasynthSession,
+    registry: *const HyasynthRegistry,
+    cc: u8,
+    node_id: u32,
+    param_id: u32,
+) -> bool {
+    if session.is_null() || registry.is_null() {
+        return false;
+    }
+    let session = unsafe { &mut (*session).inner };
+    let registry = unsafe { &(*registry).inner };
+    let Some(info) = session
+        .session()
+        .graph
+        .get_node(node_id)
+        .and_then(|node| registry.get_info(node.type_id))
+        .and_then(|info| info.find_param(param_id))
+        .cloned()
+    else {
+        return false;
+    };
+    session.map_control_change(cc, node_id, &info);
+    true
+}
+
+/// Remove the mapping for a CC number.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_unmap_control_change(session: *mut HyasynthSession, cc: u8) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.unmap_control_change(cc) };
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Session - Readback
 // ═══════════════════════════════════════════════════════════════════════════
@@ -530,6 +1201,28 @@ pub unsafe extern "C" fn session_get_readback(session: *const HyasynthSession) -
     unsafe { (*session).inner.readback().into() }
 }
 
+/// Get the notes held by the engine's voices, ascending.
+///
+/// Writes up to `max_notes` notes to `out_notes` and returns how many are
+/// held, which may be more than were written.
+///
+/// # Safety
+/// `out_notes` must have space for `max_notes` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_get_active_notes(
+    session: *const HyasynthSession,
+    out_notes: *mut u8,
+    max_notes: u32,
+) -> u32 {
+    if session.is_null() || out_notes.is_null() {
+        return 0;
+    }
+    let notes = unsafe { (*session).inner.active_notes() };
+    let written = notes.len().min(max_notes as usize);
+    unsafe { std::ptr::copy_nonoverlapping(notes.as_ptr(), out_notes, written) };
+    notes.len() as u32
+}
+
 /// Check if the transport is playing.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn session_is_playing(session: *const HyasynthSession) -> bool {
@@ -577,6 +1270,79 @@ pub unsafe extern "C" fn session_get_output_node(session: *const HyasynthSession
     }
 }
 
+/// Get a node's type ID, or u32::MAX if the node doesn't exist.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_get_node_type(
+    session: *const HyasynthSession,
+    node_id: u32,
+) -> u32 {
+    if session.is_null() {
+        return u32::MAX;
+    }
+    unsafe { (*session).inner.node_type_of(node_id) }.unwrap_or(u32::MAX)
+}
+
+/// Get the number of connections in the graph.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_connection_count(session: *const HyasynthSession) -> u32 {
+    if session.is_null() {
+        return 0;
+    }
+    unsafe { (*session).inner.session().graph.connections.len() as u32 }
+}
+
+/// Get the connection at `index` (0..session_connection_count).
+///
+/// Returns false and leaves `out_connection` untouched if `index` is out of
+/// range.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_get_connection(
+    session: *const HyasynthSession,
+    index: u32,
+    out_connection: *mut HyaConnection,
+) -> bool {
+    if session.is_null() || out_connection.is_null() {
+        return false;
+    }
+    let graph = unsafe { &(*session).inner.session().graph };
+    let Some(c) = graph.connections.get(index as usize) else {
+        return false;
+    };
+
+    unsafe {
+        *out_connection = HyaConnection {
+            source_node: c.source_node,
+            source_port: c.source_port,
+            dest_node: c.dest_node,
+            dest_port: c.dest_port,
+        }
+    };
+    true
+}
+
+/// Check the graph for problems without compiling it.
+///
+/// Writes up to `max_issues` issues to `out_issues` and returns how many
+/// were found, which may be more than were written.
+///
+/// # Safety
+/// `out_issues` must have space for `max_issues` entries.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_validate_graph(
+    session: *const HyasynthSession,
+    out_issues: *mut HyaGraphIssue,
+    max_issues: u32,
+) -> u32 {
+    if session.is_null() || out_issues.is_null() {
+        return 0;
+    }
+    let issues = unsafe { (*session).inner.validate_graph() };
+    for (i, &issue) in issues.iter().take(max_issues as usize).enumerate() {
+        unsafe { *out_issues.add(i) = issue.into() };
+    }
+    issues.len() as u32
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Engine Handle Functions (for audio thread)
 // ═══════════════════════════════════════════════════════════════════════════
@@ -693,6 +1459,26 @@ pub unsafe extern "C" fn engine_render(
     while offset < total_frames {
         let chunk_frames = (total_frames - offset).min(max_block);
 
+        // Process any pending commands (like note_on) before compiling so
+        // that a seek lands in this block. Clips aren't played here, so the
+        // seek only moves the scheduler's transport.
+        engine_wrapper.inner.process_commands();
+        if let Some(beat) = engine_wrapper.inner.take_pending_seek() {
+            engine_wrapper.scheduler.seek(beat);
+        }
+        if let Some((bpm, over_beats)) = engine_wrapper.inner.take_pending_tempo() {
+            engine_wrapper.scheduler.ramp_tempo(bpm, over_beats);
+        }
+        let loop_region = engine_wrapper.inner.loop_region();
+        engine_wrapper.scheduler.set_loop(loop_region);
+        let metronome = engine_wrapper.inner.metronome();
+        engine_wrapper.scheduler.set_metronome(metronome);
+        let arpeggiator = engine_wrapper.inner.arpeggiator();
+        let (held, velocity) = engine_wrapper.inner.held_notes();
+        engine_wrapper
+            .scheduler
+            .set_arpeggiator(arpeggiator, held, velocity);
+
         // Use the scheduler to compile a proper execution plan
         engine_wrapper.scheduler.compile_block(
             &mut engine_wrapper.handoff,
@@ -700,30 +1486,24 @@ pub unsafe extern "C" fn engine_render(
             &[], // No musical events from this path (they come via commands)
         );
 
-        // Process any pending commands (like note_on)
-        // TODO: This can be moved before the loop I think
-        engine_wrapper.inner.process_commands();
-
         // Read the compiled plan and process it
         let plan = engine_wrapper.handoff.read_plan();
         engine_wrapper.inner.process_plan(plan);
+        engine_wrapper.inner.update_output_peaks(chunk_frames);
 
         // Copy output to provided buffers
         // Note: internal buffer is PLANAR format: [L0..LN, R0..RN]
+        let channels = engine_wrapper.inner.output_channels();
         if let Some(output) = engine_wrapper.inner.output_buffer(chunk_frames) {
-            if output.len() >= chunk_frames * 2 {
+            if channels >= 2 {
                 // Stereo output - planar format: first half is left, second half is right
                 out_left[offset..offset + chunk_frames].copy_from_slice(&output[..chunk_frames]);
                 out_right[offset..offset + chunk_frames]
                     .copy_from_slice(&output[chunk_frames..chunk_frames * 2]);
-            } else if output.len() >= chunk_frames {
+            } else {
                 // Mono output - copy to both channels
                 out_left[offset..offset + chunk_frames].copy_from_slice(&output[..chunk_frames]);
                 out_right[offset..offset + chunk_frames].copy_from_slice(&output[..chunk_frames]);
-            } else {
-                // Not enough output - fill with silence
-                out_left[offset..offset + chunk_frames].fill(0.0);
-                out_right[offset..offset + chunk_frames].fill(0.0);
             }
         } else {
             // No output buffer - fill with silence
@@ -778,6 +1558,26 @@ pub unsafe extern "C" fn engine_render_interleaved(
     while offset < total_frames {
         let chunk_frames = (total_frames - offset).min(max_block);
 
+        // Process any pending commands (like note_on) before compiling so
+        // that a seek lands in this block. Clips aren't played here, so the
+        // seek only moves the scheduler's transport.
+        engine_wrapper.inner.process_commands();
+        if let Some(beat) = engine_wrapper.inner.take_pending_seek() {
+            engine_wrapper.scheduler.seek(beat);
+        }
+        if let Some((bpm, over_beats)) = engine_wrapper.inner.take_pending_tempo() {
+            engine_wrapper.scheduler.ramp_tempo(bpm, over_beats);
+        }
+        let loop_region = engine_wrapper.inner.loop_region();
+        engine_wrapper.scheduler.set_loop(loop_region);
+        let metronome = engine_wrapper.inner.metronome();
+        engine_wrapper.scheduler.set_metronome(metronome);
+        let arpeggiator = engine_wrapper.inner.arpeggiator();
+        let (held, velocity) = engine_wrapper.inner.held_notes();
+        engine_wrapper
+            .scheduler
+            .set_arpeggiator(arpeggiator, held, velocity);
+
         // Use the scheduler to compile a proper execution plan
         engine_wrapper.scheduler.compile_block(
             &mut engine_wrapper.handoff,
@@ -785,32 +1585,29 @@ pub unsafe extern "C" fn engine_render_interleaved(
             &[], // No musical events from this path (they come via commands)
         );
 
-        // Process any pending commands (like note_on)
-        engine_wrapper.inner.process_commands();
-
         // Read the compiled plan and process it
         let plan = engine_wrapper.handoff.read_plan();
         engine_wrapper.inner.process_plan(plan);
+        engine_wrapper.inner.update_output_peaks(chunk_frames);
 
         let out_chunk = &mut out_slice[offset * 2..(offset + chunk_frames) * 2];
 
         // Note: internal buffer is PLANAR format: [L0..LN, R0..RN]
         // Output should be INTERLEAVED: [L0, R0, L1, R1, ...]
+        let channels = engine_wrapper.inner.output_channels();
         if let Some(engine_output) = engine_wrapper.inner.output_buffer(chunk_frames) {
-            if engine_output.len() >= chunk_frames * 2 {
+            if channels >= 2 {
                 // Convert planar to interleaved
                 for i in 0..chunk_frames {
                     out_chunk[i * 2] = engine_output[i]; // Left from first half
                     out_chunk[i * 2 + 1] = engine_output[chunk_frames + i]; // Right from second half
                 }
-            } else if engine_output.len() >= chunk_frames {
+            } else {
                 // Mono to stereo interleaved
                 for i in 0..chunk_frames {
                     out_chunk[i * 2] = engine_output[i];
                     out_chunk[i * 2 + 1] = engine_output[i];
                 }
-            } else {
-                out_chunk.fill(0.0);
             }
         } else {
             out_chunk.fill(0.0);
@@ -830,6 +1627,28 @@ pub unsafe extern "C" fn engine_render_interleaved(
     engine_wrapper.inner.sync_readback();
 }
 
+/// Capture input from the host callback for the take being recorded.
+///
+/// Input format: [L0, R0, L1, R1, L2, R2, ...]
+///
+/// # Safety
+/// - Must be called from the audio thread
+/// - `input` must point to `frames * 2` valid floats
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_push_input(
+    engine: *mut HyasynthEngine,
+    input: *const f32,
+    frames: u32,
+) {
+    if engine.is_null() || input.is_null() {
+        return;
+    }
+    let input = unsafe {
+        std::slice::from_raw_parts(input, frames as usize * crate::engine::INPUT_CHANNELS)
+    };
+    unsafe { (*engine).inner.push_input(input) };
+}
+
 /// Check if the engine is currently playing.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn engine_is_playing(engine: *const HyasynthEngine) -> bool {
@@ -975,59 +1794,143 @@ pub unsafe extern "C" fn session_create_clip(
     }
 }
 
-/// Delete a clip.
+/// Delete a clip.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_delete_clip(session: *mut HyasynthSession, clip_id: u32) {
+    if session.is_null() {
+        return;
+    }
+    unsafe {
+        (*session)
+            .inner
+            .session_mut()
+            .arrangement
+            .delete_clip(clip_id)
+    };
+}
+
+/// Add a note to a clip.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_add_note_to_clip(
+    session: *mut HyasynthSession,
+    clip_id: u32,
+    start: f64,
+    duration: f64,
+    note: u8,
+    velocity: f32,
+) {
+    if session.is_null() {
+        return;
+    }
+    use crate::state::NoteDef;
+    unsafe {
+        (*session)
+            .inner
+            .session_mut()
+            .arrangement
+            .add_note_to_clip(clip_id, NoteDef::new(start, duration, note, velocity))
+    };
+}
+
+/// Clear all notes from a clip.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_clear_clip(session: *mut HyasynthSession, clip_id: u32) {
+    if session.is_null() {
+        return;
+    }
+    unsafe {
+        if let Some(clip) = (*session)
+            .inner
+            .session_mut()
+            .arrangement
+            .get_clip_mut(clip_id)
+        {
+            clip.clear();
+        }
+    };
+}
+
+/// Move a clip's note starts toward a grid of `grid` beats.
+///
+/// `strength`: 0 = untouched, 1 = exactly on the grid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_quantize_clip(
+    session: *mut HyasynthSession,
+    clip_id: u32,
+    grid: f64,
+    strength: f32,
+) {
+    if session.is_null() {
+        return;
+    }
+    use crate::state::Command;
+    unsafe {
+        (*session).inner.send(Command::QuantizeClip {
+            clip_id,
+            grid,
+            strength,
+        })
+    };
+}
+
+/// Transpose a clip's notes on playback, in semitones.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn session_delete_clip(session: *mut HyasynthSession, clip_id: u32) {
+pub unsafe extern "C" fn session_set_clip_transpose(
+    session: *mut HyasynthSession,
+    clip_id: u32,
+    semitones: i8,
+) {
     if session.is_null() {
         return;
     }
+    use crate::state::Command;
     unsafe {
         (*session)
             .inner
-            .session_mut()
-            .arrangement
-            .delete_clip(clip_id)
+            .send(Command::SetClipTranspose { clip_id, semitones })
     };
 }
 
-/// Add a note to a clip.
+/// Scale a clip's note velocities on playback (1.0 = unchanged).
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn session_add_note_to_clip(
+pub unsafe extern "C" fn session_set_clip_velocity_scale(
     session: *mut HyasynthSession,
     clip_id: u32,
-    start: f64,
-    duration: f64,
-    note: u8,
-    velocity: f32,
+    scale: f32,
 ) {
     if session.is_null() {
         return;
     }
-    use crate::state::NoteDef;
+    use crate::state::Command;
     unsafe {
         (*session)
             .inner
-            .session_mut()
-            .arrangement
-            .add_note_to_clip(clip_id, NoteDef::new(start, duration, note, velocity))
+            .send(Command::SetClipVelocityScale { clip_id, scale })
     };
 }
 
-/// Clear all notes from a clip.
+/// Randomly delay a clip's notes by up to `timing` beats and move their
+/// velocities by up to `velocity` on playback. The same `seed` always gives
+/// the same offsets.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn session_clear_clip(session: *mut HyasynthSession, clip_id: u32) {
+pub unsafe extern "C" fn session_set_clip_humanize(
+    session: *mut HyasynthSession,
+    clip_id: u32,
+    timing: f64,
+    velocity: f32,
+    seed: u32,
+) {
     if session.is_null() {
         return;
     }
+    use crate::state::Command;
     unsafe {
-        if let Some(clip) = (*session)
-            .inner
-            .session_mut()
-            .arrangement
-            .get_clip_mut(clip_id)
-        {
-            clip.clear();
-        }
+        (*session).inner.send(Command::SetClipHumanize {
+            clip_id,
+            timing,
+            velocity,
+            seed,
+        })
     };
 }
 
@@ -1195,6 +2098,38 @@ pub unsafe extern "C" fn session_get_audio_pool_count(session: *const HyasynthSe
     }
 }
 
+/// Start recording input onto an armed track at the playhead.
+/// Returns false if the track isn't armed or a take is already running.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_start_recording(
+    session: *mut HyasynthSession,
+    track_id: u32,
+) -> bool {
+    if session.is_null() {
+        return false;
+    }
+    unsafe { (*session).inner.start_recording(track_id) }
+}
+
+/// Stop recording; the take is committed by `session_poll_recording`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_stop_recording(session: *mut HyasynthSession) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.stop_recording() };
+}
+
+/// Commit a finished take to the audio pool as a clip on its track.
+/// Returns the clip ID, or u32::MAX if no take has come back yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_poll_recording(session: *mut HyasynthSession) -> u32 {
+    if session.is_null() {
+        return u32::MAX;
+    }
+    unsafe { (*session).inner.poll_recording().unwrap_or(u32::MAX) }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Track Functions
 // ═══════════════════════════════════════════════════════════════════════════
@@ -1305,13 +2240,20 @@ pub unsafe extern "C" fn session_set_track_solo(
     if session.is_null() {
         return;
     }
-    unsafe {
-        (*session)
-            .inner
-            .session_mut()
-            .arrangement
-            .set_track_solo(track_id, solo)
-    };
+    unsafe { (*session).inner.set_track_solo(track_id, solo) };
+}
+
+/// Arm a track for recording, or disarm it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_track_armed(
+    session: *mut HyasynthSession,
+    track_id: u32,
+    armed: bool,
+) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.set_track_armed(track_id, armed) };
 }
 
 /// Set track target node (the node this track sends MIDI to).
@@ -1338,6 +2280,68 @@ pub unsafe extern "C" fn session_set_track_target(
     };
 }
 
+/// Route the notes from `low_note` to `high_note` on a track to their own
+/// target, shifted by `transpose` semitones. Overlapping zones layer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_add_track_key_zone(
+    session: *mut HyasynthSession,
+    track_id: u32,
+    low_note: u8,
+    high_note: u8,
+    target_node: u32,
+    transpose: i8,
+) {
+    if session.is_null() {
+        return;
+    }
+    let zone = KeyZone {
+        low_note,
+        high_note,
+        target_node,
+        transpose,
+    };
+    unsafe { (*session).inner.add_track_key_zone(track_id, zone) };
+}
+
+/// Remove a track's key zones.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_clear_track_key_zones(
+    session: *mut HyasynthSession,
+    track_id: u32,
+) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.clear_track_key_zones(track_id) };
+}
+
+/// Play a note through a track's key zones.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_track_note_on(
+    session: *mut HyasynthSession,
+    track_id: u32,
+    note: u8,
+    velocity: f32,
+) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.track_note_on(track_id, note, velocity) };
+}
+
+/// Release a note played with `session_track_note_on`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_track_note_off(
+    session: *mut HyasynthSession,
+    track_id: u32,
+    note: u8,
+) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.track_note_off(track_id, note) };
+}
+
 /// Get the number of tracks.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn session_get_track_count(session: *const HyasynthSession) -> u32 {
@@ -1448,6 +2452,38 @@ pub unsafe extern "C" fn session_stop_all_clips(session: *mut HyasynthSession) {
     unsafe { (*session).inner.session_mut().arrangement.stop_all() };
 }
 
+/// Set the grid that clip and scene launches wait for.
+///
+/// `quantization`: 0 = off, 1 = beat, 2 = half bar, 3 = bar, 4 = two bars,
+/// 5 = four bars. Unknown values are ignored.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_launch_quantization(
+    session: *mut HyasynthSession,
+    quantization: u32,
+) {
+    if session.is_null() {
+        return;
+    }
+    if let Some(quantization) = crate::state::LaunchQuantization::from_index(quantization) {
+        unsafe {
+            (*session)
+                .inner
+                .session_mut()
+                .arrangement
+                .launch_quantization = quantization
+        };
+    }
+}
+
+/// Set the swing applied to off-beat 8th notes in clips (0..1).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn session_set_swing(session: *mut HyasynthSession, amount: f32) {
+    if session.is_null() {
+        return;
+    }
+    unsafe { (*session).inner.session_mut().arrangement.swing = amount.clamp(0.0, 1.0) };
+}
+
 /// Get the number of scenes.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn session_get_scene_count(session: *const HyasynthSession) -> u32 {
@@ -1544,6 +2580,12 @@ pub static NODE_TRIANGLE_OSC: u32 = crate::nodes::node_types::TRIANGLE_OSC;
 #[unsafe(no_mangle)]
 pub static NODE_ADSR_ENV: u32 = crate::nodes::node_types::ADSR_ENV;
 
+#[unsafe(no_mangle)]
+pub static NODE_DAHDSR_ENV: u32 = crate::nodes::node_types::DAHDSR_ENV;
+
+#[unsafe(no_mangle)]
+pub static NODE_FILTER_ENV: u32 = crate::nodes::node_types::FILTER_ENV;
+
 #[unsafe(no_mangle)]
 pub static NODE_GAIN: u32 = crate::nodes::node_types::GAIN;
 
@@ -1553,6 +2595,12 @@ pub static NODE_PAN: u32 = crate::nodes::node_types::PAN;
 #[unsafe(no_mangle)]
 pub static NODE_OUTPUT: u32 = crate::nodes::node_types::OUTPUT;
 
+#[unsafe(no_mangle)]
+pub static NODE_SLEW: u32 = crate::nodes::node_types::SLEW;
+
+#[unsafe(no_mangle)]
+pub static NODE_DC_BLOCKER: u32 = crate::nodes::node_types::DC_BLOCKER;
+
 #[unsafe(no_mangle)]
 pub static NODE_LOWPASS: u32 = crate::nodes::node_types::LOWPASS;
 
@@ -1565,6 +2613,15 @@ pub static NODE_BANDPASS: u32 = crate::nodes::node_types::BANDPASS;
 #[unsafe(no_mangle)]
 pub static NODE_NOTCH: u32 = crate::nodes::node_types::NOTCH;
 
+#[unsafe(no_mangle)]
+pub static NODE_LADDER: u32 = crate::nodes::node_types::LADDER;
+
+#[unsafe(no_mangle)]
+pub static NODE_ALLPASS: u32 = crate::nodes::node_types::ALLPASS;
+
+#[unsafe(no_mangle)]
+pub static NODE_COMB: u32 = crate::nodes::node_types::COMB;
+
 #[unsafe(no_mangle)]
 pub static NODE_LFO: u32 = crate::nodes::node_types::LFO;
 
@@ -1574,6 +2631,24 @@ pub static NODE_DELAY: u32 = crate::nodes::node_types::DELAY;
 #[unsafe(no_mangle)]
 pub static NODE_REVERB: u32 = crate::nodes::node_types::REVERB;
 
+#[unsafe(no_mangle)]
+pub static NODE_DISTORTION: u32 = crate::nodes::node_types::DISTORTION;
+
+#[unsafe(no_mangle)]
+pub static NODE_LIMITER: u32 = crate::nodes::node_types::LIMITER;
+
+#[unsafe(no_mangle)]
+pub static NODE_EQ: u32 = crate::nodes::node_types::EQ;
+
+#[unsafe(no_mangle)]
+pub static NODE_FLANGER: u32 = crate::nodes::node_types::FLANGER;
+
+#[unsafe(no_mangle)]
+pub static NODE_WIDTH: u32 = crate::nodes::node_types::WIDTH;
+
+#[unsafe(no_mangle)]
+pub static NODE_RINGMOD: u32 = crate::nodes::node_types::RINGMOD;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Parameter ID Constants
 // ═══════════════════════════════════════════════════════════════════════════
@@ -1596,24 +2671,51 @@ pub static PARAM_SUSTAIN: u32 = crate::nodes::params::SUSTAIN;
 #[unsafe(no_mangle)]
 pub static PARAM_RELEASE: u32 = crate::nodes::params::RELEASE;
 
+#[unsafe(no_mangle)]
+pub static PARAM_CURVE: u32 = crate::nodes::params::CURVE;
+
+#[unsafe(no_mangle)]
+pub static PARAM_ENV_DELAY: u32 = crate::nodes::params::ENV_DELAY;
+
+#[unsafe(no_mangle)]
+pub static PARAM_HOLD: u32 = crate::nodes::params::HOLD;
+
+#[unsafe(no_mangle)]
+pub static PARAM_VELOCITY: u32 = crate::nodes::params::VELOCITY;
+
+#[unsafe(no_mangle)]
+pub static PARAM_ENV_AMOUNT: u32 = crate::nodes::params::ENV_AMOUNT;
+
 #[unsafe(no_mangle)]
 pub static PARAM_GAIN: u32 = crate::nodes::params::GAIN;
 
 #[unsafe(no_mangle)]
 pub static PARAM_PAN: u32 = crate::nodes::params::PAN;
 
+#[unsafe(no_mangle)]
+pub static PARAM_AUTO_GAIN: u32 = crate::nodes::params::AUTO_GAIN;
+
 #[unsafe(no_mangle)]
 pub static PARAM_CUTOFF: u32 = crate::nodes::params::CUTOFF;
 
 #[unsafe(no_mangle)]
 pub static PARAM_RESONANCE: u32 = crate::nodes::params::RESONANCE;
 
+#[unsafe(no_mangle)]
+pub static PARAM_KEYTRACK: u32 = crate::nodes::params::KEYTRACK;
+
 #[unsafe(no_mangle)]
 pub static PARAM_RATE: u32 = crate::nodes::params::RATE;
 
 #[unsafe(no_mangle)]
 pub static PARAM_DEPTH: u32 = crate::nodes::params::DEPTH;
 
+#[unsafe(no_mangle)]
+pub static PARAM_PHASE_OFFSET: u32 = crate::nodes::params::PHASE_OFFSET;
+
+#[unsafe(no_mangle)]
+pub static PARAM_RETRIGGER: u32 = crate::nodes::params::RETRIGGER;
+
 #[unsafe(no_mangle)]
 pub static PARAM_TIME: u32 = crate::nodes::params::TIME;
 
@@ -1623,5 +2725,242 @@ pub static PARAM_FEEDBACK: u32 = crate::nodes::params::FEEDBACK;
 #[unsafe(no_mangle)]
 pub static PARAM_MIX: u32 = crate::nodes::params::MIX;
 
+#[unsafe(no_mangle)]
+pub static PARAM_SYNC_ON: u32 = crate::nodes::params::SYNC_ON;
+
+#[unsafe(no_mangle)]
+pub static PARAM_SYNC_DIV: u32 = crate::nodes::params::SYNC_DIV;
+
+#[unsafe(no_mangle)]
+pub static PARAM_PINGPONG: u32 = crate::nodes::params::PINGPONG;
+
+#[unsafe(no_mangle)]
+pub static PARAM_DELAY_DAMPING: u32 = crate::nodes::params::DELAY_DAMPING;
+
 #[unsafe(no_mangle)]
 pub static PARAM_DAMPING: u32 = crate::nodes::params::DAMPING;
+
+#[unsafe(no_mangle)]
+pub static PARAM_PREDELAY: u32 = crate::nodes::params::PREDELAY;
+
+#[unsafe(no_mangle)]
+pub static PARAM_WIDTH: u32 = crate::nodes::params::WIDTH;
+
+#[unsafe(no_mangle)]
+pub static PARAM_FREEZE: u32 = crate::nodes::params::FREEZE;
+
+#[unsafe(no_mangle)]
+pub static PARAM_DRIVE: u32 = crate::nodes::params::DRIVE;
+
+#[unsafe(no_mangle)]
+pub static PARAM_SHAPE: u32 = crate::nodes::params::SHAPE;
+
+#[unsafe(no_mangle)]
+pub static PARAM_CEILING: u32 = crate::nodes::params::CEILING;
+
+#[unsafe(no_mangle)]
+pub static PARAM_FLANGER_FEEDBACK: u32 = crate::nodes::params::FLANGER_FEEDBACK;
+
+#[unsafe(no_mangle)]
+pub static PARAM_RINGMOD_MODE: u32 = crate::nodes::params::RINGMOD_MODE;
+
+#[unsafe(no_mangle)]
+pub static PARAM_RISE: u32 = crate::nodes::params::RISE;
+
+#[unsafe(no_mangle)]
+pub static PARAM_FALL: u32 = crate::nodes::params::FALL;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::{node_types, params};
+
+    #[test]
+    fn test_registry_metadata() {
+        let registry = registry_create();
+        let count = unsafe { registry_count(registry) };
+        assert!(count > 0);
+
+        let mut sine = None;
+        let mut last_type = None;
+        for index in 0..count {
+            let mut info = HyaNodeTypeInfo {
+                type_id: 0,
+                name: std::ptr::null(),
+                category: std::ptr::null(),
+                num_inputs: 0,
+                num_outputs: 0,
+                num_params: 0,
+            };
+            assert!(unsafe { registry_get_node_type(registry, index, &mut info) });
+            assert!(last_type < Some(info.type_id));
+            last_type = Some(info.type_id);
+
+            let name = unsafe { CStr::from_ptr(info.name) }.to_str().unwrap();
+            let category = unsafe { CStr::from_ptr(info.category) }.to_str().unwrap();
+            if info.type_id == node_types::SINE_OSC {
+                assert_eq!((name, category), ("Sine", "Oscillators"));
+                sine = Some(info.num_params);
+            }
+            unsafe {
+                hya_string_free(info.name);
+                hya_string_free(info.category);
+            }
+        }
+
+        // Index past the end
+        let mut info = HyaNodeTypeInfo {
+            type_id: 0,
+            name: std::ptr::null(),
+            category: std::ptr::null(),
+            num_inputs: 0,
+            num_outputs: 0,
+            num_params: 0,
+        };
+        assert!(!unsafe { registry_get_node_type(registry, count, &mut info) });
+
+        let num_params = sine.expect("sine oscillator not listed");
+        let mut freq = None;
+        for index in 0..num_params {
+            let mut param = HyaParamInfo {
+                id: 0,
+                name: std::ptr::null(),
+                min_value: 0.0,
+                max_value: 0.0,
+                default_value: 0.0,
+            };
+            let found =
+                unsafe { registry_get_param(registry, node_types::SINE_OSC, index, &mut param) };
+            assert!(found);
+            if param.id == params::FREQ {
+                let name = unsafe { CStr::from_ptr(param.name) }.to_str().unwrap();
+                freq = Some((name.to_string(), param.min_value, param.max_value));
+            }
+            unsafe { hya_string_free(param.name) };
+        }
+        assert_eq!(freq, Some(("Frequency".to_string(), 20.0, 20_000.0)));
+
+        unsafe { registry_destroy(registry) };
+    }
+
+    #[test]
+    fn test_get_param_round_trip() {
+        let registry = registry_create();
+        let mut engine = std::ptr::null_mut();
+        let session = unsafe { session_create(std::ptr::null(), &mut engine) };
+
+        let osc = unsafe { session_add_node(session, node_types::SINE_OSC, 0.0, 0.0) };
+        unsafe { session_set_param(session, osc, params::FREQ, 220.0) };
+        let freq = unsafe { session_get_param(session, registry, osc, params::FREQ) };
+        assert_eq!(freq, 220.0);
+
+        // Unset params read back as the registry default
+        let detune = unsafe { session_get_param(session, registry, osc, params::DETUNE) };
+        assert_eq!(detune, 0.0);
+        let unset = unsafe { session_get_param(session, std::ptr::null(), osc, params::DETUNE) };
+        assert!(unset.is_nan());
+
+        // Missing nodes and unknown params
+        let missing = unsafe { session_get_param(session, registry, osc + 1, params::FREQ) };
+        assert!(missing.is_nan());
+        assert!(unsafe { session_get_param(session, registry, osc, 99) }.is_nan());
+
+        unsafe {
+            session_destroy(session);
+            engine_destroy(engine);
+            registry_destroy(registry);
+        }
+    }
+
+    #[test]
+    fn test_graph_mutation_results() {
+        let registry = registry_create();
+        let mut engine = std::ptr::null_mut();
+        let session = unsafe { session_create(std::ptr::null(), &mut engine) };
+
+        let osc = unsafe { session_add_node(session, node_types::SINE_OSC, 0.0, 0.0) };
+        let gain = unsafe { session_add_node(session, node_types::GAIN, 0.0, 0.0) };
+        let connect = |src: u32, src_port: u32, dst: u32, dst_port: u32| unsafe {
+            session_connect(session, registry, src, src_port, dst, dst_port)
+        };
+
+        assert_eq!(connect(osc, 0, gain + 1, 0), HyaResult::NodeNotFound);
+        assert_eq!(connect(osc, 1, gain, 0), HyaResult::InvalidPort);
+        assert_eq!(connect(osc, 0, gain, 7), HyaResult::InvalidPort);
+        assert_eq!(connect(osc, 0, gain, 0), HyaResult::Ok);
+        assert_eq!(connect(gain, 0, gain, 0), HyaResult::CycleDetected);
+        assert_eq!(
+            unsafe { session_set_output(session, gain + 1) },
+            HyaResult::NodeNotFound
+        );
+        assert_eq!(unsafe { session_set_output(session, gain) }, HyaResult::Ok);
+        assert_eq!(
+            unsafe { session_connect(std::ptr::null_mut(), registry, osc, 0, gain, 0) },
+            HyaResult::NullHandle
+        );
+
+        unsafe {
+            session_destroy(session);
+            engine_destroy(engine);
+            registry_destroy(registry);
+        }
+    }
+
+    #[test]
+    fn test_session_serialize_round_trip() {
+        let mut engine = std::ptr::null_mut();
+        let session = unsafe { session_create(c"Saved".as_ptr(), &mut engine) };
+        let osc = unsafe { session_add_node(session, node_types::SINE_OSC, 0.0, 0.0) };
+        let out = unsafe { session_add_node(session, node_types::OUTPUT, 0.0, 0.0) };
+        unsafe {
+            session_connect(session, std::ptr::null(), osc, 0, out, 0);
+            session_set_output(session, out);
+            session_create_track(session, c"Lead".as_ptr());
+            session_create_track(session, c"Bass".as_ptr());
+        }
+
+        let mut ptr = std::ptr::null_mut();
+        let mut len = 0;
+        assert!(unsafe { session_serialize(session, &mut ptr, &mut len) });
+        assert!(!ptr.is_null() && len > 0);
+
+        let mut loaded_engine = std::ptr::null_mut();
+        let loaded = unsafe { session_deserialize(ptr, len, std::ptr::null(), &mut loaded_engine) };
+        unsafe { hya_bytes_free(ptr, len) };
+        assert!(!loaded.is_null() && !loaded_engine.is_null());
+
+        let original = unsafe { (*session).inner.session() };
+        let restored = unsafe { (*loaded).inner.session() };
+        assert_eq!(restored.name, "Saved");
+        assert_eq!(restored.graph.nodes.len(), original.graph.nodes.len());
+        assert_eq!(restored.graph.connections.len(), 1);
+        assert_eq!(restored.graph.output_node, Some(out));
+        assert_eq!(
+            restored.arrangement.tracks.len(),
+            original.arrangement.tracks.len()
+        );
+
+        // The new engine is asked to build the loaded graph
+        assert!(unsafe { engine_process_commands(loaded_engine) });
+
+        // Garbage is rejected
+        let junk = b"not a project";
+        let mut junk_engine = std::ptr::null_mut();
+        let rejected = unsafe {
+            session_deserialize(
+                junk.as_ptr(),
+                junk.len(),
+                std::ptr::null(),
+                &mut junk_engine,
+            )
+        };
+        assert!(rejected.is_null() && junk_engine.is_null());
+
+        unsafe {
+            session_destroy(session);
+            engine_destroy(engine);
+            session_destroy(loaded);
+            engine_destroy(loaded_engine);
+        }
+    }
+}
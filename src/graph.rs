@@ -4,12 +4,94 @@
 //! and supports both global (shared) and per-voice (polyphonic) processing modes.
 
 use crate::{
-    audio_buffer::AudioBuffer,
-    node::{Node, Polyphony, ProcessContext},
+    audio_buffer::{InputBuffer, OutputBuffer},
+    modulation::{ModRoute, ModSignal, ModSum, ParamSmoother, VelocityRoute},
+    node::{DEFAULT_TUNING, Node, Polyphony, ProcessContext},
     node_factory::NodeFactory,
+    state::NOTE_COUNT,
     voice_allocator::VoiceAllocator,
 };
 
+/// Copy `input` into every channel of `output`; a narrower input repeats
+/// its last channel, and a missing one leaves the output as it is.
+fn pass_through(input: Option<&InputBuffer>, output: &mut OutputBuffer) {
+    let Some(input) = input else {
+        return;
+    };
+    let frames = output.frames.min(input.frames);
+    for ch in 0..output.channels {
+        let source = input.channel(ch.min(input.channels.saturating_sub(1)));
+        output.channel_mut(ch)[..frames].copy_from_slice(&source[..frames]);
+    }
+}
+
+/// Balance gain for channel `ch` of a voice panned to `pan` (-1..1).
+///
+/// Centered voices pass at unity on both sides, matching a mono signal
+/// spread across a stereo input.
+#[inline]
+fn pan_gain(pan: f32, ch: usize, channels: usize) -> f32 {
+    match (channels, ch) {
+        (1, _) => 1.0,
+        (_, 0) => (1.0 - pan).min(1.0),
+        (_, 1) => (1.0 + pan).min(1.0),
+        _ => 1.0,
+    }
+}
+
+/// Delays one input of a node to line it up with its slower inputs.
+struct InputDelay {
+    /// Graph index of the delayed source
+    source: usize,
+    /// Delay in samples
+    samples: usize,
+    /// One ring of `samples` per channel
+    ring: Vec<f32>,
+    pos: usize,
+    /// The source's block, delayed, read in place of the source
+    out: Vec<f32>,
+    /// Channels in `out` for the current block
+    out_channels: usize,
+}
+
+impl InputDelay {
+    fn new(source: usize, samples: usize, channels: usize, max_block: usize) -> Self {
+        Self {
+            source,
+            samples,
+            ring: vec![0.0; channels * samples],
+            pos: 0,
+            out: vec![0.0; channels * max_block],
+            out_channels: channels,
+        }
+    }
+
+    /// Push one planar block through the delay into `out`.
+    fn process(&mut self, input: &[f32], channels: usize, frames: usize) {
+        let channels = channels.min(self.ring.len() / self.samples);
+        for ch in 0..channels {
+            let ring = &mut self.ring[ch * self.samples..][..self.samples];
+            let mut pos = self.pos;
+            for (o, &i) in self.out[ch * frames..][..frames]
+                .iter_mut()
+                .zip(&input[ch * frames..][..frames])
+            {
+                *o = ring[pos];
+                ring[pos] = i;
+                pos = (pos + 1) % self.samples;
+            }
+        }
+        self.pos = (self.pos + frames) % self.samples;
+        self.out_channels = channels;
+    }
+
+    fn reset(&mut self) {
+        self.ring.fill(0.0);
+        self.out.fill(0.0);
+        self.pos = 0;
+    }
+}
+
 /// Storage for one node's output buffers.
 pub struct NodeBuffer {
     pub channels: usize,
@@ -19,6 +101,20 @@ pub struct NodeBuffer {
     pub data: Vec<f32>,
     /// Scratch buffer for mixing voices
     pub temp_voice: Vec<f32>,
+    /// Channels in the current voice mix (a mono node mixes to stereo
+    /// when voices are panned)
+    pub mix_channels: usize,
+    /// Whether a global node reads the voice mix (per-voice nodes only)
+    pub feeds_global: bool,
+    /// Per-port mix buffers for ports with zero or several sources
+    port_mix: Vec<Vec<f32>>,
+    /// Whether each voice came out silent in the last block (per-voice nodes only)
+    voice_silent: Vec<bool>,
+    /// Envelope level of each voice after the last block, if reported
+    voice_levels: Vec<Option<f32>>,
+    /// Delays on inputs that arrive ahead of the node's slowest input
+    /// (global nodes only)
+    input_delays: Vec<InputDelay>,
 }
 
 impl NodeBuffer {
@@ -34,29 +130,35 @@ impl NodeBuffer {
             channels,
             is_per_voice,
             data: vec![0.0; data_size],
-            temp_voice: vec![0.0; voice_size],
+            temp_voice: vec![0.0; channels.max(2) * max_block],
+            mix_channels: channels,
+            feeds_global: false,
+            port_mix: Vec::new(),
+            voice_silent: vec![false; if is_per_voice { max_voices } else { 0 }],
+            voice_levels: vec![None; if is_per_voice { max_voices } else { 0 }],
+            input_delays: Vec::new(),
         }
     }
 
-    /// Get a mutable AudioBuffer view for writing output (global nodes only).
-    #[inline]
-    pub fn as_buffer(&mut self, frames: usize) -> AudioBuffer<'_> {
-        AudioBuffer {
-            channels: self.channels,
-            frames,
-            data: &mut self.data[..self.channels * frames],
-        }
-    }
-
-    /// Get a mutable AudioBuffer view for a specific voice (per-voice nodes only).
-    #[inline]
-    pub fn as_voice_buffer(&mut self, voice_id: usize, frames: usize) -> AudioBuffer<'_> {
-        let voice_size = self.channels * frames;
-        let offset = voice_id * voice_size;
-        AudioBuffer {
-            channels: self.channels,
-            frames,
-            data: &mut self.data[offset..offset + voice_size],
+    /// Mix all voices into `temp_voice` for global nodes to read.
+    fn mix_voices(&mut self, pans: &[f32], panned: bool, frames: usize) {
+        let channels = self.channels;
+        let voice_size = channels * frames;
+        // Panned voices need a stereo mix, even from a mono node
+        let mix_channels = if panned { channels.max(2) } else { channels };
+        self.mix_channels = mix_channels;
+        self.temp_voice[..mix_channels * frames].fill(0.0);
+
+        for (voice_id, &pan) in pans.iter().enumerate() {
+            let voice = &self.data[voice_id * voice_size..][..voice_size];
+            for ch in 0..mix_channels {
+                let gain = pan_gain(pan, ch, mix_channels);
+                let src = &voice[ch.min(channels - 1) * frames..][..frames];
+                let dst = &mut self.temp_voice[ch * frames..][..frames];
+                for (o, i) in dst.iter_mut().zip(src) {
+                    *o += *i * gain;
+                }
+            }
         }
     }
 }
@@ -92,11 +194,28 @@ impl NodeInstance {
         }
     }
 
+    #[inline]
+    pub fn retrigger(&mut self) {
+        // Per-voice nodes get their triggers from the voice context
+        if let NodeInstance::Global(node) = self {
+            node.retrigger();
+        }
+    }
+
     #[inline]
     pub fn is_per_voice(&self) -> bool {
         matches!(self, NodeInstance::PerVoice(_))
     }
 
+    /// Latency the node adds, in samples (all voices share one value).
+    #[inline]
+    pub fn latency_samples(&self) -> usize {
+        match self {
+            NodeInstance::Global(node) => node.latency_samples(),
+            NodeInstance::PerVoice(nodes) => nodes.first().map_or(0, |n| n.latency_samples()),
+        }
+    }
+
     #[inline]
     pub fn start_audio(
         &mut self,
@@ -104,10 +223,11 @@ impl NodeInstance {
         start_sample: u64,
         duration_samples: u64,
         gain: f32,
+        pitch_shift: f32,
     ) {
         match self {
             NodeInstance::Global(node) => {
-                node.start_audio(audio_id, start_sample, duration_samples, gain);
+                node.start_audio(audio_id, start_sample, duration_samples, gain, pitch_shift);
             }
             NodeInstance::PerVoice(_) => {
                 // Audio playback is typically global, not per-voice
@@ -149,8 +269,17 @@ impl NodeInstance {
 /// One node in the graph
 pub struct GraphNode {
     pub instance: NodeInstance,
+    /// Unique source nodes (used for ordering and silence detection)
     pub inputs: Vec<usize>,
+    /// Source nodes grouped by destination port (index = port id), each
+    /// with the one channel it's read from when it has several output ports
+    pub port_inputs: Vec<Vec<(usize, Option<usize>)>>,
+    /// Declared channel count of each input port (index = port id; 0 or
+    /// missing = take sources at their own width)
+    pub port_channels: Vec<usize>,
     pub silent: bool,
+    /// Pass the first input port straight through instead of processing
+    pub bypassed: bool,
 }
 
 /// The audio graph
@@ -162,11 +291,40 @@ pub struct Graph {
     pub max_voices: usize,
     pub sample_rate: f64,
 
+    /// Reference pitch of A4 in Hz, passed to every node
+    tuning: f64,
+
+    /// Frequency ratio of each MIDI note to A4 under the session's tuning
+    /// table, if one is set
+    note_ratios: Option<[f32; NOTE_COUNT]>,
+
     /// Topologically sorted evaluation order (computed in prepare)
     eval_order: Vec<usize>,
 
-    /// Scratch space for collecting input buffer references
-    input_scratch: Vec<usize>,
+    /// Parameter modulation routes, applied before each destination processes
+    pub mod_routes: Vec<ModRoute>,
+
+    /// Velocity scaling, applied to per-voice instances on voice trigger
+    pub velocity_routes: Vec<VelocityRoute>,
+
+    /// Smoothed parameters, stepped toward their targets each block
+    pub param_smoothers: Vec<ParamSmoother>,
+
+    /// Nodes grouped by dependency depth; nodes in one level don't read
+    /// each other and can process concurrently (computed in prepare)
+    #[cfg(feature = "parallel")]
+    levels: Vec<Vec<usize>>,
+
+    /// Whether levels with several nodes are processed on the thread pool
+    #[cfg(feature = "parallel")]
+    parallel: bool,
+
+    /// Last per-voice node carrying audio in evaluation order, which
+    /// decides when a voice has finished
+    last_per_voice: Option<usize>,
+
+    /// Accumulated latency at each node's output, in samples (computed in prepare)
+    latencies: Vec<usize>,
 
     /// Maps session node IDs to runtime graph indices.
     /// Populated during compilation.
@@ -175,6 +333,16 @@ pub struct Graph {
     /// Voices that finished during this processing block (envelope went idle).
     /// The engine should drain this after processing and deactivate these voices.
     voices_to_deactivate: Vec<crate::voice::VoiceId>,
+
+    /// Loudest envelope level per voice after the last block, if any
+    /// per-voice envelope reported one.
+    voice_levels: Vec<Option<f32>>,
+
+    /// Pan of each active voice for this block, applied when voices are mixed
+    voice_pans: Vec<f32>,
+
+    /// Whether any active voice is panned off center this block
+    voices_panned: bool,
 }
 
 impl Graph {
@@ -186,10 +354,23 @@ impl Graph {
             max_block,
             max_voices,
             sample_rate: 48_000.0,
+            tuning: DEFAULT_TUNING,
+            note_ratios: None,
             eval_order: Vec::new(),
-            input_scratch: Vec::new(),
+            mod_routes: Vec::new(),
+            velocity_routes: Vec::new(),
+            param_smoothers: Vec::new(),
+            #[cfg(feature = "parallel")]
+            levels: Vec::new(),
+            #[cfg(feature = "parallel")]
+            parallel: false,
+            last_per_voice: None,
+            latencies: Vec::new(),
             id_to_index: std::collections::HashMap::new(),
             voices_to_deactivate: Vec::new(),
+            voice_levels: vec![None; max_voices],
+            voice_pans: vec![0.0; max_voices],
+            voices_panned: false,
         }
     }
 
@@ -211,7 +392,10 @@ impl Graph {
         self.nodes.push(GraphNode {
             instance,
             inputs: Vec::new(),
+            port_inputs: Vec::new(),
+            port_channels: Vec::new(),
             silent: false,
+            bypassed: false,
         });
 
         self.buffers.push(NodeBuffer::new(
@@ -224,13 +408,51 @@ impl Graph {
         idx
     }
 
-    /// Add an edge: src -> dst
+    /// Add an edge: src -> dst (input port 0)
     pub fn connect(&mut self, src: usize, dst: usize) {
-        if !self.nodes[dst].inputs.contains(&src) {
-            self.nodes[dst].inputs.push(src);
+        self.connect_port(src, dst, 0);
+    }
+
+    /// Add an edge: src -> dst, arriving at a specific input port.
+    ///
+    /// Several sources on the same port are summed before the node sees them.
+    pub fn connect_port(&mut self, src: usize, dst: usize, port: usize) {
+        self.connect_source(src, None, dst, port);
+    }
+
+    /// Add an edge from one output of a multi-output node: channel `output`
+    /// of src's buffer -> dst, arriving at a specific input port as mono.
+    pub fn connect_output(&mut self, src: usize, output: usize, dst: usize, port: usize) {
+        self.connect_source(src, Some(output), dst, port);
+    }
+
+    fn connect_source(&mut self, src: usize, output: Option<usize>, dst: usize, port: usize) {
+        let node = &mut self.nodes[dst];
+        if !node.inputs.contains(&src) {
+            node.inputs.push(src);
+        }
+
+        if node.port_inputs.len() <= port {
+            node.port_inputs.resize_with(port + 1, Vec::new);
+        }
+        if !node.port_inputs[port].contains(&(src, output)) {
+            node.port_inputs[port].push((src, output));
         }
     }
 
+    /// Declare the channel count an input port expects.
+    ///
+    /// Sources of a different width are mixed to fit before the node sees
+    /// them: down to mono by averaging their channels, or up by spreading
+    /// a mono source across every channel.
+    pub fn set_port_channels(&mut self, node: usize, port: usize, channels: usize) {
+        let port_channels = &mut self.nodes[node].port_channels;
+        if port_channels.len() <= port {
+            port_channels.resize(port + 1, 0);
+        }
+        port_channels[port] = channels;
+    }
+
     /// Prepare all nodes and compute evaluation order
     pub fn prepare(&mut self, sample_rate: f64) {
         self.sample_rate = sample_rate;
@@ -238,6 +460,55 @@ impl Graph {
         // Compute topological order
         self.eval_order = self.topological_sort();
 
+        #[cfg(feature = "parallel")]
+        {
+            self.levels = self.compute_levels();
+        }
+        // Nodes only read by mod routes (e.g. a filter envelope) don't
+        // carry the voice's sound, so they can't say when it has finished
+        let carries_audio = |idx: usize| {
+            idx == self.output_node || self.nodes.iter().any(|n| n.inputs.contains(&idx))
+        };
+        let per_voice = || {
+            self.eval_order
+                .iter()
+                .rev()
+                .copied()
+                .filter(|&idx| self.nodes[idx].instance.is_per_voice())
+        };
+        self.last_per_voice = per_voice()
+            .find(|&idx| carries_audio(idx))
+            .or_else(|| per_voice().next());
+
+        // Size port mix buffers for the widest node
+        // (voice mixes can be stereo even when every node is mono)
+        let max_channels = self.buffers.iter().map(|b| b.channels).max().unwrap_or(1).max(2);
+        for (node, buf) in self.nodes.iter().zip(&mut self.buffers) {
+            buf.port_mix = node
+                .port_inputs
+                .iter()
+                .enumerate()
+                .map(|(port, sources)| {
+                    let declared = node.port_channels.get(port).is_some_and(|&c| c > 0);
+                    match sources.len() {
+                        1 if !declared => Vec::new(),
+                        _ => vec![0.0; max_channels * self.max_block],
+                    }
+                })
+                .collect();
+            buf.feeds_global = false;
+        }
+
+        // Global nodes read the voice mix of their per-voice inputs
+        for node in &self.nodes {
+            if !node.instance.is_per_voice() {
+                for &input in &node.inputs {
+                    let buf = &mut self.buffers[input];
+                    buf.feeds_global |= buf.is_per_voice;
+                }
+            }
+        }
+
         // Prepare all nodes
         for (node, buf) in self.nodes.iter_mut().zip(&mut self.buffers) {
             match &mut node.instance {
@@ -252,13 +523,111 @@ impl Graph {
             buf.data.fill(0.0);
             buf.temp_voice.fill(0.0);
         }
+
+        // Latencies are known once the nodes have their sample rate
+        self.compensate_latency();
+    }
+
+    /// Accumulate latency along every path and delay the early inputs of
+    /// each global node so all of its inputs line up.
+    ///
+    /// Per-voice nodes pass on the latency of their slowest input but
+    /// their inputs aren't aligned.
+    fn compensate_latency(&mut self) {
+        let mut latencies = vec![0; self.nodes.len()];
+        for &idx in &self.eval_order {
+            let node = &self.nodes[idx];
+            let input_latency = node.inputs.iter().map(|&i| latencies[i]).max().unwrap_or(0);
+            latencies[idx] = input_latency + node.instance.latency_samples();
+
+            let delays = if node.instance.is_per_voice() {
+                Vec::new()
+            } else {
+                node.inputs
+                    .iter()
+                    .filter(|&&i| latencies[i] < input_latency)
+                    .map(|&i| {
+                        // Voice mixes can be stereo even from a mono node
+                        let channels = self.buffers[i].channels.max(2);
+                        InputDelay::new(i, input_latency - latencies[i], channels, self.max_block)
+                    })
+                    .collect()
+            };
+            self.buffers[idx].input_delays = delays;
+        }
+        self.latencies = latencies;
+    }
+
+    /// Latency from the graph's inputs to its output node, in samples.
+    pub fn latency_samples(&self) -> usize {
+        self.latencies.get(self.output_node).copied().unwrap_or(0)
     }
 
     /// Compute topological sort of the graph (Kahn's algorithm)
     fn topological_sort(&self) -> Vec<usize> {
+        let (mut result, processed) = self.kahn_order();
+
+        // If we didn't process all nodes, there's a cycle in the graph.
+        // `compile` rejects cyclic graphs, so this only triggers for graphs
+        // wired by hand. We append unprocessed nodes to avoid panicking,
+        // but results will be incorrect.
+        let has_cycle = processed.iter().any(|&p| !p);
+        if has_cycle {
+            debug_assert!(
+                false,
+                "Graph contains a cycle - audio output will be incorrect"
+            );
+            for (i, &was_processed) in processed.iter().enumerate() {
+                if !was_processed {
+                    result.push(i);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Graph indices of nodes that sit on a feedback cycle.
+    ///
+    /// Empty when the graph is acyclic. Nodes that are merely downstream of
+    /// a cycle are not included.
+    pub fn cyclic_nodes(&self) -> Vec<usize> {
+        let (_, processed) = self.kahn_order();
+        let mut remaining: Vec<bool> = processed.iter().map(|&p| !p).collect();
+
+        // Kahn leaves everything downstream of a cycle unprocessed too;
+        // repeatedly trim nodes that feed no other remaining node.
+        loop {
+            let mut changed = false;
+            for i in 0..remaining.len() {
+                let feeds_remaining = self
+                    .nodes
+                    .iter()
+                    .enumerate()
+                    .any(|(j, node)| remaining[j] && node.inputs.contains(&i));
+                if remaining[i] && !feeds_remaining {
+                    remaining[i] = false;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        remaining
+            .iter()
+            .enumerate()
+            .filter(|&(_, r)| *r)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Run Kahn's algorithm, returning the sorted prefix and which nodes it reached.
+    fn kahn_order(&self) -> (Vec<usize>, Vec<bool>) {
         let n = self.nodes.len();
         if n == 0 {
-            return Vec::new();
+            return (Vec::new(), Vec::new());
         }
 
         // Count incoming edges
@@ -303,250 +672,261 @@ impl Graph {
             }
         }
 
-        // If we didn't process all nodes, there's a cycle in the graph.
-        // This is a graph definition error - cycles should be prevented at the UI level.
-        // We append unprocessed nodes to avoid panicking, but results will be incorrect.
-        let has_cycle = processed.iter().any(|&p| !p);
-        if has_cycle {
-            debug_assert!(
-                false,
-                "Graph contains a cycle - audio output will be incorrect"
-            );
-            for (i, &was_processed) in processed.iter().enumerate() {
-                if !was_processed {
-                    result.push(i);
-                }
-            }
-        }
-
-        result
+        (result, processed)
     }
 
-    /// Process one block of audio
-    pub fn process(&mut self, frames: usize, sample_pos: u64, bpm: f64, voices: &VoiceAllocator) {
-        let ctx = ProcessContext::new(frames, self.sample_rate, sample_pos, bpm);
-
-        // Clear finished voices from previous block
-        self.voices_to_deactivate.clear();
-
-        // Process nodes in topological order
-        // Use index iteration to avoid cloning eval_order
-        for i in 0..self.eval_order.len() {
-            let idx = self.eval_order[i];
-            self.process_node(idx, &ctx, voices);
-        }
-    }
-
-    fn process_node(&mut self, idx: usize, ctx: &ProcessContext, voices: &VoiceAllocator) {
-        // Collect input indices first (avoid borrow issues)
-        self.input_scratch.clear();
-        self.input_scratch
-            .extend_from_slice(&self.nodes[idx].inputs);
-
-        // Check if all inputs are silent
-        let inputs_silent = self.input_scratch.iter().all(|&i| self.nodes[i].silent);
-
-        let is_per_voice = self.nodes[idx].instance.is_per_voice();
-
-        if is_per_voice {
-            self.process_per_voice_node(idx, ctx, voices);
-        } else {
-            self.process_global_node(idx, ctx, inputs_silent);
+    /// Group nodes by dependency depth for concurrent processing.
+    ///
+    /// A node sits in a later level than everything it reads this block:
+    /// its inputs and modulation sources that run before it. Modulation
+    /// sources that run after it sit in a later level than it, so their
+    /// previous block's output is read before being overwritten.
+    #[cfg(feature = "parallel")]
+    fn compute_levels(&self) -> Vec<Vec<usize>> {
+        let mut position = vec![0; self.nodes.len()];
+        for (pos, &idx) in self.eval_order.iter().enumerate() {
+            position[idx] = pos;
         }
-    }
-
-    fn process_global_node(&mut self, idx: usize, ctx: &ProcessContext, inputs_silent: bool) {
-        let frames = ctx.frames;
-        let num_inputs = self.input_scratch.len();
-        let has_inputs = num_inputs > 0;
-
-        // Clear output buffer
-        let buf = &mut self.buffers[idx];
-        buf.data[..buf.channels * frames].fill(0.0);
 
-        // Early exit if all inputs are silent
-        if inputs_silent && has_inputs {
-            self.nodes[idx].silent = true;
-            return;
+        // Cyclic graphs have no valid order to split; run one node at a time
+        let cyclic = self.eval_order.iter().any(|&idx| {
+            self.nodes[idx]
+                .inputs
+                .iter()
+                .any(|&i| position[i] > position[idx])
+        });
+        if cyclic {
+            return self.eval_order.iter().map(|&idx| vec![idx]).collect();
         }
 
-        // For global nodes receiving per-voice inputs, we need to mix all voices together.
-        // First, mix per-voice inputs into their temp_voice buffers
-        for &input_idx in &self.input_scratch {
-            let input_buf = &mut self.buffers[input_idx];
-            if input_buf.is_per_voice {
-                let channels = input_buf.channels;
-                let voice_size = channels * frames;
-                input_buf.temp_voice[..voice_size].fill(0.0);
-
-                // Mix all voices into temp_voice
-                for voice_id in 0..self.max_voices {
-                    let offset = voice_id * voice_size;
-                    for i in 0..voice_size {
-                        input_buf.temp_voice[i] += input_buf.data[offset + i];
-                    }
-                }
+        let mut depth = vec![0; self.nodes.len()];
+        let mut levels: Vec<Vec<usize>> = Vec::new();
+        for &idx in &self.eval_order {
+            let mut level = 0;
+            for &input in &self.nodes[idx].inputs {
+                level = level.max(depth[input] + 1);
             }
-        }
-
-        // Now create AudioBuffer views, using temp_voice for per-voice inputs
-        // SAFETY: We need simultaneous read access to multiple input buffers.
-        // The borrow checker cannot verify that input indices differ from the output index,
-        // but we guarantee this by construction (a node cannot be its own input).
-        let input_ptrs: Vec<_> = self
-            .input_scratch
-            .iter()
-            .map(|&i| {
-                let b = &self.buffers[i];
-                if b.is_per_voice {
-                    (b.temp_voice.as_ptr(), b.channels)
-                } else {
-                    (b.data.as_ptr(), b.channels)
+            for route in &self.mod_routes {
+                if route.dest == idx && position[route.source] < position[idx] {
+                    level = level.max(depth[route.source] + 1);
                 }
-            })
-            .collect();
-
-        let input_buffers: Vec<AudioBuffer<'_>> = input_ptrs
-            .iter()
-            .map(|&(ptr, channels)| unsafe {
-                AudioBuffer {
-                    channels,
-                    frames,
-                    data: std::slice::from_raw_parts_mut(ptr as *mut f32, channels * frames),
+                if route.source == idx && position[route.dest] < position[idx] {
+                    level = level.max(depth[route.dest] + 1);
                 }
-            })
-            .collect();
-
-        let input_refs: Vec<&AudioBuffer<'_>> = input_buffers.iter().collect();
-
-        // Process node
-        let buf = &mut self.buffers[idx];
-        let mut output = buf.as_buffer(frames);
-
-        let silent = match &mut self.nodes[idx].instance {
-            NodeInstance::Global(n) => n.process(ctx, &input_refs, &mut output),
-            NodeInstance::PerVoice(_) => unreachable!(),
-        };
+            }
 
-        self.nodes[idx].silent = silent;
+            depth[idx] = level;
+            if levels.len() <= level {
+                levels.resize_with(level + 1, Vec::new);
+            }
+            levels[level].push(idx);
+        }
+        levels
     }
 
-    fn process_per_voice_node(
-        &mut self,
-        idx: usize,
-        ctx: &ProcessContext,
-        voices: &VoiceAllocator,
-    ) {
-        let frames = ctx.frames;
+    /// Process levels with several nodes on the rayon thread pool.
+    ///
+    /// Output is identical to serial processing. Takes effect immediately;
+    /// the levels are computed in `prepare`.
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel(&mut self, enabled: bool) {
+        self.parallel = enabled;
+    }
 
-        // Clear all voice buffers for this node
-        let buf = &mut self.buffers[idx];
-        let channels = buf.channels;
-        let total_size = self.max_voices * channels * frames;
-        buf.data[..total_size].fill(0.0);
+    /// Tune note frequencies to A4 at `tuning` Hz.
+    pub fn set_tuning(&mut self, tuning: f64) {
+        self.tuning = tuning;
+    }
 
-        // Collect metadata about input buffers (whether they're per-voice or global)
-        let input_metadata: Vec<_> = self
-            .input_scratch
-            .iter()
-            .map(|&i| {
-                let b = &self.buffers[i];
-                (b.data.as_ptr(), b.channels, b.is_per_voice)
-            })
-            .collect();
+    /// Map MIDI notes to frequency ratios from A4, or back to equal
+    /// temperament with `None`.
+    pub fn set_note_ratios(&mut self, note_ratios: Option<[f32; NOTE_COUNT]>) {
+        self.note_ratios = note_ratios;
+    }
 
-        let mut all_silent = true;
+    /// Process one block of audio
+    pub fn process(&mut self, frames: usize, sample_pos: u64, bpm: f64, voices: &VoiceAllocator) {
+        self.advance_smoothers(frames);
 
-        // Process each active voice
-        for voice_ctx in voices.active_voices() {
-            let voice_id = voice_ctx.id;
-            let ctx_with_voice = ctx.with_voice(voice_ctx);
+        let ctx = ProcessContext::new(frames, self.sample_rate, sample_pos, bpm)
+            .with_tuning(self.tuning)
+            .with_note_ratios(self.note_ratios.as_ref());
 
-            // Build input buffers for this voice
-            // SAFETY: We need simultaneous read access to input buffers while writing to output.
-            // Input and output buffers are separate, so this is safe.
-            let input_buffers: Vec<AudioBuffer<'_>> = input_metadata
-                .iter()
-                .map(|&(ptr, ch, is_per_voice)| unsafe {
-                    let voice_size = ch * frames;
-                    let offset = if is_per_voice {
-                        voice_id * voice_size
-                    } else {
-                        0 // Global buffer - all voices read the same data
-                    };
-                    AudioBuffer {
-                        channels: ch,
-                        frames,
-                        data: std::slice::from_raw_parts_mut(
-                            (ptr as *mut f32).add(offset),
-                            voice_size,
-                        ),
-                    }
-                })
-                .collect();
+        self.voice_pans.fill(0.0);
+        for voice in voices.active_voices() {
+            self.voice_pans[voice.id] = voice.pan;
+        }
+        self.voices_panned = self.voice_pans.iter().any(|&p| p != 0.0);
+
+        let view = GraphView {
+            nodes: self.nodes.as_mut_ptr(),
+            buffers: self.buffers.as_mut_ptr(),
+            mod_routes: &self.mod_routes,
+            velocity_routes: &self.velocity_routes,
+            voice_pans: &self.voice_pans,
+            voices_panned: self.voices_panned,
+            max_voices: self.max_voices,
+        };
 
-            let input_refs: Vec<&AudioBuffer<'_>> = input_buffers.iter().collect();
+        #[cfg(feature = "parallel")]
+        if self.parallel {
+            use rayon::prelude::*;
+
+            for level in &self.levels {
+                // SAFETY: nodes in a level only read nodes from earlier levels
+                // (see compute_levels), and each is processed exactly once
+                level
+                    .par_iter()
+                    .with_min_len(2)
+                    .for_each(|&idx| unsafe { view.process_node(idx, &ctx, voices) });
+            }
+            self.collect_voice_state(voices);
+            return;
+        }
 
-            // Get output buffer for this voice
-            let buf = &mut self.buffers[idx];
-            let voice_size = channels * frames;
-            let offset = voice_id * voice_size;
-            let mut voice_output = AudioBuffer {
-                channels,
-                frames,
-                data: &mut buf.data[offset..offset + voice_size],
-            };
+        // Process nodes in topological order
+        for &idx in &self.eval_order {
+            // SAFETY: one node at a time
+            unsafe { view.process_node(idx, &ctx, voices) };
+        }
+        self.collect_voice_state(voices);
+    }
 
-            let silent = match &mut self.nodes[idx].instance {
-                NodeInstance::PerVoice(nodes) => {
-                    nodes[voice_id].process(&ctx_with_voice, &input_refs, &mut voice_output)
-                }
-                NodeInstance::Global(_) => unreachable!(),
-            };
+    /// Gather envelope levels and finished voices from the per-voice nodes.
+    ///
+    /// A voice finishes when the last per-voice node in evaluation order
+    /// reports it silent, or once it's released and every envelope on it
+    /// stayed silent for the whole block (nodes after an envelope, such as
+    /// filters, may never report silence themselves).
+    fn collect_voice_state(&mut self, voices: &VoiceAllocator) {
+        self.voices_to_deactivate.clear();
+        self.voice_levels.fill(None);
 
-            // Track voice lifecycle: if this per-voice node returned true (idle/silent),
-            // tentatively mark this voice for deactivation. If ANY node returns false
-            // (still producing output), remove it from the list - the voice is still active.
-            // This ensures we only deactivate when ALL per-voice nodes agree the voice is done.
-            if silent {
-                if !self.voices_to_deactivate.contains(&voice_id) {
-                    self.voices_to_deactivate.push(voice_id);
-                }
-            } else {
-                // Voice is still active - remove from deactivation list if present
-                if let Some(pos) = self
-                    .voices_to_deactivate
-                    .iter()
-                    .position(|&v| v == voice_id)
-                {
-                    self.voices_to_deactivate.swap_remove(pos);
+        for buf in self.buffers.iter().filter(|b| b.is_per_voice) {
+            for (slot, &level) in self.voice_levels.iter_mut().zip(&buf.voice_levels) {
+                if let Some(level) = level {
+                    *slot = Some(slot.map_or(level, |l| l.max(level)));
                 }
             }
+        }
 
-            if !silent {
-                all_silent = false;
+        let Some(last) = self.last_per_voice else {
+            return;
+        };
+        for voice in voices.active_voices() {
+            let id = voice.id;
+            let envelopes_idle = !voice.gate && {
+                let mut envelopes = self
+                    .buffers
+                    .iter()
+                    .filter(|b| b.is_per_voice && b.voice_levels[id].is_some())
+                    .peekable();
+                envelopes.peek().is_some() && envelopes.all(|b| b.voice_silent[id])
+            };
+            if self.buffers[last].voice_silent[id] || envelopes_idle || voices.is_released(id) {
+                self.voices_to_deactivate.push(id);
             }
         }
-
-        self.nodes[idx].silent = all_silent;
     }
 
     /// Set a parameter on a specific node by graph index.
+    ///
+    /// For modulated parameters this sets the base value the modulation
+    /// is applied around. Smoothed parameters glide to the new value over
+    /// the following blocks.
     #[inline]
     pub fn set_param(&mut self, node_idx: usize, param_id: u32, value: f32) {
+        if let Some(smoother) = self
+            .param_smoothers
+            .iter_mut()
+            .find(|s| s.node == node_idx && s.param_id == param_id)
+        {
+            smoother.target = value;
+            return;
+        }
+        self.apply_param(node_idx, param_id, value);
+    }
+
+    /// Hand a parameter value to a node and the routes built on it.
+    fn apply_param(&mut self, node_idx: usize, param_id: u32, value: f32) {
+        for route in &mut self.mod_routes {
+            if route.dest == node_idx && route.param_id == param_id {
+                route.base = value;
+            }
+        }
+        for route in &mut self.velocity_routes {
+            if route.node == node_idx && route.param_id == param_id {
+                route.base = value;
+            }
+        }
+
         if let Some(node) = self.nodes.get_mut(node_idx) {
             node.instance.set_param(param_id, value);
         }
     }
 
+    /// Bypass a node by graph index: its first input port is copied to its
+    /// output unprocessed, and nodes without inputs fall silent.
+    pub fn set_bypass(&mut self, node_idx: usize, bypassed: bool) {
+        if let Some(node) = self.nodes.get_mut(node_idx) {
+            node.bypassed = bypassed;
+        }
+    }
+
+    /// Bypass a node by session node ID.
+    pub fn set_bypass_by_id(&mut self, node_id: crate::state::NodeId, bypassed: bool) {
+        if let Some(&idx) = self.id_to_index.get(&node_id) {
+            self.set_bypass(idx, bypassed);
+        }
+    }
+
     /// Set a parameter on a node by session node ID.
     /// Uses the id_to_index mapping populated during compilation.
     #[inline]
     pub fn set_param_by_id(&mut self, node_id: crate::state::NodeId, param_id: u32, value: f32) {
         if let Some(&idx) = self.id_to_index.get(&node_id) {
-            if let Some(node) = self.nodes.get_mut(idx) {
-                node.instance.set_param(param_id, value);
+            self.set_param(idx, param_id, value);
+        }
+    }
+
+    /// Add a parameter modulation route.
+    pub fn add_mod_route(&mut self, route: ModRoute) {
+        self.mod_routes.push(route);
+
+        // Routes constrain which nodes may run together
+        #[cfg(feature = "parallel")]
+        if !self.levels.is_empty() {
+            self.levels = self.compute_levels();
+        }
+    }
+
+    /// Add a velocity-to-parameter scaling.
+    pub fn add_velocity_route(&mut self, route: VelocityRoute) {
+        self.velocity_routes.push(route);
+    }
+
+    /// Smooth a parameter's runtime changes over `time` seconds, starting
+    /// from `value`.
+    pub fn add_param_smoother(&mut self, node_idx: usize, param_id: u32, time: f32, value: f32) {
+        self.param_smoothers.push(ParamSmoother {
+            node: node_idx,
+            param_id,
+            time,
+            current: value,
+            target: value,
+        });
+    }
+
+    /// Step smoothed parameters that haven't reached their targets.
+    fn advance_smoothers(&mut self, frames: usize) {
+        for i in 0..self.param_smoothers.len() {
+            let smoother = &mut self.param_smoothers[i];
+            if smoother.current == smoother.target {
+                continue;
             }
+            let (node, param_id) = (smoother.node, smoother.param_id);
+            let value = smoother.advance(frames, self.sample_rate);
+            self.apply_param(node, param_id, value);
         }
     }
 
@@ -558,10 +938,16 @@ impl Graph {
         start_sample: u64,
         duration_samples: u64,
         gain: f32,
+        pitch_shift: f32,
     ) {
         if let Some(node) = self.nodes.get_mut(node_idx) {
-            node.instance
-                .start_audio(audio_id, start_sample, duration_samples, gain);
+            node.instance.start_audio(
+                audio_id,
+                start_sample,
+                duration_samples,
+                gain,
+                pitch_shift,
+            );
         }
     }
 
@@ -573,9 +959,17 @@ impl Graph {
         start_sample: u64,
         duration_samples: u64,
         gain: f32,
+        pitch_shift: f32,
     ) {
         if let Some(&idx) = self.id_to_index.get(&node_id) {
-            self.start_audio(idx, audio_id, start_sample, duration_samples, gain);
+            self.start_audio(
+                idx,
+                audio_id,
+                start_sample,
+                duration_samples,
+                gain,
+                pitch_shift,
+            );
         }
     }
 
@@ -626,9 +1020,25 @@ impl Graph {
             node.instance.reset();
             node.silent = false;
         }
+        for i in 0..self.param_smoothers.len() {
+            let smoother = &mut self.param_smoothers[i];
+            smoother.current = smoother.target;
+            let (node, param_id, value) = (smoother.node, smoother.param_id, smoother.target);
+            self.apply_param(node, param_id, value);
+        }
         for buf in &mut self.buffers {
             buf.data.fill(0.0);
             buf.temp_voice.fill(0.0);
+            for delay in &mut buf.input_delays {
+                delay.reset();
+            }
+        }
+    }
+
+    /// Notify global nodes that a note started.
+    pub fn retrigger(&mut self) {
+        for node in &mut self.nodes {
+            node.instance.retrigger();
         }
     }
 
@@ -639,6 +1049,14 @@ impl Graph {
             .map(|b| &b.data[..b.channels * frames])
     }
 
+    /// Envelope levels of the voices processed in the last block.
+    pub fn voice_levels(&self) -> impl Iterator<Item = (crate::voice::VoiceId, f32)> + '_ {
+        self.voice_levels
+            .iter()
+            .enumerate()
+            .filter_map(|(id, level)| level.map(|l| (id, l)))
+    }
+
     /// Drain voices that finished during the last processing block.
     ///
     /// Returns an iterator over voice IDs that should be deactivated.
@@ -649,3 +1067,506 @@ impl Graph {
         self.voices_to_deactivate.drain(..)
     }
 }
+
+/// Unchecked access to the graph's nodes and buffers while processing.
+///
+/// Processing a node writes only its own `GraphNode` and `NodeBuffer` and
+/// reads the nodes it depends on, so nodes that don't depend on each other
+/// can process at the same time. Callers uphold that through the `unsafe`
+/// methods below.
+#[derive(Clone, Copy)]
+struct GraphView<'a> {
+    nodes: *mut GraphNode,
+    buffers: *mut NodeBuffer,
+    mod_routes: &'a [ModRoute],
+    velocity_routes: &'a [VelocityRoute],
+    voice_pans: &'a [f32],
+    voices_panned: bool,
+    max_voices: usize,
+}
+
+// SAFETY: nodes are `Send` and each is only touched by the thread processing
+// it; other threads only read nodes that have finished processing.
+unsafe impl Send for GraphView<'_> {}
+unsafe impl Sync for GraphView<'_> {}
+
+impl GraphView<'_> {
+    /// Process node `idx` for one block.
+    ///
+    /// # Safety
+    /// No other thread may be processing `idx`, any node it reads this block
+    /// (inputs and modulation sources), or any node that reads it.
+    unsafe fn process_node(&self, idx: usize, ctx: &ProcessContext, voices: &VoiceAllocator) {
+        unsafe {
+            if !self.mod_routes.is_empty() {
+                self.apply_mod_routes(idx, ctx.frames);
+            }
+
+            let node = &mut *self.nodes.add(idx);
+            if node.instance.is_per_voice() {
+                self.process_per_voice_node(idx, node, ctx, voices);
+            } else {
+                self.process_global_node(idx, node, ctx);
+            }
+        }
+    }
+
+    /// Apply modulation routes targeting `idx` at control rate.
+    ///
+    /// Sources earlier in the evaluation order contribute this block's
+    /// output; later ones contribute the previous block's. Routes between
+    /// two per-voice nodes are left to `apply_voice_mod_routes`.
+    unsafe fn apply_mod_routes(&self, idx: usize, frames: usize) {
+        let node = unsafe { &mut *self.nodes.add(idx) };
+        let dest_per_voice = node.instance.is_per_voice();
+        for (i, route) in self.mod_routes.iter().enumerate() {
+            if route.dest != idx || !self.first_route_to_param(i) {
+                continue;
+            }
+            let routes = self.routes_to_param(i);
+            if dest_per_voice && routes.clone().any(|r| self.source_is_per_voice(r)) {
+                continue;
+            }
+
+            let mut sum = ModSum::default();
+            for r in routes {
+                sum.add(r, unsafe { self.mod_signal(r, frames, None) });
+            }
+            node.instance.set_param(route.param_id, sum.value(route.base));
+        }
+    }
+
+    /// Apply routes from per-voice sources to one voice of a per-voice node.
+    ///
+    /// Each voice follows its own source voice, so e.g. every note's filter
+    /// tracks that note's envelope. Global routes onto the same parameter
+    /// are summed in here too.
+    unsafe fn apply_voice_mod_routes(
+        &self,
+        idx: usize,
+        node: &mut dyn Node,
+        voice_id: usize,
+        frames: usize,
+    ) {
+        for (i, route) in self.mod_routes.iter().enumerate() {
+            if route.dest != idx || !self.first_route_to_param(i) {
+                continue;
+            }
+            let routes = self.routes_to_param(i);
+            if !routes.clone().any(|r| self.source_is_per_voice(r)) {
+                continue;
+            }
+
+            let mut sum = ModSum::default();
+            for r in routes {
+                sum.add(r, unsafe { self.mod_signal(r, frames, Some(voice_id)) });
+            }
+            node.set_param(route.param_id, sum.value(route.base));
+        }
+    }
+
+    /// Whether no earlier route targets the same parameter as route `i`.
+    fn first_route_to_param(&self, i: usize) -> bool {
+        let route = &self.mod_routes[i];
+        !self.mod_routes[..i]
+            .iter()
+            .any(|r| r.dest == route.dest && r.param_id == route.param_id)
+    }
+
+    /// Route `i` and every later route onto the same parameter.
+    fn routes_to_param(&self, i: usize) -> impl Iterator<Item = &ModRoute> + Clone {
+        let route = &self.mod_routes[i];
+        self.mod_routes[i..]
+            .iter()
+            .filter(move |r| r.dest == route.dest && r.param_id == route.param_id)
+    }
+
+    fn source_is_per_voice(&self, route: &ModRoute) -> bool {
+        unsafe { &*self.buffers.add(route.source) }.is_per_voice
+    }
+
+    /// A route's source value this block. Per-voice sources read
+    /// `voice_id`'s voice, or the sum of all voices without one.
+    unsafe fn mod_signal(&self, route: &ModRoute, frames: usize, voice_id: Option<usize>) -> f32 {
+        let src = unsafe { &*self.buffers.add(route.source) };
+        if !src.is_per_voice {
+            return ModSignal::Control(&src.data).value_control();
+        }
+        let voice_size = src.channels * frames;
+        match voice_id {
+            Some(v) => ModSignal::Control(&src.data[v * voice_size..]).value_control(),
+            None => (0..self.max_voices)
+                .map(|v| ModSignal::Control(&src.data[v * voice_size..]).value_control())
+                .sum(),
+        }
+    }
+
+    unsafe fn process_global_node(&self, idx: usize, node: &mut GraphNode, ctx: &ProcessContext) {
+        let frames = ctx.frames;
+
+        // Clear output buffer
+        let buf = unsafe { &mut *self.buffers.add(idx) };
+        buf.data[..buf.channels * frames].fill(0.0);
+
+        // Early exit if all inputs are silent (unless delayed inputs may
+        // still hold sound)
+        let inputs_silent = node
+            .inputs
+            .iter()
+            .all(|&i| unsafe { (*self.nodes.add(i)).silent });
+        if inputs_silent && !node.inputs.is_empty() && buf.input_delays.is_empty() {
+            node.silent = true;
+            return;
+        }
+
+        // Resolve one view per input port; per-voice inputs were mixed into
+        // their temp_voice buffers when they processed. The views borrow the
+        // node's scratch, leaving its output free to write.
+        let NodeBuffer {
+            channels,
+            data,
+            port_mix,
+            input_delays,
+            ..
+        } = buf;
+        let inputs = unsafe {
+            self.resolve_port_inputs(
+                &node.port_inputs,
+                &node.port_channels,
+                port_mix,
+                input_delays,
+                frames,
+                None,
+            )
+        };
+
+        // Process node
+        let mut output = OutputBuffer::new(&mut data[..*channels * frames], *channels);
+
+        node.silent = match &mut node.instance {
+            _ if node.bypassed => {
+                pass_through(inputs.first(), &mut output);
+                unsafe { self.bypass_silent(&node.port_inputs, None) }
+            }
+            NodeInstance::Global(n) => n.process(ctx, &inputs, &mut output),
+            NodeInstance::PerVoice(_) => unreachable!(),
+        };
+    }
+
+    /// Whether a bypassed node's output is silent: its first input port
+    /// has no sound (for `voice_id` when set).
+    unsafe fn bypass_silent(
+        &self,
+        port_inputs: &[Vec<(usize, Option<usize>)>],
+        voice_id: Option<usize>,
+    ) -> bool {
+        port_inputs.first().is_none_or(|sources| {
+            sources.iter().all(|&(src, _)| {
+                let buf = unsafe { &*self.buffers.add(src) };
+                match voice_id {
+                    Some(v) if buf.is_per_voice => buf.voice_silent[v],
+                    _ => unsafe { (*self.nodes.add(src)).silent },
+                }
+            })
+        })
+    }
+
+    unsafe fn process_per_voice_node(
+        &self,
+        idx: usize,
+        node: &mut GraphNode,
+        ctx: &ProcessContext,
+        voices: &VoiceAllocator,
+    ) {
+        let frames = ctx.frames;
+
+        // Clear all voice buffers for this node
+        let buf = unsafe { &mut *self.buffers.add(idx) };
+        let channels = buf.channels;
+        let total_size = self.max_voices * channels * frames;
+        buf.data[..total_size].fill(0.0);
+        buf.voice_silent.fill(false);
+        buf.voice_levels.fill(None);
+
+        let NodeInstance::PerVoice(instances) = &mut node.instance else {
+            unreachable!()
+        };
+        let mut all_silent = true;
+
+        // Process each voice that can still sound
+        for voice_ctx in voices.sounding_voices() {
+            let voice_id = voice_ctx.id;
+            let ctx_with_voice = ctx.with_voice(voice_ctx);
+            let instance = &mut instances[voice_id];
+            if !self.mod_routes.is_empty() {
+                unsafe { self.apply_voice_mod_routes(idx, instance.as_mut(), voice_id, frames) };
+            }
+
+            // Build input buffers for this voice, one per input port
+            let NodeBuffer {
+                data,
+                port_mix,
+                input_delays,
+                ..
+            } = &mut *buf;
+            let inputs = unsafe {
+                self.resolve_port_inputs(
+                    &node.port_inputs,
+                    &node.port_channels,
+                    port_mix,
+                    input_delays,
+                    frames,
+                    Some(voice_id),
+                )
+            };
+
+            // Get output buffer for this voice
+            let voice_size = channels * frames;
+            let mut voice_output = OutputBuffer::new(
+                &mut data[voice_id * voice_size..(voice_id + 1) * voice_size],
+                channels,
+            );
+
+            if voice_ctx.trigger {
+                for route in self.velocity_routes.iter().filter(|r| r.node == idx) {
+                    instance.set_param(route.param_id, route.value(voice_ctx.velocity));
+                }
+            }
+            let silent = if node.bypassed {
+                pass_through(inputs.first(), &mut voice_output);
+                unsafe { self.bypass_silent(&node.port_inputs, Some(voice_id)) }
+            } else {
+                let silent = instance.process(&ctx_with_voice, &inputs, &mut voice_output);
+                buf.voice_levels[voice_id] = instance.envelope_level();
+                silent
+            };
+
+            // Whether the voice has finished is decided after the block, by
+            // the last per-voice node (see Graph::collect_voice_state)
+            buf.voice_silent[voice_id] = silent;
+
+            if !silent {
+                all_silent = false;
+            }
+        }
+
+        // Mix voices for global nodes downstream
+        if buf.feeds_global {
+            buf.mix_voices(self.voice_pans, self.voices_panned, frames);
+        }
+
+        node.silent = all_silent;
+    }
+
+    /// Resolve one input view per destination port of a node.
+    ///
+    /// Ports fed by a single source point straight at that source's buffer
+    /// (the voice's slice for per-voice sources when `voice_id` is set, the
+    /// voice mix in `temp_voice` otherwise), or at its latency compensation
+    /// delay. Empty ports, ports with several sources and ports whose
+    /// declared width differs from their source are mixed into the node's
+    /// own `port_mix`; mono sources are spread across all channels of a
+    /// wider mix, and wider sources are averaged into a mono one.
+    ///
+    /// Views of the node's own mix and delay storage borrow `port_mix` and
+    /// `input_delays`, so the borrow checker keeps them apart from the
+    /// node's output. Views of source buffers are built from the graph's
+    /// raw pointers, since nodes processing in parallel share the view.
+    ///
+    /// # Safety
+    /// No source may be written while the views are alive. Sources are
+    /// other nodes (compile rejects cycles) that finished processing
+    /// earlier in the block, so only the node being processed writes
+    /// anything meanwhile, and its output is not among its sources.
+    unsafe fn resolve_port_inputs<'a>(
+        &'a self,
+        port_inputs: &[Vec<(usize, Option<usize>)>],
+        port_channels: &[usize],
+        port_mix: &'a mut [Vec<f32>],
+        input_delays: &'a mut [InputDelay],
+        frames: usize,
+        voice_id: Option<usize>,
+    ) -> Vec<InputBuffer<'a>> {
+        // SAFETY: a source buffer holds at least channels * frames samples
+        // past the resolved pointer. It belongs to another node, so it can't
+        // alias this node's output or the scratch borrowed above, and it
+        // isn't written again until the next block.
+        let source_slice = |ptr: *const f32, len: usize| -> &'a [f32] {
+            unsafe { std::slice::from_raw_parts(ptr, len) }
+        };
+        let raw_view = |src: usize| -> (*const f32, usize) {
+            let b = unsafe { &*self.buffers.add(src) };
+            match (b.is_per_voice, voice_id) {
+                (true, Some(v)) => (b.data[v * b.channels * frames..].as_ptr(), b.channels),
+                (true, None) => (b.temp_voice.as_ptr(), b.mix_channels),
+                (false, _) => (b.data.as_ptr(), b.channels),
+            }
+        };
+
+        // Delayed inputs advance once per block, then stand in for their source
+        if voice_id.is_none() {
+            for delay in input_delays.iter_mut() {
+                let (ptr, channels) = raw_view(delay.source);
+                delay.process(source_slice(ptr, channels * frames), channels, frames);
+            }
+        }
+        let input_delays: &'a [InputDelay] = input_delays;
+        let source_view = |(src, output): (usize, Option<usize>)| -> (&'a [f32], usize) {
+            let (data, channels) = match input_delays
+                .iter()
+                .find(|d| voice_id.is_none() && d.source == src)
+            {
+                Some(delay) => (&delay.out[..], delay.out_channels),
+                None => {
+                    let (ptr, channels) = raw_view(src);
+                    (source_slice(ptr, channels * frames), channels)
+                }
+            };
+            // One output of a multi-output node is one channel of its buffer
+            match output {
+                Some(ch) if ch < channels => (&data[ch * frames..(ch + 1) * frames], 1),
+                _ => (&data[..channels * frames], channels),
+            }
+        };
+
+        let mut views = Vec::with_capacity(port_inputs.len());
+
+        for ((port, sources), mix) in port_inputs.iter().enumerate().zip(port_mix) {
+            let declared = port_channels.get(port).copied().filter(|&c| c > 0);
+            if let [src] = sources.as_slice() {
+                let (data, channels) = source_view(*src);
+                if declared.is_none_or(|d| d == channels) {
+                    views.push(InputBuffer::new(data, channels));
+                    continue;
+                }
+            }
+
+            let channels = declared
+                .or_else(|| sources.iter().map(|&s| source_view(s).1).max())
+                .unwrap_or(1);
+            let mix = &mut mix[..channels * frames];
+            mix.fill(0.0);
+
+            for &src in sources {
+                let (data, src_channels) = source_view(src);
+                if channels == 1 && src_channels > 1 {
+                    // Down-mix to mono
+                    let scale = 1.0 / src_channels as f32;
+                    for input in data.chunks_exact(frames) {
+                        for (o, i) in mix.iter_mut().zip(input) {
+                            *o += *i * scale;
+                        }
+                    }
+                    continue;
+                }
+                for ch in 0..channels {
+                    let in_ch = ch.min(src_channels - 1);
+                    let input = &data[in_ch * frames..(in_ch + 1) * frames];
+                    let out = &mut mix[ch * frames..(ch + 1) * frames];
+                    for (o, i) in out.iter_mut().zip(input) {
+                        *o += *i;
+                    }
+                }
+            }
+
+            let mix: &'a [f32] = mix;
+            views.push(InputBuffer::new(mix, channels));
+        }
+
+        views
+    }
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use super::*;
+    use crate::compile::compile;
+    use crate::nodes::{node_types, params, register_standard_nodes};
+    use crate::state::GraphDef;
+
+    #[test]
+    fn test_parallel_matches_serial() {
+        let mut registry = crate::node_factory::NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        // Four oscillator/filter/envelope branches mixed into two parallel
+        // effects, with both per-voice and global modulation
+        let mut def = GraphDef::new();
+        let mixer = def.add_node(node_types::MIXER);
+        let lfo = def.add_node(node_types::LFO);
+        def.set_param(lfo, params::RATE, 3.0);
+        let oscillators = [
+            node_types::SAW_OSC,
+            node_types::SQUARE_OSC,
+            node_types::TRIANGLE_OSC,
+            node_types::SINE_OSC,
+        ];
+        for (i, osc_type) in oscillators.into_iter().enumerate() {
+            let osc = def.add_node(osc_type);
+            let filter = def.add_node(node_types::LOWPASS);
+            let env = def.add_node(node_types::ADSR_ENV);
+            def.set_param(filter, params::CUTOFF, 800.0 * (i + 1) as f32);
+            def.set_param(env, params::RELEASE, 0.01 * (i + 1) as f32);
+            def.connect(osc, 0, filter, 0);
+            def.connect(filter, 0, env, 0);
+            def.connect(env, 0, mixer, 0);
+            def.add_mod_route(env, filter, params::CUTOFF, 1_000.0);
+        }
+        let gain = def.add_node(node_types::GAIN);
+        let drive = def.add_node(node_types::DISTORTION);
+        let out = def.add_node(node_types::OUTPUT);
+        def.connect(mixer, 0, gain, 0);
+        def.connect(mixer, 0, drive, 0);
+        def.connect(gain, 0, out, 0);
+        def.connect(drive, 0, out, 0);
+        def.add_mod_route(lfo, gain, params::GAIN, 0.3);
+        def.output_node = Some(out);
+
+        let mut serial = compile(&def, &registry, 256, 8).unwrap();
+        let mut parallel = compile(&def, &registry, 256, 8).unwrap();
+        serial.prepare(48_000.0);
+        parallel.prepare(48_000.0);
+        parallel.set_parallel(true);
+        assert!(parallel.levels.iter().any(|level| level.len() >= 4));
+
+        let mut voices = VoiceAllocator::new(8);
+        for note in [48, 55, 60, 64, 67] {
+            voices.note_on(note, 0.8);
+        }
+
+        let mut peak = 0.0f32;
+        for block in 0..200u64 {
+            if block == 50 {
+                for note in [48, 55, 60, 64, 67] {
+                    voices.note_off(note);
+                }
+            }
+            serial.process(256, block * 256, 120.0, &voices);
+            parallel.process(256, block * 256, 120.0, &voices);
+            voices.clear_triggers();
+
+            let expected = serial.output_buffer(256).unwrap();
+            let actual = parallel.output_buffer(256).unwrap();
+            assert!(
+                expected
+                    .iter()
+                    .zip(actual)
+                    .all(|(a, b)| a.to_bits() == b.to_bits()),
+                "output differs in block {block}"
+            );
+            peak = expected.iter().fold(peak, |p, s| p.max(s.abs()));
+
+            let finished: Vec<_> = serial.drain_finished_voices().collect();
+            assert!(
+                parallel
+                    .drain_finished_voices()
+                    .eq(finished.iter().copied())
+            );
+            for voice in finished {
+                voices.deactivate(voice);
+            }
+        }
+        assert!(peak > 0.01);
+        assert_eq!(voices.active_count(), 0);
+    }
+}
@@ -0,0 +1,266 @@
+// Minimal Standard MIDI File reader and writer.
+//
+// Exports a clip's notes as a single-track (format 0) file and reads the
+// notes back out of format 0 or 1 files. Only note and tempo events are
+// written; everything else is skipped on import.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::state::{ClipDef, NoteDef};
+
+/// Ticks per quarter note used for exported files.
+pub const MIDI_PPQN: u16 = 480;
+
+/// Write a clip's notes to a format 0 Standard MIDI File.
+pub fn export_midi(clip: &ClipDef, bpm: f64, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_midi(&mut writer, clip, bpm)?;
+    writer.flush()
+}
+
+/// Write a complete format 0 MIDI stream to `writer`.
+pub fn write_midi<W: Write>(writer: &mut W, clip: &ClipDef, bpm: f64) -> io::Result<()> {
+    let to_ticks = |beats: f64| (beats.max(0.0) * MIDI_PPQN as f64).round() as u32;
+
+    // (tick, status, note, velocity); offs sort ahead of ons on the same
+    // tick so a repeated pitch isn't cut off by its predecessor's note-off
+    let mut events: Vec<(u32, u8, u8, u8)> = Vec::new();
+    for note in clip.notes() {
+        let velocity = (note.velocity * 127.0).round().clamp(1.0, 127.0) as u8;
+        let start = to_ticks(note.start);
+        let end = to_ticks(note.end()).max(start);
+        events.push((start, 0x90, note.note, velocity));
+        events.push((end, 0x80, note.note, 0));
+    }
+    events.sort_by_key(|&(tick, status, note, _)| (tick, status, note));
+
+    let mut track = Vec::new();
+
+    // Tempo: microseconds per quarter note
+    let tempo = (60_000_000.0 / bpm.max(1.0)).round() as u32;
+    write_var_len(&mut track, 0);
+    track.extend_from_slice(&[0xFF, 0x51, 0x03]);
+    track.extend_from_slice(&tempo.to_be_bytes()[1..]);
+
+    let mut last_tick = 0;
+    for (tick, status, note, velocity) in events {
+        write_var_len(&mut track, tick - last_tick);
+        track.extend_from_slice(&[status, note & 0x7F, velocity]);
+        last_tick = tick;
+    }
+
+    // End of track
+    write_var_len(&mut track, 0);
+    track.extend_from_slice(&[0xFF, 0x2F, 0x00]);
+
+    writer.write_all(b"MThd")?;
+    writer.write_all(&6u32.to_be_bytes())?;
+    writer.write_all(&0u16.to_be_bytes())?; // format 0
+    writer.write_all(&1u16.to_be_bytes())?; // one track
+    writer.write_all(&MIDI_PPQN.to_be_bytes())?;
+
+    writer.write_all(b"MTrk")?;
+    writer.write_all(&(track.len() as u32).to_be_bytes())?;
+    writer.write_all(&track)
+}
+
+/// Read the notes from a Standard MIDI File, in beats.
+///
+/// Notes from every track and channel are merged and sorted by start.
+pub fn import_midi(path: impl AsRef<Path>) -> io::Result<Vec<NoteDef>> {
+    read_midi(&mut BufReader::new(File::open(path)?))
+}
+
+/// Read the notes from a complete MIDI stream.
+pub fn read_midi<R: Read>(reader: &mut R) -> io::Result<Vec<NoteDef>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    if bytes.len() < 14 || &bytes[0..4] != b"MThd" {
+        return Err(invalid("not a Standard MIDI File"));
+    }
+    let header_len = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+    let division = u16::from_be_bytes([bytes[12], bytes[13]]);
+    if division & 0x8000 != 0 || division == 0 {
+        return Err(invalid("SMPTE time division is not supported"));
+    }
+    let ticks_per_beat = division as f64;
+
+    let mut notes = Vec::new();
+    let mut pos = 8 + header_len;
+    while pos + 8 <= bytes.len() {
+        let len = u32::from_be_bytes([
+            bytes[pos + 4],
+            bytes[pos + 5],
+            bytes[pos + 6],
+            bytes[pos + 7],
+        ]) as usize;
+        let body = bytes
+            .get(pos + 8..pos + 8 + len)
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        if &bytes[pos..pos + 4] == b"MTrk" {
+            read_track(body, ticks_per_beat, &mut notes)?;
+        }
+        pos += 8 + len;
+    }
+
+    notes.sort_by(|a, b| a.start.total_cmp(&b.start).then(a.note.cmp(&b.note)));
+    Ok(notes)
+}
+
+/// Decode one MTrk chunk body, appending its notes.
+fn read_track(track: &[u8], ticks_per_beat: f64, notes: &mut Vec<NoteDef>) -> io::Result<()> {
+    let truncated = || invalid("truncated track");
+    let mut pos = 0;
+    let mut tick = 0u32;
+    let mut running_status = 0u8;
+    // (channel, note) -> start tick and velocity of sounding notes
+    let mut sounding: HashMap<(u8, u8), Vec<(u32, u8)>> = HashMap::new();
+
+    while pos < track.len() {
+        tick += read_var_len(track, &mut pos).ok_or_else(truncated)?;
+
+        let mut status = *track.get(pos).ok_or_else(truncated)?;
+        if status & 0x80 != 0 {
+            pos += 1;
+        } else {
+            status = running_status;
+        }
+
+        match status {
+            0xFF => {
+                let kind = *track.get(pos).ok_or_else(truncated)?;
+                pos += 1;
+                let len = read_var_len(track, &mut pos).ok_or_else(truncated)? as usize;
+                pos += len;
+                if kind == 0x2F {
+                    break;
+                }
+            }
+            0xF0 | 0xF7 => {
+                let len = read_var_len(track, &mut pos).ok_or_else(truncated)? as usize;
+                pos += len;
+            }
+            0x80..=0xEF => {
+                running_status = status;
+                let data_len = if matches!(status & 0xF0, 0xC0 | 0xD0) {
+                    1
+                } else {
+                    2
+                };
+                let data = track.get(pos..pos + data_len).ok_or_else(truncated)?;
+                pos += data_len;
+
+                let channel = status & 0x0F;
+                match (status & 0xF0, data) {
+                    (0x90, &[note, velocity]) if velocity > 0 => {
+                        sounding
+                            .entry((channel, note))
+                            .or_default()
+                            .push((tick, velocity));
+                    }
+                    (0x80 | 0x90, &[note, _]) => {
+                        let started = sounding
+                            .get_mut(&(channel, note))
+                            .and_then(|s| (!s.is_empty()).then(|| s.remove(0)));
+                        if let Some((start, velocity)) = started {
+                            notes.push(NoteDef::new(
+                                start as f64 / ticks_per_beat,
+                                (tick - start) as f64 / ticks_per_beat,
+                                note,
+                                velocity as f32 / 127.0,
+                            ));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => return Err(invalid("unexpected status byte")),
+        }
+    }
+
+    Ok(())
+}
+
+fn write_var_len(out: &mut Vec<u8>, mut value: u32) {
+    let mut buf = [0u8; 5];
+    let mut i = buf.len() - 1;
+    buf[i] = (value & 0x7F) as u8;
+    value >>= 7;
+    while value > 0 {
+        i -= 1;
+        buf[i] = (value & 0x7F) as u8 | 0x80;
+        value >>= 7;
+    }
+    out.extend_from_slice(&buf[i..]);
+}
+
+fn read_var_len(bytes: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut value = 0u32;
+    for _ in 0..4 {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value = (value << 7) | (byte & 0x7F) as u32;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_midi_round_trip() {
+        let mut clip = ClipDef::new(1, "Riff", 4.0);
+        // Include a repeated pitch that starts exactly as the previous one ends
+        clip.add_note(NoteDef::new(0.0, 0.5, 60, 1.0));
+        clip.add_note(NoteDef::new(0.5, 0.5, 60, 0.5));
+        clip.add_note(NoteDef::new(1.0, 1.0, 64, 0.8));
+        clip.add_note(NoteDef::new(1.0, 2.0, 67, 0.3));
+        clip.add_note(NoteDef::new(2.3333, 0.1667, 72, 0.9));
+
+        let path = std::env::temp_dir().join(format!("hyasynth-export-{}.mid", std::process::id()));
+        export_midi(&clip, 120.0, &path).unwrap();
+        let imported = import_midi(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut expected: Vec<NoteDef> = clip.notes().copied().collect();
+        expected.sort_by(|a, b| a.start.total_cmp(&b.start).then(a.note.cmp(&b.note)));
+        assert_eq!(imported.len(), expected.len());
+
+        let tick = 1.0 / MIDI_PPQN as f64;
+        for (got, want) in imported.iter().zip(&expected) {
+            assert_eq!(got.note, want.note);
+            assert!(
+                (got.start - want.start).abs() <= tick,
+                "{got:?} vs {want:?}"
+            );
+            assert!(
+                (got.duration - want.duration).abs() <= tick,
+                "{got:?} vs {want:?}"
+            );
+            assert!((got.velocity - want.velocity).abs() <= 1.0 / 127.0);
+        }
+    }
+
+    #[test]
+    fn test_var_len_encoding() {
+        for value in [0, 0x40, 0x7F, 0x80, 0x2000, 0x3FFF, 0x4000, 0x0FFF_FFFF] {
+            let mut bytes = Vec::new();
+            write_var_len(&mut bytes, value);
+            let mut pos = 0;
+            assert_eq!(read_var_len(&bytes, &mut pos), Some(value));
+            assert_eq!(pos, bytes.len());
+        }
+    }
+}
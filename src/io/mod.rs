@@ -0,0 +1,8 @@
+// File import/export.
+//
+// Helpers for moving audio and note data in and out of the engine as
+// files. These run on the UI/worker side and are never called from the
+// audio thread.
+
+pub mod midi;
+pub mod wav;
@@ -0,0 +1,283 @@
+// Minimal RIFF/WAVE reader and writer.
+//
+// Reads and writes interleaved f32 samples as 16-bit PCM or 32-bit IEEE
+// float. No external crates: the header is a handful of little-endian fields.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_IEEE_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Sample encoding for exported WAV files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WavFormat {
+    /// 16-bit signed integer PCM (samples are clamped to -1..1).
+    Pcm16,
+    /// 32-bit IEEE float, written unchanged.
+    #[default]
+    Float32,
+}
+
+impl WavFormat {
+    fn bytes_per_sample(self) -> u16 {
+        match self {
+            WavFormat::Pcm16 => 2,
+            WavFormat::Float32 => 4,
+        }
+    }
+}
+
+/// Write interleaved samples to a 32-bit float WAV file.
+pub fn export_wav(
+    path: impl AsRef<Path>,
+    samples: &[f32],
+    channels: u16,
+    sample_rate: u32,
+) -> io::Result<()> {
+    export_wav_as(path, samples, channels, sample_rate, WavFormat::Float32)
+}
+
+/// Write interleaved samples to a WAV file in the given format.
+pub fn export_wav_as(
+    path: impl AsRef<Path>,
+    samples: &[f32],
+    channels: u16,
+    sample_rate: u32,
+    format: WavFormat,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_wav(&mut writer, samples, channels, sample_rate, format)?;
+    writer.flush()
+}
+
+/// Write a complete WAV stream (header and data) to `writer`.
+///
+/// `samples` must hold whole frames of `channels` interleaved samples.
+pub fn write_wav<W: Write>(
+    writer: &mut W,
+    samples: &[f32],
+    channels: u16,
+    sample_rate: u32,
+    format: WavFormat,
+) -> io::Result<()> {
+    if channels == 0 || !samples.len().is_multiple_of(channels as usize) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "sample count is not a whole number of frames",
+        ));
+    }
+
+    let bytes_per_sample = format.bytes_per_sample();
+    let block_align = channels * bytes_per_sample;
+    let data_len = u32::try_from(samples.len() * bytes_per_sample as usize)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too much audio for WAV"))?;
+    let frames = (samples.len() / channels as usize) as u32;
+
+    // Float files carry the extended fmt chunk and a fact chunk
+    let (format_tag, fmt_len, fact_len) = match format {
+        WavFormat::Pcm16 => (FORMAT_PCM, 16u32, 0u32),
+        WavFormat::Float32 => (FORMAT_IEEE_FLOAT, 18, 12),
+    };
+    let riff_len = 4 + (8 + fmt_len) + fact_len + (8 + data_len);
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&riff_len.to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&fmt_len.to_le_bytes())?;
+    writer.write_all(&format_tag.to_le_bytes())?;
+    writer.write_all(&channels.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&(bytes_per_sample * 8).to_le_bytes())?;
+    if format == WavFormat::Float32 {
+        writer.write_all(&0u16.to_le_bytes())?; // cbSize
+        writer.write_all(b"fact")?;
+        writer.write_all(&4u32.to_le_bytes())?;
+        writer.write_all(&frames.to_le_bytes())?;
+    }
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_len.to_le_bytes())?;
+    match format {
+        WavFormat::Pcm16 => {
+            for &s in samples {
+                let value = (s.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        WavFormat::Float32 => {
+            for &s in samples {
+                writer.write_all(&s.to_le_bytes())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decode a 16-bit PCM or 32-bit float WAV file.
+///
+/// Returns `(sample_rate, channels, interleaved samples)` at the file's
+/// native sample rate.
+pub fn import_wav(path: impl AsRef<Path>) -> io::Result<(u32, u16, Vec<f32>)> {
+    read_wav(&mut BufReader::new(File::open(path)?))
+}
+
+/// Decode a complete WAV stream from `reader`.
+pub fn read_wav<R: Read>(reader: &mut R) -> io::Result<(u32, u16, Vec<f32>)> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    let mut header = [0u8; 12];
+    reader.read_exact(&mut header)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(invalid("not a RIFF/WAVE file"));
+    }
+
+    let mut format: Option<(WavFormat, u16, u32)> = None;
+    loop {
+        let mut chunk = [0u8; 8];
+        reader.read_exact(&mut chunk)?;
+        let len = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as usize;
+        // Read through `take` so a corrupt length can't allocate more than
+        // the stream holds; chunks we don't use are skipped unbuffered
+        let mut body = Vec::new();
+        let mut chunk_reader = reader.by_ref().take(len as u64);
+        let read = match &chunk[0..4] {
+            b"fmt " | b"data" => chunk_reader.read_to_end(&mut body)?,
+            _ => io::copy(&mut chunk_reader, &mut io::sink())? as usize,
+        };
+        if read < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "chunk extends past the end of the stream",
+            ));
+        }
+        // Chunks are word aligned
+        if len % 2 == 1 {
+            reader.read_exact(&mut [0u8; 1])?;
+        }
+
+        match &chunk[0..4] {
+            b"fmt " => {
+                if len < 16 {
+                    return Err(invalid("fmt chunk too short"));
+                }
+                let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+                let mut tag = u16_at(0);
+                if tag == FORMAT_EXTENSIBLE && len >= 26 {
+                    // The sub-format GUID starts with the real format tag
+                    tag = u16_at(24);
+                }
+                let channels = u16_at(2);
+                let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                let bits = u16_at(14);
+                let sample_format = match (tag, bits) {
+                    (FORMAT_PCM, 16) => WavFormat::Pcm16,
+                    (FORMAT_IEEE_FLOAT, 32) => WavFormat::Float32,
+                    _ => return Err(invalid("only 16-bit PCM and 32-bit float are supported")),
+                };
+                if channels == 0 {
+                    return Err(invalid("zero channels"));
+                }
+                format = Some((sample_format, channels, sample_rate));
+            }
+            b"data" => {
+                let (sample_format, channels, sample_rate) =
+                    format.ok_or_else(|| invalid("data chunk before fmt chunk"))?;
+                let samples = match sample_format {
+                    WavFormat::Pcm16 => body
+                        .chunks_exact(2)
+                        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / i16::MAX as f32)
+                        .collect(),
+                    WavFormat::Float32 => body
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect(),
+                };
+                return Ok((sample_rate, channels, samples));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Byte offset of a chunk's body, found by walking the RIFF chunks.
+    fn chunk_body(bytes: &[u8], id: &[u8; 4]) -> (usize, u32) {
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let len = u32_at(bytes, pos + 4);
+            if &bytes[pos..pos + 4] == id {
+                return (pos + 8, len);
+            }
+            pos += 8 + len as usize;
+        }
+        panic!("missing {:?} chunk", std::str::from_utf8(id));
+    }
+
+    #[test]
+    fn test_export_wav_header() {
+        let path = std::env::temp_dir().join(format!("hyasynth-export-{}.wav", std::process::id()));
+
+        for (format, channels) in [
+            (WavFormat::Float32, 2u16),
+            (WavFormat::Pcm16, 1),
+            (WavFormat::Pcm16, 2),
+        ] {
+            let samples: Vec<f32> = (0..480).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+            export_wav_as(&path, &samples, channels, 44_100, format).unwrap();
+            let bytes = std::fs::read(&path).unwrap();
+
+            assert_eq!(&bytes[0..4], b"RIFF");
+            assert_eq!(&bytes[8..12], b"WAVE");
+            assert_eq!(u32_at(&bytes, 4) as usize, bytes.len() - 8);
+
+            let (fmt, _) = chunk_body(&bytes, b"fmt ");
+            let bytes_per_sample = format.bytes_per_sample();
+            assert_eq!(u16_at(&bytes, fmt + 2), channels);
+            assert_eq!(u32_at(&bytes, fmt + 4), 44_100);
+            assert_eq!(u16_at(&bytes, fmt + 14), bytes_per_sample * 8);
+
+            let (data, data_len) = chunk_body(&bytes, b"data");
+            assert_eq!(data_len as usize, samples.len() * bytes_per_sample as usize);
+            assert_eq!(data + data_len as usize, bytes.len());
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_wav_rejects_partial_frames() {
+        let mut out = Vec::new();
+        let result = write_wav(&mut out, &[0.0; 3], 2, 48_000, WavFormat::Float32);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_read_wav_rejects_oversized_chunk() {
+        let mut bytes = Vec::new();
+        write_wav(&mut bytes, &[0.5; 4], 2, 48_000, WavFormat::Float32).unwrap();
+        let (data, _) = chunk_body(&bytes, b"data");
+        bytes[data - 4..data].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let err = read_wav(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
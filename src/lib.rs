@@ -34,6 +34,7 @@
 //! - **iOS/Swift**: Enable the `ios` feature and use the [`ffi`] module for C-compatible functions.
 //! - **WebAssembly**: Enable the `web` feature and use the [`wasm`] module for wasm-bindgen exports.
 
+mod arpeggiator;
 mod audio_buffer;
 mod bridge;
 mod clip_playback;
@@ -42,18 +43,24 @@ mod engine;
 mod event;
 mod execution_plan;
 mod graph;
+mod io;
+mod metronome;
 mod modulation;
 mod node;
 mod node_factory;
 mod nodes;
 mod parameter;
 mod plan_handoff;
+mod render;
 mod scheduler;
 mod state;
 mod transport;
 mod voice;
 mod voice_allocator;
 
+/// Note, level and time conversions shared by nodes and bindings.
+pub mod util;
+
 /// C-compatible FFI bindings for iOS/Swift integration.
 /// Only available when the `ios` feature is enabled.
 #[cfg(feature = "ios")]
@@ -65,10 +72,17 @@ pub mod ffi;
 pub mod wasm;
 
 // Re-export key types for Rust consumers
+pub use arpeggiator::{ArpMode, ArpSettings};
 pub use bridge::{EngineHandle, SessionHandle, create_bridge};
 pub use clip_playback::ClipPlayback;
 pub use compile::compile;
 pub use engine::Engine;
+pub use io::midi::{export_midi, import_midi};
+pub use io::wav::{WavFormat, export_wav, export_wav_as, import_wav};
 pub use node_factory::NodeRegistry;
 pub use nodes::register_standard_nodes;
-pub use state::{GraphDef, NodeId, NodeTypeId, Session};
+pub use render::render_offline;
+pub use state::{
+    GraphDef, LaunchQuantization, NodeId, NodePreset, NodeTypeId, PresetError, Session,
+};
+pub use voice_allocator::{VoiceMode, VoiceStealMode};
@@ -1,3 +1,4 @@
+mod arpeggiator;
 mod audio_buffer;
 mod bridge;
 mod compile;
@@ -5,6 +6,8 @@ mod engine;
 mod event;
 mod execution_plan;
 mod graph;
+mod io;
+mod metronome;
 mod modulation;
 mod node;
 mod node_factory;
@@ -14,6 +17,7 @@ mod plan_handoff;
 mod scheduler;
 mod state;
 mod transport;
+mod util;
 mod voice;
 mod voice_allocator;
 
@@ -0,0 +1,100 @@
+//! Metronome click synthesis.
+//!
+//! The scheduler places a click event on every beat while the metronome is
+//! enabled; the engine triggers this generator and mixes it over the graph
+//! output, so the click reaches the master output without a graph node.
+
+/// Click length in seconds.
+const CLICK_SECONDS: f64 = 0.03;
+
+/// Pitch of the bar downbeat click.
+const ACCENT_FREQ: f64 = 1_500.0;
+
+/// Pitch of the other beats.
+const BEAT_FREQ: f64 = 1_000.0;
+
+/// Peak level of the bar downbeat click.
+const ACCENT_LEVEL: f32 = 0.5;
+
+/// Peak level of the other beats.
+const BEAT_LEVEL: f32 = 0.3;
+
+/// Short decaying sine blip, retriggered on every beat.
+#[derive(Debug, Clone)]
+pub struct Metronome {
+    sample_rate: f64,
+    phase: f64,
+    phase_inc: f64,
+    level: f32,
+    decay: f32,
+    remaining: usize,
+}
+
+impl Metronome {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            phase: 0.0,
+            phase_inc: 0.0,
+            level: 0.0,
+            decay: 1.0,
+            remaining: 0,
+        }
+    }
+
+    /// Sample rate used for clicks triggered from now on.
+    #[inline]
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Start a click; accented clicks mark the first beat of a bar.
+    pub fn trigger(&mut self, accent: bool) {
+        let (freq, level) = if accent {
+            (ACCENT_FREQ, ACCENT_LEVEL)
+        } else {
+            (BEAT_FREQ, BEAT_LEVEL)
+        };
+        let length = (CLICK_SECONDS * self.sample_rate).max(1.0);
+
+        self.phase = 0.0;
+        self.phase_inc = freq / self.sample_rate;
+        self.level = level;
+        // Falls 60 dB over the click
+        self.decay = 0.001f64.powf(1.0 / length) as f32;
+        self.remaining = length as usize;
+    }
+
+    /// Whether a click is still sounding.
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Add the click to `frames` samples of every channel of a planar block.
+    ///
+    /// Channel `ch` starts at `ch * stride + offset` in `output`.
+    pub fn mix_into(
+        &mut self,
+        output: &mut [f32],
+        channels: usize,
+        stride: usize,
+        offset: usize,
+        frames: usize,
+    ) {
+        for i in 0..frames.min(self.remaining) {
+            let sample = (self.phase * std::f64::consts::TAU).sin() as f32 * self.level;
+            for ch in 0..channels {
+                output[ch * stride + offset + i] += sample;
+            }
+            self.phase = (self.phase + self.phase_inc).fract();
+            self.level *= self.decay;
+        }
+        self.remaining = self.remaining.saturating_sub(frames);
+    }
+
+    /// Silence any click in progress.
+    pub fn reset(&mut self) {
+        self.remaining = 0;
+    }
+}
@@ -35,3 +35,110 @@ impl<'a> ModSignal<'a> {
         }
     }
 }
+
+/// How a modulation route's source moves its parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModScale {
+    /// `base + source * depth`
+    #[default]
+    Linear,
+    /// `base * 2^(source * depth)`, for sources in octaves such as a
+    /// filter envelope sweeping a cutoff
+    Octaves,
+}
+
+/// A compiled modulation route between runtime graph nodes.
+///
+/// Applied by the graph just before the destination node processes:
+/// the parameter is set to `value(source)`, where `source` is the
+/// first sample of the source's first output channel. Per-voice sources
+/// are summed across voices, except into per-voice destinations, where
+/// each voice reads its own source voice. Routes onto the same parameter
+/// add up around the shared base (see `ModSum`).
+#[derive(Debug, Clone, Copy)]
+pub struct ModRoute {
+    /// Source node (graph index)
+    pub source: usize,
+    /// Destination node (graph index)
+    pub dest: usize,
+    pub param_id: u32,
+    pub depth: f32,
+    pub scale: ModScale,
+    /// Unmodulated parameter value, kept in sync by `Graph::set_param`
+    pub base: f32,
+}
+
+/// The combined offset of every route onto one parameter.
+///
+/// Linear offsets add and octave offsets multiply, so the parameter is
+/// `base * 2^octaves + linear`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ModSum {
+    linear: f32,
+    octaves: f32,
+}
+
+impl ModSum {
+    /// Add `route`'s contribution for a source value.
+    #[inline]
+    pub fn add(&mut self, route: &ModRoute, source: f32) {
+        match route.scale {
+            ModScale::Linear => self.linear += source * route.depth,
+            ModScale::Octaves => self.octaves += source * route.depth,
+        }
+    }
+
+    #[inline]
+    pub fn value(&self, base: f32) -> f32 {
+        base * self.octaves.exp2() + self.linear
+    }
+}
+
+/// A compiled velocity-to-parameter scaling for a per-voice node.
+///
+/// When a voice triggers, that voice's instance gets
+/// `base * (1 - amount + amount * velocity)`.
+#[derive(Debug, Clone, Copy)]
+pub struct VelocityRoute {
+    /// Node (graph index)
+    pub node: usize,
+    pub param_id: u32,
+    pub amount: f32,
+    /// Unscaled parameter value, kept in sync by `Graph::set_param`
+    pub base: f32,
+}
+
+impl VelocityRoute {
+    #[inline]
+    pub fn value(&self, velocity: f32) -> f32 {
+        self.base * (1.0 - self.amount + self.amount * velocity)
+    }
+}
+
+/// A compiled one-pole smoother for a parameter with a smoothing time.
+///
+/// `Graph::set_param` only moves the target; once per block the graph
+/// steps `current` toward it and applies that to the node.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamSmoother {
+    /// Node (graph index)
+    pub node: usize,
+    pub param_id: u32,
+    /// Time constant in seconds
+    pub time: f32,
+    pub current: f32,
+    pub target: f32,
+}
+
+impl ParamSmoother {
+    /// Step `frames` toward the target and return the new value.
+    #[inline]
+    pub fn advance(&mut self, frames: usize, sample_rate: f64) -> f32 {
+        let coeff = 1.0 - (-(frames as f64) / (self.time as f64 * sample_rate)).exp() as f32;
+        self.current += (self.target - self.current) * coeff;
+        if (self.target - self.current).abs() <= f32::EPSILON * self.target.abs().max(1.0) {
+            self.current = self.target;
+        }
+        self.current
+    }
+}
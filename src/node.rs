@@ -1,5 +1,6 @@
-use crate::audio_buffer::AudioBuffer;
-use crate::state::AudioPoolId;
+use crate::audio_buffer::{InputBuffer, OutputBuffer};
+use crate::state::{AudioPoolId, NOTE_COUNT};
+use crate::util::convert::{DEFAULT_A4, note_to_freq};
 use crate::voice::VoiceContext;
 
 /// Node instancing strategy
@@ -12,6 +13,9 @@ pub enum Polyphony {
     PerVoice,
 }
 
+/// Standard concert pitch of A4 in Hz.
+pub const DEFAULT_TUNING: f64 = DEFAULT_A4 as f64;
+
 /// Context passed to nodes during processing.
 #[derive(Debug, Clone, Copy)]
 pub struct ProcessContext<'a> {
@@ -30,6 +34,13 @@ pub struct ProcessContext<'a> {
     /// Tempo in BPM
     pub bpm: f64,
 
+    /// Reference pitch of A4 in Hz, which note frequencies are tuned to
+    pub tuning: f64,
+
+    /// Frequency ratio of each MIDI note to A4 under a tuning table, or
+    /// `None` for equal temperament
+    pub note_ratios: Option<&'a [f32; NOTE_COUNT]>,
+
     /// Marker for lifetime
     _marker: std::marker::PhantomData<&'a ()>,
 }
@@ -41,6 +52,8 @@ impl<'a> ProcessContext<'a> {
             sample_rate,
             sample_pos,
             bpm,
+            tuning: DEFAULT_TUNING,
+            note_ratios: None,
             voice: None,
             _marker: std::marker::PhantomData,
         }
@@ -50,6 +63,26 @@ impl<'a> ProcessContext<'a> {
         self.voice = Some(voice);
         self
     }
+
+    pub fn with_tuning(mut self, tuning: f64) -> Self {
+        self.tuning = tuning;
+        self
+    }
+
+    pub fn with_note_ratios(mut self, note_ratios: Option<&'a [f32; NOTE_COUNT]>) -> Self {
+        self.note_ratios = note_ratios;
+        self
+    }
+
+    /// Frequency of a MIDI note in Hz, under the A4 reference and any
+    /// tuning table.
+    #[inline]
+    pub fn note_freq(&self, note: u8) -> f32 {
+        match self.note_ratios {
+            Some(ratios) => self.tuning as f32 * ratios[note as usize % NOTE_COUNT],
+            None => note_to_freq(note as f32, self.tuning as f32),
+        }
+    }
 }
 
 /// Core DSP node trait.
@@ -71,15 +104,15 @@ pub trait Node: Send {
     ///
     /// Arguments:
     /// - `ctx`: Processing context (frames, sample rate, voice info)
-    /// - `inputs`: Buffers from connected input nodes (may be empty for sources)
+    /// - `inputs`: Read-only views of each input port (may be empty for sources)
     /// - `output`: Buffer to write output to
     ///
     /// Returns `true` if the output is silent (optimization hint).
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool;
 
     /// Number of output channels.
@@ -96,6 +129,26 @@ pub trait Node: Send {
     /// Reset node state (called on transport stop/seek).
     fn reset(&mut self) {}
 
+    /// Called on every note-on.
+    ///
+    /// Per-voice nodes see triggers through `ProcessContext::voice`; this
+    /// lets global nodes (e.g. LFOs) restart on new notes as well.
+    fn retrigger(&mut self) {}
+
+    /// Current envelope level (0..1), for envelope nodes.
+    ///
+    /// Lets the voice allocator find the quietest voice when stealing.
+    fn envelope_level(&self) -> Option<f32> {
+        None
+    }
+
+    /// Delay the node adds to its input, in samples at the prepared rate.
+    ///
+    /// The graph delays parallel paths into the same node to match.
+    fn latency_samples(&self) -> usize {
+        0
+    }
+
     // ─────────────────────────────────────────────────────────────────
     // Audio playback (optional, for sampler/player nodes)
     // ─────────────────────────────────────────────────────────────────
@@ -109,6 +162,7 @@ pub trait Node: Send {
         _start_sample: u64,
         _duration_samples: u64,
         _gain: f32,
+        _pitch_shift: f32,
     ) {
         // Default: ignore
     }
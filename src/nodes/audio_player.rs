@@ -7,13 +7,16 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::audio_buffer::AudioBuffer;
+use crate::audio_buffer::{InputBuffer, OutputBuffer};
 use crate::node::{Node, Polyphony, ProcessContext};
 use crate::state::AudioPoolId;
 
 /// Maximum number of simultaneous audio playback voices.
 const MAX_AUDIO_VOICES: usize = 16;
 
+/// Length of the fade-out when a voice is stopped, so it doesn't click.
+const STOP_FADE_SECONDS: f64 = 0.005;
+
 /// Shared audio data that can be passed to the audio player.
 ///
 /// This is an Arc-wrapped slice of samples that can be safely
@@ -46,67 +49,110 @@ impl SharedAudioData {
 }
 
 /// A single audio playback voice.
+///
+/// Positions are in source frames. The read position advances by `step`
+/// source frames per output frame, covering both sample-rate conversion
+/// and varispeed pitch shift, and samples are linearly interpolated.
 #[derive(Debug, Clone)]
 struct AudioVoice {
     /// The audio data being played.
     data: SharedAudioData,
-    /// Current playback position (in frames).
-    position: usize,
-    /// Remaining frames to play.
-    remaining: usize,
+    /// Current fractional playback position (in source frames).
+    position: f64,
+    /// Source frame at which playback stops.
+    end: f64,
+    /// Source frames advanced per output frame.
+    step: f64,
     /// Gain level.
     gain: f32,
+    /// Fade-out gain, falling from 1 once the voice is stopped.
+    fade: f32,
+    /// Fade decrement per output frame (0 = not fading).
+    fade_step: f32,
     /// Whether this voice is active.
     active: bool,
 }
 
 impl AudioVoice {
-    fn new(data: SharedAudioData, start_frame: usize, duration_frames: usize, gain: f32) -> Self {
+    fn new(
+        data: SharedAudioData,
+        start_frame: usize,
+        duration_frames: usize,
+        gain: f32,
+        pitch_shift: f32,
+        sample_rate: f64,
+    ) -> Self {
+        let rate = if data.sample_rate > 0.0 && sample_rate > 0.0 {
+            data.sample_rate / sample_rate
+        } else {
+            1.0
+        };
+        let step = rate * 2f64.powf(pitch_shift as f64 / 12.0);
+        let end = (start_frame + duration_frames).min(data.frames) as f64;
         Self {
             data,
-            position: start_frame,
-            remaining: duration_frames,
+            position: start_frame as f64,
+            end,
+            step,
             gain,
+            fade: 1.0,
+            fade_step: 0.0,
             active: true,
         }
     }
 
-    /// Process one block of audio, writing to the output buffer.
+    /// Start fading out; the voice finishes once the fade reaches zero.
+    fn release(&mut self, sample_rate: f64) {
+        if self.fade_step == 0.0 {
+            self.fade_step = 1.0 / (STOP_FADE_SECONDS * sample_rate).max(1.0) as f32;
+        }
+    }
+
+    /// Process one block of audio, adding to the planar output buffer.
     /// Returns true if the voice finished.
     fn process(&mut self, output: &mut [f32], output_channels: usize) -> bool {
         if !self.active {
             return true;
         }
 
-        let frames_to_process = (output.len() / output_channels).min(self.remaining);
         let samples = &self.data.samples;
-        let src_channels = self.data.channels;
+        let src_channels = self.data.channels.max(1);
+        let last_frame = (self.end.ceil() as usize).saturating_sub(1);
+        let frames = output.len() / output_channels;
 
-        for frame in 0..frames_to_process {
-            let src_frame = self.position + frame;
-
-            // Check bounds
-            if src_frame >= self.data.frames {
+        for frame in 0..frames {
+            if self.position >= self.end {
                 self.active = false;
                 return true;
             }
 
+            let index = self.position as usize;
+            let next = (index + 1).min(last_frame);
+            let frac = (self.position - index as f64) as f32;
+
             // Read source samples
             for ch in 0..output_channels {
                 let src_ch = ch % src_channels; // Handle mono -> stereo
-                let src_idx = src_frame * src_channels + src_ch;
-                let dst_idx = frame * output_channels + ch;
+                let a = samples.get(index * src_channels + src_ch).copied();
+                let b = samples.get(next * src_channels + src_ch).copied();
+                let dst_idx = ch * frames + frame;
 
-                if src_idx < samples.len() && dst_idx < output.len() {
-                    output[dst_idx] += samples[src_idx] * self.gain;
+                if let (Some(a), Some(b)) = (a, b) {
+                    output[dst_idx] += (a + (b - a) * frac) * self.gain * self.fade;
                 }
             }
-        }
 
-        self.position += frames_to_process;
-        self.remaining -= frames_to_process;
+            self.position += self.step;
+            if self.fade_step > 0.0 {
+                self.fade -= self.fade_step;
+                if self.fade <= 0.0 {
+                    self.active = false;
+                    return true;
+                }
+            }
+        }
 
-        if self.remaining == 0 {
+        if self.position >= self.end {
             self.active = false;
             true
         } else {
@@ -174,15 +220,17 @@ impl AudioPlayerNode {
     /// Start playing an audio region.
     ///
     /// - `audio_id`: The audio pool entry to play
-    /// - `start_sample`: Offset into the source audio
-    /// - `duration_samples`: How long to play
+    /// - `start_sample`: Offset into the source audio, in source frames
+    /// - `duration_samples`: How long to play, in source frames
     /// - `gain`: Playback gain
+    /// - `pitch_shift`: Varispeed shift in semitones (changes speed too)
     pub fn start_audio(
         &mut self,
         audio_id: AudioPoolId,
         start_sample: u64,
         duration_samples: u64,
         gain: f32,
+        pitch_shift: f32,
     ) {
         let Some(data) = self.audio_data.get(&audio_id).cloned() else {
             return;
@@ -200,17 +248,20 @@ impl AudioPlayerNode {
                 start_sample as usize,
                 duration_samples as usize,
                 gain,
+                pitch_shift,
+                self.sample_rate,
             ));
         }
         // If no slots available, the audio is dropped (could log a warning)
     }
 
-    /// Stop playing a specific audio.
+    /// Stop playing a specific audio, fading it out over a few
+    /// milliseconds rather than cutting it.
     pub fn stop_audio(&mut self, audio_id: AudioPoolId) {
         for voice in &mut self.voices {
             if let Some(v) = voice {
                 if v.data.id == audio_id {
-                    v.active = false;
+                    v.release(self.sample_rate);
                 }
             }
         }
@@ -248,8 +299,8 @@ impl Node for AudioPlayerNode {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         let frames = ctx.frames;
         let out_samples = output.samples_mut();
@@ -311,8 +362,16 @@ impl Node for AudioPlayerNode {
         start_sample: u64,
         duration_samples: u64,
         gain: f32,
+        pitch_shift: f32,
     ) {
-        AudioPlayerNode::start_audio(self, audio_id, start_sample, duration_samples, gain);
+        AudioPlayerNode::start_audio(
+            self,
+            audio_id,
+            start_sample,
+            duration_samples,
+            gain,
+            pitch_shift,
+        );
     }
 
     fn stop_audio(&mut self, audio_id: AudioPoolId) {
@@ -369,14 +428,14 @@ mod tests {
         player.load_audio(audio);
 
         // Start playback
-        player.start_audio(1, 0, 48000, 1.0);
+        player.start_audio(1, 0, 48000, 1.0, 0.0);
         assert!(player.is_playing());
         assert_eq!(player.active_voice_count(), 1);
 
         // Process some audio
         let ctx = ProcessContext::new(512, 48000.0, 0, 120.0);
         let mut output_data = vec![0.0f32; 512 * 2];
-        let mut output = AudioBuffer::new(&mut output_data, 2);
+        let mut output = OutputBuffer::new(&mut output_data, 2);
 
         player.process(&ctx, &[], &mut output);
 
@@ -392,12 +451,86 @@ mod tests {
         let audio = make_test_audio();
         player.load_audio(audio);
 
-        player.start_audio(1, 0, 48000, 1.0);
+        player.start_audio(1, 0, 48000, 1.0, 0.0);
         assert!(player.is_playing());
 
         player.stop_audio(1);
 
+        // Fades out over the first block instead of cutting off
+        let ctx = ProcessContext::new(512, 48000.0, 0, 120.0);
+        let mut output_data = vec![0.0f32; 512 * 2];
+        player.process(&ctx, &[], &mut OutputBuffer::new(&mut output_data, 2));
+        let fade = (STOP_FADE_SECONDS * 48000.0) as usize;
+        let left = &output_data[..512];
+        assert!(left[..fade].iter().any(|&s| s != 0.0));
+        assert!(left[fade / 2..fade].iter().all(|&s| s.abs() <= 0.25 + 1e-3));
+        assert!(left[fade + 1..].iter().all(|&s| s == 0.0));
+
         // Voice is marked inactive but still in slot
         assert!(!player.is_playing());
     }
+
+    #[test]
+    fn test_audio_player_resamples_to_engine_rate() {
+        // One second of 441 Hz mono at 44.1 kHz: exactly 100 samples per cycle
+        let frames = 44_100;
+        let samples: Vec<f32> = (0..frames)
+            .map(|i| (i as f32 / 100.0 * 2.0 * std::f32::consts::PI).sin())
+            .collect();
+
+        let mut player = AudioPlayerNode::new(1);
+        player.prepare(48_000.0, 512);
+        player.load_audio(SharedAudioData {
+            id: 7,
+            sample_rate: 44_100.0,
+            channels: 1,
+            frames,
+            samples: Arc::new(samples),
+        });
+        player.start_audio(7, 0, frames as u64, 1.0, 0.0);
+
+        let mut rendered = Vec::new();
+        let mut block = vec![0.0f32; 512];
+        while player.is_playing() {
+            block.fill(0.0);
+            let ctx = ProcessContext::new(512, 48_000.0, 0, 120.0);
+            player.process(&ctx, &[], &mut OutputBuffer::new(&mut block, 1));
+            rendered.extend_from_slice(&block);
+        }
+
+        // One second at the engine rate
+        let duration = rendered.iter().rposition(|s| *s != 0.0).unwrap() + 1;
+        assert!((duration as i64 - 48_000).abs() <= 2, "duration = {duration}");
+
+        // Pitch is unchanged: 441 rising zero crossings per second
+        let crossings = rendered[..duration]
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        assert!((440..=441).contains(&crossings), "crossings = {crossings}");
+    }
+
+    #[test]
+    fn test_audio_player_pitch_shift_octave_up() {
+        let mut player = AudioPlayerNode::new(2);
+        player.prepare(48000.0, 512);
+        player.load_audio(make_test_audio());
+
+        // +12 semitones reads the source at double speed
+        player.start_audio(1, 0, 48000, 1.0, 12.0);
+        let ctx = ProcessContext::new(512, 48000.0, 0, 120.0);
+        let mut output_data = vec![0.0f32; 512 * 2];
+        player.process(&ctx, &[], &mut OutputBuffer::new(&mut output_data, 2));
+
+        let voice = player.voices[0].as_ref().unwrap();
+        assert_eq!(voice.position, 1024.0);
+
+        // The whole second of source is consumed in half a second
+        let mut blocks = 1;
+        while player.is_playing() {
+            player.process(&ctx, &[], &mut OutputBuffer::new(&mut output_data, 2));
+            blocks += 1;
+        }
+        assert_eq!(blocks, 24000usize.div_ceil(512));
+    }
 }
@@ -1,7 +1,10 @@
 // Audio effect nodes.
 
-use crate::audio_buffer::AudioBuffer;
+use std::collections::VecDeque;
+
+use crate::audio_buffer::{InputBuffer, OutputBuffer};
 use crate::node::{Node, ProcessContext};
+use crate::util::convert::db_to_linear;
 
 use super::params;
 
@@ -11,6 +14,30 @@ use super::params;
 
 const MAX_DELAY_SAMPLES: usize = 192_000 * 2; // 2 seconds at 192kHz
 
+/// Time for the mixer's auto gain to recover after a peak.
+const AUTO_GAIN_RELEASE_SECONDS: f32 = 0.3;
+
+/// Tempo-synced delay divisions, in beats (quarter notes).
+///
+/// Index order: 1/1, 1/2, 1/2., 1/2T, 1/4, 1/4., 1/4T, 1/8, 1/8., 1/8T,
+/// 1/16, 1/16., 1/16T, 1/32 (`.` = dotted, `T` = triplet).
+const SYNC_DIVISIONS: [f32; 14] = [
+    4.0,
+    2.0,
+    3.0,
+    4.0 / 3.0,
+    1.0,
+    1.5,
+    2.0 / 3.0,
+    0.5,
+    0.75,
+    1.0 / 3.0,
+    0.25,
+    0.375,
+    1.0 / 6.0,
+    0.125,
+];
+
 // ═══════════════════════════════════════════════════════════════════
 // Gain Node
 // ═══════════════════════════════════════════════════════════════════
@@ -29,7 +56,7 @@ impl GainNode {
     }
 
     fn update_linear(&mut self) {
-        self.gain_linear = 10.0_f32.powf(self.gain_db / 20.0);
+        self.gain_linear = db_to_linear(self.gain_db);
     }
 }
 
@@ -45,8 +72,8 @@ impl Node for GainNode {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         if self.gain_linear < 0.0001 {
             output.clear();
@@ -125,8 +152,8 @@ impl Node for PanNode {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         if inputs.is_empty() || output.channels < 2 {
             return true;
@@ -170,9 +197,18 @@ impl Node for PanNode {
 // ═══════════════════════════════════════════════════════════════════
 
 /// Sums multiple stereo inputs together with a master gain.
+///
+/// With auto gain on, a peak follower turns the sum down whenever it
+/// would pass full scale, so a busy mix never clips. Peaks are caught
+/// instantly and the gain recovers over `AUTO_GAIN_RELEASE_SECONDS`.
 pub struct MixerNode {
     gain_db: f32,
     gain_linear: f32,
+    auto_gain: bool,
+
+    // Peak follower for auto gain, and its per-sample release factor
+    peak: f32,
+    peak_release: f32,
 }
 
 impl MixerNode {
@@ -180,11 +216,18 @@ impl MixerNode {
         Self {
             gain_db: 0.0,
             gain_linear: 1.0,
+            auto_gain: false,
+            peak: 0.0,
+            peak_release: Self::release_factor(48_000.0),
         }
     }
 
+    fn release_factor(sample_rate: f64) -> f32 {
+        (-1.0 / (AUTO_GAIN_RELEASE_SECONDS * sample_rate as f32)).exp()
+    }
+
     fn update_linear(&mut self) {
-        self.gain_linear = 10.0_f32.powf(self.gain_db / 20.0);
+        self.gain_linear = db_to_linear(self.gain_db);
     }
 }
 
@@ -195,13 +238,15 @@ impl Default for MixerNode {
 }
 
 impl Node for MixerNode {
-    fn prepare(&mut self, _sample_rate: f64, _max_block: usize) {}
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.peak_release = Self::release_factor(sample_rate);
+    }
 
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         output.clear();
 
@@ -232,6 +277,21 @@ impl Node for MixerNode {
             }
         }
 
+        if self.auto_gain {
+            for i in 0..ctx.frames {
+                let level = (0..output.channels)
+                    .map(|ch| output.channel(ch)[i].abs())
+                    .fold(0.0, f32::max);
+                self.peak = level.max(self.peak * self.peak_release);
+                if self.peak > 1.0 {
+                    let gain = 1.0 / self.peak;
+                    for ch in 0..output.channels {
+                        output.channel_mut(ch)[i] *= gain;
+                    }
+                }
+            }
+        }
+
         false
     }
 
@@ -245,9 +305,14 @@ impl Node for MixerNode {
                 self.gain_db = value;
                 self.update_linear();
             }
+            params::AUTO_GAIN => self.auto_gain = value > 0.5,
             _ => {}
         }
     }
+
+    fn reset(&mut self) {
+        self.peak = 0.0;
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -255,14 +320,27 @@ impl Node for MixerNode {
 // ═══════════════════════════════════════════════════════════════════
 
 /// Simple stereo delay effect.
+///
+/// The delay time is either the raw `TIME` param in seconds or, with
+/// `SYNC_ON` enabled, a note division of the current tempo. Damping
+/// low-passes the feedback, so each repeat is darker than the last, as
+/// on tape and analog delays.
 pub struct DelayNode {
-    delay_time: f32, // In seconds
+    delay_time: f32, // Effective delay in seconds
+    time_param: f32, // Unsynced delay in seconds
+    sync_on: bool,
+    sync_div: usize, // Index into SYNC_DIVISIONS
+    ping_pong: bool, // Cross-feed echoes between channels
     feedback: f32,   // 0.0 - 1.0
+    damping: f32,    // Feedback high frequency damping (0.0 - 1.0)
     mix: f32,        // Dry/wet mix (0.0 = dry, 1.0 = wet)
 
     buffer_l: Vec<f32>,
     buffer_r: Vec<f32>,
     write_pos: usize,
+    // Low-pass filtered feedback for damping
+    damp_l: f32,
+    damp_r: f32,
     sample_rate: f64,
 }
 
@@ -270,15 +348,31 @@ impl DelayNode {
     pub fn new() -> Self {
         Self {
             delay_time: 0.25,
+            time_param: 0.25,
+            sync_on: false,
+            sync_div: 4,
+            ping_pong: false,
             feedback: 0.4,
+            damping: 0.0,
             mix: 0.5,
             buffer_l: vec![0.0; MAX_DELAY_SAMPLES],
             buffer_r: vec![0.0; MAX_DELAY_SAMPLES],
             write_pos: 0,
+            damp_l: 0.0,
+            damp_r: 0.0,
             sample_rate: 48000.0,
         }
     }
 
+    /// Recompute the effective delay time for this block.
+    fn update_delay_time(&mut self, bpm: f64) {
+        self.delay_time = if self.sync_on && bpm > 0.0 {
+            SYNC_DIVISIONS[self.sync_div] * 60.0 / bpm as f32
+        } else {
+            self.time_param
+        };
+    }
+
     fn delay_samples(&self) -> usize {
         let samples = (self.delay_time * self.sample_rate as f32) as usize;
         samples.min(MAX_DELAY_SAMPLES - 1)
@@ -299,10 +393,11 @@ impl Node for DelayNode {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         self.sample_rate = ctx.sample_rate;
+        self.update_delay_time(ctx.bpm);
 
         if inputs.is_empty() {
             output.clear();
@@ -313,39 +408,34 @@ impl Node for DelayNode {
         let delay_samples = self.delay_samples();
         let buf_len = self.buffer_l.len();
 
-        // Process left channel
-        let in_l = input.channel(0);
-        let out_l = output.channel_mut(0);
-
-        for i in 0..ctx.frames {
-            let dry = in_l.get(i).copied().unwrap_or(0.0);
-            let read_pos = (self.write_pos + buf_len - delay_samples) % buf_len;
-            let delayed = self.buffer_l[read_pos];
-
-            self.buffer_l[self.write_pos] = dry + delayed * self.feedback;
-            out_l[i] = dry * (1.0 - self.mix) + delayed * self.mix;
-
-            self.write_pos = (self.write_pos + 1) % buf_len;
-        }
-
-        // Reset write_pos for right channel
-        self.write_pos = (self.write_pos + buf_len - ctx.frames) % buf_len;
-
-        // Process right channel (or copy left if mono)
-        let in_r = if input.channels > 1 {
-            input.channel(1)
-        } else {
-            input.channel(0)
-        };
-        let out_r = output.channel_mut(1);
+        let (in_l, in_r) = input.stereo();
 
+        // Both channels share one read/write index and are processed in the
+        // same per-sample step, so they can never drift apart. In ping-pong
+        // mode each channel's input and feedback enter the opposite line,
+        // so echoes alternate L→R→L.
         for i in 0..ctx.frames {
-            let dry = in_r.get(i).copied().unwrap_or(0.0);
+            let dry_l = in_l.get(i).copied().unwrap_or(0.0);
+            let dry_r = in_r.get(i).copied().unwrap_or(0.0);
             let read_pos = (self.write_pos + buf_len - delay_samples) % buf_len;
-            let delayed = self.buffer_r[read_pos];
+            let delayed_l = self.buffer_l[read_pos];
+            let delayed_r = self.buffer_r[read_pos];
+
+            self.damp_l = delayed_l * (1.0 - self.damping) + self.damp_l * self.damping;
+            self.damp_r = delayed_r * (1.0 - self.damping) + self.damp_r * self.damping;
+
+            let feed_l = dry_l + self.damp_l * self.feedback;
+            let feed_r = dry_r + self.damp_r * self.feedback;
+            if self.ping_pong {
+                self.buffer_l[self.write_pos] = feed_r;
+                self.buffer_r[self.write_pos] = feed_l;
+            } else {
+                self.buffer_l[self.write_pos] = feed_l;
+                self.buffer_r[self.write_pos] = feed_r;
+            }
 
-            self.buffer_r[self.write_pos] = dry + delayed * self.feedback;
-            out_r[i] = dry * (1.0 - self.mix) + delayed * self.mix;
+            output.channel_mut(0)[i] = dry_l * (1.0 - self.mix) + delayed_l * self.mix;
+            output.channel_mut(1)[i] = dry_r * (1.0 - self.mix) + delayed_r * self.mix;
 
             self.write_pos = (self.write_pos + 1) % buf_len;
         }
@@ -359,9 +449,13 @@ impl Node for DelayNode {
 
     fn set_param(&mut self, param_id: u32, value: f32) {
         match param_id {
-            0 => self.delay_time = value.clamp(0.001, 2.0), // Time in seconds
+            0 => self.time_param = value.clamp(0.001, 2.0), // Time in seconds
             1 => self.feedback = value.clamp(0.0, 0.99),    // Feedback
             2 => self.mix = value.clamp(0.0, 1.0),          // Mix
+            3 => self.sync_on = value > 0.5,                // Tempo sync
+            4 => self.sync_div = (value.max(0.0) as usize).min(SYNC_DIVISIONS.len() - 1),
+            5 => self.ping_pong = value > 0.5,              // Ping-pong
+            6 => self.damping = value.clamp(0.0, 1.0),      // Damping
             _ => {}
         }
     }
@@ -370,6 +464,8 @@ impl Node for DelayNode {
         self.buffer_l.fill(0.0);
         self.buffer_r.fill(0.0);
         self.write_pos = 0;
+        self.damp_l = 0.0;
+        self.damp_r = 0.0;
     }
 }
 
@@ -379,20 +475,40 @@ impl Node for DelayNode {
 
 /// Simple algorithmic reverb using a Schroeder topology.
 ///
-/// Uses 4 parallel comb filters and 2 series allpass filters.
+/// Each channel has its own bank of 4 parallel comb filters and 2 series
+/// allpass filters. The right bank's delays are offset by
+/// `REVERB_STEREO_SPREAD` so the tails decorrelate, and the width control
+/// crossfades between the decorrelated and a mono-summed wet signal.
+///
+/// Freeze sets comb feedback to unity with no damping and mutes the input,
+/// so the current tail sustains indefinitely. Entering and leaving freeze
+/// is ramped over `FREEZE_RAMP_SECONDS` to avoid clicks.
 pub struct ReverbNode {
-    decay: f32,   // Decay time (0.0 - 1.0)
-    damping: f32, // High frequency damping (0.0 - 1.0)
-    mix: f32,     // Dry/wet mix
-
-    // Comb filter buffers (4 parallel)
-    comb_buffers: [Vec<f32>; 4],
-    comb_pos: [usize; 4],
-    comb_filter: [f32; 4], // Low-pass filtered feedback
-
-    // Allpass filter buffers (2 series)
-    allpass_buffers: [Vec<f32>; 2],
-    allpass_pos: [usize; 2],
+    decay: f32,     // Decay time (0.0 - 1.0)
+    damping: f32,   // High frequency damping (0.0 - 1.0)
+    mix: f32,       // Dry/wet mix
+    pre_delay: f32, // Pre-delay in milliseconds
+    width: f32,     // Stereo width (0.0 = mono, 1.0 = full)
+    freeze: bool,
+
+    // Freeze crossfade (0.0 = normal, 1.0 = frozen) and the
+    // decay/damping it currently implies
+    freeze_amount: f32,
+    effective_decay: f32,
+    effective_damping: f32,
+
+    // Pre-delay line (mono, before the comb banks)
+    pre_delay_buffer: Vec<f32>,
+    pre_delay_pos: usize,
+
+    // Per-channel comb filter buffers (4 parallel)
+    comb_buffers: [[Vec<f32>; 4]; 2],
+    comb_pos: [[usize; 4]; 2],
+    comb_filter: [[f32; 4]; 2], // Low-pass filtered feedback
+
+    // Per-channel allpass filter buffers (2 series)
+    allpass_buffers: [[Vec<f32>; 2]; 2],
+    allpass_pos: [[usize; 2]; 2],
 
     sample_rate: f64,
 }
@@ -401,72 +517,134 @@ pub struct ReverbNode {
 const COMB_DELAYS: [usize; 4] = [1557, 1617, 1491, 1422];
 const ALLPASS_DELAYS: [usize; 2] = [225, 556];
 
+/// Extra delay (in samples at 48kHz) applied to the right channel's
+/// combs and allpasses to decorrelate the stereo tails.
+const REVERB_STEREO_SPREAD: usize = 23;
+
+/// Time to crossfade into or out of freeze.
+const FREEZE_RAMP_SECONDS: f32 = 0.05;
+
+/// Maximum pre-delay in milliseconds.
+const MAX_PRE_DELAY_MS: f32 = 200.0;
+
+/// Pre-delay buffer length: 200ms at 192kHz.
+const PRE_DELAY_BUFFER: usize = 38_400;
+
 impl ReverbNode {
     pub fn new() -> Self {
         Self {
             decay: 0.5,
             damping: 0.5,
             mix: 0.3,
-            comb_buffers: [
-                vec![0.0; 4096],
-                vec![0.0; 4096],
-                vec![0.0; 4096],
-                vec![0.0; 4096],
-            ],
-            comb_pos: [0; 4],
-            comb_filter: [0.0; 4],
-            allpass_buffers: [vec![0.0; 1024], vec![0.0; 1024]],
-            allpass_pos: [0; 2],
+            pre_delay: 0.0,
+            width: 1.0,
+            freeze: false,
+            freeze_amount: 0.0,
+            effective_decay: 0.5,
+            effective_damping: 0.5,
+            pre_delay_buffer: vec![0.0; PRE_DELAY_BUFFER],
+            pre_delay_pos: 0,
+            comb_buffers: std::array::from_fn(|_| std::array::from_fn(|_| vec![0.0; 4096])),
+            comb_pos: [[0; 4]; 2],
+            comb_filter: [[0.0; 4]; 2],
+            allpass_buffers: std::array::from_fn(|_| std::array::from_fn(|_| vec![0.0; 1024])),
+            allpass_pos: [[0; 2]; 2],
             sample_rate: 48000.0,
         }
     }
 
-    fn comb_delay(&self, index: usize) -> usize {
-        let base = COMB_DELAYS[index];
+    /// Scale a 48kHz delay length to the current sample rate, adding the
+    /// stereo spread for the right channel.
+    fn scaled_delay(&self, base: usize, ch: usize, buf_len: usize) -> usize {
+        let base = base + ch * REVERB_STEREO_SPREAD;
         let scaled = (base as f64 * self.sample_rate / 48000.0) as usize;
-        scaled.min(self.comb_buffers[index].len() - 1)
+        scaled.min(buf_len - 1)
     }
 
-    fn allpass_delay(&self, index: usize) -> usize {
-        let base = ALLPASS_DELAYS[index];
-        let scaled = (base as f64 * self.sample_rate / 48000.0) as usize;
-        scaled.min(self.allpass_buffers[index].len() - 1)
+    /// Move the freeze crossfade one sample toward its target and update
+    /// the effective comb parameters. Returns the input gain.
+    #[inline]
+    fn advance_freeze(&mut self, step: f32) -> f32 {
+        let target = if self.freeze { 1.0 } else { 0.0 };
+        if self.freeze_amount != target {
+            self.freeze_amount = if self.freeze_amount < target {
+                (self.freeze_amount + step).min(target)
+            } else {
+                (self.freeze_amount - step).max(target)
+            };
+        }
+
+        let f = self.freeze_amount;
+        self.effective_decay = self.decay + (1.0 - self.decay) * f;
+        self.effective_damping = self.damping * (1.0 - f);
+        1.0 - f
+    }
+
+    fn pre_delay_samples(&self) -> usize {
+        let samples = (self.pre_delay / 1000.0 * self.sample_rate as f32) as usize;
+        samples.min(PRE_DELAY_BUFFER - 1)
+    }
+
+    #[inline]
+    fn process_pre_delay(&mut self, input: f32, delay: usize) -> f32 {
+        self.pre_delay_buffer[self.pre_delay_pos] = input;
+        let read_pos = (self.pre_delay_pos + PRE_DELAY_BUFFER - delay) % PRE_DELAY_BUFFER;
+        self.pre_delay_pos = (self.pre_delay_pos + 1) % PRE_DELAY_BUFFER;
+        self.pre_delay_buffer[read_pos]
     }
 
     #[inline]
-    fn process_comb(&mut self, index: usize, input: f32) -> f32 {
-        let delay = self.comb_delay(index);
-        let buf_len = self.comb_buffers[index].len();
-        let read_pos = (self.comb_pos[index] + buf_len - delay) % buf_len;
+    fn process_comb(&mut self, ch: usize, index: usize, input: f32) -> f32 {
+        let buf_len = self.comb_buffers[ch][index].len();
+        let delay = self.scaled_delay(COMB_DELAYS[index], ch, buf_len);
+        let pos = self.comb_pos[ch][index];
+        let read_pos = (pos + buf_len - delay) % buf_len;
 
-        let delayed = self.comb_buffers[index][read_pos];
+        let delayed = self.comb_buffers[ch][index][read_pos];
 
         // Low-pass filtered feedback for damping
-        self.comb_filter[index] =
-            delayed * (1.0 - self.damping) + self.comb_filter[index] * self.damping;
+        let damping = self.effective_damping;
+        self.comb_filter[ch][index] =
+            delayed * (1.0 - damping) + self.comb_filter[ch][index] * damping;
 
-        let feedback = self.comb_filter[index] * self.decay;
-        self.comb_buffers[index][self.comb_pos[index]] = input + feedback;
-        self.comb_pos[index] = (self.comb_pos[index] + 1) % buf_len;
+        let feedback = self.comb_filter[ch][index] * self.effective_decay;
+        self.comb_buffers[ch][index][pos] = input + feedback;
+        self.comb_pos[ch][index] = (pos + 1) % buf_len;
 
         delayed
     }
 
     #[inline]
-    fn process_allpass(&mut self, index: usize, input: f32) -> f32 {
-        let delay = self.allpass_delay(index);
-        let buf_len = self.allpass_buffers[index].len();
-        let read_pos = (self.allpass_pos[index] + buf_len - delay) % buf_len;
+    fn process_allpass(&mut self, ch: usize, index: usize, input: f32) -> f32 {
+        let buf_len = self.allpass_buffers[ch][index].len();
+        let delay = self.scaled_delay(ALLPASS_DELAYS[index], ch, buf_len);
+        let pos = self.allpass_pos[ch][index];
+        let read_pos = (pos + buf_len - delay) % buf_len;
 
-        let delayed = self.allpass_buffers[index][read_pos];
+        let delayed = self.allpass_buffers[ch][index][read_pos];
         let g = 0.5_f32; // Allpass coefficient
 
         let output = -g * input + delayed;
-        self.allpass_buffers[index][self.allpass_pos[index]] = input + g * delayed;
-        self.allpass_pos[index] = (self.allpass_pos[index] + 1) % buf_len;
+        self.allpass_buffers[ch][index][pos] = input + g * delayed;
+        self.allpass_pos[ch][index] = (pos + 1) % buf_len;
 
         output
     }
+
+    /// Run one channel's comb bank and allpass chain.
+    #[inline]
+    fn process_tank(&mut self, ch: usize, input: f32) -> f32 {
+        // Parallel comb filters
+        let mut wet = 0.0_f32;
+        for c in 0..4 {
+            wet += self.process_comb(ch, c, input);
+        }
+        wet *= 0.25;
+
+        // Series allpass filters
+        wet = self.process_allpass(ch, 0, wet);
+        self.process_allpass(ch, 1, wet)
+    }
 }
 
 impl Default for ReverbNode {
@@ -483,77 +661,355 @@ impl Node for ReverbNode {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         self.sample_rate = ctx.sample_rate;
 
-        if inputs.is_empty() {
+        if inputs.is_empty() || output.channels < 2 {
             output.clear();
             return false;
         }
 
         let input = inputs[0];
-        let in_l = input.channel(0);
-        let in_r = if input.channels > 1 {
-            input.channel(1)
-        } else {
-            input.channel(0)
-        };
+        let (in_l, in_r) = input.stereo();
 
-        let out_l = output.channel_mut(0);
+        let pre_delay = self.pre_delay_samples();
+        let freeze_step = 1.0 / (FREEZE_RAMP_SECONDS * self.sample_rate as f32);
+
+        // Width matrix: full width keeps the banks separate, zero width
+        // feeds both outputs the same mono wet signal.
+        let wet_direct = 0.5 + self.width * 0.5;
+        let wet_cross = 0.5 - self.width * 0.5;
 
         for i in 0..ctx.frames {
             let dry_l = in_l.get(i).copied().unwrap_or(0.0);
             let dry_r = in_r.get(i).copied().unwrap_or(0.0);
-            let mono = (dry_l + dry_r) * 0.5;
+            let input_gain = self.advance_freeze(freeze_step);
+            let mono = self.process_pre_delay((dry_l + dry_r) * 0.5 * input_gain, pre_delay);
+
+            let tank_l = self.process_tank(0, mono);
+            let tank_r = self.process_tank(1, mono);
+
+            let wet_l = tank_l * wet_direct + tank_r * wet_cross;
+            let wet_r = tank_r * wet_direct + tank_l * wet_cross;
+
+            output.channel_mut(0)[i] = dry_l * (1.0 - self.mix) + wet_l * self.mix;
+            output.channel_mut(1)[i] = dry_r * (1.0 - self.mix) + wet_r * self.mix;
+        }
+
+        true
+    }
+
+    fn num_channels(&self) -> usize {
+        2
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            0 => self.decay = value.clamp(0.0, 0.99),                 // Decay
+            1 => self.damping = value.clamp(0.0, 1.0),                // Damping
+            2 => self.mix = value.clamp(0.0, 1.0),                    // Mix
+            3 => self.pre_delay = value.clamp(0.0, MAX_PRE_DELAY_MS), // Pre-delay (ms)
+            4 => self.width = value.clamp(0.0, 1.0),                  // Width
+            5 => self.freeze = value > 0.5,                           // Freeze
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pre_delay_buffer.fill(0.0);
+        self.pre_delay_pos = 0;
+        for bank in &mut self.comb_buffers {
+            for buf in bank {
+                buf.fill(0.0);
+            }
+        }
+        for bank in &mut self.allpass_buffers {
+            for buf in bank {
+                buf.fill(0.0);
+            }
+        }
+        self.comb_pos = [[0; 4]; 2];
+        self.allpass_pos = [[0; 2]; 2];
+        self.comb_filter = [[0.0; 4]; 2];
+        self.freeze_amount = if self.freeze { 1.0 } else { 0.0 };
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Waveshaper Node (distortion)
+// ═══════════════════════════════════════════════════════════════════
+
+/// Transfer curve used by the waveshaper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaveshapeCurve {
+    /// Smooth saturation
+    Tanh,
+    /// Hard clipping at ±1
+    HardClip,
+    /// Cubic soft-clip (`1.5x - 0.5x³`), knee at ±1
+    SoftClip,
+}
 
-            // Parallel comb filters
-            let mut wet = 0.0_f32;
-            for c in 0..4 {
-                wet += self.process_comb(c, mono);
+impl WaveshapeCurve {
+    #[inline]
+    fn apply(self, x: f32) -> f32 {
+        match self {
+            WaveshapeCurve::Tanh => x.tanh(),
+            WaveshapeCurve::HardClip => x.clamp(-1.0, 1.0),
+            WaveshapeCurve::SoftClip => {
+                let x = x.clamp(-1.0, 1.0);
+                1.5 * x - 0.5 * x * x * x
             }
-            wet *= 0.25;
+        }
+    }
+}
+
+/// Stereo drive/saturation stage with parallel dry/wet mix.
+pub struct WaveshaperNode {
+    drive_db: f32,
+    drive_linear: f32,
+    curve: WaveshapeCurve,
+    mix: f32, // Dry/wet mix (0.0 = dry, 1.0 = wet)
+}
+
+impl WaveshaperNode {
+    pub fn new() -> Self {
+        let mut node = Self {
+            drive_db: 12.0,
+            drive_linear: 1.0,
+            curve: WaveshapeCurve::Tanh,
+            mix: 1.0,
+        };
+        node.update_linear();
+        node
+    }
+
+    fn update_linear(&mut self) {
+        self.drive_linear = db_to_linear(self.drive_db);
+    }
+}
+
+impl Default for WaveshaperNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-            // Series allpass filters
-            wet = self.process_allpass(0, wet);
-            wet = self.process_allpass(1, wet);
+impl Node for WaveshaperNode {
+    fn prepare(&mut self, _sample_rate: f64, _max_block: usize) {}
 
-            out_l[i] = dry_l * (1.0 - self.mix) + wet * self.mix;
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        if inputs.is_empty() {
+            output.clear();
+            return false;
         }
 
-        // Process right channel (same reverb, different dry)
-        // Reset positions for right channel
-        for c in 0..4 {
-            self.comb_pos[c] = (self.comb_pos[c] + self.comb_buffers[c].len() - ctx.frames)
-                % self.comb_buffers[c].len();
+        let input = inputs[0];
+        for ch in 0..output.channels {
+            // For mono-to-stereo: use channel 0 for all output channels if input has fewer
+            let in_ch = input.channel(ch.min(input.channels.saturating_sub(1)));
+            let out_ch = output.channel_mut(ch);
+            for (i, out) in out_ch.iter_mut().take(ctx.frames).enumerate() {
+                let dry = in_ch.get(i).copied().unwrap_or(0.0);
+                let wet = self.curve.apply(dry * self.drive_linear);
+                *out = dry * (1.0 - self.mix) + wet * self.mix;
+            }
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        2
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            params::DRIVE => {
+                self.drive_db = value;
+                self.update_linear();
+            }
+            params::SHAPE => {
+                // Shape (0=tanh, 1=hard clip, 2=cubic soft clip)
+                self.curve = match value as u32 {
+                    0 => WaveshapeCurve::Tanh,
+                    1 => WaveshapeCurve::HardClip,
+                    _ => WaveshapeCurve::SoftClip,
+                };
+            }
+            params::MIX => self.mix = value.clamp(0.0, 1.0),
+            _ => {}
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Limiter Node (lookahead brickwall)
+// ═══════════════════════════════════════════════════════════════════
+
+/// Lookahead time in seconds. Fixed so the added latency is constant.
+const LIMITER_LOOKAHEAD: f64 = 0.005;
+
+/// Stereo lookahead brickwall limiter.
+///
+/// The gain applied to each output sample is the minimum gain required by
+/// any sample in the lookahead window, so peaks are caught before they
+/// leave the delay line. Gain recovers exponentially after the window
+/// clears. The signal is delayed by a constant `LIMITER_LOOKAHEAD`.
+pub struct LimiterNode {
+    ceiling_db: f32,
+    ceiling_linear: f32,
+    release: f32, // Release time in seconds
+    release_coeff: f32,
+
+    // Lookahead delay lines
+    delay_l: Vec<f32>,
+    delay_r: Vec<f32>,
+    delay_pos: usize,
+
+    // Sliding-window minimum of required gain: (sample index, gain)
+    gain_window: VecDeque<(u64, f32)>,
+    sample_index: u64,
+    gain: f32,
+
+    sample_rate: f64,
+}
+
+impl LimiterNode {
+    pub fn new() -> Self {
+        let mut node = Self {
+            ceiling_db: -0.3,
+            ceiling_linear: 1.0,
+            release: 0.1,
+            release_coeff: 0.0,
+            delay_l: Vec::new(),
+            delay_r: Vec::new(),
+            delay_pos: 0,
+            gain_window: VecDeque::new(),
+            sample_index: 0,
+            gain: 1.0,
+            sample_rate: 48000.0,
+        };
+        node.update_ceiling();
+        node.allocate(48000.0);
+        node
+    }
+
+    /// Lookahead latency in samples at the prepared sample rate.
+    pub fn lookahead_samples(&self) -> usize {
+        self.delay_l.len()
+    }
+
+    fn update_ceiling(&mut self) {
+        self.ceiling_linear = db_to_linear(self.ceiling_db);
+    }
+
+    fn update_release(&mut self) {
+        self.release_coeff = (-1.0 / (self.release * self.sample_rate as f32)).exp();
+    }
+
+    fn allocate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        let lookahead = ((LIMITER_LOOKAHEAD * sample_rate) as usize).max(1);
+        self.delay_l = vec![0.0; lookahead];
+        self.delay_r = vec![0.0; lookahead];
+        self.gain_window = VecDeque::with_capacity(lookahead + 1);
+        self.update_release();
+        self.reset();
+    }
+
+    /// Push the gain required by the newest input sample and return the
+    /// minimum over the lookahead window.
+    #[inline]
+    fn window_min(&mut self, required: f32) -> f32 {
+        let window = self.delay_l.len() as u64;
+        while self
+            .gain_window
+            .back()
+            .is_some_and(|&(_, g)| g >= required)
+        {
+            self.gain_window.pop_back();
+        }
+        self.gain_window.push_back((self.sample_index, required));
+        while self
+            .gain_window
+            .front()
+            .is_some_and(|&(i, _)| i + window < self.sample_index)
+        {
+            self.gain_window.pop_front();
+        }
+        self.gain_window.front().map_or(1.0, |&(_, g)| g)
+    }
+}
+
+impl Default for LimiterNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for LimiterNode {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        if (self.sample_rate - sample_rate).abs() > 0.1 {
+            self.allocate(sample_rate);
         }
-        for a in 0..2 {
-            self.allpass_pos[a] = (self.allpass_pos[a] + self.allpass_buffers[a].len()
-                - ctx.frames)
-                % self.allpass_buffers[a].len();
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        if inputs.is_empty() || output.channels < 2 {
+            output.clear();
+            return false;
         }
 
-        let out_r = output.channel_mut(1);
+        let input = inputs[0];
+        let (in_l, in_r) = input.stereo();
+
+        let lookahead = self.delay_l.len();
+        let ceiling = self.ceiling_linear;
 
         for i in 0..ctx.frames {
             let dry_l = in_l.get(i).copied().unwrap_or(0.0);
             let dry_r = in_r.get(i).copied().unwrap_or(0.0);
-            let mono = (dry_l + dry_r) * 0.5;
-
-            let mut wet = 0.0_f32;
-            for c in 0..4 {
-                wet += self.process_comb(c, mono);
-            }
-            wet *= 0.25;
 
-            wet = self.process_allpass(0, wet);
-            wet = self.process_allpass(1, wet);
-
-            out_r[i] = dry_r * (1.0 - self.mix) + wet * self.mix;
+            let peak = dry_l.abs().max(dry_r.abs());
+            let required = if peak > ceiling { ceiling / peak } else { 1.0 };
+            let target = self.window_min(required);
+
+            // Instant attack (already ahead of the peak), exponential release
+            self.gain = if target < self.gain {
+                target
+            } else {
+                target + (self.gain - target) * self.release_coeff
+            };
+
+            let delayed_l = self.delay_l[self.delay_pos];
+            let delayed_r = self.delay_r[self.delay_pos];
+            self.delay_l[self.delay_pos] = dry_l;
+            self.delay_r[self.delay_pos] = dry_r;
+            self.delay_pos = (self.delay_pos + 1) % lookahead;
+            self.sample_index += 1;
+
+            output.channel_mut(0)[i] = (delayed_l * self.gain).clamp(-ceiling, ceiling);
+            output.channel_mut(1)[i] = (delayed_r * self.gain).clamp(-ceiling, ceiling);
         }
 
-        true
+        false
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.lookahead_samples()
     }
 
     fn num_channels(&self) -> usize {
@@ -562,22 +1018,1098 @@ impl Node for ReverbNode {
 
     fn set_param(&mut self, param_id: u32, value: f32) {
         match param_id {
-            0 => self.decay = value.clamp(0.0, 0.99),  // Decay
-            1 => self.damping = value.clamp(0.0, 1.0), // Damping
-            2 => self.mix = value.clamp(0.0, 1.0),     // Mix
+            params::CEILING => {
+                self.ceiling_db = value.min(0.0);
+                self.update_ceiling();
+            }
+            params::RELEASE => {
+                self.release = value.max(0.001);
+                self.update_release();
+            }
             _ => {}
         }
     }
 
     fn reset(&mut self) {
-        for buf in &mut self.comb_buffers {
-            buf.fill(0.0);
+        self.delay_l.fill(0.0);
+        self.delay_r.fill(0.0);
+        self.delay_pos = 0;
+        self.gain_window.clear();
+        self.sample_index = 0;
+        self.gain = 1.0;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Parametric EQ Node
+// ═══════════════════════════════════════════════════════════════════
+
+/// Response shape of a single EQ band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EqBandType {
+    LowShelf,
+    Peaking,
+    HighShelf,
+}
+
+/// One biquad band (RBJ cookbook coefficients, transposed direct form II).
+struct EqBand {
+    band_type: EqBandType,
+    freq: f32,
+    gain_db: f32,
+    q: f32,
+
+    // Normalized coefficients
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+
+    // Per-channel state (L, R)
+    z1: [f32; 2],
+    z2: [f32; 2],
+}
+
+impl EqBand {
+    fn new(band_type: EqBandType, freq: f32, q: f32) -> Self {
+        Self {
+            band_type,
+            freq,
+            gain_db: 0.0,
+            q,
+            b0: 1.0,
+            b1: 0.0,
+            b2: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+            z1: [0.0; 2],
+            z2: [0.0; 2],
+        }
+    }
+
+    fn recalc_coeffs(&mut self, sample_rate: f64) {
+        let freq = self.freq.clamp(20.0, (sample_rate as f32 * 0.49).max(20.0));
+        let a = 10.0_f32.powf(self.gain_db / 40.0);
+        let w0 = std::f32::consts::TAU * freq / sample_rate as f32;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha = sin_w0 / (2.0 * self.q.max(0.05));
+
+        let (b0, b1, b2, a0, a1, a2) = match self.band_type {
+            EqBandType::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cos_w0,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos_w0,
+                1.0 - alpha / a,
+            ),
+            EqBandType::LowShelf => {
+                let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 + sqrt_a_alpha),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 - sqrt_a_alpha),
+                    (a + 1.0) + (a - 1.0) * cos_w0 + sqrt_a_alpha,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    (a + 1.0) + (a - 1.0) * cos_w0 - sqrt_a_alpha,
+                )
+            }
+            EqBandType::HighShelf => {
+                let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 + sqrt_a_alpha),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 - sqrt_a_alpha),
+                    (a + 1.0) - (a - 1.0) * cos_w0 + sqrt_a_alpha,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    (a + 1.0) - (a - 1.0) * cos_w0 - sqrt_a_alpha,
+                )
+            }
+        };
+
+        self.b0 = b0 / a0;
+        self.b1 = b1 / a0;
+        self.b2 = b2 / a0;
+        self.a1 = a1 / a0;
+        self.a2 = a2 / a0;
+    }
+
+    #[inline]
+    fn process_sample(&mut self, ch: usize, input: f32) -> f32 {
+        let out = self.b0 * input + self.z1[ch];
+        self.z1[ch] = self.b1 * input - self.a1 * out + self.z2[ch];
+        self.z2[ch] = self.b2 * input - self.a2 * out;
+        out
+    }
+
+    fn reset(&mut self) {
+        self.z1 = [0.0; 2];
+        self.z2 = [0.0; 2];
+    }
+}
+
+/// Four-band stereo parametric EQ: low shelf, two peaking bands, high shelf.
+///
+/// Band coefficients are only recomputed when one of their params changes.
+pub struct ParametricEqNode {
+    bands: [EqBand; 4],
+    sample_rate: f64,
+}
+
+impl ParametricEqNode {
+    pub fn new() -> Self {
+        let mut node = Self {
+            bands: [
+                EqBand::new(EqBandType::LowShelf, 100.0, 0.707),
+                EqBand::new(EqBandType::Peaking, 500.0, 1.0),
+                EqBand::new(EqBandType::Peaking, 2000.0, 1.0),
+                EqBand::new(EqBandType::HighShelf, 8000.0, 0.707),
+            ],
+            sample_rate: 48000.0,
+        };
+        for band in &mut node.bands {
+            band.recalc_coeffs(node.sample_rate);
         }
-        for buf in &mut self.allpass_buffers {
-            buf.fill(0.0);
+        node
+    }
+}
+
+impl Default for ParametricEqNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for ParametricEqNode {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.sample_rate = sample_rate;
+        for band in &mut self.bands {
+            band.recalc_coeffs(sample_rate);
         }
-        self.comb_pos = [0; 4];
-        self.allpass_pos = [0; 2];
-        self.comb_filter = [0.0; 4];
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        if inputs.is_empty() {
+            output.clear();
+            return false;
+        }
+
+        let input = inputs[0];
+        for ch in 0..output.channels.min(2) {
+            // For mono-to-stereo: use channel 0 for all output channels if input has fewer
+            let in_ch = input.channel(ch.min(input.channels.saturating_sub(1)));
+            let out_ch = output.channel_mut(ch);
+            for (i, out) in out_ch.iter_mut().take(ctx.frames).enumerate() {
+                let mut sample = in_ch.get(i).copied().unwrap_or(0.0);
+                for band in &mut self.bands {
+                    sample = band.process_sample(ch, sample);
+                }
+                *out = sample;
+            }
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        2
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        let band_index = (param_id / params::EQ_BAND_STRIDE) as usize;
+        let Some(band) = self.bands.get_mut(band_index) else {
+            return;
+        };
+
+        match param_id % params::EQ_BAND_STRIDE {
+            params::EQ_FREQ => band.freq = value.clamp(20.0, 20000.0),
+            params::EQ_GAIN => band.gain_db = value.clamp(-24.0, 24.0),
+            params::EQ_Q => band.q = value.clamp(0.1, 18.0),
+            _ => return,
+        }
+        band.recalc_coeffs(self.sample_rate);
+    }
+
+    fn reset(&mut self) {
+        for band in &mut self.bands {
+            band.reset();
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Flanger Node
+// ═══════════════════════════════════════════════════════════════════
+
+/// Shortest flanger delay in milliseconds (the top of the sweep).
+const FLANGER_MIN_DELAY_MS: f32 = 0.5;
+
+/// Longest flanger delay in milliseconds (the bottom of the sweep).
+const FLANGER_MAX_DELAY_MS: f32 = 5.0;
+
+/// Flanger delay line length; covers the longest delay at 192kHz.
+const FLANGER_BUFFER: usize = 1_024;
+
+/// Stereo flanger: a short comb whose delay an internal sine LFO sweeps.
+///
+/// Depth sets how far the delay sweeps up from `FLANGER_MIN_DELAY_MS`,
+/// and width offsets the right channel's LFO by up to half a cycle so the
+/// notches move in opposite directions on each side.
+pub struct FlangerNode {
+    rate: f32,     // LFO rate in Hz
+    depth: f32,    // 0.0 - 1.0 of the delay range
+    feedback: f32, // -0.95 - 0.95
+    mix: f32,      // Dry/wet mix (0.0 = dry, 1.0 = wet)
+    width: f32,    // 0.0 - 1.0 of half an LFO cycle
+
+    phase: f64,
+    buffer_l: Vec<f32>,
+    buffer_r: Vec<f32>,
+    write_pos: usize,
+    sample_rate: f64,
+}
+
+impl FlangerNode {
+    pub fn new() -> Self {
+        Self {
+            rate: 0.25,
+            depth: 0.7,
+            feedback: 0.5,
+            mix: 0.5,
+            width: 0.5,
+            phase: 0.0,
+            buffer_l: vec![0.0; FLANGER_BUFFER],
+            buffer_r: vec![0.0; FLANGER_BUFFER],
+            write_pos: 0,
+            sample_rate: 48000.0,
+        }
+    }
+
+    /// Delay in samples for an LFO phase (in cycles).
+    #[inline]
+    fn delay_at(&self, phase: f64) -> f32 {
+        let sweep = 0.5 + 0.5 * (phase * std::f64::consts::TAU).sin() as f32;
+        let ms = FLANGER_MIN_DELAY_MS
+            + (FLANGER_MAX_DELAY_MS - FLANGER_MIN_DELAY_MS) * self.depth * sweep;
+        (ms * 0.001 * self.sample_rate as f32).clamp(1.0, (FLANGER_BUFFER - 2) as f32)
+    }
+
+    /// Linearly interpolated read `delay` samples behind the write position.
+    #[inline]
+    fn read(buffer: &[f32], write_pos: usize, delay: f32) -> f32 {
+        let pos = write_pos as f32 - delay + FLANGER_BUFFER as f32;
+        let index = pos as usize;
+        let frac = pos - index as f32;
+        let a = buffer[index % FLANGER_BUFFER];
+        let b = buffer[(index + 1) % FLANGER_BUFFER];
+        a + (b - a) * frac
+    }
+}
+
+impl Default for FlangerNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for FlangerNode {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.sample_rate = sample_rate;
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        if inputs.is_empty() {
+            output.clear();
+            return false;
+        }
+
+        let input = inputs[0];
+        let (in_l, in_r) = input.stereo();
+        let phase_inc = self.rate as f64 / self.sample_rate;
+        let offset = 0.5 * self.width as f64;
+
+        for i in 0..ctx.frames {
+            let dry_l = in_l.get(i).copied().unwrap_or(0.0);
+            let dry_r = in_r.get(i).copied().unwrap_or(0.0);
+
+            let delayed_l = Self::read(&self.buffer_l, self.write_pos, self.delay_at(self.phase));
+            let delayed_r = Self::read(
+                &self.buffer_r,
+                self.write_pos,
+                self.delay_at(self.phase + offset),
+            );
+
+            self.buffer_l[self.write_pos] = dry_l + delayed_l * self.feedback;
+            self.buffer_r[self.write_pos] = dry_r + delayed_r * self.feedback;
+            self.write_pos = (self.write_pos + 1) % FLANGER_BUFFER;
+            self.phase = (self.phase + phase_inc).fract();
+
+            output.channel_mut(0)[i] = dry_l * (1.0 - self.mix) + delayed_l * self.mix;
+            output.channel_mut(1)[i] = dry_r * (1.0 - self.mix) + delayed_r * self.mix;
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        2
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            params::RATE => self.rate = value.clamp(0.01, 10.0),
+            params::DEPTH => self.depth = value.clamp(0.0, 1.0),
+            params::MIX => self.mix = value.clamp(0.0, 1.0),
+            params::FLANGER_FEEDBACK => self.feedback = value.clamp(-0.95, 0.95),
+            params::WIDTH => self.width = value.clamp(0.0, 1.0),
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buffer_l.fill(0.0);
+        self.buffer_r.fill(0.0);
+        self.write_pos = 0;
+        self.phase = 0.0;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Stereo Width Node (mid/side)
+// ═══════════════════════════════════════════════════════════════════
+
+/// Widest setting of the stereo width node.
+const MAX_STEREO_WIDTH: f32 = 2.0;
+
+/// Mid/side stereo width control.
+///
+/// Splits the input into mid `(L + R) / 2` and side `(L - R) / 2`, scales
+/// the side by width (0 = mono, 1 = unchanged, 2 = twice as wide) and
+/// recombines. A mono input has no side signal and passes through to
+/// both channels.
+pub struct StereoWidthNode {
+    width: f32,
+}
+
+impl StereoWidthNode {
+    pub fn new() -> Self {
+        Self { width: 1.0 }
+    }
+}
+
+impl Default for StereoWidthNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for StereoWidthNode {
+    fn prepare(&mut self, _sample_rate: f64, _max_block: usize) {}
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        if inputs.is_empty() {
+            output.clear();
+            return false;
+        }
+
+        let input = inputs[0];
+        let (in_l, in_r) = input.stereo();
+
+        for i in 0..ctx.frames {
+            let l = in_l.get(i).copied().unwrap_or(0.0);
+            let r = in_r.get(i).copied().unwrap_or(0.0);
+            let mid = (l + r) * 0.5;
+            let side = (l - r) * 0.5 * self.width;
+
+            output.channel_mut(0)[i] = mid + side;
+            output.channel_mut(1)[i] = mid - side;
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        2
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        if param_id == params::WIDTH {
+            self.width = value.clamp(0.0, MAX_STEREO_WIDTH);
+        }
+    }
+
+    fn reset(&mut self) {}
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Ring Modulator Node
+// ═══════════════════════════════════════════════════════════════════
+
+/// Ring modulator / amplitude modulator.
+///
+/// Multiplies the input by a carrier: the second input port when one is
+/// connected, an internal sine at the frequency param otherwise. Ring mode
+/// uses the bipolar carrier, leaving only the sum and difference
+/// frequencies; AM mode shifts it to 0..1 so the input passes through at
+/// half level alongside the sidebands.
+pub struct RingModNode {
+    freq: f32,      // Internal carrier frequency in Hz
+    unipolar: bool, // AM mode: carrier shifted to 0..1
+    mix: f32,       // Dry/wet mix (0.0 = dry, 1.0 = wet)
+
+    phase: f64,
+    sample_rate: f64,
+}
+
+impl RingModNode {
+    pub fn new() -> Self {
+        Self {
+            freq: 100.0,
+            unipolar: false,
+            mix: 1.0,
+            phase: 0.0,
+            sample_rate: 48000.0,
+        }
+    }
+}
+
+impl Default for RingModNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for RingModNode {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.sample_rate = sample_rate;
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        if inputs.is_empty() {
+            output.clear();
+            return false;
+        }
+
+        let (in_l, in_r) = inputs[0].stereo();
+        let carrier_in = inputs.get(1).map(|c| c.channel(0));
+        let phase_inc = self.freq as f64 / self.sample_rate;
+
+        for i in 0..ctx.frames {
+            let mut carrier = match carrier_in {
+                Some(c) => c.get(i).copied().unwrap_or(0.0),
+                None => {
+                    let s = (self.phase * std::f64::consts::TAU).sin() as f32;
+                    self.phase = (self.phase + phase_inc).fract();
+                    s
+                }
+            };
+            if self.unipolar {
+                carrier = 0.5 * (1.0 + carrier);
+            }
+
+            let dry_l = in_l.get(i).copied().unwrap_or(0.0);
+            let dry_r = in_r.get(i).copied().unwrap_or(0.0);
+            output.channel_mut(0)[i] = dry_l * (1.0 - self.mix) + dry_l * carrier * self.mix;
+            output.channel_mut(1)[i] = dry_r * (1.0 - self.mix) + dry_r * carrier * self.mix;
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        2
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            params::FREQ => self.freq = value.max(0.0),
+            // Mode (0 = ring, 1 = AM)
+            params::RINGMOD_MODE => self.unipolar = value >= 0.5,
+            params::MIX => self.mix = value.clamp(0.0, 1.0),
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::convert::linear_to_db;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    /// Magnitude of a single DFT bin at `freq`.
+    fn dft_magnitude(samples: &[f32], freq: f64) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (i, &s) in samples.iter().enumerate() {
+            let angle = std::f64::consts::TAU * freq * i as f64 / SAMPLE_RATE;
+            re += s as f64 * angle.cos();
+            im -= s as f64 * angle.sin();
+        }
+        (re * re + im * im).sqrt() / samples.len() as f64
+    }
+
+    #[test]
+    fn test_gain_copies_mono_input_to_both_channels() {
+        let frames = 256;
+        let mut node = GainNode::new();
+        node.set_param(params::GAIN, -6.0);
+
+        let input: Vec<f32> = (0..frames).map(|i| (i as f32 * 0.1).sin()).collect();
+        let input_buf = InputBuffer::new(&input, 1);
+        let mut data = vec![0.0f32; frames * 2];
+        let mut output = OutputBuffer::new(&mut data, 2);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        node.process(&ctx, &[input_buf], &mut output);
+
+        let gain = db_to_linear(-6.0);
+        for (i, &s) in input.iter().enumerate() {
+            assert_eq!(output.channel(0)[i], s * gain);
+            assert_eq!(output.channel(1)[i], s * gain);
+        }
+    }
+
+    #[test]
+    fn test_mixer_auto_gain_keeps_sum_in_range() {
+        let frames = 4_800;
+        let input: Vec<f32> = (0..frames)
+            .map(|i| (std::f64::consts::TAU * 440.0 * i as f64 / SAMPLE_RATE).sin() as f32)
+            .collect();
+        let inputs = [InputBuffer::new(&input, 1); 4];
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+
+        let peak_with = |auto_gain: f32| {
+            let mut node = MixerNode::new();
+            node.prepare(SAMPLE_RATE, frames);
+            node.set_param(params::AUTO_GAIN, auto_gain);
+            let mut data = vec![0.0f32; frames * 2];
+            let mut output = OutputBuffer::new(&mut data, 2);
+            node.process(&ctx, &inputs, &mut output);
+            data.iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
+        };
+
+        // Four full-scale signals sum to four times full scale...
+        assert!((peak_with(0.0) - 4.0).abs() < 0.01);
+        // ...unless auto gain turns them back down to it
+        let peak = peak_with(1.0);
+        assert!(peak <= 1.0 && peak > 0.99, "peak {peak}");
+    }
+
+    #[test]
+    fn test_waveshaper_defaults_match_registry() {
+        use crate::node_factory::NodeRegistry;
+        use crate::nodes::{node_types, register_standard_nodes};
+
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+        let info = registry.get_info(node_types::DISTORTION).unwrap();
+        let drive = info.find_param(params::DRIVE).unwrap().default;
+
+        let fresh = WaveshaperNode::new();
+        assert_eq!(fresh.drive_db, drive);
+        assert_eq!(fresh.drive_linear, db_to_linear(drive));
+
+        // A fresh node sounds the same as one given every registry default
+        let mut configured = WaveshaperNode::new();
+        for param in &info.parameters {
+            configured.set_param(param.id, param.default);
+        }
+        let frames = 256;
+        let input: Vec<f32> = (0..frames).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let input_buf = InputBuffer::new(&input, 1);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        let render = |node: &mut WaveshaperNode| {
+            let mut data = vec![0.0f32; frames * 2];
+            let mut output = OutputBuffer::new(&mut data, 2);
+            node.process(&ctx, &[input_buf], &mut output);
+            data
+        };
+        assert_eq!(render(&mut WaveshaperNode::new()), render(&mut configured));
+    }
+
+    #[test]
+    fn test_waveshaper_tanh_adds_odd_harmonics() {
+        let frames = 4_800;
+        let freq = 1_000.0;
+        let mut node = WaveshaperNode::new();
+        node.set_param(params::DRIVE, 40.0);
+        node.set_param(params::SHAPE, 0.0);
+        node.set_param(params::MIX, 1.0);
+
+        let input: Vec<f32> = (0..frames)
+            .map(|i| 0.1 * (std::f64::consts::TAU * freq * i as f64 / SAMPLE_RATE).sin() as f32)
+            .collect();
+        let input_buf = InputBuffer::new(&input, 1);
+        let mut data = vec![0.0f32; frames * 2];
+        let mut output = OutputBuffer::new(&mut data, 2);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        node.process(&ctx, &[input_buf], &mut output);
+
+        let left = output.channel(0);
+        let h1 = dft_magnitude(left, freq);
+        let h2 = dft_magnitude(left, freq * 2.0);
+        let h3 = dft_magnitude(left, freq * 3.0);
+        let h5 = dft_magnitude(left, freq * 5.0);
+
+        assert!(h3 / h1 > 0.2, "3rd harmonic too weak: {:.3}", h3 / h1);
+        assert!(h5 / h1 > 0.1, "5th harmonic too weak: {:.3}", h5 / h1);
+        // Symmetric curve: even harmonics stay negligible
+        assert!(h2 / h1 < 0.01, "Unexpected 2nd harmonic: {:.3}", h2 / h1);
+    }
+
+    #[test]
+    fn test_limiter_never_exceeds_ceiling() {
+        let frames = 9_600;
+        let mut node = LimiterNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::CEILING, -0.3);
+        let ceiling = db_to_linear(-0.3);
+
+        // +6dB sine with a few isolated spikes on top
+        let gain = db_to_linear(6.0);
+        let mut input: Vec<f32> = (0..frames)
+            .map(|i| gain * (std::f64::consts::TAU * 440.0 * i as f64 / SAMPLE_RATE).sin() as f32)
+            .collect();
+        for i in (1_000..frames).step_by(1_777) {
+            input[i] = 4.0;
+        }
+
+        // Process in small blocks to exercise state across block boundaries
+        let mut rendered = Vec::with_capacity(frames * 2);
+        for (n, chunk) in input.chunks(128).enumerate() {
+            let input_buf = InputBuffer::new(chunk, 1);
+            let mut data = vec![0.0f32; input_buf.frames * 2];
+            let mut output = OutputBuffer::new(&mut data, 2);
+            let ctx = ProcessContext::new(input_buf.frames, SAMPLE_RATE, n as u64 * 128, 120.0);
+            node.process(&ctx, &[input_buf], &mut output);
+            rendered.extend_from_slice(&data);
+        }
+
+        let peak = rendered.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak <= ceiling, "Peak {} exceeds ceiling {}", peak, ceiling);
+        // Limiting, not muting
+        assert!(peak > ceiling * 0.9);
+    }
+
+    /// Steady-state gain in dB of `node` for a sine at `freq`.
+    fn sine_gain_db(node: &mut dyn Node, freq: f64) -> f32 {
+        let frames = 9_600;
+        node.reset();
+        let input: Vec<f32> = (0..frames)
+            .map(|i| (std::f64::consts::TAU * freq * i as f64 / SAMPLE_RATE).sin() as f32)
+            .collect();
+        let input_buf = InputBuffer::new(&input, 1);
+        let mut data = vec![0.0f32; frames * 2];
+        let mut output = OutputBuffer::new(&mut data, 2);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        node.process(&ctx, &[input_buf], &mut output);
+
+        let tail = &output.channel(0)[frames / 2..];
+        let peak = tail.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        linear_to_db(peak)
+    }
+
+    #[test]
+    fn test_parametric_eq_peaking_band() {
+        let mut node = ParametricEqNode::new();
+        node.prepare(SAMPLE_RATE, 512);
+        node.set_param(params::EQ_MID1_FREQ, 1_000.0);
+        node.set_param(params::EQ_MID1_GAIN, 12.0);
+        node.set_param(params::EQ_MID1_Q, 1.0);
+
+        let boosted = sine_gain_db(&mut node, 1_000.0);
+        let untouched = sine_gain_db(&mut node, 100.0);
+
+        assert!((boosted - 12.0).abs() < 0.5, "1kHz gain {:.2} dB", boosted);
+        assert!(untouched.abs() < 0.5, "100Hz gain {:.2} dB", untouched);
+    }
+
+    #[test]
+    fn test_delay_tempo_sync_quarter_note() {
+        let frames = 256;
+        let mut node = DelayNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::TIME, 0.1);
+        node.set_param(params::SYNC_ON, 1.0);
+        node.set_param(params::SYNC_DIV, 4.0); // 1/4
+
+        let input = vec![0.0f32; frames];
+        let input_buf = InputBuffer::new(&input, 1);
+        let mut data = vec![0.0f32; frames * 2];
+        let mut output = OutputBuffer::new(&mut data, 2);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        node.process(&ctx, &[input_buf], &mut output);
+
+        assert_eq!(node.delay_samples(), (0.5 * SAMPLE_RATE) as usize);
+
+        // Turning sync off falls back to the raw time param
+        node.set_param(params::SYNC_ON, 0.0);
+        node.process(&ctx, &[input_buf], &mut output);
+        assert_eq!(node.delay_samples(), (0.1 * SAMPLE_RATE) as usize);
+    }
+
+    #[test]
+    fn test_delay_ping_pong_first_echo_on_right() {
+        let frames = 4_096;
+        let mut node = DelayNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::TIME, 0.01);
+        node.set_param(params::FEEDBACK, 0.5);
+        node.set_param(params::MIX, 1.0);
+        node.set_param(params::PINGPONG, 1.0);
+        let delay = (0.01 * SAMPLE_RATE) as usize;
+
+        // Impulse on the left input only
+        let mut input = vec![0.0f32; frames * 2];
+        input[0] = 1.0;
+        let input_buf = InputBuffer::new(&input, 2);
+        let mut data = vec![0.0f32; frames * 2];
+        let mut output = OutputBuffer::new(&mut data, 2);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        node.process(&ctx, &[input_buf], &mut output);
+
+        let left = output.channel(0);
+        let right = output.channel(1);
+        assert_eq!(right[delay], 1.0);
+        assert_eq!(left[delay], 0.0);
+        // Second echo bounces back to the left
+        assert_eq!(left[2 * delay], 0.5);
+        assert_eq!(right[2 * delay], 0.0);
+    }
+
+    #[test]
+    fn test_delay_identical_inputs_give_identical_outputs() {
+        let frames = 512;
+        let mut node = DelayNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::TIME, 0.0037);
+        node.set_param(params::FEEDBACK, 0.95);
+        node.set_param(params::MIX, 0.5);
+
+        let mut phase = 0.0f32;
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        // Long feedback tail across many blocks
+        for _ in 0..200 {
+            let mut input = vec![0.0f32; frames * 2];
+            for i in 0..frames {
+                let s = (phase * std::f32::consts::TAU).sin() * 0.3;
+                phase = (phase + 331.0 / SAMPLE_RATE as f32).fract();
+                input[i] = s;
+                input[frames + i] = s;
+            }
+            let input_buf = InputBuffer::new(&input, 2);
+            let mut data = vec![0.0f32; frames * 2];
+            let mut output = OutputBuffer::new(&mut data, 2);
+            node.process(&ctx, &[input_buf], &mut output);
+
+            assert_eq!(output.channel(0), output.channel(1));
+        }
+    }
+
+    #[test]
+    fn test_delay_damping_darkens_each_repeat() {
+        let frames = 4_800;
+        let mut node = DelayNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::TIME, 0.01);
+        node.set_param(params::FEEDBACK, 0.95);
+        node.set_param(params::DELAY_DAMPING, 0.5);
+        node.set_param(params::MIX, 1.0);
+        let delay = (0.01 * SAMPLE_RATE) as usize;
+
+        let (left, _) = impulse_response(&mut node, frames);
+
+        // Magnitude-weighted mean frequency of each echo
+        let centroid = |echo: &[f32]| {
+            let freqs = (1..=64).map(|k| k as f64 * 300.0);
+            let (weighted, total) = freqs.fold((0.0, 0.0), |(w, t), f| {
+                let mag = dft_magnitude(echo, f);
+                (w + f * mag, t + mag)
+            });
+            weighted / total
+        };
+        let centroids: Vec<f64> = (1..frames / delay)
+            .map(|k| centroid(&left[k * delay..(k + 1) * delay]))
+            .collect();
+        assert!(centroids.windows(2).all(|w| w[1] < w[0]), "{centroids:?}");
+    }
+
+    /// Feed a mono impulse through `node` and return the (left, right) output.
+    fn impulse_response(node: &mut dyn Node, frames: usize) -> (Vec<f32>, Vec<f32>) {
+        let mut input = vec![0.0f32; frames];
+        input[0] = 1.0;
+        let input_buf = InputBuffer::new(&input, 1);
+        let mut data = vec![0.0f32; frames * 2];
+        let mut output = OutputBuffer::new(&mut data, 2);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        node.process(&ctx, &[input_buf], &mut output);
+        (output.channel(0).to_vec(), output.channel(1).to_vec())
+    }
+
+    /// Normalized cross-correlation at zero lag.
+    fn correlation(a: &[f32], b: &[f32]) -> f32 {
+        let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+        let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+        dot / (norm_a * norm_b)
+    }
+
+    #[test]
+    fn test_reverb_stereo_tails_decorrelated() {
+        let frames = 24_000;
+        let mut node = ReverbNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::MIX, 1.0);
+        node.set_param(params::WIDTH, 1.0);
+
+        let (left, right) = impulse_response(&mut node, frames);
+        assert_ne!(left, right);
+        let corr = correlation(&left, &right);
+        assert!(corr < 0.5, "Tails too correlated: {:.3}", corr);
+
+        // Zero width collapses to mono
+        let mut node = ReverbNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::MIX, 1.0);
+        node.set_param(params::WIDTH, 0.0);
+        let (left, right) = impulse_response(&mut node, frames);
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn test_reverb_pre_delay() {
+        let frames = 9_600;
+        let mut node = ReverbNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::MIX, 1.0);
+        node.set_param(params::PREDELAY, 50.0);
+
+        let (left, _) = impulse_response(&mut node, frames);
+        let first = left.iter().position(|s| s.abs() > 1e-6).unwrap();
+        // 50ms pre-delay plus the shortest comb delay
+        assert!(first >= (0.05 * SAMPLE_RATE) as usize + COMB_DELAYS[3]);
+    }
+
+    #[test]
+    fn test_reverb_freeze_sustains_tail() {
+        let block = 4_800;
+        let mut node = ReverbNode::new();
+        node.prepare(SAMPLE_RATE, block);
+        node.set_param(params::MIX, 1.0);
+        node.set_param(params::DECAY, 0.7);
+
+        let ctx = ProcessContext::new(block, SAMPLE_RATE, 0, 120.0);
+        let mut data = vec![0.0f32; block * 2];
+        let mut run_block = |node: &mut ReverbNode, input: &mut [f32]| -> f32 {
+            let input_buf = InputBuffer::new(input, 1);
+            let mut output = OutputBuffer::new(&mut data, 2);
+            node.process(&ctx, &[input_buf], &mut output);
+            let sum: f32 = output.samples().iter().map(|s| s * s).sum();
+            (sum / output.samples().len() as f32).sqrt()
+        };
+
+        // Excite the tank, then freeze while the tail is still dense
+        let mut input = vec![0.0f32; block];
+        input[0] = 1.0;
+        run_block(&mut node, &mut input);
+        node.set_param(params::FREEZE, 1.0);
+
+        // Skip the crossfade, then measure ~4 seconds of frozen tail
+        let mut silence = vec![0.0f32; block];
+        run_block(&mut node, &mut silence);
+        let levels: Vec<f32> = (0..40).map(|_| run_block(&mut node, &mut silence)).collect();
+
+        let max = levels.iter().cloned().fold(0.0f32, f32::max);
+        let min = levels.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(min > 0.0);
+        assert!(max / min < 1.5, "Frozen tail level drifted: {} .. {}", min, max);
+
+        // New input is ignored while frozen
+        let mut loud = vec![1.0f32; block];
+        let frozen = run_block(&mut node, &mut loud);
+        assert!(frozen < max * 1.5);
+    }
+
+    #[test]
+    fn test_flanger_notches_move() {
+        // Four seconds of noise through one full 0.25 Hz sweep
+        let frames = 192_000;
+        let mut node = FlangerNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::RATE, 0.25);
+        node.set_param(params::DEPTH, 1.0);
+        node.set_param(params::FLANGER_FEEDBACK, 0.0);
+        node.set_param(params::MIX, 0.5);
+        node.set_param(params::WIDTH, 1.0);
+
+        let mut seed = 1u32;
+        let input: Vec<f32> = (0..frames)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0
+            })
+            .collect();
+        let input_buf = InputBuffer::new(&input, 1);
+        let mut data = vec![0.0f32; frames * 2];
+        let mut output = OutputBuffer::new(&mut data, 2);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        node.process(&ctx, &[input_buf], &mut output);
+
+        // Gain at `freq` over a Hann window centred on `center`
+        let gain = |out: &[f32], center: usize, freq: f64| {
+            let window = 8_192;
+            let start = center - window / 2;
+            let hann = |i: usize| {
+                let x = std::f64::consts::TAU * i as f64 / window as f64;
+                (0.5 - 0.5 * x.cos()) as f32
+            };
+            let windowed =
+                |s: &[f32]| -> Vec<f32> { (0..window).map(|i| s[start + i] * hann(i)).collect() };
+            dft_magnitude(&windowed(out), freq) / dft_magnitude(&windowed(&input), freq)
+        };
+
+        // The left sweep bottoms out at 5ms one second in (notches at odd
+        // multiples of 100Hz) and peaks at 0.5ms after three (first notch
+        // at 1kHz)
+        let left = output.channel(0);
+        let slow = 48_000;
+        let fast = 144_000;
+        assert!(gain(left, slow, 300.0) < 0.2);
+        assert!(gain(left, slow, 1_000.0) > 0.8);
+        assert!(gain(left, fast, 1_000.0) < 0.2);
+        assert!(gain(left, fast, 300.0) > 0.8);
+
+        // Full width puts the right channel half a cycle away
+        let right = output.channel(1);
+        assert!(gain(right, slow, 1_000.0) < 0.2);
+        assert!(gain(right, fast, 300.0) < 0.2);
+    }
+
+    #[test]
+    fn test_stereo_width_mid_side() {
+        let frames = 64;
+        let mut node = StereoWidthNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+
+        // Hard-panned left, plus a quieter centred component
+        let mut input = vec![0.0f32; frames * 2];
+        for i in 0..frames {
+            let s = (i as f32 * 0.3).sin();
+            input[i] = s + 0.25;
+            input[frames + i] = 0.25;
+        }
+        let input_buf = InputBuffer::new(&input, 2);
+        let mut data = vec![0.0f32; frames * 2];
+
+        let mut render = |node: &mut StereoWidthNode, width: f32| {
+            node.set_param(params::WIDTH, width);
+            let mut output = OutputBuffer::new(&mut data, 2);
+            node.process(&ctx, &[input_buf], &mut output);
+            (output.channel(0).to_vec(), output.channel(1).to_vec())
+        };
+
+        // Unity width leaves the image alone
+        let (l, r) = render(&mut node, 1.0);
+        for i in 0..frames {
+            assert!((l[i] - input_buf.channel(0)[i]).abs() < 1e-6);
+            assert!((r[i] - input_buf.channel(1)[i]).abs() < 1e-6);
+        }
+
+        // Zero width collapses both channels to the mono sum
+        let (l, r) = render(&mut node, 0.0);
+        for i in 0..frames {
+            let mono = (input_buf.channel(0)[i] + input_buf.channel(1)[i]) * 0.5;
+            assert!((l[i] - mono).abs() < 1e-6);
+            assert!((r[i] - mono).abs() < 1e-6);
+        }
+
+        // Double width doubles the side signal
+        let (l, r) = render(&mut node, 2.0);
+        for i in 0..frames {
+            let (in_l, in_r) = (input_buf.channel(0)[i], input_buf.channel(1)[i]);
+            assert!((l[i] - r[i] - 2.0 * (in_l - in_r)).abs() < 1e-6);
+            assert!((l[i] + r[i] - (in_l + in_r)).abs() < 1e-6);
+        }
+
+        // Mono input has no side signal
+        let mono_in: Vec<f32> = (0..frames).map(|i| (i as f32 * 0.3).sin()).collect();
+        let mono_buf = InputBuffer::new(&mono_in, 1);
+        let mut output = OutputBuffer::new(&mut data, 2);
+        node.process(&ctx, &[mono_buf], &mut output);
+        for i in 0..frames {
+            assert_eq!(output.channel(0)[i], mono_buf.channel(0)[i]);
+            assert_eq!(output.channel(1)[i], mono_buf.channel(0)[i]);
+        }
+    }
+
+    #[test]
+    fn test_ring_mod_sidebands() {
+        let frames = 4_800;
+        let sine = |freq: f64| -> Vec<f32> {
+            (0..frames)
+                .map(|i| (std::f64::consts::TAU * freq * i as f64 / SAMPLE_RATE).sin() as f32)
+                .collect()
+        };
+        let input = sine(440.0);
+        let carrier = sine(100.0);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        let mut data = vec![0.0f32; frames * 2];
+
+        let mut render = |node: &mut RingModNode, inputs: &[InputBuffer]| {
+            let mut output = OutputBuffer::new(&mut data, 2);
+            node.process(&ctx, inputs, &mut output);
+            output.channel(0).to_vec()
+        };
+
+        // Ring mode with the carrier on the second port: only the sidebands remain
+        let mut node = RingModNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        let carrier_buf = InputBuffer::new(&carrier, 1);
+        let ring = render(&mut node, &[InputBuffer::new(&input, 1), carrier_buf]);
+        assert!((dft_magnitude(&ring, 340.0) - 0.25).abs() < 0.01);
+        assert!((dft_magnitude(&ring, 540.0) - 0.25).abs() < 0.01);
+        assert!(dft_magnitude(&ring, 440.0) < 0.01);
+        assert!(dft_magnitude(&ring, 100.0) < 0.01);
+
+        // The internal 100Hz carrier gives the same result
+        node.set_param(params::FREQ, 100.0);
+        node.reset();
+        let internal = render(&mut node, &[InputBuffer::new(&input, 1)]);
+        for (a, b) in internal.iter().zip(&ring) {
+            assert!((a - b).abs() < 1e-4);
+        }
+
+        // AM mode keeps the input at half level between the sidebands
+        node.set_param(params::RINGMOD_MODE, 1.0);
+        let am = render(&mut node, &[InputBuffer::new(&input, 1), carrier_buf]);
+        assert!((dft_magnitude(&am, 440.0) - 0.25).abs() < 0.01);
+        assert!((dft_magnitude(&am, 340.0) - 0.125).abs() < 0.01);
+        assert!((dft_magnitude(&am, 540.0) - 0.125).abs() < 0.01);
     }
 }
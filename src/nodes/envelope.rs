@@ -1,6 +1,6 @@
 // Envelope generators.
 
-use crate::audio_buffer::AudioBuffer;
+use crate::audio_buffer::{InputBuffer, OutputBuffer};
 use crate::node::{Node, ProcessContext};
 
 use super::params;
@@ -9,6 +9,68 @@ use super::params;
 // ADSR Envelope
 // ═══════════════════════════════════════════════════════════════════
 
+/// Power applied to the progress of exponential segments.
+const EXP_CURVE_POWER: i32 = 3;
+
+/// Shortest release in seconds, so a note-off never cuts the voice dead.
+const MIN_RELEASE_SECONDS: f32 = 0.005;
+
+/// Smoothed level below which the output fade counts as finished.
+const SMOOTH_FLOOR: f32 = 1e-8;
+
+/// Shape of the falling (decay and release) segments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EnvelopeCurve {
+    Linear,
+    Exponential,
+}
+
+impl EnvelopeCurve {
+    fn from_value(value: f32) -> Self {
+        if value >= 0.5 {
+            EnvelopeCurve::Exponential
+        } else {
+            EnvelopeCurve::Linear
+        }
+    }
+
+    /// Fraction of a falling segment still to go at `progress` (0..1).
+    ///
+    /// Exponential segments drop quickly and then tail off, like an RC
+    /// discharge, instead of falling at a constant rate.
+    #[inline]
+    fn fall(self, progress: f32) -> f32 {
+        let remaining = (1.0 - progress).clamp(0.0, 1.0);
+        match self {
+            EnvelopeCurve::Linear => remaining,
+            EnvelopeCurve::Exponential => remaining.powi(EXP_CURVE_POWER),
+        }
+    }
+}
+
+/// Output scale for a note struck at `velocity` (0-1).
+///
+/// With `amount` at 0 every note plays at full level; at 1 the level
+/// follows velocity directly.
+#[inline]
+fn velocity_gain(amount: f32, velocity: f32) -> f32 {
+    1.0 - amount + amount * velocity.clamp(0.0, 1.0)
+}
+
+/// Advance the smoothed level toward `env` and return the gain applied to
+/// the input.
+///
+/// Once the envelope reaches zero the smoothed level keeps falling for a
+/// few samples, so the output fades out instead of dropping to zero.
+#[inline]
+fn smoothed_gain(smooth_level: &mut f32, env: f32, coeff: f32) -> f32 {
+    *smooth_level += (env - *smooth_level) * coeff;
+    if env <= 0.0 && *smooth_level < SMOOTH_FLOOR {
+        *smooth_level = 0.0;
+    }
+    smooth_level.sqrt().min(1.0)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum EnvelopeStage {
     Idle,
@@ -22,16 +84,23 @@ pub struct AdsrEnvelope {
     stage: EnvelopeStage,
     level: f32,
     smooth_level: f32,
+    /// Progress (0..1) through the current decay or release segment.
+    progress: f32,
 
     // Parameters (in seconds)
     attack: f32,
     decay: f32,
     sustain: f32, // 0-1 level
     release: f32,
+    curve: EnvelopeCurve,
+    /// How much note velocity scales the output (0-1)
+    velocity_amount: f32,
 
     sample_rate: f32,
     release_level: f32,
     last_note: Option<u8>,
+    /// Output scale for the current note, from its velocity
+    velocity_gain: f32,
 }
 
 impl AdsrEnvelope {
@@ -40,13 +109,17 @@ impl AdsrEnvelope {
             stage: EnvelopeStage::Idle,
             level: 0.0,
             smooth_level: 0.0,
+            progress: 0.0,
             attack: 0.01,
             decay: 0.1,
             sustain: 0.7,
             release: 0.3,
+            curve: EnvelopeCurve::Exponential,
+            velocity_amount: 0.0,
             sample_rate: 48_000.0,
             release_level: 0.0,
             last_note: None,
+            velocity_gain: 1.0,
         }
     }
 
@@ -61,14 +134,15 @@ impl AdsrEnvelope {
                 if self.level >= 1.0 {
                     self.level = 1.0;
                     self.stage = EnvelopeStage::Decay;
+                    self.progress = 0.0;
                 }
                 self.level
             }
 
             EnvelopeStage::Decay => {
-                let rate = (1.0 - self.sustain) / (self.decay * self.sample_rate).max(1.0);
-                self.level -= rate;
-                if self.level <= self.sustain {
+                self.progress += 1.0 / (self.decay * self.sample_rate).max(1.0);
+                self.level = self.sustain + (1.0 - self.sustain) * self.curve.fall(self.progress);
+                if self.progress >= 1.0 {
                     self.level = self.sustain;
                     self.stage = EnvelopeStage::Sustain;
                 }
@@ -78,9 +152,9 @@ impl AdsrEnvelope {
             EnvelopeStage::Sustain => self.sustain,
 
             EnvelopeStage::Release => {
-                let rate = self.release_level / (self.release * self.sample_rate).max(1.0);
-                self.level -= rate;
-                if self.level <= 0.0 {
+                self.progress += 1.0 / (self.release * self.sample_rate).max(1.0);
+                self.level = self.release_level * self.curve.fall(self.progress);
+                if self.progress >= 1.0 {
                     self.level = 0.0;
                     self.stage = EnvelopeStage::Idle;
                 }
@@ -104,8 +178,8 @@ impl Node for AdsrEnvelope {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         // Handle voice triggers
         if let Some(voice) = ctx.voice {
@@ -120,6 +194,7 @@ impl Node for AdsrEnvelope {
                 }
                 self.stage = EnvelopeStage::Attack;
                 self.last_note = Some(voice.note);
+                self.velocity_gain = velocity_gain(self.velocity_amount, voice.velocity);
             }
             if voice.release
                 && self.stage != EnvelopeStage::Idle
@@ -127,6 +202,7 @@ impl Node for AdsrEnvelope {
             {
                 self.release_level = self.level;
                 self.stage = EnvelopeStage::Release;
+                self.progress = 0.0;
             }
         }
 
@@ -141,23 +217,19 @@ impl Node for AdsrEnvelope {
 
         for i in 0..ctx.frames {
             let env = self.process_sample();
-            let gain = if env > 0.0 {
-                self.smooth_level += (env - self.smooth_level) * coeff;
-                self.smooth_level.sqrt().min(1.0)
-            } else {
-                0.0
-            };
+            let gain = smoothed_gain(&mut self.smooth_level, env, coeff);
 
             if gain > 0.0 {
                 produced_sound = true;
             }
 
             // If we have input, multiply by envelope
-            // Otherwise, output raw envelope value
+            // Otherwise, output the raw envelope contour as a control
+            // signal, e.g. for a mod route to a filter cutoff
             buf[i] = if has_input {
-                inputs[0].channel(0).get(i).copied().unwrap_or(0.0) * gain
+                inputs[0].channel(0).get(i).copied().unwrap_or(0.0) * gain * self.velocity_gain
             } else {
-                gain
+                env * self.velocity_gain
             };
         }
 
@@ -165,6 +237,15 @@ impl Node for AdsrEnvelope {
         !produced_sound
     }
 
+    fn envelope_level(&self) -> Option<f32> {
+        // Keep the voice alive until the output fade finishes
+        if self.stage == EnvelopeStage::Idle {
+            Some(self.smooth_level)
+        } else {
+            Some(self.level)
+        }
+    }
+
     fn num_channels(&self) -> usize {
         1
     }
@@ -174,7 +255,9 @@ impl Node for AdsrEnvelope {
             params::ATTACK => self.attack = value.max(0.001),
             params::DECAY => self.decay = value.max(0.001),
             params::SUSTAIN => self.sustain = value.clamp(0.0, 1.0),
-            params::RELEASE => self.release = value.max(0.001),
+            params::RELEASE => self.release = value.max(MIN_RELEASE_SECONDS),
+            params::CURVE => self.curve = EnvelopeCurve::from_value(value),
+            params::VELOCITY => self.velocity_amount = value.clamp(0.0, 1.0),
             _ => {}
         }
     }
@@ -183,6 +266,462 @@ impl Node for AdsrEnvelope {
         self.stage = EnvelopeStage::Idle;
         self.level = 0.0;
         self.smooth_level = 0.0;
+        self.progress = 0.0;
         self.last_note = None;
+        self.velocity_gain = 1.0;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// DAHDSR Envelope
+// ═══════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DahdsrStage {
+    Idle,
+    Delay,
+    Attack,
+    Hold,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// ADSR with a delay before the attack and a hold at the peak.
+pub struct DahdsrEnvelope {
+    stage: DahdsrStage,
+    level: f32,
+    smooth_level: f32,
+    /// Samples spent in the current delay or hold stage.
+    stage_samples: usize,
+    /// Progress (0..1) through the current decay or release segment.
+    progress: f32,
+
+    // Parameters (in seconds)
+    delay: f32,
+    attack: f32,
+    hold: f32,
+    decay: f32,
+    sustain: f32, // 0-1 level
+    release: f32,
+    curve: EnvelopeCurve,
+    /// How much note velocity scales the output (0-1)
+    velocity_amount: f32,
+
+    sample_rate: f32,
+    release_level: f32,
+    last_note: Option<u8>,
+    /// Output scale for the current note, from its velocity
+    velocity_gain: f32,
+}
+
+impl DahdsrEnvelope {
+    pub fn new() -> Self {
+        Self {
+            stage: DahdsrStage::Idle,
+            level: 0.0,
+            smooth_level: 0.0,
+            stage_samples: 0,
+            progress: 0.0,
+            delay: 0.0,
+            attack: 0.01,
+            hold: 0.0,
+            decay: 0.1,
+            sustain: 0.7,
+            release: 0.3,
+            curve: EnvelopeCurve::Exponential,
+            velocity_amount: 0.0,
+            sample_rate: 48_000.0,
+            release_level: 0.0,
+            last_note: None,
+            velocity_gain: 1.0,
+        }
+    }
+
+    #[inline]
+    fn process_sample(&mut self) -> f32 {
+        match self.stage {
+            DahdsrStage::Idle => 0.0,
+
+            DahdsrStage::Delay => {
+                self.stage_samples += 1;
+                if self.stage_samples as f32 >= self.delay * self.sample_rate {
+                    self.stage = DahdsrStage::Attack;
+                }
+                self.level
+            }
+
+            DahdsrStage::Attack => {
+                let rate = 1.0 / (self.attack * self.sample_rate).max(1.0);
+                self.level += rate;
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = DahdsrStage::Hold;
+                    self.stage_samples = 0;
+                }
+                self.level
+            }
+
+            DahdsrStage::Hold => {
+                self.stage_samples += 1;
+                if self.stage_samples as f32 >= self.hold * self.sample_rate {
+                    self.stage = DahdsrStage::Decay;
+                    self.progress = 0.0;
+                }
+                self.level
+            }
+
+            DahdsrStage::Decay => {
+                self.progress += 1.0 / (self.decay * self.sample_rate).max(1.0);
+                self.level = self.sustain + (1.0 - self.sustain) * self.curve.fall(self.progress);
+                if self.progress >= 1.0 {
+                    self.level = self.sustain;
+                    self.stage = DahdsrStage::Sustain;
+                }
+                self.level
+            }
+
+            DahdsrStage::Sustain => self.sustain,
+
+            DahdsrStage::Release => {
+                self.progress += 1.0 / (self.release * self.sample_rate).max(1.0);
+                self.level = self.release_level * self.curve.fall(self.progress);
+                if self.progress >= 1.0 {
+                    self.level = 0.0;
+                    self.stage = DahdsrStage::Idle;
+                }
+                self.level
+            }
+        }
+    }
+}
+
+impl Default for DahdsrEnvelope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for DahdsrEnvelope {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        // Same trigger/release handling as the ADSR
+        if let Some(voice) = ctx.voice {
+            if voice.trigger {
+                let note_changed = self.last_note != Some(voice.note);
+                if self.stage == DahdsrStage::Idle || note_changed {
+                    self.level = 0.0;
+                    self.smooth_level = 0.0;
+                }
+                self.stage = DahdsrStage::Delay;
+                self.stage_samples = 0;
+                self.last_note = Some(voice.note);
+                self.velocity_gain = velocity_gain(self.velocity_amount, voice.velocity);
+            }
+            if voice.release
+                && self.stage != DahdsrStage::Idle
+                && self.stage != DahdsrStage::Release
+            {
+                self.release_level = self.level;
+                self.stage = DahdsrStage::Release;
+                self.progress = 0.0;
+            }
+        }
+
+        let has_input = !inputs.is_empty();
+        let buf = output.channel_mut(0);
+        let mut produced_sound = false;
+
+        let cutoff = 1000.0;
+        let coeff = 1.0 - (-2.0 * std::f32::consts::PI * cutoff / self.sample_rate).exp();
+
+        for (i, sample) in buf.iter_mut().enumerate().take(ctx.frames) {
+            let env = self.process_sample();
+            let gain = smoothed_gain(&mut self.smooth_level, env, coeff);
+
+            if gain > 0.0 {
+                produced_sound = true;
+            }
+
+            *sample = if has_input {
+                inputs[0].channel(0).get(i).copied().unwrap_or(0.0) * gain * self.velocity_gain
+            } else {
+                env * self.velocity_gain
+            };
+        }
+
+        !produced_sound
+    }
+
+    fn envelope_level(&self) -> Option<f32> {
+        // Keep the voice alive until the output fade finishes
+        if self.stage == DahdsrStage::Idle {
+            Some(self.smooth_level)
+        } else {
+            Some(self.level)
+        }
+    }
+
+    fn num_channels(&self) -> usize {
+        1
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            params::ENV_DELAY => self.delay = value.max(0.0),
+            params::ATTACK => self.attack = value.max(0.001),
+            params::HOLD => self.hold = value.max(0.0),
+            params::DECAY => self.decay = value.max(0.001),
+            params::SUSTAIN => self.sustain = value.clamp(0.0, 1.0),
+            params::RELEASE => self.release = value.max(MIN_RELEASE_SECONDS),
+            params::CURVE => self.curve = EnvelopeCurve::from_value(value),
+            params::VELOCITY => self.velocity_amount = value.clamp(0.0, 1.0),
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        self.stage = DahdsrStage::Idle;
+        self.level = 0.0;
+        self.smooth_level = 0.0;
+        self.stage_samples = 0;
+        self.progress = 0.0;
+        self.last_note = None;
+        self.velocity_gain = 1.0;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Filter Envelope
+// ═══════════════════════════════════════════════════════════════════
+
+/// ADSR contour for sweeping a filter, scaled to octaves.
+///
+/// Outputs the contour times its amount, in octaves. Compiled mod routes
+/// from it move their destination exponentially, so a cutoff sweeps
+/// evenly in pitch and each voice follows its own envelope. It doesn't
+/// report an envelope level, so it never keeps a voice alive or decides
+/// which voice gets stolen; the amp envelope does that.
+pub struct FilterEnvelope {
+    env: AdsrEnvelope,
+    /// Sweep at full level, in semitones
+    amount: f32,
+}
+
+impl FilterEnvelope {
+    pub fn new() -> Self {
+        Self {
+            env: AdsrEnvelope::new(),
+            amount: 24.0,
+        }
+    }
+}
+
+impl Default for FilterEnvelope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for FilterEnvelope {
+    fn prepare(&mut self, sample_rate: f64, max_block: usize) {
+        self.env.prepare(sample_rate, max_block);
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        _inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        let silent = self.env.process(ctx, &[], output);
+        let octaves = self.amount / 12.0;
+        for sample in &mut output.channel_mut(0)[..ctx.frames] {
+            *sample *= octaves;
+        }
+        silent
+    }
+
+    fn num_channels(&self) -> usize {
+        1
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            params::ENV_AMOUNT => self.amount = value,
+            _ => self.env.set_param(param_id, value),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.env.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::VoiceContext;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    fn render(env: &mut dyn Node, frames: usize, trigger: bool, release: bool) -> Vec<f32> {
+        render_velocity(env, frames, trigger, release, 1.0)
+    }
+
+    fn render_velocity(
+        env: &mut dyn Node,
+        frames: usize,
+        trigger: bool,
+        release: bool,
+        velocity: f32,
+    ) -> Vec<f32> {
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0).with_voice(VoiceContext {
+            id: 0,
+            note: 60,
+            velocity,
+            pitch_bend: 0.0,
+            glide_time: 0.0,
+            detune: 0.0,
+            pan: 0.0,
+            gate: !release,
+            trigger,
+            release,
+        });
+        let mut data = vec![0.0f32; frames];
+        let mut output = OutputBuffer::new(&mut data, 1);
+        env.process(&ctx, &[], &mut output);
+        data
+    }
+
+    #[test]
+    fn test_fast_note_off_fades_out() {
+        let envelopes: [&mut dyn Node; 2] = [&mut AdsrEnvelope::new(), &mut DahdsrEnvelope::new()];
+        for env in envelopes {
+            env.prepare(SAMPLE_RATE, 512);
+            env.set_param(params::RELEASE, 0.0);
+            env.set_param(params::CURVE, 0.0);
+
+            // Run a steady input through the envelope, releasing mid-attack
+            let input = [1.0f32; 64];
+            let mut out = Vec::new();
+            for block in 0..16 {
+                let (trigger, release) = (block == 0, block > 0);
+                let ctx = ProcessContext::new(64, SAMPLE_RATE, 0, 120.0).with_voice(VoiceContext {
+                    id: 0,
+                    note: 60,
+                    velocity: 1.0,
+                    pitch_bend: 0.0,
+                    glide_time: 0.0,
+                    detune: 0.0,
+                    pan: 0.0,
+                    gate: !release,
+                    trigger,
+                    release,
+                });
+                let mut data = vec![0.0f32; 64];
+                let mut output = OutputBuffer::new(&mut data, 1);
+                env.process(&ctx, &[InputBuffer::new(&input, 1)], &mut output);
+                out.extend(data);
+            }
+
+            // Reaches silence well within the rendered blocks...
+            let last = out.iter().rposition(|&s| s > 0.0).unwrap();
+            assert!(last < 512, "still sounding at {last}");
+            // ...without any sample-to-sample drop large enough to click
+            let max_drop = out.windows(2).map(|w| w[0] - w[1]).fold(0.0f32, f32::max);
+            assert!(max_drop < 0.02, "max drop = {max_drop}");
+            assert!(out[last] < 1e-3, "last sample = {}", out[last]);
+        }
+    }
+
+    #[test]
+    fn test_dahdsr_delay_attack_hold() {
+        let mut env = DahdsrEnvelope::new();
+        env.prepare(SAMPLE_RATE, 512);
+        env.set_param(params::ENV_DELAY, 0.1);
+        env.set_param(params::ATTACK, 0.05);
+        env.set_param(params::HOLD, 0.2);
+        env.set_param(params::DECAY, 0.1);
+        env.set_param(params::SUSTAIN, 0.25);
+
+        // 100 ms delay, 50 ms attack, 200 ms hold, then decay
+        let mut out = render(&mut env, 512, true, false);
+        out.extend(render(&mut env, 24_000 - 512, false, false));
+
+        // Silent through the delay
+        assert!(out[..4_800].iter().all(|&s| s == 0.0));
+
+        // Rising through the attack
+        let attack = &out[4_800..7_200];
+        assert!(attack[0] > 0.0);
+        assert!(attack.windows(2).all(|w| w[1] >= w[0]));
+        assert!(attack[2_399] > attack[1_200]);
+
+        // Flat at the peak through the hold, once the smoothing settles
+        let hold = &out[8_000..16_800];
+        assert!(hold.iter().all(|&s| s > 0.999 && s <= 1.0));
+
+        // Falling toward sustain after the hold
+        assert!(out[23_999] < 0.9);
+    }
+
+    #[test]
+    fn test_release_curve_shapes() {
+        // Level halfway through a 100 ms release from full sustain
+        let release_midpoint = |curve: f32| {
+            let mut env = AdsrEnvelope::new();
+            env.prepare(SAMPLE_RATE, 512);
+            env.set_param(params::SUSTAIN, 1.0);
+            env.set_param(params::RELEASE, 0.1);
+            env.set_param(params::CURVE, curve);
+            render(&mut env, 4_800, true, false);
+            render(&mut env, 2_400, false, true);
+            env.envelope_level().unwrap()
+        };
+
+        let linear = release_midpoint(0.0);
+        let exponential = release_midpoint(1.0);
+        assert!((linear - 0.5).abs() < 1e-3, "linear = {linear}");
+        // The exponential release has already fallen most of the way
+        assert!(exponential < 0.2, "exponential = {exponential}");
+
+        // Decay and release are exponential by default
+        assert_eq!(AdsrEnvelope::new().curve, EnvelopeCurve::Exponential);
+    }
+
+    #[test]
+    fn test_velocity_scales_peak() {
+        let peak = |env: &mut dyn Node, amount: f32, velocity: f32| {
+            env.prepare(SAMPLE_RATE, 4_800);
+            env.reset();
+            env.set_param(params::VELOCITY, amount);
+            render_velocity(env, 4_800, true, false, velocity)
+                .into_iter()
+                .fold(0.0f32, f32::max)
+        };
+
+        let envelopes: [&mut dyn Node; 2] = [&mut AdsrEnvelope::new(), &mut DahdsrEnvelope::new()];
+        for env in envelopes {
+            let soft = peak(env, 1.0, 0.25);
+            let hard = peak(env, 1.0, 1.0);
+            assert!((hard - 1.0).abs() < 1e-3, "hard = {hard}");
+            assert!((soft - 0.25).abs() < 1e-3, "soft = {soft}");
+
+            // Halfway sensitivity lands between the two
+            let half = peak(env, 0.5, 0.25);
+            assert!((half - 0.625).abs() < 1e-3, "half = {half}");
+
+            // Without sensitivity every note peaks at full level
+            assert_eq!(peak(env, 0.0, 0.25), hard);
+        }
     }
 }
@@ -1,9 +1,14 @@
 // Filter nodes using State Variable Filter (SVF) topology.
-// This provides lowpass, highpass, and bandpass outputs.
+// This provides lowpass, highpass, bandpass, notch, and allpass outputs.
+// A 4-pole ladder lowpass is also provided for Moog-style sounds, and a
+// tuned comb for resonators and flanging.
 
-use crate::audio_buffer::AudioBuffer;
+use crate::audio_buffer::{InputBuffer, OutputBuffer};
 use crate::node::{Node, ProcessContext};
 
+/// Reference note for keyboard tracking (middle C).
+const KEYTRACK_REF_NOTE: u8 = 60;
+
 /// Filter type for the SVF.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterType {
@@ -11,6 +16,8 @@ pub enum FilterType {
     Highpass,
     Bandpass,
     Notch,
+    /// Flat magnitude; phase turns through 180 degrees at the cutoff.
+    Allpass,
 }
 
 /// State Variable Filter implementation.
@@ -22,6 +29,10 @@ pub struct SvfFilter {
     cutoff: f32,
     resonance: f32,
 
+    // Keyboard tracking amount (0 = fixed cutoff, 1 = follows pitch)
+    keytrack: f32,
+    tracked_note: u8,
+
     // Filter state
     ic1eq: f32,
     ic2eq: f32,
@@ -42,6 +53,8 @@ impl SvfFilter {
             filter_type,
             cutoff: 1000.0,
             resonance: 0.5,
+            keytrack: 0.0,
+            tracked_note: KEYTRACK_REF_NOTE,
             ic1eq: 0.0,
             ic2eq: 0.0,
             g: 0.0,
@@ -69,6 +82,10 @@ impl SvfFilter {
         Self::new(FilterType::Notch)
     }
 
+    pub fn allpass() -> Self {
+        Self::new(FilterType::Allpass)
+    }
+
     fn update_coefficients(&mut self, sample_rate: f64) {
         if (self.last_sample_rate - sample_rate).abs() < 0.1 {
             return;
@@ -78,10 +95,16 @@ impl SvfFilter {
         self.recalc_coeffs();
     }
 
+    /// Cutoff after keyboard tracking, relative to the reference note.
+    fn effective_cutoff(&self) -> f32 {
+        let semitones = self.tracked_note as f32 - KEYTRACK_REF_NOTE as f32;
+        self.cutoff * 2.0_f32.powf(semitones / 12.0 * self.keytrack)
+    }
+
     fn recalc_coeffs(&mut self) {
         // Clamp cutoff to valid range
         let cutoff = self
-            .cutoff
+            .effective_cutoff()
             .clamp(20.0, (self.last_sample_rate as f32 * 0.49).max(20.0));
 
         // Resonance clamped to prevent self-oscillation issues
@@ -109,6 +132,7 @@ impl SvfFilter {
             FilterType::Highpass => input - self.k * v1 - v2,
             FilterType::Bandpass => v1,
             FilterType::Notch => input - self.k * v1,
+            FilterType::Allpass => input - 2.0 * self.k * v1,
         }
     }
 }
@@ -133,6 +157,12 @@ impl Node for SvfFilter {
                     self.recalc_coeffs();
                 }
             }
+            2 => {
+                self.keytrack = value.clamp(0.0, 1.0);
+                if self.last_sample_rate > 0.0 {
+                    self.recalc_coeffs();
+                }
+            }
             _ => {}
         }
     }
@@ -140,11 +170,20 @@ impl Node for SvfFilter {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         self.update_coefficients(ctx.sample_rate);
 
+        // Follow the voice's note for keyboard tracking
+        let note = ctx.voice.map_or(KEYTRACK_REF_NOTE, |v| v.note);
+        if note != self.tracked_note {
+            self.tracked_note = note;
+            if self.keytrack != 0.0 {
+                self.recalc_coeffs();
+            }
+        }
+
         let input = match inputs.first() {
             Some(buf) => buf,
             None => {
@@ -161,7 +200,7 @@ impl Node for SvfFilter {
             out_ch[i] = self.process_sample(sample);
         }
 
-        true
+        false
     }
 
     fn num_channels(&self) -> usize {
@@ -174,6 +213,274 @@ impl Node for SvfFilter {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// Ladder Filter
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Feedback gain at full resonance. Slightly above 4.0 so the ladder
+/// self-oscillates near the top of the resonance range.
+const LADDER_MAX_FEEDBACK: f32 = 4.2;
+
+/// Moog-style 4-pole (24 dB/oct) transistor ladder lowpass.
+///
+/// Four cascaded zero-delay-feedback one-pole stages with global negative
+/// feedback. The feedback path is solved implicitly so the cutoff stays
+/// accurate, and a `tanh` on the ladder input bounds self-oscillation.
+pub struct LadderFilter {
+    cutoff: f32,
+    resonance: f32,
+
+    // One-pole stage states
+    s: [f32; 4],
+
+    // Cached coefficients
+    g: f32,
+    k: f32,
+
+    last_sample_rate: f64,
+}
+
+impl LadderFilter {
+    pub fn new() -> Self {
+        Self {
+            cutoff: 1000.0,
+            resonance: 0.5,
+            s: [0.0; 4],
+            g: 0.0,
+            k: 0.0,
+            last_sample_rate: 0.0,
+        }
+    }
+
+    fn update_coefficients(&mut self, sample_rate: f64) {
+        if (self.last_sample_rate - sample_rate).abs() < 0.1 {
+            return;
+        }
+
+        self.last_sample_rate = sample_rate;
+        self.recalc_coeffs();
+    }
+
+    fn recalc_coeffs(&mut self) {
+        let cutoff = self
+            .cutoff
+            .clamp(20.0, (self.last_sample_rate as f32 * 0.49).max(20.0));
+
+        let wc = (std::f32::consts::PI * cutoff / self.last_sample_rate as f32).tan();
+        self.g = wc / (1.0 + wc);
+        self.k = self.resonance.clamp(0.0, 1.0) * LADDER_MAX_FEEDBACK;
+    }
+
+    #[inline]
+    fn process_sample(&mut self, input: f32) -> f32 {
+        let g = self.g;
+        let one_minus_g = 1.0 - g;
+
+        // Each stage is y = g * x + (1 - g) * s, so the ladder output is
+        // g^4 * u plus the contribution of the stored states.
+        let sigma = self.s.iter().fold(0.0, |acc, &s| acc * g + s * one_minus_g);
+        let g4 = g * g * g * g;
+
+        let u = ((input - self.k * sigma) / (1.0 + self.k * g4)).tanh();
+
+        let mut x = u;
+        for s in &mut self.s {
+            let v = (x - *s) * g;
+            let y = v + *s;
+            *s = y + v;
+            x = y;
+        }
+
+        x
+    }
+}
+
+impl Default for LadderFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for LadderFilter {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.last_sample_rate = sample_rate;
+        self.recalc_coeffs();
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            0 => {
+                self.cutoff = value;
+                if self.last_sample_rate > 0.0 {
+                    self.recalc_coeffs();
+                }
+            }
+            1 => {
+                self.resonance = value;
+                if self.last_sample_rate > 0.0 {
+                    self.recalc_coeffs();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        self.update_coefficients(ctx.sample_rate);
+
+        let input = match inputs.first() {
+            Some(buf) => buf,
+            None => {
+                output.clear();
+                return false;
+            }
+        };
+
+        let in_ch = input.channel(0);
+        let out_ch = output.channel_mut(0);
+
+        for (i, out) in out_ch.iter_mut().take(ctx.frames).enumerate() {
+            let sample = in_ch.get(i).copied().unwrap_or(0.0);
+            *out = self.process_sample(sample);
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        1
+    }
+
+    fn reset(&mut self) {
+        self.s = [0.0; 4];
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Comb Filter
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Lowest comb tuning, which sets the delay line length.
+const COMB_MIN_FREQ: f32 = 20.0;
+
+/// Tuned feedforward/feedback comb filter.
+///
+/// The delay line holds `x + feedback * delayed`, and the output mixes the
+/// dry input with the delayed signal, so mix alone gives a feedforward
+/// comb and feedback turns it into a resonator (Karplus-Strong style).
+/// The delay is set as a frequency; a control signal on port 1 shifts it
+/// per sample in octaves.
+pub struct CombFilterNode {
+    freq: f32,
+    feedback: f32,
+    mix: f32,
+
+    buffer: Vec<f32>,
+    write_pos: usize,
+    sample_rate: f64,
+}
+
+impl CombFilterNode {
+    pub fn new() -> Self {
+        Self {
+            freq: 440.0,
+            feedback: 0.5,
+            mix: 0.5,
+            buffer: Vec::new(),
+            write_pos: 0,
+            sample_rate: 48_000.0,
+        }
+    }
+
+    /// Read the delay line `delay` samples behind the write position.
+    #[inline]
+    fn read_delayed(&self, delay: f32) -> f32 {
+        let len = self.buffer.len();
+        let pos = self.write_pos as f32 - delay + len as f32;
+        let index = pos as usize;
+        let frac = pos - index as f32;
+        let a = self.buffer[index % len];
+        let b = self.buffer[(index + 1) % len];
+        a + (b - a) * frac
+    }
+}
+
+impl Default for CombFilterNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for CombFilterNode {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.sample_rate = sample_rate;
+        let len = (sample_rate as f32 / COMB_MIN_FREQ) as usize + 2;
+        self.buffer = vec![0.0; len];
+        self.write_pos = 0;
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            0 => self.freq = value.max(COMB_MIN_FREQ),
+            1 => self.feedback = value.clamp(-0.99, 0.99),
+            2 => self.mix = value.clamp(0.0, 1.0),
+            _ => {}
+        }
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        let input = match inputs.first() {
+            Some(buf) if !self.buffer.is_empty() => buf,
+            _ => {
+                output.clear();
+                return false;
+            }
+        };
+
+        let in_ch = input.channel(0);
+        let delay_mod = inputs.get(1).map(|buf| buf.channel(0));
+        let out_ch = output.channel_mut(0);
+
+        let sample_rate = self.sample_rate as f32;
+        let max_delay = (self.buffer.len() - 2) as f32;
+        let max_freq = sample_rate * 0.45;
+
+        for (i, out) in out_ch.iter_mut().take(ctx.frames).enumerate() {
+            let x = in_ch.get(i).copied().unwrap_or(0.0);
+            let octaves = delay_mod.and_then(|m| m.get(i)).copied().unwrap_or(0.0);
+            let freq = (self.freq * 2.0_f32.powf(octaves)).clamp(COMB_MIN_FREQ, max_freq);
+            let delay = (sample_rate / freq).clamp(1.0, max_delay);
+
+            let delayed = self.read_delayed(delay);
+            self.buffer[self.write_pos] = x + self.feedback * delayed;
+            self.write_pos = (self.write_pos + 1) % self.buffer.len();
+
+            *out = x * (1.0 - self.mix) + delayed * self.mix;
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        1
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_pos = 0;
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Convenience type aliases
 // ═══════════════════════════════════════════════════════════════════════════
@@ -182,3 +489,236 @@ pub type LowpassFilter = SvfFilter;
 pub type HighpassFilter = SvfFilter;
 pub type BandpassFilter = SvfFilter;
 pub type NotchFilter = SvfFilter;
+pub type AllpassFilter = SvfFilter;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::VoiceContext;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    fn render(filter: &mut dyn Node, input: &mut [f32]) -> Vec<f32> {
+        let frames = input.len();
+        filter.prepare(SAMPLE_RATE, frames);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        let input_buf = InputBuffer::new(input, 1);
+        let mut data = vec![0.0f32; frames];
+        let mut output = OutputBuffer::new(&mut data, 1);
+        filter.process(&ctx, &[input_buf], &mut output);
+        data
+    }
+
+    /// Find the -3dB point of a lowpass by sweeping sines in 1/48 octave steps.
+    fn minus_3db_point(filter: &mut SvfFilter, note: u8) -> f32 {
+        let frames = 4_800;
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0).with_voice(VoiceContext {
+            id: 0,
+            note,
+            velocity: 1.0,
+            pitch_bend: 0.0,
+            glide_time: 0.0,
+            detune: 0.0,
+            pan: 0.0,
+            gate: true,
+            trigger: false,
+            release: false,
+        });
+        filter.prepare(SAMPLE_RATE, frames);
+
+        let mut freq = 100.0f32;
+        while freq < 20_000.0 {
+            filter.reset();
+            let input: Vec<f32> = (0..frames)
+                .map(|i| (std::f32::consts::TAU * freq * i as f32 / SAMPLE_RATE as f32).sin())
+                .collect();
+            let input_buf = InputBuffer::new(&input, 1);
+            let mut data = vec![0.0f32; frames];
+            let mut output = OutputBuffer::new(&mut data, 1);
+            filter.process(&ctx, &[input_buf], &mut output);
+
+            if peak(&data[frames / 2..]) < std::f32::consts::FRAC_1_SQRT_2 {
+                return freq;
+            }
+            freq *= 2.0_f32.powf(1.0 / 48.0);
+        }
+        freq
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0f32, |m, s| m.max(s.abs()))
+    }
+
+    #[test]
+    fn test_ladder_rings_at_cutoff() {
+        let cutoff = 1_000.0;
+        let mut filter = LadderFilter::new();
+        filter.set_param(0, cutoff);
+        filter.set_param(1, 0.9);
+
+        // DC-free impulse (doublet)
+        let mut input = vec![0.0f32; 9_600];
+        input[0] = 1.0;
+        input[1] = -1.0;
+        let out = render(&mut filter, &mut input);
+
+        // Estimate ringing frequency from zero crossings after the transient
+        let tail = &out[480..4_800];
+        let crossings = tail
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count();
+        let measured = crossings as f64 / 2.0 / (tail.len() as f64 / SAMPLE_RATE);
+        assert!(
+            (measured - cutoff as f64).abs() < cutoff as f64 * 0.15,
+            "Ringing at {:.1} Hz, expected ~{} Hz",
+            measured,
+            cutoff
+        );
+
+        // Ringing decays at sub-oscillation resonance
+        assert!(peak(&out[480..1_440]) > 0.0);
+        assert!(peak(&out[8_000..]) < peak(&out[480..1_440]) * 0.5);
+    }
+
+    #[test]
+    fn test_ladder_self_oscillates_at_full_resonance() {
+        let mut filter = LadderFilter::new();
+        filter.set_param(0, 1_000.0);
+        filter.set_param(1, 1.0);
+
+        let mut input = vec![0.0f32; 48_000];
+        input[0] = 1.0;
+        input[1] = -1.0;
+        let out = render(&mut filter, &mut input);
+
+        let late = peak(&out[40_000..]);
+        assert!(late > 0.05, "Expected sustained oscillation, got {}", late);
+        assert!(late.is_finite() && late < 2.0);
+    }
+
+    #[test]
+    fn test_svf_keytrack_doubles_cutoff_an_octave_up() {
+        // Butterworth Q so the -3dB point sits at the cutoff
+        let resonance = 1.0 - std::f32::consts::FRAC_1_SQRT_2;
+
+        let mut fixed = SvfFilter::lowpass();
+        fixed.set_param(0, 1_000.0);
+        fixed.set_param(1, resonance);
+        fixed.set_param(2, 0.0);
+
+        let mut tracked = SvfFilter::lowpass();
+        tracked.set_param(0, 1_000.0);
+        tracked.set_param(1, resonance);
+        tracked.set_param(2, 1.0);
+
+        let fixed_point = minus_3db_point(&mut fixed, 72);
+        let tracked_point = minus_3db_point(&mut tracked, 72);
+
+        let ratio = tracked_point / fixed_point;
+        assert!(
+            (ratio - 2.0).abs() < 0.1,
+            "Expected cutoff to double: {:.1} Hz vs {:.1} Hz",
+            tracked_point,
+            fixed_point
+        );
+    }
+
+    #[test]
+    fn test_allpass_flat_magnitude_with_phase_shift() {
+        let cutoff = 1_000.0;
+
+        // Gain and phase of a steady sine, measured over whole cycles
+        let response = |freq: f32| {
+            let mut filter = SvfFilter::allpass();
+            filter.set_param(0, cutoff);
+            filter.set_param(1, 0.5);
+
+            let w = std::f32::consts::TAU * freq / SAMPLE_RATE as f32;
+            let mut input: Vec<f32> = (0..48_000).map(|i| (w * i as f32).sin()).collect();
+            let out = render(&mut filter, &mut input);
+
+            let (mut re, mut im) = (0.0f64, 0.0f64);
+            for (i, &s) in out.iter().enumerate().skip(24_000) {
+                let t = (w * i as f32) as f64;
+                re += s as f64 * t.sin();
+                im += s as f64 * t.cos();
+            }
+            let n = (out.len() - 24_000) as f64;
+            let gain = 2.0 * (re * re + im * im).sqrt() / n;
+            (gain as f32, im.atan2(re) as f32)
+        };
+
+        let mut phases = Vec::new();
+        for freq in [100.0, 300.0, 1_000.0, 3_000.0, 10_000.0] {
+            let (gain, phase) = response(freq);
+            let db = 20.0 * gain.log10();
+            assert!(db.abs() < 0.1, "{freq} Hz: {db:.3} dB");
+            phases.push(phase);
+        }
+
+        // Little shift well below the cutoff, half a turn at it
+        use std::f32::consts::PI;
+        assert!(phases[0].abs() < PI / 4.0, "{phases:?}");
+        assert!((phases[2].abs() - PI).abs() < 0.05, "{phases:?}");
+        // Approaching a full turn well above it
+        assert!(phases[4].abs() < PI / 4.0, "{phases:?}");
+        assert!(phases[1] < 0.0 && phases[3] > 0.0, "{phases:?}");
+    }
+
+    #[test]
+    fn test_comb_rings_at_tuned_pitch() {
+        // Ringing period from the autocorrelation peak, in Hz
+        let ringing_freq = |octaves: f32| {
+            let mut comb = CombFilterNode::new();
+            comb.set_param(0, 440.0);
+            comb.set_param(1, 0.98);
+            comb.set_param(2, 1.0);
+
+            // Short burst of deterministic noise, then silence
+            let frames = 9_600;
+            let mut seed = 12_345u32;
+            let input: Vec<f32> = (0..frames)
+                .map(|i| {
+                    seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    if i < 100 {
+                        (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0
+                    } else {
+                        0.0
+                    }
+                })
+                .collect();
+            let delay_mod = vec![octaves; frames];
+
+            comb.prepare(SAMPLE_RATE, frames);
+            let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+            let input_buf = InputBuffer::new(&input, 1);
+            let mod_buf = InputBuffer::new(&delay_mod, 1);
+            let mut out = vec![0.0f32; frames];
+            let mut output = OutputBuffer::new(&mut out, 1);
+            comb.process(&ctx, &[input_buf, mod_buf], &mut output);
+
+            let tail = &out[4_800..];
+            assert!(peak(tail) > 0.01, "comb should still be ringing");
+            let correlation =
+                |lag: usize| -> f32 { tail.iter().zip(&tail[lag..]).map(|(a, b)| a * b).sum() };
+            let lag = (30..200)
+                .max_by(|&a, &b| correlation(a).total_cmp(&correlation(b)))
+                .unwrap();
+            SAMPLE_RATE as f32 / lag as f32
+        };
+
+        let tuned = ringing_freq(0.0);
+        assert!(
+            (tuned - 440.0).abs() < 440.0 * 0.02,
+            "rang at {tuned:.1} Hz"
+        );
+
+        // The control port shifts the tuning in octaves
+        let shifted = ringing_freq(1.0);
+        assert!(
+            (shifted - 880.0).abs() < 880.0 * 0.02,
+            "rang at {shifted:.1} Hz"
+        );
+    }
+}
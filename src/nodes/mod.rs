@@ -30,9 +30,12 @@ pub mod node_types {
     pub const SAW_OSC: u32 = 2;
     pub const SQUARE_OSC: u32 = 3;
     pub const TRIANGLE_OSC: u32 = 4;
+    pub const MULTI_OSC: u32 = 5;
 
     // Envelopes (10-19)
     pub const ADSR_ENV: u32 = 10;
+    pub const DAHDSR_ENV: u32 = 11;
+    pub const FILTER_ENV: u32 = 12;
 
     // Effects (20-39)
     pub const GAIN: u32 = 20;
@@ -40,12 +43,21 @@ pub mod node_types {
     pub const MIXER: u32 = 22;
     pub const DELAY: u32 = 23;
     pub const REVERB: u32 = 24;
+    pub const DISTORTION: u32 = 25;
+    pub const LIMITER: u32 = 26;
+    pub const EQ: u32 = 27;
+    pub const FLANGER: u32 = 28;
+    pub const WIDTH: u32 = 29;
+    pub const RINGMOD: u32 = 30;
 
     // Filters (40-49)
     pub const LOWPASS: u32 = 40;
     pub const HIGHPASS: u32 = 41;
     pub const BANDPASS: u32 = 42;
     pub const NOTCH: u32 = 43;
+    pub const LADDER: u32 = 44;
+    pub const ALLPASS: u32 = 45;
+    pub const COMB: u32 = 46;
 
     // Modulators (50-59)
     pub const LFO: u32 = 50;
@@ -55,6 +67,8 @@ pub mod node_types {
 
     // Utility (100+)
     pub const OUTPUT: u32 = 100;
+    pub const SLEW: u32 = 101;
+    pub const DC_BLOCKER: u32 = 102;
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -68,33 +82,110 @@ pub mod params {
     pub const PHASE: u32 = 2;
     pub const PULSE_WIDTH: u32 = 3;
 
+    // Sub-oscillator params (saw, square and multi-output oscillators)
+    pub const SUB_LEVEL: u32 = 6;
+    pub const SUB_OCTAVE: u32 = 7; // Octaves below the main pitch (1 or 2)
+
+    // Multi-output oscillator params
+    // Uses: FREQ (0), DETUNE (1), PULSE_WIDTH (3), SUB_LEVEL (6),
+    // SUB_OCTAVE (7) plus these
+    pub const SAW_LEVEL: u32 = 4;
+    pub const SQUARE_LEVEL: u32 = 5;
+
     // Envelope params
     pub const ATTACK: u32 = 0;
     pub const DECAY: u32 = 1;
     pub const SUSTAIN: u32 = 2;
     pub const RELEASE: u32 = 3;
+    pub const CURVE: u32 = 6; // 0 = linear, 1 = exponential decay/release
+    pub const VELOCITY: u32 = 7; // How much note velocity scales the output (0-1)
+
+    // DAHDSR params
+    // Uses: ATTACK (0), DECAY (1), SUSTAIN (2), RELEASE (3), CURVE (6),
+    // VELOCITY (7) plus these
+    pub const ENV_DELAY: u32 = 4;
+    pub const HOLD: u32 = 5;
+
+    // Filter envelope params
+    // Uses: ATTACK (0), DECAY (1), SUSTAIN (2), RELEASE (3), CURVE (6),
+    // VELOCITY (7) plus this
+    pub const ENV_AMOUNT: u32 = 8; // Sweep at full level, in semitones
 
     // Gain/mixer params
     pub const GAIN: u32 = 0;
     pub const PAN: u32 = 1;
+    pub const AUTO_GAIN: u32 = 2; // Mixer: turn the sum down to stay within full scale
 
     // Filter params
     pub const CUTOFF: u32 = 0;
     pub const RESONANCE: u32 = 1;
+    pub const KEYTRACK: u32 = 2;
 
     // LFO params
     pub const RATE: u32 = 0;
     pub const DEPTH: u32 = 1;
     pub const WAVEFORM: u32 = 2;
+    pub const RETRIGGER: u32 = 5;
+    pub const PHASE_OFFSET: u32 = 6;
 
     // Delay params
     pub const TIME: u32 = 0;
     pub const FEEDBACK: u32 = 1;
     pub const MIX: u32 = 2;
+    pub const SYNC_ON: u32 = 3;
+    pub const SYNC_DIV: u32 = 4;
+    pub const PINGPONG: u32 = 5;
+    pub const DELAY_DAMPING: u32 = 6;
 
     // Reverb params
-    // Uses: DECAY (0), DAMPING (1), MIX (2)
+    // Uses: DECAY (0), DAMPING (1), MIX (2), PREDELAY (3), WIDTH (4), FREEZE (5)
     pub const DAMPING: u32 = 1;
+    pub const PREDELAY: u32 = 3;
+    pub const WIDTH: u32 = 4;
+    pub const FREEZE: u32 = 5;
+
+    // Distortion params
+    // Uses: DRIVE (0), SHAPE (1), MIX (2)
+    pub const DRIVE: u32 = 0;
+    pub const SHAPE: u32 = 1;
+
+    // Flanger params
+    // Uses: RATE (0), DEPTH (1), MIX (2), FLANGER_FEEDBACK (3), WIDTH (4)
+    pub const FLANGER_FEEDBACK: u32 = 3;
+
+    // Stereo width params
+    // Uses: WIDTH (4)
+
+    // Ring modulator params
+    // Uses: FREQ (0), RINGMOD_MODE (1), MIX (2)
+    pub const RINGMOD_MODE: u32 = 1; // 0 = ring (bipolar), 1 = AM (unipolar)
+
+    // Slew limiter params (units per second)
+    pub const RISE: u32 = 0;
+    pub const FALL: u32 = 1;
+
+    // Limiter params
+    // Uses: CEILING (0), RELEASE (3)
+    pub const CEILING: u32 = 0;
+
+    // Parametric EQ params
+    // Band N uses N * EQ_BAND_STRIDE + (EQ_FREQ | EQ_GAIN | EQ_Q)
+    pub const EQ_BAND_STRIDE: u32 = 3;
+    pub const EQ_FREQ: u32 = 0;
+    pub const EQ_GAIN: u32 = 1;
+    pub const EQ_Q: u32 = 2;
+    pub const EQ_LOW_FREQ: u32 = 0;
+    pub const EQ_LOW_GAIN: u32 = 1;
+    pub const EQ_LOW_Q: u32 = 2;
+    pub const EQ_MID1_FREQ: u32 = 3;
+    pub const EQ_MID1_GAIN: u32 = 4;
+    pub const EQ_MID1_Q: u32 = 5;
+    pub const EQ_MID2_FREQ: u32 = 6;
+    pub const EQ_MID2_GAIN: u32 = 7;
+    pub const EQ_MID2_Q: u32 = 8;
+    pub const EQ_HIGH_FREQ: u32 = 9;
+    pub const EQ_HIGH_GAIN: u32 = 10;
+    pub const EQ_HIGH_Q: u32 = 11;
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -151,6 +242,18 @@ fn register_oscillators(registry: &mut NodeRegistry) {
                     .default(0.0)
                     .unit(ParamUnit::Semitones)
                     .curve(DisplayCurve::Symmetric),
+            )
+            .with_param(
+                ParamInfo::new(params::SUB_LEVEL, "Sub Level")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::SUB_OCTAVE, "Sub Octave")
+                    .range(1.0, 2.0)
+                    .default(1.0)
+                    .unit(ParamUnit::None),
             ),
         SimpleNodeFactory::new(|| Box::new(SawOsc::new()), Polyphony::PerVoice).channels(1),
     );
@@ -158,6 +261,7 @@ fn register_oscillators(registry: &mut NodeRegistry) {
     // Square Oscillator
     registry.register(
         NodeTypeInfo::new(node_types::SQUARE_OSC, "Square", "Oscillators")
+            .with_input(PortInfo::control_input(0, "PWM"))
             .with_output(PortInfo::audio_output(0, "Out"))
             .with_param(
                 ParamInfo::new(params::FREQ, "Frequency")
@@ -171,6 +275,18 @@ fn register_oscillators(registry: &mut NodeRegistry) {
                     .range(0.01, 0.99)
                     .default(0.5)
                     .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::SUB_LEVEL, "Sub Level")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::SUB_OCTAVE, "Sub Octave")
+                    .range(1.0, 2.0)
+                    .default(1.0)
+                    .unit(ParamUnit::None),
             ),
         SimpleNodeFactory::new(|| Box::new(SquareOsc::new()), Polyphony::PerVoice).channels(1),
     );
@@ -188,6 +304,59 @@ fn register_oscillators(registry: &mut NodeRegistry) {
             ),
         SimpleNodeFactory::new(|| Box::new(TriangleOsc::new()), Polyphony::PerVoice).channels(1),
     );
+
+    // Multi-output Oscillator (saw, square and sub on separate ports)
+    registry.register(
+        NodeTypeInfo::new(node_types::MULTI_OSC, "Multi", "Oscillators")
+            .with_output(PortInfo::audio_output(0, "Saw"))
+            .with_output(PortInfo::audio_output(1, "Square"))
+            .with_output(PortInfo::audio_output(2, "Sub"))
+            .with_param(
+                ParamInfo::new(params::FREQ, "Frequency")
+                    .range(20.0, 20000.0)
+                    .default(440.0)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::DETUNE, "Detune")
+                    .range(-100.0, 100.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Semitones)
+                    .curve(DisplayCurve::Symmetric),
+            )
+            .with_param(
+                ParamInfo::new(params::PULSE_WIDTH, "Pulse Width")
+                    .range(0.01, 0.99)
+                    .default(0.5)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::SAW_LEVEL, "Saw Level")
+                    .range(0.0, 1.0)
+                    .default(1.0)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::SQUARE_LEVEL, "Square Level")
+                    .range(0.0, 1.0)
+                    .default(1.0)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::SUB_LEVEL, "Sub Level")
+                    .range(0.0, 1.0)
+                    .default(1.0)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::SUB_OCTAVE, "Sub Octave")
+                    .range(1.0, 2.0)
+                    .default(1.0)
+                    .unit(ParamUnit::None),
+            ),
+        SimpleNodeFactory::new(|| Box::new(MultiOsc::new()), Polyphony::PerVoice).channels(3),
+    );
 }
 
 fn register_envelopes(registry: &mut NodeRegistry) {
@@ -221,9 +390,136 @@ fn register_envelopes(registry: &mut NodeRegistry) {
                     .default(0.3)
                     .unit(ParamUnit::Seconds)
                     .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::CURVE, "Curve")
+                    .range(0.0, 1.0)
+                    .default(1.0)
+                    .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::VELOCITY, "Velocity")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
             ),
         SimpleNodeFactory::new(|| Box::new(AdsrEnvelope::new()), Polyphony::PerVoice).channels(1),
     );
+
+    registry.register(
+        NodeTypeInfo::new(node_types::DAHDSR_ENV, "DAHDSR", "Envelopes")
+            .with_input(PortInfo::audio_input(0, "In"))
+            .with_output(PortInfo::audio_output(0, "Out"))
+            .with_param(
+                ParamInfo::new(params::ENV_DELAY, "Delay")
+                    .range(0.0, 10.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Seconds)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::ATTACK, "Attack")
+                    .range(0.001, 10.0)
+                    .default(0.01)
+                    .unit(ParamUnit::Seconds)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::HOLD, "Hold")
+                    .range(0.0, 10.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Seconds)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::DECAY, "Decay")
+                    .range(0.001, 10.0)
+                    .default(0.1)
+                    .unit(ParamUnit::Seconds)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::SUSTAIN, "Sustain")
+                    .range(0.0, 1.0)
+                    .default(0.7)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::RELEASE, "Release")
+                    .range(0.001, 10.0)
+                    .default(0.3)
+                    .unit(ParamUnit::Seconds)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::CURVE, "Curve")
+                    .range(0.0, 1.0)
+                    .default(1.0)
+                    .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::VELOCITY, "Velocity")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
+            ),
+        SimpleNodeFactory::new(|| Box::new(DahdsrEnvelope::new()), Polyphony::PerVoice)
+            .channels(1),
+    );
+
+    // Filter EG: mod routes from it sweep their destination in octaves
+    registry.register(
+        NodeTypeInfo::new(node_types::FILTER_ENV, "Filter EG", "Envelopes")
+            .with_output(PortInfo::audio_output(0, "Out"))
+            .with_param(
+                ParamInfo::new(params::ATTACK, "Attack")
+                    .range(0.001, 10.0)
+                    .default(0.01)
+                    .unit(ParamUnit::Seconds)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::DECAY, "Decay")
+                    .range(0.001, 10.0)
+                    .default(0.1)
+                    .unit(ParamUnit::Seconds)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::SUSTAIN, "Sustain")
+                    .range(0.0, 1.0)
+                    .default(0.7)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::RELEASE, "Release")
+                    .range(0.001, 10.0)
+                    .default(0.3)
+                    .unit(ParamUnit::Seconds)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::CURVE, "Curve")
+                    .range(0.0, 1.0)
+                    .default(1.0)
+                    .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::VELOCITY, "Velocity")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::ENV_AMOUNT, "Amount")
+                    .range(-96.0, 96.0)
+                    .default(24.0)
+                    .unit(ParamUnit::Semitones)
+                    .curve(DisplayCurve::Symmetric),
+            ),
+        SimpleNodeFactory::new(|| Box::new(FilterEnvelope::new()), Polyphony::PerVoice)
+            .channels(1),
+    );
 }
 
 fn register_filters(registry: &mut NodeRegistry) {
@@ -237,13 +533,20 @@ fn register_filters(registry: &mut NodeRegistry) {
                     .range(20.0, 20000.0)
                     .default(1000.0)
                     .unit(ParamUnit::Hz)
-                    .curve(DisplayCurve::Logarithmic),
+                    .curve(DisplayCurve::Logarithmic)
+                    .smooth_ms(20.0),
             )
             .with_param(
                 ParamInfo::new(params::RESONANCE, "Resonance")
                     .range(0.0, 1.0)
                     .default(0.5)
                     .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::KEYTRACK, "Key Track")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
             ),
         SimpleNodeFactory::new(|| Box::new(SvfFilter::lowpass()), Polyphony::PerVoice).channels(1),
     );
@@ -258,13 +561,20 @@ fn register_filters(registry: &mut NodeRegistry) {
                     .range(20.0, 20000.0)
                     .default(200.0)
                     .unit(ParamUnit::Hz)
-                    .curve(DisplayCurve::Logarithmic),
+                    .curve(DisplayCurve::Logarithmic)
+                    .smooth_ms(20.0),
             )
             .with_param(
                 ParamInfo::new(params::RESONANCE, "Resonance")
                     .range(0.0, 1.0)
                     .default(0.5)
                     .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::KEYTRACK, "Key Track")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
             ),
         SimpleNodeFactory::new(|| Box::new(SvfFilter::highpass()), Polyphony::PerVoice).channels(1),
     );
@@ -286,6 +596,12 @@ fn register_filters(registry: &mut NodeRegistry) {
                     .range(0.0, 1.0)
                     .default(0.5)
                     .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::KEYTRACK, "Key Track")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
             ),
         SimpleNodeFactory::new(|| Box::new(SvfFilter::bandpass()), Polyphony::PerVoice).channels(1),
     );
@@ -307,9 +623,95 @@ fn register_filters(registry: &mut NodeRegistry) {
                     .range(0.0, 1.0)
                     .default(0.5)
                     .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::KEYTRACK, "Key Track")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
             ),
         SimpleNodeFactory::new(|| Box::new(SvfFilter::notch()), Polyphony::PerVoice).channels(1),
     );
+
+    // Allpass Filter
+    registry.register(
+        NodeTypeInfo::new(node_types::ALLPASS, "Allpass", "Filters")
+            .with_input(PortInfo::audio_input(0, "In"))
+            .with_output(PortInfo::audio_output(0, "Out"))
+            .with_param(
+                ParamInfo::new(params::CUTOFF, "Frequency")
+                    .range(20.0, 20000.0)
+                    .default(1000.0)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::RESONANCE, "Resonance")
+                    .range(0.0, 1.0)
+                    .default(0.5)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::KEYTRACK, "Key Track")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
+            ),
+        SimpleNodeFactory::new(|| Box::new(SvfFilter::allpass()), Polyphony::PerVoice)
+            .channels(1),
+    );
+
+    // Comb Filter
+    registry.register(
+        NodeTypeInfo::new(node_types::COMB, "Comb", "Filters")
+            .with_input(PortInfo::audio_input(0, "In"))
+            .with_input(PortInfo::control_input(1, "Tune Mod"))
+            .with_output(PortInfo::audio_output(0, "Out"))
+            .with_param(
+                ParamInfo::new(params::FREQ, "Frequency")
+                    .range(20.0, 20000.0)
+                    .default(440.0)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::FEEDBACK, "Feedback")
+                    .range(-0.99, 0.99)
+                    .default(0.5)
+                    .unit(ParamUnit::Percent)
+                    .curve(DisplayCurve::Symmetric),
+            )
+            .with_param(
+                ParamInfo::new(params::MIX, "Mix")
+                    .range(0.0, 1.0)
+                    .default(0.5)
+                    .unit(ParamUnit::Percent),
+            ),
+        SimpleNodeFactory::new(|| Box::new(CombFilterNode::new()), Polyphony::PerVoice)
+            .channels(1),
+    );
+
+    // Ladder Filter
+    registry.register(
+        NodeTypeInfo::new(node_types::LADDER, "Ladder", "Filters")
+            .with_input(PortInfo::audio_input(0, "In"))
+            .with_output(PortInfo::audio_output(0, "Out"))
+            .with_param(
+                ParamInfo::new(params::CUTOFF, "Cutoff")
+                    .range(20.0, 20000.0)
+                    .default(1000.0)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic)
+                    .smooth_ms(20.0),
+            )
+            .with_param(
+                ParamInfo::new(params::RESONANCE, "Resonance")
+                    .range(0.0, 1.0)
+                    .default(0.5)
+                    .unit(ParamUnit::Percent),
+            ),
+        SimpleNodeFactory::new(|| Box::new(LadderFilter::new()), Polyphony::PerVoice).channels(1),
+    );
 }
 
 fn register_modulators(registry: &mut NodeRegistry) {
@@ -335,6 +737,18 @@ fn register_modulators(registry: &mut NodeRegistry) {
                     .range(0.0, 4.0)
                     .default(0.0)
                     .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::PHASE_OFFSET, "Phase")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::RETRIGGER, "Retrigger")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::None),
             ),
         SimpleNodeFactory::new(|| Box::new(Lfo::new()), Polyphony::Global).channels(1),
     );
@@ -344,14 +758,15 @@ fn register_effects(registry: &mut NodeRegistry) {
     // Gain
     registry.register(
         NodeTypeInfo::new(node_types::GAIN, "Gain", "Effects")
-            .with_input(PortInfo::audio_input(0, "In"))
-            .with_output(PortInfo::audio_output(0, "Out"))
+            .with_input(PortInfo::audio_input(0, "In").stereo())
+            .with_output(PortInfo::audio_output(0, "Out").stereo())
             .with_param(
                 ParamInfo::new(params::GAIN, "Gain")
                     .range(-60.0, 12.0)
                     .default(0.0)
                     .unit(ParamUnit::Db)
-                    .curve(DisplayCurve::Logarithmic),
+                    .curve(DisplayCurve::Logarithmic)
+                    .smooth_ms(20.0),
             ),
         SimpleNodeFactory::new(|| Box::new(GainNode::new()), Polyphony::Global).channels(2),
     );
@@ -382,6 +797,12 @@ fn register_effects(registry: &mut NodeRegistry) {
                     .default(0.0)
                     .unit(ParamUnit::Db)
                     .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::AUTO_GAIN, "Auto Gain")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::None),
             ),
         SimpleNodeFactory::new(|| Box::new(MixerNode::new()), Polyphony::Global).channels(2),
     );
@@ -409,6 +830,30 @@ fn register_effects(registry: &mut NodeRegistry) {
                     .range(0.0, 1.0)
                     .default(0.5)
                     .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::SYNC_ON, "Sync")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::SYNC_DIV, "Division")
+                    .range(0.0, 13.0)
+                    .default(4.0)
+                    .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::PINGPONG, "Ping-Pong")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::DELAY_DAMPING, "Damping")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Percent),
             ),
         SimpleNodeFactory::new(|| Box::new(DelayNode::new()), Polyphony::Global).channels(2),
     );
@@ -435,9 +880,248 @@ fn register_effects(registry: &mut NodeRegistry) {
                     .range(0.0, 1.0)
                     .default(0.3)
                     .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::PREDELAY, "Pre-Delay")
+                    .range(0.0, 200.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Ms),
+            )
+            .with_param(
+                ParamInfo::new(params::WIDTH, "Width")
+                    .range(0.0, 1.0)
+                    .default(1.0)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::FREEZE, "Freeze")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::None),
             ),
         SimpleNodeFactory::new(|| Box::new(ReverbNode::new()), Polyphony::Global).channels(2),
     );
+
+    // Distortion
+    registry.register(
+        NodeTypeInfo::new(node_types::DISTORTION, "Distortion", "Effects")
+            .with_input(PortInfo::audio_input(0, "In").stereo())
+            .with_output(PortInfo::audio_output(0, "Out").stereo())
+            .with_param(
+                ParamInfo::new(params::DRIVE, "Drive")
+                    .range(0.0, 48.0)
+                    .default(12.0)
+                    .unit(ParamUnit::Db),
+            )
+            .with_param(
+                ParamInfo::new(params::SHAPE, "Shape")
+                    .range(0.0, 2.0)
+                    .default(0.0)
+                    .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::MIX, "Mix")
+                    .range(0.0, 1.0)
+                    .default(1.0)
+                    .unit(ParamUnit::Percent),
+            ),
+        SimpleNodeFactory::new(|| Box::new(WaveshaperNode::new()), Polyphony::Global).channels(2),
+    );
+
+    // Limiter
+    registry.register(
+        NodeTypeInfo::new(node_types::LIMITER, "Limiter", "Effects")
+            .with_input(PortInfo::audio_input(0, "In").stereo())
+            .with_output(PortInfo::audio_output(0, "Out").stereo())
+            .with_param(
+                ParamInfo::new(params::CEILING, "Ceiling")
+                    .range(-24.0, 0.0)
+                    .default(-0.3)
+                    .unit(ParamUnit::Db),
+            )
+            .with_param(
+                ParamInfo::new(params::RELEASE, "Release")
+                    .range(0.001, 1.0)
+                    .default(0.1)
+                    .unit(ParamUnit::Seconds)
+                    .curve(DisplayCurve::Logarithmic),
+            ),
+        SimpleNodeFactory::new(|| Box::new(LimiterNode::new()), Polyphony::Global).channels(2),
+    );
+
+    // Parametric EQ
+    registry.register(
+        NodeTypeInfo::new(node_types::EQ, "Parametric EQ", "Effects")
+            .with_input(PortInfo::audio_input(0, "In").stereo())
+            .with_output(PortInfo::audio_output(0, "Out").stereo())
+            .with_param(
+                ParamInfo::new(params::EQ_LOW_FREQ, "Low Freq")
+                    .range(20.0, 20000.0)
+                    .default(100.0)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_LOW_GAIN, "Low Gain")
+                    .range(-24.0, 24.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Db)
+                    .curve(DisplayCurve::Symmetric),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_LOW_Q, "Low Q")
+                    .range(0.1, 18.0)
+                    .default(0.707)
+                    .unit(ParamUnit::None)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_MID1_FREQ, "Mid 1 Freq")
+                    .range(20.0, 20000.0)
+                    .default(500.0)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_MID1_GAIN, "Mid 1 Gain")
+                    .range(-24.0, 24.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Db)
+                    .curve(DisplayCurve::Symmetric),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_MID1_Q, "Mid 1 Q")
+                    .range(0.1, 18.0)
+                    .default(1.0)
+                    .unit(ParamUnit::None)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_MID2_FREQ, "Mid 2 Freq")
+                    .range(20.0, 20000.0)
+                    .default(2000.0)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_MID2_GAIN, "Mid 2 Gain")
+                    .range(-24.0, 24.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Db)
+                    .curve(DisplayCurve::Symmetric),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_MID2_Q, "Mid 2 Q")
+                    .range(0.1, 18.0)
+                    .default(1.0)
+                    .unit(ParamUnit::None)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_HIGH_FREQ, "High Freq")
+                    .range(20.0, 20000.0)
+                    .default(8000.0)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_HIGH_GAIN, "High Gain")
+                    .range(-24.0, 24.0)
+                    .default(0.0)
+                    .unit(ParamUnit::Db)
+                    .curve(DisplayCurve::Symmetric),
+            )
+            .with_param(
+                ParamInfo::new(params::EQ_HIGH_Q, "High Q")
+                    .range(0.1, 18.0)
+                    .default(0.707)
+                    .unit(ParamUnit::None)
+                    .curve(DisplayCurve::Logarithmic),
+            ),
+        SimpleNodeFactory::new(|| Box::new(ParametricEqNode::new()), Polyphony::Global).channels(2),
+    );
+
+    // Flanger
+    registry.register(
+        NodeTypeInfo::new(node_types::FLANGER, "Flanger", "Effects")
+            .with_input(PortInfo::audio_input(0, "In").stereo())
+            .with_output(PortInfo::audio_output(0, "Out").stereo())
+            .with_param(
+                ParamInfo::new(params::RATE, "Rate")
+                    .range(0.01, 10.0)
+                    .default(0.25)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::DEPTH, "Depth")
+                    .range(0.0, 1.0)
+                    .default(0.7)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::FLANGER_FEEDBACK, "Feedback")
+                    .range(-0.95, 0.95)
+                    .default(0.5)
+                    .unit(ParamUnit::Percent)
+                    .curve(DisplayCurve::Symmetric),
+            )
+            .with_param(
+                ParamInfo::new(params::MIX, "Mix")
+                    .range(0.0, 1.0)
+                    .default(0.5)
+                    .unit(ParamUnit::Percent),
+            )
+            .with_param(
+                ParamInfo::new(params::WIDTH, "Width")
+                    .range(0.0, 1.0)
+                    .default(0.5)
+                    .unit(ParamUnit::Percent),
+            ),
+        SimpleNodeFactory::new(|| Box::new(FlangerNode::new()), Polyphony::Global).channels(2),
+    );
+
+    // Stereo Width
+    registry.register(
+        NodeTypeInfo::new(node_types::WIDTH, "Stereo Width", "Effects")
+            .with_input(PortInfo::audio_input(0, "In").stereo())
+            .with_output(PortInfo::audio_output(0, "Out").stereo())
+            .with_param(
+                ParamInfo::new(params::WIDTH, "Width")
+                    .range(0.0, 2.0)
+                    .default(1.0)
+                    .unit(ParamUnit::Percent),
+            ),
+        SimpleNodeFactory::new(|| Box::new(StereoWidthNode::new()), Polyphony::Global).channels(2),
+    );
+
+    // Ring Modulator
+    registry.register(
+        NodeTypeInfo::new(node_types::RINGMOD, "Ring Mod", "Effects")
+            .with_input(PortInfo::audio_input(0, "In").stereo())
+            .with_input(PortInfo::audio_input(1, "Carrier"))
+            .with_output(PortInfo::audio_output(0, "Out").stereo())
+            .with_param(
+                ParamInfo::new(params::FREQ, "Frequency")
+                    .range(1.0, 5000.0)
+                    .default(100.0)
+                    .unit(ParamUnit::Hz)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::RINGMOD_MODE, "Mode")
+                    .range(0.0, 1.0)
+                    .default(0.0)
+                    .unit(ParamUnit::None),
+            )
+            .with_param(
+                ParamInfo::new(params::MIX, "Mix")
+                    .range(0.0, 1.0)
+                    .default(1.0)
+                    .unit(ParamUnit::Percent),
+            ),
+        SimpleNodeFactory::new(|| Box::new(RingModNode::new()), Polyphony::Global).channels(2),
+    );
 }
 
 fn register_samplers(registry: &mut NodeRegistry) {
@@ -460,8 +1144,8 @@ fn register_utility(registry: &mut NodeRegistry) {
     // Output
     registry.register(
         NodeTypeInfo::new(node_types::OUTPUT, "Output", "Utility")
-            .with_input(PortInfo::audio_input(0, "L"))
-            .with_input(PortInfo::audio_input(1, "R"))
+            .with_input(PortInfo::audio_input(0, "In 1").stereo())
+            .with_input(PortInfo::audio_input(1, "In 2").stereo())
             .with_param(
                 ParamInfo::new(params::GAIN, "Master")
                     .range(-60.0, 6.0)
@@ -470,4 +1154,69 @@ fn register_utility(registry: &mut NodeRegistry) {
             ),
         SimpleNodeFactory::new(|| Box::new(OutputNode::new()), Polyphony::Global).channels(2),
     );
+
+    // Slew Limiter
+    registry.register(
+        NodeTypeInfo::new(node_types::SLEW, "Slew Limiter", "Utility")
+            .with_input(PortInfo::control_input(0, "In"))
+            .with_output(PortInfo::audio_output(0, "Out"))
+            .with_param(
+                ParamInfo::new(params::RISE, "Rise")
+                    .range(0.01, 10000.0)
+                    .default(10.0)
+                    .unit(ParamUnit::None)
+                    .curve(DisplayCurve::Logarithmic),
+            )
+            .with_param(
+                ParamInfo::new(params::FALL, "Fall")
+                    .range(0.01, 10000.0)
+                    .default(10.0)
+                    .unit(ParamUnit::None)
+                    .curve(DisplayCurve::Logarithmic),
+            ),
+        SimpleNodeFactory::new(|| Box::new(SlewLimiterNode::new()), Polyphony::Global).channels(1),
+    );
+
+    // DC Blocker
+    registry.register(
+        NodeTypeInfo::new(node_types::DC_BLOCKER, "DC Blocker", "Utility")
+            .with_input(PortInfo::audio_input(0, "In").stereo())
+            .with_output(PortInfo::audio_output(0, "Out").stereo()),
+        SimpleNodeFactory::new(|| Box::new(DcBlockerNode::new()), Polyphony::Global).channels(2),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_buffer::{InputBuffer, OutputBuffer};
+    use crate::node::ProcessContext;
+
+    #[test]
+    fn test_standard_nodes_process_input_views() {
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        let frames = 128;
+        let input: Vec<f32> = (0..frames * 2).map(|i| (i as f32 * 0.05).sin()).collect();
+        let inputs = [InputBuffer::new(&input, 2), InputBuffer::new(&input, 2)];
+        let ctx = ProcessContext::new(frames, 48_000.0, 0, 120.0);
+
+        for info in registry.iter() {
+            let factory = registry.get_factory(info.type_id).unwrap();
+            let mut node = factory.create();
+            node.prepare(48_000.0, frames);
+
+            let channels = factory.num_channels();
+            let mut data = vec![0.0f32; channels * frames];
+            let mut output = OutputBuffer::new(&mut data, channels);
+            node.process(&ctx, &inputs[..info.inputs.len().min(2)], &mut output);
+
+            assert!(
+                output.samples().iter().all(|s| s.is_finite()),
+                "{} produced non-finite output",
+                info.name
+            );
+        }
+    }
 }
@@ -1,9 +1,13 @@
 // Modulation sources like LFOs.
 
-use crate::audio_buffer::AudioBuffer;
+use crate::audio_buffer::{InputBuffer, OutputBuffer};
 use crate::node::{Node, ProcessContext};
 use std::f32::consts::PI;
 
+/// Seed for the sample & hold generator, restored on reset so the
+/// stepped sequence is repeatable.
+const SH_SEED: u32 = 0x12345678;
+
 /// LFO waveform types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LfoWaveform {
@@ -18,11 +22,16 @@ pub enum LfoWaveform {
 ///
 /// Outputs a control signal that can modulate other parameters.
 /// The output range is -1.0 to 1.0, scaled by the depth parameter.
+///
+/// With retrigger enabled the cycle restarts on every note-on, so each
+/// note sees the same modulation shape starting at the phase offset.
 pub struct Lfo {
     rate: f32,  // Hz
     depth: f32, // 0.0 - 1.0
     waveform: LfoWaveform,
-    phase: f32, // 0.0 - 1.0
+    phase: f32,        // 0.0 - 1.0
+    phase_offset: f32, // 0.0 - 1.0, added to phase at the output
+    retrigger: bool,
     sync_to_transport: bool,
 
     // For sample & hold
@@ -51,37 +60,41 @@ impl Lfo {
             depth: 1.0,
             waveform: LfoWaveform::Sine,
             phase: 0.0,
+            phase_offset: 0.0,
+            retrigger: false,
             sync_to_transport: false,
             sh_value: 0.0,
-            sh_last_phase: 0.0,
-            rng_state: 0x12345678,
+            sh_last_phase: 1.0,
+            rng_state: SH_SEED,
         }
     }
 
     fn generate_sample(&mut self) -> f32 {
+        let phase = (self.phase + self.phase_offset).fract();
         let raw = match self.waveform {
-            LfoWaveform::Sine => (self.phase * 2.0 * PI).sin(),
+            LfoWaveform::Sine => (phase * 2.0 * PI).sin(),
             LfoWaveform::Triangle => {
-                if self.phase < 0.5 {
-                    4.0 * self.phase - 1.0
+                if phase < 0.5 {
+                    4.0 * phase - 1.0
                 } else {
-                    3.0 - 4.0 * self.phase
+                    3.0 - 4.0 * phase
                 }
             }
-            LfoWaveform::Saw => 2.0 * self.phase - 1.0,
+            LfoWaveform::Saw => 2.0 * phase - 1.0,
             LfoWaveform::Square => {
-                if self.phase < 0.5 {
+                if phase < 0.5 {
                     1.0
                 } else {
                     -1.0
                 }
             }
             LfoWaveform::SampleAndHold => {
-                // Update S&H value when phase wraps
-                if self.phase < self.sh_last_phase {
+                // Draw a new step each time the phase wraps. `sh_last_phase`
+                // starts at 1.0 so the first sample of a cycle draws too.
+                if phase < self.sh_last_phase {
                     self.sh_value = self.next_random() * 2.0 - 1.0;
                 }
-                self.sh_last_phase = self.phase;
+                self.sh_last_phase = phase;
                 self.sh_value
             }
         };
@@ -115,6 +128,8 @@ impl Node for Lfo {
             }
             3 => self.phase = value.clamp(0.0, 1.0), // Initial phase
             4 => self.sync_to_transport = value > 0.5,
+            5 => self.retrigger = value > 0.5, // Restart on note-on
+            6 => self.phase_offset = value.clamp(0.0, 1.0), // Phase offset
             _ => {}
         }
     }
@@ -122,8 +137,8 @@ impl Node for Lfo {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        _inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        _inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         let phase_inc = self.rate / ctx.sample_rate as f32;
         let out_ch = output.channel_mut(0);
@@ -146,6 +161,95 @@ impl Node for Lfo {
     fn reset(&mut self) {
         self.phase = 0.0;
         self.sh_value = 0.0;
-        self.sh_last_phase = 0.0;
+        self.sh_last_phase = 1.0;
+        self.rng_state = SH_SEED;
+    }
+
+    fn retrigger(&mut self) {
+        if self.retrigger {
+            self.phase = 0.0;
+            self.sh_last_phase = 1.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::params;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    fn render(lfo: &mut Lfo, frames: usize) -> Vec<f32> {
+        let mut data = vec![0.0f32; frames];
+        let mut output = OutputBuffer::new(&mut data, 1);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        lfo.process(&ctx, &[], &mut output);
+        data
+    }
+
+    #[test]
+    fn test_lfo_retrigger_restarts_phase() {
+        let mut lfo = Lfo::new();
+        lfo.set_param(params::RATE, 3.3);
+        lfo.set_param(params::PHASE_OFFSET, 0.25);
+        lfo.set_param(params::RETRIGGER, 1.0);
+
+        // Two notes arriving at unrelated points in the cycle
+        render(&mut lfo, 1_000);
+        lfo.retrigger();
+        let first = render(&mut lfo, 64);
+        render(&mut lfo, 7_777);
+        lfo.retrigger();
+        let second = render(&mut lfo, 64);
+
+        assert_eq!(first, second);
+        // A quarter-cycle offset starts the sine at its peak
+        assert!((first[0] - 1.0).abs() < 1e-6);
+
+        // Free-running mode ignores note-ons
+        lfo.set_param(params::RETRIGGER, 0.0);
+        render(&mut lfo, 1_234);
+        lfo.retrigger();
+        assert_ne!(render(&mut lfo, 64), first);
+    }
+
+    #[test]
+    fn test_lfo_param_3_sets_initial_phase() {
+        // Param 3 predates the phase offset; saved sessions rely on it
+        let mut lfo = Lfo::new();
+        lfo.set_param(3, 0.25);
+        assert!((render(&mut lfo, 1)[0] - 1.0).abs() < 1e-6);
+        lfo.reset();
+        assert!(render(&mut lfo, 1)[0].abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lfo_sample_and_hold_steps_at_rate() {
+        let mut lfo = Lfo::new();
+        lfo.set_param(params::RATE, 4.0);
+        lfo.set_param(params::WAVEFORM, 4.0);
+
+        let out = render(&mut lfo, SAMPLE_RATE as usize);
+
+        // Piecewise constant: collect the sample index of every change
+        let mut step_starts = vec![0];
+        for i in 1..out.len() {
+            if out[i] != out[i - 1] {
+                step_starts.push(i);
+            }
+        }
+        assert_eq!(step_starts.len(), 4, "expected 4 steps in one second");
+
+        // Allow a few samples of f32 phase-accumulation drift
+        let period = SAMPLE_RATE as usize / 4;
+        for (n, start) in step_starts.iter().enumerate() {
+            assert!(start.abs_diff(n * period) <= 4, "step {n} at {start}");
+        }
+        assert!(out.iter().all(|v| v.abs() <= 1.0));
+
+        // Deterministic after reset
+        lfo.reset();
+        assert_eq!(render(&mut lfo, SAMPLE_RATE as usize), out);
     }
 }
@@ -2,13 +2,137 @@
 
 use std::f32::consts::TAU;
 
-use crate::audio_buffer::AudioBuffer;
+use crate::audio_buffer::{InputBuffer, OutputBuffer};
 use crate::node::{Node, ProcessContext};
+use crate::voice::VoiceContext;
 
 use super::params;
 
 const PHASE_START: f32 = std::f32::consts::PI / 2.0;
 
+/// Triangle phase at its rising zero crossing, so notes start from silence.
+const TRIANGLE_PHASE_START: f32 = 0.25;
+
+/// Frequency of a voice's note, including its pitch bend and unison detune.
+#[inline]
+fn voice_freq(voice: VoiceContext, ctx: &ProcessContext) -> f32 {
+    let semitones = voice.pitch_bend + voice.detune / 100.0;
+    ctx.note_freq(voice.note) * 2.0_f32.powf(semitones / 12.0)
+}
+
+/// PolyBLEP residual for a unit step at phase 0.
+///
+/// `t` is the current phase (0..1) and `dt` the per-sample phase increment.
+/// Returns the correction to subtract from a naive rising discontinuity so
+/// that the step is band-limited over the two samples surrounding it.
+#[inline]
+fn poly_blep(t: f32, dt: f32) -> f32 {
+    if dt <= 0.0 {
+        return 0.0;
+    }
+    if t < dt {
+        let t = t / dt;
+        t + t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// Portamento state: the sounding pitch ramps linearly (in semitones)
+/// from the previous note to the voice's note.
+#[derive(Default)]
+struct Glide {
+    /// Sounding pitch as a fractional MIDI note
+    note: f32,
+    target: f32,
+    step: f32,
+    remaining: u32,
+}
+
+impl Glide {
+    /// Ramp from the sounding pitch to `target` over `seconds` (0 = jump).
+    fn start(&mut self, target: f32, seconds: f32, sample_rate: f32) {
+        let samples = (seconds * sample_rate) as u32;
+        self.target = target;
+        if samples == 0 {
+            self.note = target;
+            self.remaining = 0;
+        } else {
+            self.step = (target - self.note) / samples as f32;
+            self.remaining = samples;
+        }
+    }
+
+    /// Advance one sample, scaling the target note's phase increment to
+    /// the sounding pitch.
+    #[inline]
+    fn advance(&mut self, inc: f32) -> f32 {
+        if self.remaining == 0 {
+            return inc;
+        }
+        self.remaining -= 1;
+        self.note = if self.remaining == 0 {
+            self.target
+        } else {
+            self.note + self.step
+        };
+        inc * 2.0_f32.powf((self.note - self.target) / 12.0)
+    }
+}
+
+/// Square wave one or two octaves below an oscillator, derived from the
+/// main phase by counting its cycles.
+struct SubOsc {
+    level: f32,
+    /// Octaves below the main pitch (1 or 2)
+    octave: u32,
+    /// Main cycles completed, modulo the sub's period
+    cycle: u32,
+}
+
+impl SubOsc {
+    fn new(level: f32) -> Self {
+        Self {
+            level,
+            octave: 1,
+            cycle: 0,
+        }
+    }
+
+    fn set_octave(&mut self, value: f32) {
+        self.octave = value.round().clamp(1.0, 2.0) as u32;
+    }
+
+    /// Sub sample for the main `phase` and its increment `inc`.
+    #[inline]
+    fn sample(&self, phase: f32, inc: f32) -> f32 {
+        if self.level == 0.0 {
+            return 0.0;
+        }
+        let cycles = (1u32 << self.octave) as f32;
+        let t = ((self.cycle % (1 << self.octave)) as f32 + phase.fract()) / cycles;
+        let dt = inc / cycles;
+        let naive = if t < 0.5 { 1.0 } else { -1.0 };
+        let falling = (t + 0.5).fract();
+        (naive + poly_blep(t, dt) - poly_blep(falling, dt)) * self.level
+    }
+
+    /// Count a main cycle if `phase` wraps this sample.
+    #[inline]
+    fn advance(&mut self, phase: f32, inc: f32) {
+        if phase + inc >= 1.0 {
+            self.cycle = (self.cycle + 1) % 4;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.cycle = 0;
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // Sine Oscillator
 // ═══════════════════════════════════════════════════════════════════
@@ -20,6 +144,7 @@ pub struct SineOsc {
     sample_rate: f32,
     was_silent: bool,
     last_note: Option<u8>,
+    glide: Glide,
 }
 
 impl SineOsc {
@@ -31,14 +156,13 @@ impl SineOsc {
             sample_rate: 48_000.0,
             was_silent: true,
             last_note: None,
+            glide: Glide::default(),
         }
     }
 
     #[inline]
-    fn effective_freq(&self, voice_note: Option<u8>) -> f32 {
-        let base = voice_note
-            .map(|n| 440.0 * 2.0_f32.powf((n as f32 - 69.0) / 12.0))
-            .unwrap_or(self.freq);
+    fn effective_freq(&self, ctx: &ProcessContext) -> f32 {
+        let base = ctx.voice.map_or(self.freq, |v| voice_freq(v, ctx));
         base * 2.0_f32.powf(self.detune / 1200.0)
     }
 }
@@ -57,11 +181,10 @@ impl Node for SineOsc {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        _inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        _inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
-        let voice_note = ctx.voice.map(|v| v.note);
-        let freq = self.effective_freq(voice_note);
+        let freq = self.effective_freq(ctx);
         let inc = freq / self.sample_rate;
 
         // Check gate for per-voice operation
@@ -70,12 +193,17 @@ impl Node for SineOsc {
                 self.was_silent = true;
                 return true; // Silent if voice is fully released
             }
-            if voice.trigger {
-                let note_changed = self.last_note != Some(voice.note);
+            // Legato note changes arrive without a trigger
+            let note_changed = self.last_note != Some(voice.note);
+            if voice.trigger || note_changed {
+                // Gliding from a sounding note keeps the waveform continuous
+                let glide = !self.was_silent && voice.glide_time > 0.0;
                 // Reset phase if: previously silent, or voice was stolen for different note
-                if self.was_silent || note_changed {
+                if voice.trigger && (self.was_silent || note_changed) && !glide {
                     self.phase = PHASE_START;
                 }
+                let time = if glide { voice.glide_time } else { 0.0 };
+                self.glide.start(voice.note as f32, time, self.sample_rate);
                 self.last_note = Some(voice.note);
             }
             self.was_silent = false;
@@ -83,6 +211,7 @@ impl Node for SineOsc {
 
         let buf = output.channel_mut(0);
         for sample in buf.iter_mut().take(ctx.frames) {
+            let inc = self.glide.advance(inc);
             *sample = (self.phase * TAU).sin();
             self.phase = (self.phase + inc).fract();
         }
@@ -106,11 +235,12 @@ impl Node for SineOsc {
         self.phase = PHASE_START;
         self.was_silent = true;
         self.last_note = None;
+        self.glide = Glide::default();
     }
 }
 
 // ═══════════════════════════════════════════════════════════════════
-// Saw Oscillator (naive, non-bandlimited)
+// Saw Oscillator (PolyBLEP band-limited)
 // ═══════════════════════════════════════════════════════════════════
 
 pub struct SawOsc {
@@ -120,6 +250,8 @@ pub struct SawOsc {
     sample_rate: f32,
     was_silent: bool,
     last_note: Option<u8>,
+    glide: Glide,
+    sub: SubOsc,
 }
 
 impl SawOsc {
@@ -131,14 +263,14 @@ impl SawOsc {
             sample_rate: 48_000.0,
             was_silent: true,
             last_note: None,
+            glide: Glide::default(),
+            sub: SubOsc::new(0.0),
         }
     }
 
     #[inline]
-    fn effective_freq(&self, voice_note: Option<u8>) -> f32 {
-        let base = voice_note
-            .map(|n| 440.0 * 2.0_f32.powf((n as f32 - 69.0) / 12.0))
-            .unwrap_or(self.freq);
+    fn effective_freq(&self, ctx: &ProcessContext) -> f32 {
+        let base = ctx.voice.map_or(self.freq, |v| voice_freq(v, ctx));
         base * 2.0_f32.powf(self.detune / 1200.0)
     }
 }
@@ -157,11 +289,10 @@ impl Node for SawOsc {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        _inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        _inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
-        let voice_note = ctx.voice.map(|v| v.note);
-        let freq = self.effective_freq(voice_note);
+        let freq = self.effective_freq(ctx);
         let inc = freq / self.sample_rate;
 
         if let Some(voice) = ctx.voice {
@@ -169,11 +300,16 @@ impl Node for SawOsc {
                 self.was_silent = true;
                 return true;
             }
-            if voice.trigger {
-                let note_changed = self.last_note != Some(voice.note);
-                if self.was_silent || note_changed {
-                    self.phase = PHASE_START;
+            // Legato note changes arrive without a trigger
+            let note_changed = self.last_note != Some(voice.note);
+            if voice.trigger || note_changed {
+                let glide = !self.was_silent && voice.glide_time > 0.0;
+                if voice.trigger && (self.was_silent || note_changed) && !glide {
+                    self.phase = 0.0;
+                    self.sub.reset();
                 }
+                let time = if glide { voice.glide_time } else { 0.0 };
+                self.glide.start(voice.note as f32, time, self.sample_rate);
                 self.last_note = Some(voice.note);
             }
             self.was_silent = false;
@@ -181,7 +317,10 @@ impl Node for SawOsc {
 
         let buf = output.channel_mut(0);
         for sample in buf.iter_mut().take(ctx.frames) {
-            *sample = 2.0 * self.phase - 1.0;
+            let inc = self.glide.advance(inc);
+            *sample = 2.0 * self.phase - 1.0 - poly_blep(self.phase, inc)
+                + self.sub.sample(self.phase, inc);
+            self.sub.advance(self.phase, inc);
             self.phase = (self.phase + inc).fract();
         }
 
@@ -196,19 +335,23 @@ impl Node for SawOsc {
         match param_id {
             params::FREQ => self.freq = value,
             params::DETUNE => self.detune = value,
+            params::SUB_LEVEL => self.sub.level = value.clamp(0.0, 1.0),
+            params::SUB_OCTAVE => self.sub.set_octave(value),
             _ => {}
         }
     }
 
     fn reset(&mut self) {
-        self.phase = PHASE_START;
+        self.phase = 0.0;
         self.was_silent = true;
         self.last_note = None;
+        self.glide = Glide::default();
+        self.sub.reset();
     }
 }
 
 // ═══════════════════════════════════════════════════════════════════
-// Square Oscillator (with pulse width)
+// Square Oscillator (PolyBLEP band-limited, with pulse width)
 // ═══════════════════════════════════════════════════════════════════
 
 pub struct SquareOsc {
@@ -218,6 +361,8 @@ pub struct SquareOsc {
     sample_rate: f32,
     was_silent: bool,
     last_note: Option<u8>,
+    glide: Glide,
+    sub: SubOsc,
 }
 
 impl SquareOsc {
@@ -229,14 +374,14 @@ impl SquareOsc {
             sample_rate: 48_000.0,
             was_silent: true,
             last_note: None,
+            glide: Glide::default(),
+            sub: SubOsc::new(0.0),
         }
     }
 
     #[inline]
-    fn effective_freq(&self, voice_note: Option<u8>) -> f32 {
-        voice_note
-            .map(|n| 440.0 * 2.0_f32.powf((n as f32 - 69.0) / 12.0))
-            .unwrap_or(self.freq)
+    fn effective_freq(&self, ctx: &ProcessContext) -> f32 {
+        ctx.voice.map_or(self.freq, |v| voice_freq(v, ctx))
     }
 }
 
@@ -254,11 +399,10 @@ impl Node for SquareOsc {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        _inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
-        let voice_note = ctx.voice.map(|v| v.note);
-        let freq = self.effective_freq(voice_note);
+        let freq = self.effective_freq(ctx);
         let inc = freq / self.sample_rate;
 
         if let Some(voice) = ctx.voice {
@@ -266,23 +410,38 @@ impl Node for SquareOsc {
                 self.was_silent = true;
                 return true;
             }
-            if voice.trigger {
-                let note_changed = self.last_note != Some(voice.note);
-                if self.was_silent || note_changed {
-                    self.phase = PHASE_START;
+            // Legato note changes arrive without a trigger
+            let note_changed = self.last_note != Some(voice.note);
+            if voice.trigger || note_changed {
+                let glide = !self.was_silent && voice.glide_time > 0.0;
+                if voice.trigger && (self.was_silent || note_changed) && !glide {
+                    self.phase = 0.0;
+                    self.sub.reset();
                 }
+                let time = if glide { voice.glide_time } else { 0.0 };
+                self.glide.start(voice.note as f32, time, self.sample_rate);
                 self.last_note = Some(voice.note);
             }
             self.was_silent = false;
         }
 
+        // Optional PWM input, summed with the static pulse width per-sample
+        let pwm = inputs.first().map(|input| input.channel(0));
+
         let buf = output.channel_mut(0);
-        for sample in buf.iter_mut().take(ctx.frames) {
-            *sample = if self.phase < self.pulse_width {
-                1.0
-            } else {
-                -1.0
+        for (i, sample) in buf.iter_mut().take(ctx.frames).enumerate() {
+            let inc = self.glide.advance(inc);
+            let width = match pwm {
+                Some(pwm) => (self.pulse_width + pwm.get(i).copied().unwrap_or(0.0))
+                    .clamp(0.01, 0.99),
+                None => self.pulse_width,
             };
+            let naive = if self.phase < width { 1.0 } else { -1.0 };
+            // Rising edge at phase 0, falling edge at the pulse width
+            let falling = (self.phase - width + 1.0).fract();
+            *sample = naive + poly_blep(self.phase, inc) - poly_blep(falling, inc)
+                + self.sub.sample(self.phase, inc);
+            self.sub.advance(self.phase, inc);
             self.phase = (self.phase + inc).fract();
         }
 
@@ -297,14 +456,18 @@ impl Node for SquareOsc {
         match param_id {
             params::FREQ => self.freq = value,
             params::PULSE_WIDTH => self.pulse_width = value.clamp(0.01, 0.99),
+            params::SUB_LEVEL => self.sub.level = value.clamp(0.0, 1.0),
+            params::SUB_OCTAVE => self.sub.set_octave(value),
             _ => {}
         }
     }
 
     fn reset(&mut self) {
-        self.phase = PHASE_START;
+        self.phase = 0.0;
         self.was_silent = true;
         self.last_note = None;
+        self.glide = Glide::default();
+        self.sub.reset();
     }
 }
 
@@ -318,24 +481,24 @@ pub struct TriangleOsc {
     sample_rate: f32,
     was_silent: bool,
     last_note: Option<u8>,
+    glide: Glide,
 }
 
 impl TriangleOsc {
     pub fn new() -> Self {
         Self {
-            phase: 0.0,
+            phase: TRIANGLE_PHASE_START,
             freq: 440.0,
             sample_rate: 48_000.0,
             was_silent: true,
             last_note: None,
+            glide: Glide::default(),
         }
     }
 
     #[inline]
-    fn effective_freq(&self, voice_note: Option<u8>) -> f32 {
-        voice_note
-            .map(|n| 440.0 * 2.0_f32.powf((n as f32 - 69.0) / 12.0))
-            .unwrap_or(self.freq)
+    fn effective_freq(&self, ctx: &ProcessContext) -> f32 {
+        ctx.voice.map_or(self.freq, |v| voice_freq(v, ctx))
     }
 }
 
@@ -353,11 +516,10 @@ impl Node for TriangleOsc {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        _inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        _inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
-        let voice_note = ctx.voice.map(|v| v.note);
-        let freq = self.effective_freq(voice_note);
+        let freq = self.effective_freq(ctx);
         let inc = freq / self.sample_rate;
 
         if let Some(voice) = ctx.voice {
@@ -365,11 +527,15 @@ impl Node for TriangleOsc {
                 self.was_silent = true;
                 return true;
             }
-            if voice.trigger {
-                let note_changed = self.last_note != Some(voice.note);
-                if self.was_silent || note_changed {
-                    self.phase = PHASE_START;
+            // Legato note changes arrive without a trigger
+            let note_changed = self.last_note != Some(voice.note);
+            if voice.trigger || note_changed {
+                let glide = !self.was_silent && voice.glide_time > 0.0;
+                if voice.trigger && (self.was_silent || note_changed) && !glide {
+                    self.phase = TRIANGLE_PHASE_START;
                 }
+                let time = if glide { voice.glide_time } else { 0.0 };
+                self.glide.start(voice.note as f32, time, self.sample_rate);
                 self.last_note = Some(voice.note);
             }
             self.was_silent = false;
@@ -377,6 +543,7 @@ impl Node for TriangleOsc {
 
         let buf = output.channel_mut(0);
         for sample in buf.iter_mut().take(ctx.frames) {
+            let inc = self.glide.advance(inc);
             *sample = if self.phase < 0.5 {
                 4.0 * self.phase - 1.0
             } else {
@@ -400,8 +567,470 @@ impl Node for TriangleOsc {
     }
 
     fn reset(&mut self) {
-        self.phase = PHASE_START;
+        self.phase = TRIANGLE_PHASE_START;
         self.was_silent = true;
         self.last_note = None;
+        self.glide = Glide::default();
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Multi-output Oscillator (saw, square and sub from one phase)
+// ═══════════════════════════════════════════════════════════════════
+
+/// Saw, square and a sub square sharing one phase, each on its own
+/// output channel (port 0 = saw, 1 = square, 2 = sub) with its own level.
+pub struct MultiOsc {
+    phase: f32,
+    freq: f32,
+    detune: f32,
+    pulse_width: f32,
+    saw_level: f32,
+    square_level: f32,
+    sample_rate: f32,
+    was_silent: bool,
+    last_note: Option<u8>,
+    glide: Glide,
+    sub: SubOsc,
+}
+
+impl MultiOsc {
+    pub fn new() -> Self {
+        Self {
+            phase: 0.0,
+            freq: 440.0,
+            detune: 0.0,
+            pulse_width: 0.5,
+            saw_level: 1.0,
+            square_level: 1.0,
+            sample_rate: 48_000.0,
+            was_silent: true,
+            last_note: None,
+            glide: Glide::default(),
+            sub: SubOsc::new(1.0),
+        }
+    }
+
+    #[inline]
+    fn effective_freq(&self, ctx: &ProcessContext) -> f32 {
+        let base = ctx.voice.map_or(self.freq, |v| voice_freq(v, ctx));
+        base * 2.0_f32.powf(self.detune / 1200.0)
+    }
+}
+
+impl Default for MultiOsc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for MultiOsc {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.sample_rate = sample_rate as f32;
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        _inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        let freq = self.effective_freq(ctx);
+        let inc = freq / self.sample_rate;
+
+        if let Some(voice) = ctx.voice {
+            if !voice.gate && !voice.release {
+                self.was_silent = true;
+                return true;
+            }
+            // Legato note changes arrive without a trigger
+            let note_changed = self.last_note != Some(voice.note);
+            if voice.trigger || note_changed {
+                let glide = !self.was_silent && voice.glide_time > 0.0;
+                if voice.trigger && (self.was_silent || note_changed) && !glide {
+                    self.phase = 0.0;
+                    self.sub.reset();
+                }
+                let time = if glide { voice.glide_time } else { 0.0 };
+                self.glide.start(voice.note as f32, time, self.sample_rate);
+                self.last_note = Some(voice.note);
+            }
+            self.was_silent = false;
+        }
+
+        let width = self.pulse_width;
+        for i in 0..ctx.frames {
+            let inc = self.glide.advance(inc);
+            let saw = 2.0 * self.phase - 1.0 - poly_blep(self.phase, inc);
+
+            let naive = if self.phase < width { 1.0 } else { -1.0 };
+            let falling = (self.phase - width + 1.0).fract();
+            let square = naive + poly_blep(self.phase, inc) - poly_blep(falling, inc);
+
+            output.channel_mut(0)[i] = saw * self.saw_level;
+            output.channel_mut(1)[i] = square * self.square_level;
+            output.channel_mut(2)[i] = self.sub.sample(self.phase, inc);
+
+            self.sub.advance(self.phase, inc);
+            self.phase = (self.phase + inc).fract();
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        3
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            params::FREQ => self.freq = value,
+            params::DETUNE => self.detune = value,
+            params::PULSE_WIDTH => self.pulse_width = value.clamp(0.01, 0.99),
+            params::SAW_LEVEL => self.saw_level = value.clamp(0.0, 1.0),
+            params::SQUARE_LEVEL => self.square_level = value.clamp(0.0, 1.0),
+            params::SUB_LEVEL => self.sub.level = value.clamp(0.0, 1.0),
+            params::SUB_OCTAVE => self.sub.set_octave(value),
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+        self.sub.reset();
+        self.was_silent = true;
+        self.last_note = None;
+        self.glide = Glide::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::Lfo;
+    use crate::voice_allocator::{VoiceAllocator, VoiceMode};
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    /// Render `frames` samples from a global (voiceless) oscillator node.
+    fn render(node: &mut dyn Node, frames: usize) -> Vec<f32> {
+        node.prepare(SAMPLE_RATE, frames);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        let mut data = vec![0.0f32; frames];
+        let mut output = OutputBuffer::new(&mut data, 1);
+        node.process(&ctx, &[], &mut output);
+        data
+    }
+
+    /// Magnitude of a single DFT bin at `freq` (Hann-windowed).
+    fn dft_magnitude(samples: &[f32], freq: f64) -> f64 {
+        let n = samples.len() as f64;
+        let (mut re, mut im) = (0.0, 0.0);
+        for (i, &s) in samples.iter().enumerate() {
+            let window = 0.5 - 0.5 * (std::f64::consts::TAU * i as f64 / n).cos();
+            let angle = std::f64::consts::TAU * freq * i as f64 / SAMPLE_RATE;
+            re += s as f64 * window * angle.cos();
+            im -= s as f64 * window * angle.sin();
+        }
+        (re * re + im * im).sqrt() / n
+    }
+
+    /// Count rising zero crossings to estimate frequency over `samples`.
+    fn estimate_freq(samples: &[f32]) -> f64 {
+        let crossings: Vec<usize> = samples
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| w[0] < 0.0 && w[1] >= 0.0)
+            .map(|(i, _)| i)
+            .collect();
+        let periods = (crossings.len() - 1) as f64;
+        let span = (crossings[crossings.len() - 1] - crossings[0]) as f64;
+        SAMPLE_RATE * periods / span
+    }
+
+    #[test]
+    fn test_note_on_starts_within_full_scale() {
+        let frames = 256;
+        let oscillators: [(&str, Box<dyn Node>); 4] = [
+            ("sine", Box::new(SineOsc::new())),
+            ("saw", Box::new(SawOsc::new())),
+            ("square", Box::new(SquareOsc::new())),
+            ("triangle", Box::new(TriangleOsc::new())),
+        ];
+        for (name, mut osc) in oscillators {
+            let mut voices = VoiceAllocator::new(1);
+            voices.note_on(60, 1.0);
+            let voice = voices.active_voices().next().unwrap();
+
+            osc.prepare(SAMPLE_RATE, frames);
+            let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0).with_voice(voice);
+            let mut data = vec![0.0f32; frames];
+            let mut output = OutputBuffer::new(&mut data, 1);
+            osc.process(&ctx, &[], &mut output);
+
+            for (i, s) in data.iter().enumerate() {
+                assert!(s.abs() <= 1.0, "{name} sample {i} after note-on is {s}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_pitch_bend_raises_voice_frequency() {
+        let frames = 48_000;
+        let render_voice = |bend: f32| {
+            let mut voices = VoiceAllocator::new(1);
+            voices.set_pitch_bend(bend);
+            voices.note_on(69, 1.0);
+            let voice = voices.active_voices().next().unwrap();
+
+            let mut osc = SineOsc::new();
+            osc.prepare(SAMPLE_RATE, frames);
+            let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0).with_voice(voice);
+            let mut data = vec![0.0f32; frames];
+            let mut output = OutputBuffer::new(&mut data, 1);
+            osc.process(&ctx, &[], &mut output);
+            estimate_freq(&data)
+        };
+
+        let flat = render_voice(0.0);
+        let bent = render_voice(2.0);
+        assert!((flat - 440.0).abs() < 0.5, "flat = {flat}");
+        let ratio = bent / flat;
+        assert!(
+            (ratio - 2.0_f64.powf(2.0 / 12.0)).abs() < 1e-3,
+            "ratio = {ratio}"
+        );
+    }
+
+    #[test]
+    fn test_tuning_sets_a4_frequency() {
+        let frames = 48_000;
+        let mut voices = VoiceAllocator::new(1);
+        voices.note_on(69, 1.0);
+        let voice = voices.active_voices().next().unwrap();
+
+        let mut osc = SineOsc::new();
+        osc.prepare(SAMPLE_RATE, frames);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0)
+            .with_voice(voice)
+            .with_tuning(432.0);
+        let mut data = vec![0.0f32; frames];
+        let mut output = OutputBuffer::new(&mut data, 1);
+        osc.process(&ctx, &[], &mut output);
+
+        let freq = estimate_freq(&data);
+        assert!((freq - 432.0).abs() < 0.5, "freq = {freq}");
+    }
+
+    #[test]
+    fn test_saw_polyblep_suppresses_aliasing() {
+        // Slightly off 2kHz so aliases don't land exactly on harmonics
+        // (48kHz / 2kHz is an integer ratio).
+        let freq = 2_050.0;
+        let mut osc = SawOsc::new();
+        osc.set_param(params::FREQ, freq as f32);
+        let samples = render(&mut osc, 8192);
+
+        let fundamental = dft_magnitude(&samples, freq);
+        let nyquist = SAMPLE_RATE / 2.0;
+
+        // Harmonics above Nyquist fold back to (sample_rate - k * freq)
+        let mut alias_energy = 0.0;
+        let mut k = 1;
+        while k as f64 * freq < SAMPLE_RATE {
+            let harmonic = k as f64 * freq;
+            if harmonic > nyquist {
+                alias_energy += dft_magnitude(&samples, SAMPLE_RATE - harmonic).powi(2);
+            }
+            k += 1;
+        }
+
+        let alias_ratio = alias_energy.sqrt() / fundamental;
+        assert!(
+            alias_ratio < 0.05,
+            "Aliased energy too high: {:.4} of fundamental",
+            alias_ratio
+        );
+    }
+
+    #[test]
+    fn test_square_polyblep_stays_bounded() {
+        let mut osc = SquareOsc::new();
+        osc.set_param(params::FREQ, 2_050.0);
+        osc.set_param(params::PULSE_WIDTH, 0.3);
+        let samples = render(&mut osc, 4096);
+
+        assert!(samples.iter().all(|s| s.abs() <= 1.0 + 1e-3));
+        assert!(samples.iter().any(|&s| s > 0.9));
+        assert!(samples.iter().any(|&s| s < -0.9));
+    }
+
+    #[test]
+    fn test_sub_adds_energy_an_octave_down() {
+        let freq = 440.0;
+        let mut osc = SawOsc::new();
+        osc.set_param(params::FREQ, freq as f32);
+        let plain = render(&mut osc, 8192);
+
+        let mut osc = SawOsc::new();
+        osc.set_param(params::FREQ, freq as f32);
+        osc.set_param(params::SUB_LEVEL, 0.5);
+        osc.set_param(params::SUB_OCTAVE, 1.0);
+        let thick = render(&mut osc, 8192);
+
+        // A plain saw has nothing below its fundamental
+        let fundamental = dft_magnitude(&plain, freq);
+        assert!(dft_magnitude(&plain, freq / 2.0) < 0.01 * fundamental);
+
+        // The sub puts a strong partial at half the fundamental, but not a quarter
+        let sub = dft_magnitude(&thick, freq / 2.0);
+        assert!(
+            sub > 0.5 * fundamental,
+            "sub = {sub}, fundamental = {fundamental}"
+        );
+        assert!(dft_magnitude(&thick, freq / 4.0) < 0.01 * fundamental);
+    }
+
+    #[test]
+    fn test_square_pwm_input_modulates_duty_cycle() {
+        let frames = 48_000;
+        let mut lfo = Lfo::new();
+        lfo.set_param(params::RATE, 1.0);
+        lfo.set_param(params::DEPTH, 0.4);
+        let pwm = render(&mut lfo, frames);
+
+        let mut osc = SquareOsc::new();
+        osc.set_param(params::FREQ, 220.0);
+        osc.prepare(SAMPLE_RATE, frames);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        let pwm_buf = InputBuffer::new(&pwm, 1);
+        let mut data = vec![0.0f32; frames];
+        let mut output = OutputBuffer::new(&mut data, 1);
+        osc.process(&ctx, &[pwm_buf], &mut output);
+
+        // Duty cycle measured over the first and second halves of one LFO cycle
+        let positive_ratio = |samples: &[f32]| {
+            samples.iter().filter(|&&s| s > 0.0).count() as f32 / samples.len() as f32
+        };
+        let first = positive_ratio(&data[..frames / 2]);
+        let second = positive_ratio(&data[frames / 2..]);
+
+        assert!(
+            (first - second).abs() > 0.3,
+            "PWM input should shift the duty cycle: {:.3} vs {:.3}",
+            first,
+            second
+        );
+    }
+
+    #[test]
+    fn test_multi_osc_outputs_each_shape_on_its_own_channel() {
+        let frames = 48_000;
+        let mut voices = VoiceAllocator::new(1);
+        voices.note_on(69, 1.0);
+        let voice = voices.active_voices().next().unwrap();
+
+        let mut osc = MultiOsc::new();
+        osc.set_param(params::SUB_LEVEL, 0.5);
+        osc.prepare(SAMPLE_RATE, frames);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0).with_voice(voice);
+        let mut data = vec![0.0f32; frames * 3];
+        let mut output = OutputBuffer::new(&mut data, 3);
+        osc.process(&ctx, &[], &mut output);
+        let (saw, rest) = data.split_at(frames);
+        let (square, sub) = rest.split_at(frames);
+
+        // Saw and square play the note, the sub an octave below it
+        assert!((estimate_freq(saw) - 440.0).abs() < 0.5);
+        assert!((estimate_freq(square) - 440.0).abs() < 0.5);
+        assert!((estimate_freq(sub) - 220.0).abs() < 0.5);
+
+        // The saw ramps up through each cycle; the square sits at its rails
+        let period = 48_000 / 440;
+        let ramp = &saw[period / 4..period * 3 / 4];
+        assert!(ramp.windows(2).all(|w| w[1] > w[0]));
+        let at_rails = |s: &[f32], level: f32| {
+            s.iter().filter(|x| (x.abs() - level).abs() < 1e-3).count() as f32 / s.len() as f32
+        };
+        assert!(at_rails(square, 1.0) > 0.9);
+
+        // The sub follows its level
+        assert!(at_rails(sub, 0.5) > 0.9);
+        assert!(sub.iter().all(|s| s.abs() <= 0.5 + 1e-3));
+    }
+
+    #[test]
+    fn test_mono_glide_sweeps_frequency() {
+        let block = 480;
+        let mut voices = VoiceAllocator::new(4);
+        voices.set_mode(VoiceMode::Mono);
+        voices.set_glide_time(0.1);
+
+        let mut osc = SineOsc::new();
+        osc.prepare(SAMPLE_RATE, block);
+        let mut render_blocks = |voices: &mut VoiceAllocator, blocks: usize| {
+            let mut out = Vec::with_capacity(blocks * block);
+            for _ in 0..blocks {
+                let voice = voices.active_voices().next().unwrap();
+                let ctx = ProcessContext::new(block, SAMPLE_RATE, 0, 120.0).with_voice(voice);
+                let mut data = vec![0.0f32; block];
+                let mut output = OutputBuffer::new(&mut data, 1);
+                osc.process(&ctx, &[], &mut output);
+                voices.clear_triggers();
+                out.extend(data);
+            }
+            out
+        };
+
+        let first = voices.note_on(60, 1.0);
+        let held = render_blocks(&mut voices, 10);
+        let second = voices.note_on(72, 1.0);
+        assert_eq!(first, second, "mono mode reuses the sounding voice");
+        let glide = render_blocks(&mut voices, 30);
+
+        let c4 = 440.0 * 2.0_f64.powf(-9.0 / 12.0);
+        let c5 = c4 * 2.0;
+        assert!((estimate_freq(&held) - c4).abs() < 1.0);
+
+        // 20 ms windows across the 100 ms glide climb steadily from C4 to C5
+        let window = 960;
+        let sweep: Vec<f64> = (0..5)
+            .map(|w| estimate_freq(&glide[w * window..(w + 1) * window]))
+            .collect();
+        assert!(sweep[0] > c4 + 5.0, "sweep = {sweep:?}");
+        assert!(sweep[4] < c5 - 5.0, "sweep = {sweep:?}");
+        assert!(sweep.windows(2).all(|w| w[1] > w[0]), "sweep = {sweep:?}");
+
+        let settled = estimate_freq(&glide[6 * window..]);
+        assert!((settled - c5).abs() < 1.0, "settled = {settled}");
+    }
+
+    #[test]
+    fn test_unison_starts_detuned_voices() {
+        let mut voices = VoiceAllocator::new(8);
+        voices.set_unison(3, 10.0, 1.0);
+        voices.note_on(69, 1.0);
+
+        let stack: Vec<VoiceContext> = voices.active_voices().collect();
+        assert_eq!(stack.len(), 3);
+
+        let osc = SineOsc::new();
+        let freqs: Vec<f32> = stack
+            .iter()
+            .map(|&v| {
+                let ctx = ProcessContext::new(1, SAMPLE_RATE, 0, 120.0).with_voice(v);
+                osc.effective_freq(&ctx)
+            })
+            .collect();
+        let cents = |f: f32| 1200.0 * (f / 440.0).log2();
+        for (freq, expected) in freqs.iter().zip([-10.0, 0.0, 10.0]) {
+            assert!((cents(*freq) - expected).abs() < 0.01, "freqs = {freqs:?}");
+        }
+        let pans: Vec<f32> = stack.iter().map(|v| v.pan).collect();
+        assert_eq!(pans, [-1.0, 0.0, 1.0]);
+
+        // The whole stack releases with its note
+        voices.note_off(69);
+        assert!(voices.active_voices().all(|v| !v.gate));
     }
 }
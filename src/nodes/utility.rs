@@ -1,7 +1,8 @@
 // Utility nodes (output, mixer, etc.)
 
-use crate::audio_buffer::AudioBuffer;
+use crate::audio_buffer::{InputBuffer, OutputBuffer};
 use crate::node::{Node, ProcessContext};
+use crate::util::convert::db_to_linear;
 
 use super::params;
 
@@ -23,7 +24,7 @@ impl OutputNode {
     }
 
     fn update_linear(&mut self) {
-        self.master_linear = 10.0_f32.powf(self.master_db / 20.0);
+        self.master_linear = db_to_linear(self.master_db);
     }
 }
 
@@ -39,8 +40,8 @@ impl Node for OutputNode {
     fn process(
         &mut self,
         ctx: &ProcessContext,
-        inputs: &[&AudioBuffer],
-        output: &mut AudioBuffer,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
     ) -> bool {
         if inputs.is_empty() {
             output.clear();
@@ -79,3 +80,237 @@ impl Node for OutputNode {
         }
     }
 }
+
+// ═══════════════════════════════════════════════════════════════════
+// Slew Limiter Node (glide for control signals)
+// ═══════════════════════════════════════════════════════════════════
+
+/// Limits how fast a control signal can change, smoothing steps into ramps.
+///
+/// Rise and fall rates are in units per second, so a step of 1.0 with a
+/// rise rate of 10 takes 100 ms to arrive.
+pub struct SlewLimiterNode {
+    rise: f32, // Units per second while rising
+    fall: f32, // Units per second while falling
+    value: f32,
+    sample_rate: f64,
+}
+
+impl SlewLimiterNode {
+    pub fn new() -> Self {
+        Self {
+            rise: 10.0,
+            fall: 10.0,
+            value: 0.0,
+            sample_rate: 48000.0,
+        }
+    }
+}
+
+impl Default for SlewLimiterNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for SlewLimiterNode {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.sample_rate = sample_rate;
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        let input = inputs.first().map(|input| input.channel(0));
+        let max_rise = self.rise / self.sample_rate as f32;
+        let max_fall = self.fall / self.sample_rate as f32;
+
+        for (i, out) in output
+            .channel_mut(0)
+            .iter_mut()
+            .take(ctx.frames)
+            .enumerate()
+        {
+            let target = input.and_then(|c| c.get(i)).copied().unwrap_or(0.0);
+            self.value += (target - self.value).clamp(-max_fall, max_rise);
+            *out = self.value;
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        1
+    }
+
+    fn set_param(&mut self, param_id: u32, value: f32) {
+        match param_id {
+            params::RISE => self.rise = value.max(0.0),
+            params::FALL => self.fall = value.max(0.0),
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        self.value = 0.0;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// DC Blocker Node
+// ═══════════════════════════════════════════════════════════════════
+
+/// Corner frequency of the DC blocker in Hz.
+const DC_BLOCKER_CUTOFF: f64 = 20.0;
+
+/// One-pole high-pass that removes DC offset from a stereo signal.
+///
+/// `y[n] = x[n] - x[n-1] + r * y[n-1]`, with the pole placed for a
+/// `DC_BLOCKER_CUTOFF` corner so audible lows pass untouched.
+pub struct DcBlockerNode {
+    r: f32,
+    x1: [f32; 2],
+    y1: [f32; 2],
+}
+
+impl DcBlockerNode {
+    pub fn new() -> Self {
+        let mut node = Self {
+            r: 0.0,
+            x1: [0.0; 2],
+            y1: [0.0; 2],
+        };
+        node.prepare(48000.0, 0);
+        node
+    }
+}
+
+impl Default for DcBlockerNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for DcBlockerNode {
+    fn prepare(&mut self, sample_rate: f64, _max_block: usize) {
+        self.r = (-std::f64::consts::TAU * DC_BLOCKER_CUTOFF / sample_rate).exp() as f32;
+    }
+
+    fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        inputs: &[InputBuffer],
+        output: &mut OutputBuffer,
+    ) -> bool {
+        if inputs.is_empty() {
+            output.clear();
+            return true;
+        }
+
+        let (in_l, in_r) = inputs[0].stereo();
+        for (ch, input) in [in_l, in_r].into_iter().enumerate() {
+            let out_ch = output.channel_mut(ch);
+            for (i, out) in out_ch.iter_mut().take(ctx.frames).enumerate() {
+                let x = input.get(i).copied().unwrap_or(0.0);
+                let y = x - self.x1[ch] + self.r * self.y1[ch];
+                self.x1[ch] = x;
+                self.y1[ch] = y;
+                *out = y;
+            }
+        }
+
+        false
+    }
+
+    fn num_channels(&self) -> usize {
+        2
+    }
+
+    fn set_param(&mut self, _param_id: u32, _value: f32) {}
+
+    fn reset(&mut self) {
+        self.x1 = [0.0; 2];
+        self.y1 = [0.0; 2];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    #[test]
+    fn test_slew_limiter_step_ramps_at_rise_rate() {
+        let frames = 1_024;
+        let mut node = SlewLimiterNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        node.set_param(params::RISE, 100.0);
+        node.set_param(params::FALL, 1_000.0);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+        let mut data = vec![0.0f32; frames];
+
+        let mut render = |node: &mut SlewLimiterNode, level: f32| {
+            let input = vec![level; frames];
+            let mut output = OutputBuffer::new(&mut data, 1);
+            node.process(&ctx, &[InputBuffer::new(&input, 1)], &mut output);
+            output.channel(0).to_vec()
+        };
+
+        // Rising 1.0 at 100 units/s takes 10 ms, a straight ramp of 480 samples
+        let rise = render(&mut node, 1.0);
+        for (i, &s) in rise.iter().take(479).enumerate() {
+            assert!(
+                (s - (i + 1) as f32 / 480.0).abs() < 1e-4,
+                "sample {i} = {s}"
+            );
+        }
+        let arrived = rise.iter().position(|&s| s == 1.0).unwrap();
+        assert!((479..=480).contains(&arrived), "arrived at {arrived}");
+        assert!(rise[arrived..].iter().all(|&s| s == 1.0));
+
+        // Falling back at 1000 units/s takes 48 samples
+        let fall = render(&mut node, 0.0);
+        let arrived = fall.iter().position(|&s| s == 0.0).unwrap();
+        assert!((47..=48).contains(&arrived), "arrived at {arrived}");
+    }
+
+    #[test]
+    fn test_dc_blocker_removes_offset() {
+        let frames = 4_800;
+        let mut node = DcBlockerNode::new();
+        node.prepare(SAMPLE_RATE, frames);
+        let ctx = ProcessContext::new(frames, SAMPLE_RATE, 0, 120.0);
+
+        // 100 Hz sine riding on a +0.3 offset, one 100 ms block at a time
+        let mut pos = 0;
+        let mut block = || {
+            let input: Vec<f32> = (pos..pos + frames)
+                .map(|i| {
+                    let t = i as f64 / SAMPLE_RATE;
+                    0.3 + 0.5 * (std::f64::consts::TAU * 100.0 * t).sin() as f32
+                })
+                .collect();
+            pos += frames;
+            let mut data = vec![0.0f32; frames * 2];
+            let mut output = OutputBuffer::new(&mut data, 2);
+            node.process(&ctx, &[InputBuffer::new(&input, 1)], &mut output);
+            data
+        };
+        let mean = |s: &[f32]| s.iter().sum::<f32>() / s.len() as f32;
+
+        // The offset is gone within the first 100 ms, on both channels
+        block();
+        let settled = block();
+        let (left, right) = settled.split_at(frames);
+        assert!(mean(left).abs() < 0.01, "mean = {}", mean(left));
+        assert!(mean(right).abs() < 0.01, "mean = {}", mean(right));
+
+        // The sine itself passes at close to full level
+        let peak = left.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.03, "peak = {peak}");
+    }
+}
@@ -0,0 +1,533 @@
+// Offline rendering.
+//
+// Drives the scheduler, clip playback and engine in a tight loop on the
+// calling thread, so an arrangement can be bounced faster than real time.
+// There is no audio thread, so plans are compiled straight into a single
+// ExecutionPlan instead of going through the PlanHandoff double buffer.
+//
+// Freezing builds on this: a track is bounced on its own and the result
+// plays back through an audio player node in place of its clips.
+
+use crate::clip_playback::ClipPlayback;
+use crate::compile::{CompileError, compile};
+use crate::engine::Engine;
+use crate::event::MusicalEvent;
+use crate::execution_plan::ExecutionPlan;
+use crate::node_factory::NodeRegistry;
+use crate::nodes::{SharedAudioData, node_types};
+use crate::scheduler::Scheduler;
+use crate::state::{Arrangement, ClipId, FrozenTrack, Session, TrackId, TuningTable};
+use crate::util::convert::beats_to_samples;
+use crate::voice_allocator::VoiceAllocator;
+
+/// Render `start_beat..end_beat` of a session to interleaved stereo.
+///
+/// The session's graph is compiled fresh and its playing clips are
+/// launched at `start_beat`. Blocks are always `session.max_block_size`
+/// frames, so the result depends only on the session and arguments.
+pub fn render_offline(
+    session: &Session,
+    registry: &NodeRegistry,
+    start_beat: f64,
+    end_beat: f64,
+    sample_rate: f64,
+) -> Result<Vec<f32>, CompileError> {
+    let max_block = session.max_block_size.max(1);
+    let max_voices = session.max_voices;
+
+    let mut graph = compile(&session.graph, registry, max_block, max_voices)?;
+    graph.prepare(sample_rate);
+    graph.set_tuning(session.transport.tuning);
+    graph.set_note_ratios(session.tuning_table.as_ref().map(TuningTable::note_ratios));
+    for entry in session.arrangement.audio_pool.iter() {
+        graph.load_audio_to_all(SharedAudioData::from_pool_entry(entry));
+    }
+
+    let mut voices = VoiceAllocator::new(max_voices);
+    voices.set_mode(session.voice_mode);
+    voices.set_glide_time(session.glide_time);
+    voices.set_steal_mode(session.voice_steal_mode);
+    voices.set_unison(
+        session.unison_voices,
+        session.unison_detune,
+        session.unison_spread,
+    );
+    voices.set_pan_spread(session.voice_pan_spread);
+    let mut engine = Engine::new(graph, voices);
+
+    let transport = &session.transport;
+    let bpm = transport.bpm;
+    let mut scheduler = Scheduler::new(sample_rate);
+    scheduler.set_bpm(bpm);
+    scheduler.seek(start_beat);
+    if transport.loop_enabled {
+        scheduler.set_loop(Some((transport.loop_start, transport.loop_end)));
+    }
+    if session.metronome_enabled {
+        scheduler.set_metronome(Some(transport.beats_per_bar.max(1)));
+    }
+
+    let arrangement = &session.arrangement;
+    let mut clips = ClipPlayback::new(sample_rate);
+    clips.set_beats_per_bar(transport.beats_per_bar as f64);
+    clips.sync_with_arrangement(arrangement, start_beat);
+
+    let beats_per_sample = bpm / 60.0 / sample_rate;
+    let total_frames =
+        beats_to_samples((end_beat - start_beat).max(0.0), bpm, sample_rate).round() as usize;
+    let mut output = vec![0.0; total_frames * 2];
+    let mut plan = ExecutionPlan::new(sample_rate);
+    let mut events = Vec::new();
+
+    let mut offset = 0;
+    while offset < total_frames {
+        let frames = (total_frames - offset).min(max_block);
+
+        events.clear();
+        block_events(
+            &mut clips,
+            &scheduler,
+            arrangement,
+            frames as f64 * beats_per_sample,
+            frames,
+            bpm,
+            &mut events,
+        );
+
+        scheduler.compile_plan(&mut plan, frames, &events);
+        engine.process_plan(&plan);
+
+        let out = &mut output[offset * 2..(offset + frames) * 2];
+        if let Some(rendered) = engine.output_buffer(frames) {
+            // Planar to interleaved; mono output goes to both channels
+            let right = if engine.output_channels() >= 2 {
+                frames
+            } else {
+                0
+            };
+            for (i, frame) in out.chunks_exact_mut(2).enumerate() {
+                frame[0] = rendered[i];
+                frame[1] = rendered[right + i];
+            }
+        }
+
+        offset += frames;
+    }
+
+    Ok(output)
+}
+
+/// Collect clip events for the scheduler's next block of `frames`.
+///
+/// A block that crosses the loop end is generated in two parts, with the
+/// clips jumped back to the loop start in between.
+fn block_events(
+    clips: &mut ClipPlayback,
+    scheduler: &Scheduler,
+    arrangement: &Arrangement,
+    block_beats: f64,
+    frames: usize,
+    bpm: f64,
+    events: &mut Vec<MusicalEvent>,
+) {
+    let block_start = scheduler.beat_position();
+
+    match (scheduler.loop_wrap_offset(frames), scheduler.loop_region()) {
+        (Some(_), Some((loop_start, loop_end))) => {
+            let remaining = block_beats - (loop_end - block_start);
+            events.extend_from_slice(clips.generate_events(
+                arrangement,
+                block_start,
+                loop_end,
+                bpm,
+            ));
+            events.extend_from_slice(clips.wrap_loop(loop_start, arrangement));
+            events.extend_from_slice(clips.generate_events(
+                arrangement,
+                loop_start,
+                loop_start + remaining,
+                bpm,
+            ));
+        }
+        _ => {
+            let block_end = block_start + block_beats;
+            events.extend_from_slice(clips.generate_events(
+                arrangement,
+                block_start,
+                block_end,
+                bpm,
+            ));
+        }
+    }
+}
+
+impl Session {
+    /// Render a track to audio and play that back in place of its clips.
+    ///
+    /// Only the track's playing clip is rendered, from beat 0 to
+    /// `end_beat`, through the session's graph. The rendered clip becomes
+    /// the track's playing clip and the track is routed to a new audio
+    /// player on the output node.
+    ///
+    /// Returns the rendered clip, or `None` if the track doesn't exist or
+    /// is already frozen.
+    pub fn freeze_track(
+        &mut self,
+        track_id: TrackId,
+        registry: &NodeRegistry,
+        end_beat: f64,
+    ) -> Result<Option<ClipId>, CompileError> {
+        let Some(track) = self.arrangement.get_track(track_id) else {
+            return Ok(None);
+        };
+        if track.frozen.is_some() {
+            return Ok(None);
+        }
+        let name = format!("{} Freeze", track.name);
+        let target_node = track.target_node;
+
+        let solo = self.isolate_track(track_id);
+        let samples = render_offline(&solo, registry, 0.0, end_beat, self.sample_rate)?;
+
+        let arrangement = &mut self.arrangement;
+        let audio_id = arrangement.add_audio_to_pool(name, self.sample_rate, 2, samples);
+        let Some(clip_id) = arrangement.create_clip_from_audio(audio_id, self.transport.bpm) else {
+            return Ok(None);
+        };
+        if let Some(clip) = arrangement.get_clip_mut(clip_id) {
+            clip.looping = false;
+        }
+        let playing_clip = arrangement.playing_clips.insert(track_id, clip_id);
+
+        let player_node = self.graph.add_node(node_types::AUDIO_PLAYER);
+        if let Some(output) = self.graph.output_node {
+            self.graph.connect(player_node, 0, output, 0);
+        }
+
+        if let Some(track) = self.arrangement.get_track_mut(track_id) {
+            track.target_node = Some(player_node);
+            track.frozen = Some(FrozenTrack {
+                audio_id,
+                clip_id,
+                player_node,
+                target_node,
+                playing_clip,
+            });
+        }
+        Ok(Some(clip_id))
+    }
+
+    /// Put a frozen track's clip and routing back.
+    ///
+    /// The rendered clip, its audio and its player node are removed.
+    /// Returns false if the track isn't frozen.
+    pub fn unfreeze_track(&mut self, track_id: TrackId) -> bool {
+        let Some(frozen) = self
+            .arrangement
+            .get_track_mut(track_id)
+            .and_then(|track| track.frozen.take())
+        else {
+            return false;
+        };
+
+        let arrangement = &mut self.arrangement;
+        arrangement.delete_clip(frozen.clip_id);
+        arrangement.remove_audio(frozen.audio_id);
+        if let Some(clip_id) = frozen.playing_clip {
+            arrangement.playing_clips.insert(track_id, clip_id);
+        }
+        if let Some(track) = arrangement.get_track_mut(track_id) {
+            track.target_node = frozen.target_node;
+        }
+        self.graph.remove_node(frozen.player_node);
+        true
+    }
+
+    /// Copy of the session with only `track_id`'s clip playing.
+    fn isolate_track(&self, track_id: TrackId) -> Session {
+        let mut solo = self.clone();
+        solo.metronome_enabled = false;
+        solo.arrangement
+            .playing_clips
+            .retain(|&playing, _| playing == track_id);
+        solo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::{params, register_standard_nodes};
+    use crate::state::NoteDef;
+
+    #[test]
+    fn test_render_offline_clip() {
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        let mut session = Session::new("Bounce");
+        let osc = session.graph.add_node(node_types::SINE_OSC);
+        let env = session.graph.add_node(node_types::ADSR_ENV);
+        let out = session.graph.add_node(node_types::OUTPUT);
+        session.graph.connect(osc, 0, env, 0);
+        session.graph.connect(env, 0, out, 0);
+        session.graph.output_node = Some(out);
+        session.graph.set_param(env, params::RELEASE, 0.05);
+
+        let track = session.arrangement.create_track("Lead");
+        session.arrangement.set_track_target(track, Some(osc));
+        let clip = session.arrangement.create_clip("Note", 2.0);
+        session
+            .arrangement
+            .add_note_to_clip(clip, NoteDef::new(0.0, 1.0, 69, 1.0));
+        session.arrangement.get_clip_mut(clip).unwrap().looping = false;
+        session.arrangement.launch_clip(track, clip);
+
+        let sample_rate = 48_000.0;
+        let rendered = render_offline(&session, &registry, 0.0, 2.0, sample_rate).unwrap();
+
+        // 2 beats at 120 BPM = 1 second of stereo frames
+        assert_eq!(rendered.len(), 48_000 * 2);
+
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        // The note sounds during its first beat and has released by the end
+        assert!(peak(&rendered[..48_000]) > 0.5);
+        assert!(peak(&rendered[90_000..]) < 1e-3);
+        // Mono output lands on both channels
+        assert!(rendered.chunks_exact(2).all(|f| f[0] == f[1]));
+
+        // Block size only changes how the work is chunked
+        session.max_block_size = 100;
+        let rechunked = render_offline(&session, &registry, 0.0, 2.0, sample_rate).unwrap();
+        let max_diff = rendered
+            .iter()
+            .zip(&rechunked)
+            .fold(0.0f32, |m, (a, b)| m.max((a - b).abs()));
+        assert!(max_diff < 1e-4, "max_diff = {max_diff}");
+    }
+
+    #[test]
+    fn test_voice_pan_spread_by_note() {
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        let mut session = Session::new("Spread");
+        let osc = session.graph.add_node(node_types::SINE_OSC);
+        let env = session.graph.add_node(node_types::ADSR_ENV);
+        let out = session.graph.add_node(node_types::OUTPUT);
+        session.graph.connect(osc, 0, env, 0);
+        session.graph.connect(env, 0, out, 0);
+        session.graph.output_node = Some(out);
+
+        let track = session.arrangement.create_track("Chord");
+        session.arrangement.set_track_target(track, Some(osc));
+        let clip = session.arrangement.create_clip("Low and high", 2.0);
+        for note in [36, 84] {
+            session
+                .arrangement
+                .add_note_to_clip(clip, NoteDef::new(0.0, 2.0, note, 1.0));
+        }
+        session.arrangement.launch_clip(track, clip);
+
+        // Half a second of the held chord, de-interleaved
+        let render = |session: &Session| {
+            let rendered = render_offline(session, &registry, 0.0, 1.0, 48_000.0).unwrap();
+            let left: Vec<f32> = rendered.iter().step_by(2).copied().collect();
+            let right: Vec<f32> = rendered.iter().skip(1).step_by(2).copied().collect();
+            (left, right)
+        };
+        let crossings = |s: &[f32]| {
+            s.windows(2)
+                .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+                .count()
+        };
+
+        // Centered voices land identically on both sides
+        let (left, right) = render(&session);
+        assert_eq!(left, right);
+
+        // Full spread puts note 36 (65 Hz) hard left and note 84 (1047 Hz)
+        // hard right
+        session.voice_pan_spread = 1.0;
+        let (left, right) = render(&session);
+        assert!(crossings(&left) < 100, "left = {}", crossings(&left));
+        assert!(crossings(&right) > 900, "right = {}", crossings(&right));
+    }
+
+    #[test]
+    fn test_metronome_clicks_on_beats() {
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        let mut session = Session::new("Click");
+        let out = session.graph.add_node(node_types::OUTPUT);
+        session.graph.output_node = Some(out);
+        session.metronome_enabled = true;
+        session.transport.beats_per_bar = 4;
+
+        let rendered = render_offline(&session, &registry, 0.0, 2.0, 48_000.0).unwrap();
+        let left: Vec<f32> = rendered.iter().step_by(2).copied().collect();
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+
+        // One burst at each beat (24000 frames apart), silence in between
+        let click = 1_440; // 30 ms
+        let downbeat = peak(&left[..click]);
+        let beat_two = peak(&left[24_000..24_000 + click]);
+        assert!(downbeat > 0.1 && beat_two > 0.1);
+        assert!(left[0].abs() < 1e-6 && left[24_000].abs() < 1e-6);
+        assert!(left[1].abs() > 0.0 && left[24_001].abs() > 0.0);
+        assert!(peak(&left[click..24_000]) < 1e-6);
+        assert!(peak(&left[24_000 + click..]) < 1e-6);
+
+        // The bar downbeat is accented
+        assert!(downbeat > beat_two);
+    }
+
+    #[test]
+    fn test_loop_region_wraps_and_retriggers() {
+        use crate::event::Event;
+
+        let mut arrangement = Arrangement::new();
+        let track = arrangement.create_track("Loop");
+        arrangement.set_track_target(track, Some(100));
+        let clip = arrangement.create_clip("Phrase", 8.0);
+        arrangement.add_note_to_clip(clip, NoteDef::new(0.0, 0.5, 60, 1.0));
+        // Still sounding when the loop wraps at beat 4
+        arrangement.add_note_to_clip(clip, NoteDef::new(3.0, 2.0, 67, 1.0));
+        arrangement.launch_clip(track, clip);
+
+        let sample_rate = 48_000.0;
+        let mut scheduler = Scheduler::new(sample_rate);
+        scheduler.set_bpm(120.0);
+        scheduler.set_loop(Some((0.0, 4.0)));
+        let mut clips = ClipPlayback::new(sample_rate);
+        clips.sync_with_arrangement(&arrangement, 0.0);
+
+        // 700-frame blocks, so the wrap at frame 96000 lands mid-block
+        let frames = 700;
+        let beats_per_sample = 120.0 / 60.0 / sample_rate;
+        let mut plan = ExecutionPlan::new(sample_rate);
+        let mut events = Vec::new();
+        let mut note_events = Vec::new();
+        let mut slice_starts = Vec::new();
+        let blocks = 6 * 24_000 / frames;
+        for _ in 0..blocks {
+            events.clear();
+            let block_beats = frames as f64 * beats_per_sample;
+            block_events(
+                &mut clips,
+                &scheduler,
+                &arrangement,
+                block_beats,
+                frames,
+                120.0,
+                &mut events,
+            );
+            scheduler.compile_plan(&mut plan, frames, &events);
+
+            for slice in &plan.slices {
+                let frame = plan.block_start_sample as usize + slice.frame_offset;
+                slice_starts.push(frame);
+                for event in &slice.events {
+                    match *event {
+                        Event::NoteOnTarget { note, .. } => note_events.push((frame, note, true)),
+                        Event::NoteOffTarget { note, .. } => note_events.push((frame, note, false)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        // Almost 6 beats of playback, wrapped once at beat 4
+        let played = (blocks * frames) as f64 * beats_per_sample;
+        assert!((scheduler.beat_position() - (played - 4.0)).abs() < 1e-9);
+        assert!(slice_starts.contains(&96_000));
+        let expected = [
+            (0, 60, true),
+            (12_000, 60, false),
+            (72_000, 67, true),
+            // The held note is released before the loop start retriggers
+            (96_000, 67, false),
+            (96_000, 60, true),
+            (108_000, 60, false),
+        ];
+        assert_eq!(note_events.len(), expected.len(), "{note_events:?}");
+        for (got, want) in note_events.iter().zip(&expected) {
+            // Beat-to-sample conversion truncates, so allow a frame of slack
+            assert_eq!((got.1, got.2), (want.1, want.2), "{note_events:?}");
+            assert!(got.0.abs_diff(want.0) <= 1, "{note_events:?}");
+        }
+    }
+
+    #[test]
+    fn test_freeze_matches_track_render() {
+        let mut registry = NodeRegistry::new();
+        register_standard_nodes(&mut registry);
+
+        let mut session = Session::new("Freeze");
+        let out = session.graph.add_node(node_types::OUTPUT);
+        session.graph.output_node = Some(out);
+
+        // Two instruments on their own tracks, one note each
+        let mut tracks = Vec::new();
+        for (name, note) in [("Lead", 69), ("Bass", 45)] {
+            let osc = session.graph.add_node(node_types::SINE_OSC);
+            let env = session.graph.add_node(node_types::ADSR_ENV);
+            session.graph.connect(osc, 0, env, 0);
+            session.graph.connect(env, 0, out, 0);
+            session.graph.set_param(env, params::RELEASE, 0.05);
+
+            let track = session.arrangement.create_track(name);
+            session.arrangement.set_track_target(track, Some(osc));
+            let clip = session.arrangement.create_clip(name, 2.0);
+            session
+                .arrangement
+                .add_note_to_clip(clip, NoteDef::new(0.0, 1.0, note, 1.0));
+            session.arrangement.get_clip_mut(clip).unwrap().looping = false;
+            session.arrangement.launch_clip(track, clip);
+            tracks.push((track, osc, clip));
+        }
+        let (lead, lead_osc, lead_clip) = tracks[0];
+        let (bass, _, _) = tracks[1];
+
+        // The lead on its own
+        let mut direct = session.clone();
+        direct.arrangement.stop_clip(bass);
+        let expected = render_offline(&direct, &registry, 0.0, 2.0, 48_000.0).unwrap();
+
+        let clip_id = session.freeze_track(lead, &registry, 2.0).unwrap().unwrap();
+        assert_eq!(session.freeze_track(lead, &registry, 2.0).unwrap(), None);
+
+        let arrangement = &session.arrangement;
+        let frozen = arrangement.get_track(lead).unwrap().frozen.clone().unwrap();
+        let entry = arrangement.get_audio(frozen.audio_id).unwrap();
+        assert_eq!(entry.name, "Lead Freeze");
+        assert_eq!(entry.samples.as_slice(), expected.as_slice());
+        assert_eq!(arrangement.playing_clips[&lead], clip_id);
+        assert_eq!(
+            arrangement.get_track(lead).unwrap().target_node,
+            Some(frozen.player_node)
+        );
+
+        // Playing the frozen track back sounds like the instrument did
+        let mut playback = session.clone();
+        playback.arrangement.stop_clip(bass);
+        let rendered = render_offline(&playback, &registry, 0.0, 2.0, 48_000.0).unwrap();
+        let max_diff = rendered
+            .iter()
+            .zip(&expected)
+            .fold(0.0f32, |m, (a, b)| m.max((a - b).abs()));
+        assert!(max_diff < 1e-4, "max_diff = {max_diff}");
+
+        assert!(session.unfreeze_track(lead));
+        assert!(!session.unfreeze_track(lead));
+        let arrangement = &session.arrangement;
+        let track = arrangement.get_track(lead).unwrap();
+        assert_eq!(track.target_node, Some(lead_osc));
+        assert_eq!(track.frozen, None);
+        assert_eq!(arrangement.playing_clips[&lead], lead_clip);
+        assert!(arrangement.get_clip(clip_id).is_none());
+        assert_eq!(arrangement.audio_pool.iter().count(), 0);
+        assert!(session.graph.get_node(frozen.player_node).is_none());
+    }
+}
@@ -1,5 +1,6 @@
+use crate::arpeggiator::{ArpSettings, Arpeggiator};
 use crate::event::{Event, MusicalEvent};
-use crate::execution_plan::SlicePlan;
+use crate::execution_plan::ExecutionPlan;
 use crate::plan_handoff::PlanHandoff;
 use crate::transport::MusicalTransport;
 
@@ -16,6 +17,15 @@ pub struct Scheduler {
 
     /// Pre-allocated scratch for compiled events per slice
     compiled_scratch: Vec<Event>,
+
+    /// Beats per bar while the metronome is on
+    metronome: Option<u32>,
+
+    /// Plays the notes held while the arpeggiator is on
+    arpeggiator: Arpeggiator,
+
+    /// Pre-allocated scratch for the arpeggiator's notes
+    arp_scratch: Vec<MusicalEvent>,
 }
 
 impl Scheduler {
@@ -24,18 +34,32 @@ impl Scheduler {
             musical_transport: MusicalTransport::new(sample_rate),
             event_scratch: Vec::with_capacity(64),
             compiled_scratch: Vec::with_capacity(16),
+            metronome: None,
+            arpeggiator: Arpeggiator::new(),
+            arp_scratch: Vec::with_capacity(16),
         }
     }
 
-    /// Compile the next audio block.
+    /// Compile the next audio block and publish it to the audio thread.
     pub fn compile_block(
         &mut self,
         handoff: &mut PlanHandoff,
         block_frames: usize,
         musical_events: &[MusicalEvent],
     ) {
-        let plan = handoff.write_plan();
+        self.compile_plan(handoff.write_plan(), block_frames, musical_events);
+        handoff.publish();
+    }
 
+    /// Compile the next audio block into `plan`.
+    ///
+    /// Used directly when the engine runs on the same thread (offline render).
+    pub fn compile_plan(
+        &mut self,
+        plan: &mut ExecutionPlan,
+        block_frames: usize,
+        musical_events: &[MusicalEvent],
+    ) {
         let block_start_sample = self.musical_transport.sample_position();
         let block_end_sample = block_start_sample + block_frames as u64;
 
@@ -43,7 +67,7 @@ impl Scheduler {
         plan.block_frames = block_frames;
         plan.bpm = self.musical_transport.bpm();
         plan.sample_rate = self.musical_transport.sample_rate();
-        plan.slices.clear();
+        plan.clear_slices();
 
         // Sort events by sample position using scratch buffer
         self.event_scratch.clear();
@@ -54,13 +78,19 @@ impl Scheduler {
                 }
             }
         }
+        if let Some(beats_per_bar) = self.metronome {
+            self.push_clicks(block_frames, beats_per_bar);
+        }
+        self.push_arp_notes(block_frames);
         self.event_scratch.sort_by_key(|(pos, _)| *pos);
 
+        // A loop wrap inside the block always starts a new slice
+        let wrap_frame = self.musical_transport.loop_wrap_offset(block_frames);
+
         // If no events, emit single slice for whole block
-        if self.event_scratch.is_empty() {
-            plan.slices.push(SlicePlan::new(0, block_frames));
+        if self.event_scratch.is_empty() && wrap_frame.is_none() {
+            plan.push_slice(0, block_frames);
             self.musical_transport.advance_samples(block_frames);
-            handoff.publish();
             return;
         }
 
@@ -86,21 +116,24 @@ impl Scheduler {
                 }
             }
 
-            // Find next event boundary (or end of block)
-            let next_boundary_frame = self
+            // Find next event or loop boundary (or end of block)
+            let mut next_boundary_frame = self
                 .event_scratch
                 .get(event_index)
                 .map(|(pos, _)| (*pos - block_start_sample) as usize)
                 .unwrap_or(block_frames);
+            if let Some(wrap) = wrap_frame.filter(|&wrap| wrap > cursor_frame) {
+                next_boundary_frame = next_boundary_frame.min(wrap);
+            }
 
             let slice_end_frame = next_boundary_frame.min(block_frames);
             let slice_frames = slice_end_frame - cursor_frame;
 
             // Emit slice (may have 0 events)
             if slice_frames > 0 {
-                let mut slice = SlicePlan::new(cursor_frame, slice_frames);
-                slice.events.extend(self.compiled_scratch.drain(..));
-                plan.slices.push(slice);
+                plan.push_slice(cursor_frame, slice_frames)
+                    .events
+                    .extend(self.compiled_scratch.drain(..));
                 cursor_frame = slice_end_frame;
             } else {
                 // Events at same position as end - attach to last slice if possible
@@ -122,8 +155,59 @@ impl Scheduler {
             plan.slices.iter().map(|s| s.frame_count).sum::<usize>(),
             plan.block_frames
         );
+    }
 
-        handoff.publish();
+    /// Queue a metronome click for every beat that starts in the next block.
+    fn push_clicks(&mut self, block_frames: usize, beats_per_bar: u32) {
+        let transport = &self.musical_transport;
+        let start = transport.beat_position();
+        let block_beats = block_frames as f64 * transport.bpm() / 60.0 / transport.sample_rate();
+        let block_end_sample = transport.sample_position() + block_frames as u64;
+
+        // A wrapping block covers the tail of the loop, then its head
+        let wrap = transport.loop_wrap_offset(block_frames);
+        let ranges = match (wrap, transport.loop_region()) {
+            (Some(_), Some((loop_start, loop_end))) => [
+                (start, loop_end),
+                (loop_start, loop_start + block_beats - (loop_end - start)),
+            ],
+            _ => [(start, start + block_beats), (0.0, 0.0)],
+        };
+
+        for (from, to) in ranges {
+            let mut beat = from.ceil();
+            while beat < to {
+                let accent = (beat as i64).rem_euclid(beats_per_bar.max(1) as i64) == 0;
+                let event = MusicalEvent::Click { beat, accent };
+                let pos = transport.event_sample_position(&event);
+                if let Some(pos) = pos.filter(|&pos| pos < block_end_sample) {
+                    self.event_scratch.push((pos, event));
+                }
+                beat += 1.0;
+            }
+        }
+    }
+
+    /// Queue the arpeggiator's notes for the next block.
+    fn push_arp_notes(&mut self, block_frames: usize) {
+        let transport = &self.musical_transport;
+        let start = transport.beat_position();
+        let block_beats = block_frames as f64 * transport.bpm() / 60.0 / transport.sample_rate();
+        let block_start_sample = transport.sample_position();
+        let block_end_sample = block_start_sample + block_frames as u64;
+
+        // The pattern follows the transport's beats but not its loop, so a
+        // note the transport can't place is played at the block start
+        // rather than dropped, which could leave it hanging
+        self.arp_scratch.clear();
+        self.arpeggiator
+            .generate_events(start, start + block_beats, &mut self.arp_scratch);
+        for event in self.arp_scratch.drain(..) {
+            let pos = transport
+                .event_sample_position(&event)
+                .map_or(block_start_sample, |pos| pos.min(block_end_sample - 1));
+            self.event_scratch.push((pos, event));
+        }
     }
 
     /// Convert a musical event into an engine event.
@@ -153,6 +237,10 @@ impl Scheduler {
                 note: *note,
             }),
 
+            MusicalEvent::PitchBend { semitones, .. } => Some(Event::PitchBend {
+                semitones: *semitones,
+            }),
+
             MusicalEvent::ParamChange {
                 node_id,
                 param_id,
@@ -170,6 +258,7 @@ impl Scheduler {
                 start_sample,
                 duration_samples,
                 gain,
+                pitch_shift,
                 ..
             } => Some(Event::AudioStart {
                 node_id: *node_id,
@@ -177,6 +266,7 @@ impl Scheduler {
                 start_sample: *start_sample,
                 duration_samples: *duration_samples,
                 gain: *gain,
+                pitch_shift: *pitch_shift,
             }),
 
             MusicalEvent::AudioStop {
@@ -185,6 +275,8 @@ impl Scheduler {
                 node_id: *node_id,
                 audio_id: *audio_id,
             }),
+
+            MusicalEvent::Click { accent, .. } => Some(Event::Click { accent: *accent }),
         }
     }
 
@@ -202,4 +294,95 @@ impl Scheduler {
     pub fn set_bpm(&mut self, bpm: f64) {
         self.musical_transport.set_bpm(bpm);
     }
+
+    /// Glide to `target_bpm` over the next `over_beats` beats.
+    pub fn ramp_tempo(&mut self, target_bpm: f64, over_beats: f64) {
+        self.musical_transport.ramp_tempo(target_bpm, over_beats);
+    }
+
+    /// Set the transport loop region in beats (`None` disables looping).
+    pub fn set_loop(&mut self, region: Option<(f64, f64)>) {
+        self.musical_transport.set_loop(region);
+    }
+
+    /// The active loop region, if looping is enabled.
+    pub fn loop_region(&self) -> Option<(f64, f64)> {
+        self.musical_transport.loop_region()
+    }
+
+    /// Click on every beat, accenting each bar of `beats_per_bar` beats
+    /// (`None` turns the metronome off).
+    pub fn set_metronome(&mut self, beats_per_bar: Option<u32>) {
+        self.metronome = beats_per_bar;
+    }
+
+    /// Arpeggiate `held` notes (one bit per MIDI note) with `settings`
+    /// (`None` turns the arpeggiator off).
+    pub fn set_arpeggiator(
+        &mut self,
+        settings: Option<ArpSettings>,
+        held: [u64; 2],
+        velocity: f32,
+    ) {
+        self.arpeggiator.set_settings(settings);
+        self.arpeggiator.set_held(held, velocity);
+    }
+
+    /// Frames until the transport wraps, if it wraps within `block_frames`.
+    pub fn loop_wrap_offset(&self, block_frames: usize) -> Option<usize> {
+        self.musical_transport.loop_wrap_offset(block_frames)
+    }
+
+    /// Move the transport to a beat position at the current tempo.
+    pub fn seek(&mut self, beat: f64) {
+        self.musical_transport.seek(beat);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_plan_reuses_storage() {
+        let sample_rate = 48_000.0;
+        let block_frames = 512;
+        let mut scheduler = Scheduler::new(sample_rate);
+        let mut plan = ExecutionPlan::new(sample_rate);
+        let beats_per_sample = 120.0 / 60.0 / sample_rate;
+
+        // Four notes inside every block, so each plan has five slices
+        let compile = |scheduler: &mut Scheduler, plan: &mut ExecutionPlan| {
+            let start = scheduler.beat_position();
+            let events: Vec<_> = (1..=4)
+                .map(|k| MusicalEvent::NoteOn {
+                    beat: start + (k * 100) as f64 * beats_per_sample,
+                    note: 60 + k as u8,
+                    velocity: 1.0,
+                })
+                .collect();
+            scheduler.compile_plan(plan, block_frames, &events);
+            assert_eq!(plan.slices.len(), 5);
+        };
+
+        // Warm up, then snapshot where every buffer lives
+        for _ in 0..4 {
+            compile(&mut scheduler, &mut plan);
+        }
+        let storage = |plan: &ExecutionPlan| {
+            let slices = (plan.slices.as_ptr(), plan.slices.capacity());
+            let events: Vec<_> = plan
+                .slices
+                .iter()
+                .map(|s| (s.events.as_ptr(), s.events.capacity()))
+                .collect();
+            (slices, events)
+        };
+        let warm = storage(&plan);
+
+        for _ in 0..1_000 {
+            compile(&mut scheduler, &mut plan);
+            assert_eq!(storage(&plan), warm);
+        }
+    }
 }
@@ -7,8 +7,9 @@
 use std::collections::HashMap;
 
 use super::clip::{
-    AudioPool, AudioPoolEntry, AudioPoolId, AudioRegionDef, ClipDef, ClipId, NoteDef,
+    AudioPool, AudioPoolEntry, AudioPoolId, AudioRegionDef, ClipDef, ClipEvent, ClipId, NoteDef,
 };
+use super::{MAX_AUX_BUSES, NodeId};
 
 /// Unique identifier for a track.
 pub type TrackId = u32;
@@ -16,6 +17,9 @@ pub type TrackId = u32;
 /// Unique identifier for a scene.
 pub type SceneId = u32;
 
+/// Unique identifier for an aux return bus.
+pub type AuxId = u32;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Tracks
 // ═══════════════════════════════════════════════════════════════════════════
@@ -27,6 +31,7 @@ pub type SceneId = u32;
 /// - Contain clips in the session view (clip slots)
 /// - Contain clip placements in the arrangement view
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrackDef {
     /// Unique track ID.
     pub id: TrackId,
@@ -58,6 +63,84 @@ pub struct TrackDef {
     /// Clip slots for session view (index = scene index).
     /// None means empty slot.
     pub clip_slots: Vec<Option<ClipId>>,
+
+    /// Group track this track is submixed into (None = master bus).
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub parent_track: Option<TrackId>,
+
+    /// Post-fader sends to aux return buses.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub sends: Vec<TrackSend>,
+
+    /// What the track's clips were before freezing, if frozen.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub frozen: Option<FrozenTrack>,
+
+    /// Note ranges routed to their own targets; empty plays everything on
+    /// `target_node`.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub key_zones: Vec<KeyZone>,
+}
+
+/// A note range a track routes to its own target, for splits and layers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyZone {
+    /// Lowest note in the zone (inclusive).
+    pub low_note: u8,
+
+    /// Highest note in the zone (inclusive).
+    pub high_note: u8,
+
+    /// The node notes in this zone play.
+    pub target_node: NodeId,
+
+    /// Semitones added to notes in this zone.
+    pub transpose: i8,
+}
+
+impl KeyZone {
+    /// The note `note` plays in this zone, or None if it falls outside it
+    /// or is transposed out of the MIDI range.
+    pub fn map_note(&self, note: u8) -> Option<u8> {
+        if note < self.low_note || note > self.high_note {
+            return None;
+        }
+        u8::try_from(note as i16 + self.transpose as i16)
+            .ok()
+            .filter(|n| *n < 128)
+    }
+}
+
+/// A track's send to an aux return bus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackSend {
+    /// The aux bus receiving the send.
+    pub aux_id: AuxId,
+
+    /// Send level (0.0 - 1.0).
+    pub level: f32,
+}
+
+/// What a frozen track replaced, kept for unfreezing.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrozenTrack {
+    /// Rendered audio in the pool.
+    pub audio_id: AudioPoolId,
+
+    /// Clip holding the rendered audio.
+    pub clip_id: ClipId,
+
+    /// Audio player node the rendered clip plays through.
+    pub player_node: NodeId,
+
+    /// Node the track targeted before freezing.
+    pub target_node: Option<NodeId>,
+
+    /// Clip the track was playing before freezing.
+    pub playing_clip: Option<ClipId>,
 }
 
 impl TrackDef {
@@ -73,7 +156,36 @@ impl TrackDef {
             color: 0x3388FFFF, // Blue default
             target_node: None,
             clip_slots: Vec::new(),
+            parent_track: None,
+            sends: Vec::new(),
+            frozen: None,
+            key_zones: Vec::new(),
+        }
+    }
+
+    /// Targets and notes a played note reaches.
+    ///
+    /// Overlapping zones layer, so a note can reach several targets.
+    pub fn note_targets(&self, note: u8) -> Vec<(NodeId, u8)> {
+        if self.key_zones.is_empty() {
+            return self
+                .target_node
+                .map(|node| (node, note))
+                .into_iter()
+                .collect();
         }
+        self.key_zones
+            .iter()
+            .filter_map(|zone| Some((zone.target_node, zone.map_note(note)?)))
+            .collect()
+    }
+
+    /// Send level to an aux bus (0.0 when not sending).
+    pub fn send_level(&self, aux_id: AuxId) -> f32 {
+        self.sends
+            .iter()
+            .find(|s| s.aux_id == aux_id)
+            .map_or(0.0, |s| s.level)
     }
 
     /// Ensure we have enough clip slots for the given scene count.
@@ -97,6 +209,7 @@ impl TrackDef {
 
 /// A clip placement in the arrangement timeline.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClipPlacement {
     /// The clip to play.
     pub clip_id: ClipId,
@@ -131,6 +244,7 @@ impl ClipPlacement {
 ///
 /// Launching a scene triggers all clips in that row simultaneously.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct SceneDef {
     /// Unique scene ID.
     pub id: SceneId,
@@ -156,6 +270,69 @@ impl SceneDef {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// Launch quantization
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Grid that session-view clip launches and stops snap to.
+///
+/// Bars assume 4/4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum LaunchQuantization {
+    /// Launch immediately.
+    #[default]
+    Off,
+    /// Next quarter note.
+    Beat,
+    /// Next half bar.
+    HalfBar,
+    /// Next bar.
+    Bar,
+    /// Next 2-bar boundary.
+    TwoBars,
+    /// Next 4-bar boundary.
+    FourBars,
+}
+
+impl LaunchQuantization {
+    /// Look up a grid by its binding index (0 = Off .. 5 = FourBars).
+    pub fn from_index(index: u32) -> Option<Self> {
+        match index {
+            0 => Some(Self::Off),
+            1 => Some(Self::Beat),
+            2 => Some(Self::HalfBar),
+            3 => Some(Self::Bar),
+            4 => Some(Self::TwoBars),
+            5 => Some(Self::FourBars),
+            _ => None,
+        }
+    }
+
+    /// Grid size in beats for bars of `beats_per_bar` beats, or `None`
+    /// when launches are immediate.
+    pub fn beats(self, beats_per_bar: f64) -> Option<f64> {
+        match self {
+            Self::Off => None,
+            Self::Beat => Some(1.0),
+            Self::HalfBar => Some(beats_per_bar / 2.0),
+            Self::Bar => Some(beats_per_bar),
+            Self::TwoBars => Some(beats_per_bar * 2.0),
+            Self::FourBars => Some(beats_per_bar * 4.0),
+        }
+    }
+
+    /// The first grid line at or after `beat`, for bars of `beats_per_bar`
+    /// beats.
+    pub fn next_boundary(self, beat: f64, beats_per_bar: f64) -> f64 {
+        match self.beats(beats_per_bar) {
+            // Tolerate float drift just past a grid line
+            Some(grid) => ((beat / grid) - 1e-9).ceil().max(0.0) * grid,
+            None => beat,
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Arrangement
 // ═══════════════════════════════════════════════════════════════════════════
@@ -164,6 +341,7 @@ impl SceneDef {
 ///
 /// Contains all clips, tracks, scenes, timeline placements, and audio pool.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Arrangement {
     /// Audio pool - stores all recorded/imported audio samples.
     pub audio_pool: AudioPool,
@@ -187,6 +365,24 @@ pub struct Arrangement {
     /// Currently launched scene (if any).
     pub active_scene: Option<SceneId>,
 
+    /// Grid that clip launches and stops wait for.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub launch_quantization: LaunchQuantization,
+
+    /// Effect node each aux return feeds before the master bus.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub aux_effects: HashMap<AuxId, NodeId>,
+
+    /// Swing amount (0..1) applied to off-beat 8th notes in clips.
+    ///
+    /// At 1.0 an off-beat is pushed half an 8th late (a dotted-8th feel).
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub swing: f32,
+
+    /// Track and start beat of the take being recorded, if any.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    recording: Option<(TrackId, f64)>,
+
     /// Next available clip ID.
     next_clip_id: ClipId,
 
@@ -246,6 +442,35 @@ impl Arrangement {
         self.clips.remove(&id)
     }
 
+    /// Copy a clip, events and automation included, under a new ID.
+    pub fn duplicate_clip(&mut self, id: ClipId) -> Option<ClipId> {
+        let mut clip = self.clips.get(&id)?.clone();
+        let new_id = self.next_clip_id;
+        self.next_clip_id += 1;
+        clip.id = new_id;
+        self.clips.insert(new_id, clip);
+        Some(new_id)
+    }
+
+    /// Split a clip in two at `at_beat` (see `ClipDef::split_off`).
+    ///
+    /// The clip keeps the part before the split and a new clip takes the
+    /// rest. Slots and timeline placements still refer to the first part.
+    /// Returns `(first, second)`, or `None` unless the split falls inside
+    /// the clip.
+    pub fn split_clip(&mut self, id: ClipId, at_beat: f64) -> Option<(ClipId, ClipId)> {
+        let clip = self.clips.get_mut(&id)?;
+        if at_beat <= 0.0 || at_beat >= clip.length {
+            return None;
+        }
+        let mut tail = clip.split_off(at_beat);
+        let new_id = self.next_clip_id;
+        self.next_clip_id += 1;
+        tail.id = new_id;
+        self.clips.insert(new_id, tail);
+        Some((id, new_id))
+    }
+
     /// Add a note to a clip.
     pub fn add_note_to_clip(&mut self, clip_id: ClipId, note: NoteDef) -> bool {
         if let Some(clip) = self.clips.get_mut(&clip_id) {
@@ -308,6 +533,50 @@ impl Arrangement {
         Some(clip_id)
     }
 
+    // ─────────────────────────────────────────────────────────────────────────
+    // Recording
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Start a take on an armed track, to be placed at `start_beat`.
+    ///
+    /// Returns false if the track isn't armed or a take is already running.
+    pub fn start_recording(&mut self, track_id: TrackId, start_beat: f64) -> bool {
+        let armed = self.get_track(track_id).is_some_and(|t| t.armed);
+        if !armed || self.recording.is_some() {
+            return false;
+        }
+        self.recording = Some((track_id, start_beat));
+        true
+    }
+
+    /// Track the running take is recorded onto, if any.
+    pub fn recording_track(&self) -> Option<TrackId> {
+        self.recording.map(|(track_id, _)| track_id)
+    }
+
+    /// Finish the running take.
+    ///
+    /// The samples are added to the audio pool and a clip holding them is
+    /// placed on the track where recording started. Returns the clip, or
+    /// `None` if nothing was recording or the take is empty.
+    pub fn stop_recording(
+        &mut self,
+        sample_rate: f64,
+        channels: usize,
+        samples: Vec<f32>,
+        bpm: f64,
+    ) -> Option<ClipId> {
+        let (track_id, start_beat) = self.recording.take()?;
+        if samples.len() < channels {
+            return None;
+        }
+        let name = format!("{} Take", self.get_track(track_id)?.name);
+        let audio_id = self.add_audio_to_pool(name, sample_rate, channels, samples);
+        let clip_id = self.create_clip_from_audio(audio_id, bpm)?;
+        self.schedule_clip(track_id, clip_id, start_beat);
+        Some(clip_id)
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Track Management
     // ─────────────────────────────────────────────────────────────────────────
@@ -345,6 +614,12 @@ impl Arrangement {
         if let Some(pos) = self.tracks.iter().position(|t| t.id == id) {
             self.timeline.remove(&id);
             self.playing_clips.remove(&id);
+            // Children of a deleted group fall back to the master bus
+            for track in &mut self.tracks {
+                if track.parent_track == Some(id) {
+                    track.parent_track = None;
+                }
+            }
             Some(self.tracks.remove(pos))
         } else {
             None
@@ -386,6 +661,108 @@ impl Arrangement {
         }
     }
 
+    /// Add a key zone to a track.
+    pub fn add_track_key_zone(&mut self, id: TrackId, zone: KeyZone) {
+        if let Some(track) = self.get_track_mut(id) {
+            track.key_zones.push(zone);
+        }
+    }
+
+    /// Remove a track's key zones, so it plays everything on its target.
+    pub fn clear_track_key_zones(&mut self, id: TrackId) {
+        if let Some(track) = self.get_track_mut(id) {
+            track.key_zones.clear();
+        }
+    }
+
+    /// Route a track into a group track, or back to the master bus with `None`.
+    ///
+    /// Returns false (and changes nothing) if the parent doesn't exist or
+    /// the change would make a track its own ancestor.
+    pub fn set_track_parent(&mut self, id: TrackId, parent: Option<TrackId>) -> bool {
+        if parent.is_some_and(|p| self.get_track(p).is_none() || self.is_ancestor_of(id, p)) {
+            return false;
+        }
+        match self.get_track_mut(id) {
+            Some(track) => {
+                track.parent_track = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The group a track's output is routed into, if it is valid.
+    ///
+    /// A parent that no longer exists, or whose chain leads back to the
+    /// track, is ignored so the track falls back to the master bus.
+    pub fn group_parent(&self, id: TrackId) -> Option<TrackId> {
+        let parent = self.get_track(id)?.parent_track?;
+        if self.get_track(parent).is_none() || self.is_ancestor_of(id, parent) {
+            return None;
+        }
+        Some(parent)
+    }
+
+    /// Whether `ancestor` is `id` or appears in `id`'s parent chain.
+    fn is_ancestor_of(&self, ancestor: TrackId, id: TrackId) -> bool {
+        let mut current = Some(id);
+        // A chain can't be longer than the track count without looping
+        for _ in 0..=self.tracks.len() {
+            match current {
+                Some(track) if track == ancestor => return true,
+                Some(track) => current = self.get_track(track).and_then(|t| t.parent_track),
+                None => return false,
+            }
+        }
+        // Looping chain that doesn't include `ancestor`
+        false
+    }
+
+    /// Set a track's send level to an aux bus; 0.0 removes the send.
+    ///
+    /// Aux IDs at or above `MAX_AUX_BUSES` are ignored.
+    pub fn set_track_send(&mut self, id: TrackId, aux_id: AuxId, level: f32) {
+        if aux_id >= MAX_AUX_BUSES {
+            return;
+        }
+        if let Some(track) = self.get_track_mut(id) {
+            let level = level.clamp(0.0, 1.0);
+            match track.sends.iter().position(|s| s.aux_id == aux_id) {
+                Some(pos) if level == 0.0 => {
+                    track.sends.remove(pos);
+                }
+                Some(pos) => track.sends[pos].level = level,
+                None if level > 0.0 => track.sends.push(TrackSend { aux_id, level }),
+                None => {}
+            }
+        }
+    }
+
+    /// Insert an effect node on an aux return, or clear it with `None`.
+    pub fn set_aux_effect(&mut self, aux_id: AuxId, node_id: Option<NodeId>) {
+        match node_id {
+            Some(node_id) => {
+                self.aux_effects.insert(aux_id, node_id);
+            }
+            None => {
+                self.aux_effects.remove(&aux_id);
+            }
+        }
+    }
+
+    /// Aux buses that at least one track sends to, in ascending order.
+    pub fn active_aux_buses(&self) -> Vec<AuxId> {
+        let mut ids: Vec<AuxId> = self
+            .tracks
+            .iter()
+            .flat_map(|t| t.sends.iter().map(|s| s.aux_id))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Scene Management
     // ─────────────────────────────────────────────────────────────────────────
@@ -475,6 +852,122 @@ impl Arrangement {
         }
     }
 
+    /// Timeline beat where a placement stops playing.
+    fn placement_end(&self, placement: &ClipPlacement) -> f64 {
+        placement.end_beat.unwrap_or_else(|| {
+            let length = self.clips.get(&placement.clip_id).map_or(0.0, |c| c.length);
+            placement.start_beat + length - placement.clip_offset
+        })
+    }
+
+    /// Bounce the placements on a track between two beats into one clip.
+    ///
+    /// Events are copied at the timeline positions they play at, so gaps
+    /// stay silent and overlapping placements are layered. Notes must start
+    /// inside the range and are cut off at its end; audio regions are
+    /// trimmed to it. Looping clips repeat up to their placement's end.
+    /// Placements sticking out of the range keep their parts outside it,
+    /// and the original clips stay in the project. Automation isn't
+    /// carried over.
+    ///
+    /// Returns the new clip, placed at `start_beat`, or `None` if no
+    /// placement overlaps the range.
+    pub fn consolidate_range(
+        &mut self,
+        track_id: TrackId,
+        start_beat: f64,
+        end_beat: f64,
+    ) -> Option<ClipId> {
+        if end_beat <= start_beat {
+            return None;
+        }
+        let placements = self.timeline.get(&track_id)?;
+        let (inside, mut kept): (Vec<ClipPlacement>, Vec<ClipPlacement>) = placements
+            .iter()
+            .partition(|p| p.start_beat < end_beat && self.placement_end(p) > start_beat);
+        let first_clip = self.clips.get(&inside.first()?.clip_id)?;
+        let mut merged = ClipDef::new(0, first_clip.name.clone(), end_beat - start_beat);
+        merged.color = first_clip.color;
+        merged.looping = false;
+
+        for placement in &inside {
+            let Some(clip) = self.clips.get(&placement.clip_id) else {
+                continue;
+            };
+            let span_end = self.placement_end(placement);
+            let from = placement.start_beat.max(start_beat);
+            let to = span_end.min(end_beat);
+
+            // Timeline beat of the clip's beat 0, once per pass through it
+            let base = placement.start_beat - placement.clip_offset;
+            let passes = if clip.looping && clip.length > 0.0 {
+                let first = ((from - base) / clip.length).floor() as i64;
+                let last = ((to - base) / clip.length).ceil() as i64;
+                first..last
+            } else {
+                0..1
+            };
+
+            for pass in passes {
+                let origin = base + pass as f64 * clip.length;
+                for event in &clip.events {
+                    let start = origin + event.start();
+                    let trimmed = match *event {
+                        // The merged clip is untransposed, so bake the
+                        // source clip's transpose and scaling in
+                        ClipEvent::Note(note) if start >= from && start < to => {
+                            let mut note = clip.played(&note);
+                            note.start = start - start_beat;
+                            note.duration = note.duration.min(to - start);
+                            ClipEvent::Note(note)
+                        }
+                        ClipEvent::Audio(mut region)
+                            if start < to && start + region.duration > from =>
+                        {
+                            // Varispeed regions read the source faster
+                            let cut = (from - start).max(0.0);
+                            let speed = 2f64.powf(region.pitch_shift as f64 / 12.0);
+                            region.start = start + cut - start_beat;
+                            region.source_offset += cut * speed;
+                            region.duration = (region.duration - cut).min(to - start - cut);
+                            ClipEvent::Audio(region)
+                        }
+                        _ => continue,
+                    };
+                    merged.events.push(trimmed);
+                }
+            }
+
+            // Parts of the placement outside the range stay where they are
+            if placement.start_beat < start_beat {
+                kept.push(ClipPlacement {
+                    end_beat: Some(start_beat),
+                    ..*placement
+                });
+            }
+            if span_end > end_beat {
+                kept.push(ClipPlacement {
+                    start_beat: end_beat,
+                    clip_offset: placement.clip_offset + (end_beat - placement.start_beat),
+                    ..*placement
+                });
+            }
+        }
+
+        let clip_id = self.next_clip_id;
+        self.next_clip_id += 1;
+        merged.id = clip_id;
+        merged
+            .events
+            .sort_by(|a, b| a.start().total_cmp(&b.start()));
+        self.clips.insert(clip_id, merged);
+
+        kept.push(ClipPlacement::new(clip_id, start_beat));
+        kept.sort_by(|a, b| a.start_beat.total_cmp(&b.start_beat));
+        self.timeline.insert(track_id, kept);
+        Some(clip_id)
+    }
+
     /// Get clip placements in a time range for a track.
     pub fn placements_in_range(
         &self,
@@ -559,3 +1052,132 @@ impl Arrangement {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AutomationLane;
+
+    #[test]
+    fn test_duplicate_clip_is_independent() {
+        let mut arr = Arrangement::new();
+        let clip_id = arr.create_clip("Riff", 4.0);
+        arr.add_note_to_clip(clip_id, NoteDef::new(0.0, 1.0, 60, 0.8));
+        arr.add_note_to_clip(clip_id, NoteDef::new(2.0, 1.0, 64, 0.8));
+        arr.get_clip_mut(clip_id).unwrap().looping = false;
+
+        let copy_id = arr.duplicate_clip(clip_id).unwrap();
+        assert_ne!(copy_id, clip_id);
+        assert_eq!(arr.duplicate_clip(99), None);
+
+        // Editing the original leaves the copy alone
+        arr.get_clip_mut(clip_id).unwrap().clear();
+        arr.add_note_to_clip(clip_id, NoteDef::new(1.0, 1.0, 72, 0.8));
+
+        let copy = arr.get_clip(copy_id).unwrap();
+        assert_eq!(copy.id, copy_id);
+        assert_eq!(copy.length, 4.0);
+        assert!(!copy.looping);
+        let notes: Vec<u8> = copy.notes().map(|n| n.note).collect();
+        assert_eq!(notes, [60, 64]);
+        assert_eq!(arr.get_clip(clip_id).unwrap().note_count(), 1);
+    }
+
+    #[test]
+    fn test_split_clip_at_beat() {
+        let mut arr = Arrangement::new();
+        let clip_id = arr.create_clip("Verse", 4.0);
+        arr.add_note_to_clip(clip_id, NoteDef::new(0.0, 1.0, 60, 0.8));
+        arr.add_note_to_clip(clip_id, NoteDef::new(1.5, 1.0, 62, 0.8)); // crosses the split
+        arr.add_note_to_clip(clip_id, NoteDef::new(2.0, 1.0, 64, 0.8));
+        arr.add_note_to_clip(clip_id, NoteDef::new(3.0, 0.5, 67, 0.8));
+        arr.add_audio_to_clip(clip_id, AudioRegionDef::new(1.0, 2.0, 0));
+        arr.get_clip_mut(clip_id).unwrap().add_automation(
+            AutomationLane::new(7, 0)
+                .with_point(0.0, 0.0)
+                .with_point(4.0, 1.0),
+        );
+
+        assert_eq!(arr.split_clip(clip_id, 4.0), None);
+        let (first, second) = arr.split_clip(clip_id, 2.0).unwrap();
+        assert_eq!(first, clip_id);
+
+        let notes = |id: ClipId| -> Vec<(f64, f64, u8)> {
+            arr.get_clip(id)
+                .unwrap()
+                .notes()
+                .map(|n| (n.start, n.duration, n.note))
+                .collect()
+        };
+
+        // The first half keeps its notes, cutting the crossing one short
+        let head = arr.get_clip(first).unwrap();
+        assert_eq!(head.length, 2.0);
+        assert!(head.looping);
+        assert_eq!(notes(first), [(0.0, 1.0, 60), (1.5, 0.5, 62)]);
+        let region = head.audio_regions().next().unwrap();
+        assert_eq!((region.start, region.duration), (1.0, 1.0));
+        assert_eq!(head.automation[0].points, [(0.0, 0.0), (2.0, 0.5)]);
+
+        // The second half starts at its own beat 0, audio picking up where it was cut
+        let tail = arr.get_clip(second).unwrap();
+        assert_eq!(tail.length, 2.0);
+        assert!(tail.looping);
+        assert_eq!(notes(second), [(0.0, 1.0, 64), (1.0, 0.5, 67)]);
+        let region = tail.audio_regions().next().unwrap();
+        assert_eq!(
+            (region.start, region.duration, region.source_offset),
+            (0.0, 1.0, 1.0)
+        );
+        assert_eq!(tail.automation[0].points, [(0.0, 0.5), (2.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_consolidate_placements() {
+        let mut arr = Arrangement::new();
+        let track_id = arr.create_track("Drums");
+        let kick = arr.create_clip("Kick", 2.0);
+        arr.add_note_to_clip(kick, NoteDef::new(0.0, 0.5, 36, 0.8));
+        arr.add_note_to_clip(kick, NoteDef::new(1.0, 0.5, 36, 0.8));
+        let snare = arr.create_clip("Snare", 2.0);
+        arr.add_note_to_clip(snare, NoteDef::new(0.5, 0.5, 38, 0.8));
+
+        // Kick at beat 0, a one-beat gap, then the snare at beat 3
+        arr.schedule_clip(track_id, kick, 0.0);
+        arr.schedule_clip(track_id, snare, 3.0);
+
+        let notes = |arr: &Arrangement, id: ClipId| -> Vec<(f64, u8)> {
+            arr.get_clip(id)
+                .unwrap()
+                .notes()
+                .map(|n| (n.start, n.note))
+                .collect()
+        };
+
+        let merged = arr.consolidate_range(track_id, 0.0, 5.0).unwrap();
+        assert_eq!(notes(&arr, merged), [(0.0, 36), (1.0, 36), (3.5, 38)]);
+        assert_eq!(arr.get_clip(merged).unwrap().length, 5.0);
+        assert_eq!(arr.timeline[&track_id], [ClipPlacement::new(merged, 0.0)]);
+        assert!(arr.get_clip(kick).is_some());
+
+        // A range cutting through placements leaves their outer parts in place
+        let mut arr2 = Arrangement::new();
+        let track_id = arr2.create_track("Drums");
+        let kick = arr2.create_clip("Kick", 2.0);
+        arr2.add_note_to_clip(kick, NoteDef::new(0.0, 0.5, 36, 0.8));
+        arr2.add_note_to_clip(kick, NoteDef::new(1.0, 0.5, 36, 0.8));
+        arr2.schedule_clip(track_id, kick, 0.0);
+        arr2.schedule_clip(track_id, kick, 2.0);
+
+        let merged = arr2.consolidate_range(track_id, 1.0, 3.0).unwrap();
+        assert_eq!(notes(&arr2, merged), [(0.0, 36), (1.0, 36)]);
+        let timeline = &arr2.timeline[&track_id];
+        assert_eq!(timeline.len(), 3);
+        assert_eq!(timeline[0].end_beat, Some(1.0));
+        assert_eq!(timeline[1], ClipPlacement::new(merged, 1.0));
+        assert_eq!(
+            (timeline[2].start_beat, timeline[2].clip_offset),
+            (3.0, 1.0)
+        );
+    }
+}
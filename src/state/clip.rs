@@ -8,11 +8,14 @@
 // - NoteDef: A MIDI note event
 // - AudioRegionDef: A reference to audio in the pool
 // - ClipEvent: Either a Note or AudioRegion (unified event model)
+// - AutomationLane: Parameter breakpoints played back with a clip
 // - ClipDef: A container holding a stream of ClipEvents
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use super::{NodeId, ParamId};
+
 /// Unique identifier for a clip.
 pub type ClipId = u32;
 
@@ -28,6 +31,7 @@ pub type AudioPoolId = u32;
 /// This is the actual waveform data that can be referenced by multiple clips.
 /// Using Arc allows cheap cloning and sharing across clips.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct AudioPoolEntry {
     /// Unique ID in the pool.
     pub id: AudioPoolId,
@@ -46,6 +50,10 @@ pub struct AudioPoolEntry {
 
     /// The actual sample data (interleaved if stereo).
     /// Wrapped in Arc for efficient sharing across clips.
+    ///
+    /// Not serialized: saved projects refer to audio by ID and the host
+    /// reattaches the PCM with `AudioPool::set_samples` after loading.
+    #[cfg_attr(feature = "serialize", serde(skip))]
     pub samples: Arc<Vec<f32>>,
 }
 
@@ -81,6 +89,7 @@ impl AudioPoolEntry {
 
 /// The audio pool stores all recorded/imported audio.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct AudioPool {
     entries: HashMap<AudioPoolId, AudioPoolEntry>,
     next_id: AudioPoolId,
@@ -113,6 +122,18 @@ impl AudioPool {
         self.entries.get(&id)
     }
 
+    /// Attach sample data to an existing entry (e.g. after loading a project).
+    ///
+    /// Returns false if no entry has this ID.
+    pub fn set_samples(&mut self, id: AudioPoolId, samples: Vec<f32>) -> bool {
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return false;
+        };
+        entry.frames = samples.len() / entry.channels.max(1);
+        entry.samples = Arc::new(samples);
+        true
+    }
+
     /// Remove audio from the pool.
     pub fn remove(&mut self, id: AudioPoolId) -> Option<AudioPoolEntry> {
         self.entries.remove(&id)
@@ -130,6 +151,7 @@ impl AudioPool {
 
 /// A MIDI note event within a clip.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct NoteDef {
     /// Start position in beats (relative to clip start).
     pub start: f64,
@@ -164,6 +186,7 @@ impl NoteDef {
 ///
 /// References audio from the pool and specifies how it should be played.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct AudioRegionDef {
     /// Start position in beats (relative to clip start).
     pub start: f64,
@@ -236,6 +259,7 @@ impl AudioRegionDef {
 /// This is the core of the unified signal model. Both note events
 /// and audio regions are treated as events in the same stream.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub enum ClipEvent {
     /// A MIDI note event.
     Note(NoteDef),
@@ -301,6 +325,63 @@ impl ClipEvent {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// Automation
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A breakpoint envelope driving one node parameter over a clip.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct AutomationLane {
+    /// Node whose parameter is automated.
+    pub node_id: NodeId,
+
+    /// Parameter being automated.
+    pub param_id: ParamId,
+
+    /// Breakpoints as (beat within clip, value), sorted by beat.
+    pub points: Vec<(f64, f32)>,
+}
+
+impl AutomationLane {
+    pub fn new(node_id: NodeId, param_id: ParamId) -> Self {
+        Self {
+            node_id,
+            param_id,
+            points: Vec::new(),
+        }
+    }
+
+    /// Builder: add a breakpoint, keeping points sorted.
+    pub fn with_point(mut self, beat: f64, value: f32) -> Self {
+        self.add_point(beat, value);
+        self
+    }
+
+    /// Add a breakpoint, keeping points sorted.
+    pub fn add_point(&mut self, beat: f64, value: f32) {
+        let index = self.points.partition_point(|&(b, _)| b <= beat);
+        self.points.insert(index, (beat, value));
+    }
+
+    /// Linearly interpolated value at a beat within the clip.
+    ///
+    /// Holds the first/last value outside the breakpoint range. Returns
+    /// `None` for an empty lane.
+    pub fn value_at(&self, beat: f64) -> Option<f32> {
+        let index = self.points.partition_point(|&(b, _)| b <= beat);
+        let before = index.checked_sub(1).map(|i| self.points[i]);
+        match (before, self.points.get(index)) {
+            (Some((b0, v0)), Some(&(b1, v1))) => {
+                let t = ((beat - b0) / (b1 - b0)) as f32;
+                Some(v0 + (v1 - v0) * t)
+            }
+            (Some((_, v)), None) | (None, Some(&(_, v))) => Some(v),
+            (None, None) => None,
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Clips - Unified containers for events
 // ═══════════════════════════════════════════════════════════════════════════
@@ -311,6 +392,7 @@ impl ClipEvent {
 /// placed on tracks at specific positions, or triggered from the
 /// session view's clip launcher.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClipDef {
     /// Unique clip ID.
     pub id: ClipId,
@@ -329,6 +411,33 @@ pub struct ClipDef {
 
     /// Whether the clip loops when played.
     pub looping: bool,
+
+    /// Parameter automation played back alongside the events.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub automation: Vec<AutomationLane>,
+
+    /// Semitones added to every note on playback.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub transpose: i8,
+
+    /// Factor applied to every note's velocity on playback.
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default = "ClipDef::default_velocity_scale")
+    )]
+    pub velocity_scale: f32,
+
+    /// Most a note is randomly delayed on playback, in beats.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub humanize_timing: f64,
+
+    /// Most a note's velocity is randomly moved on playback, either way.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub humanize_velocity: f32,
+
+    /// Seed for the humanize offsets, so a clip plays the same every time.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub humanize_seed: u32,
 }
 
 impl ClipDef {
@@ -340,9 +449,33 @@ impl ClipDef {
             events: Vec::new(),
             color: 0xFF5500FF, // Orange default
             looping: true,
+            automation: Vec::new(),
+            transpose: 0,
+            velocity_scale: Self::default_velocity_scale(),
+            humanize_timing: 0.0,
+            humanize_velocity: 0.0,
+            humanize_seed: 0,
         }
     }
 
+    fn default_velocity_scale() -> f32 {
+        1.0
+    }
+
+    /// `note` as it plays, with the clip's transpose and velocity scaling.
+    pub fn played(&self, note: &NoteDef) -> NoteDef {
+        NoteDef {
+            note: (note.note as i16 + self.transpose as i16).clamp(0, 127) as u8,
+            velocity: (note.velocity * self.velocity_scale).clamp(0.0, 1.0),
+            ..*note
+        }
+    }
+
+    /// Add an automation lane to the clip.
+    pub fn add_automation(&mut self, lane: AutomationLane) {
+        self.automation.push(lane);
+    }
+
     /// Sort events by start time.
     fn sort_events(&mut self) {
         self.events
@@ -455,4 +588,156 @@ impl ClipDef {
     pub fn audio_count(&self) -> usize {
         self.events.iter().filter(|e| e.is_audio()).count()
     }
+
+    /// Move each note's start toward the nearest multiple of `grid_beats`.
+    ///
+    /// `strength` is how far to move, from 0 (untouched) to 1 (exactly on
+    /// the grid). Audio regions stay where they are.
+    pub fn quantize(&mut self, grid_beats: f64, strength: f32) {
+        if grid_beats <= 0.0 {
+            return;
+        }
+        let strength = strength.clamp(0.0, 1.0) as f64;
+        for note in self.notes_mut() {
+            let target = (note.start / grid_beats).round() * grid_beats;
+            note.start = target * strength + note.start * (1.0 - strength);
+        }
+        self.sort_events();
+    }
+
+    /// Move each note's duration toward a whole number of grid steps.
+    ///
+    /// Works like `quantize`; notes never shrink below one step.
+    pub fn quantize_durations(&mut self, grid_beats: f64, strength: f32) {
+        if grid_beats <= 0.0 {
+            return;
+        }
+        let strength = strength.clamp(0.0, 1.0) as f64;
+        for note in self.notes_mut() {
+            let target = (note.duration / grid_beats).round().max(1.0) * grid_beats;
+            note.duration = target * strength + note.duration * (1.0 - strength);
+        }
+    }
+
+    /// Split the clip at `beat`, returning everything from there on.
+    ///
+    /// This clip keeps the part before the split; the returned clip holds
+    /// the rest, shifted to start at beat 0. Notes that cross the split are
+    /// cut short, while audio regions carry on in the second part from
+    /// where they were cut. Automation lanes hold their value at the split
+    /// on both sides. The returned clip keeps this clip's ID, name, color
+    /// and looping flag.
+    pub fn split_off(&mut self, beat: f64) -> ClipDef {
+        let beat = beat.clamp(0.0, self.length);
+        let mut tail = ClipDef {
+            id: self.id,
+            name: self.name.clone(),
+            length: self.length - beat,
+            events: Vec::new(),
+            color: self.color,
+            looping: self.looping,
+            automation: Vec::new(),
+            transpose: self.transpose,
+            velocity_scale: self.velocity_scale,
+            humanize_timing: self.humanize_timing,
+            humanize_velocity: self.humanize_velocity,
+            humanize_seed: self.humanize_seed,
+        };
+        self.length = beat;
+
+        let mut head = Vec::with_capacity(self.events.len());
+        for event in self.events.drain(..) {
+            match event {
+                ClipEvent::Note(mut note) if note.start >= beat => {
+                    note.start -= beat;
+                    tail.events.push(ClipEvent::Note(note));
+                }
+                ClipEvent::Audio(mut region) if region.start >= beat => {
+                    region.start -= beat;
+                    tail.events.push(ClipEvent::Audio(region));
+                }
+                ClipEvent::Note(mut note) => {
+                    note.duration = note.duration.min(beat - note.start);
+                    head.push(ClipEvent::Note(note));
+                }
+                ClipEvent::Audio(mut region) => {
+                    if region.end() > beat {
+                        // Varispeed regions read the source faster
+                        let cut = beat - region.start;
+                        let speed = 2f64.powf(region.pitch_shift as f64 / 12.0);
+                        tail.events.push(ClipEvent::Audio(AudioRegionDef {
+                            start: 0.0,
+                            duration: region.duration - cut,
+                            source_offset: region.source_offset + cut * speed,
+                            ..region
+                        }));
+                        region.duration = cut;
+                    }
+                    head.push(ClipEvent::Audio(region));
+                }
+            }
+        }
+        self.events = head;
+        tail.sort_events();
+
+        for lane in &mut self.automation {
+            let Some(value) = lane.value_at(beat) else {
+                continue;
+            };
+            let split = lane.points.partition_point(|&(b, _)| b < beat);
+            let mut tail_lane = AutomationLane::new(lane.node_id, lane.param_id);
+            tail_lane.points.push((0.0, value));
+            tail_lane.points.extend(
+                lane.points[split..]
+                    .iter()
+                    .filter(|&&(b, _)| b > beat)
+                    .map(|&(b, v)| (b - beat, v)),
+            );
+            lane.points.truncate(split);
+            lane.points.push((beat, value));
+            tail.automation.push(tail_lane);
+        }
+
+        tail
+    }
+
+    fn notes_mut(&mut self) -> impl Iterator<Item = &mut NoteDef> {
+        self.events.iter_mut().filter_map(|e| match e {
+            ClipEvent::Note(note) => Some(note),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize_snaps_to_eighths() {
+        let mut clip = ClipDef::new(0, "Loose", 4.0);
+        for (start, duration) in [(0.02, 0.46), (0.53, 0.4), (0.97, 0.55), (1.48, 0.2)] {
+            clip.add_note(NoteDef::new(start, duration, 60, 0.8));
+        }
+        clip.add_audio(AudioRegionDef::new(0.1, 1.0, 0));
+
+        // Half strength closes half the gap
+        let mut half = clip.clone();
+        half.quantize(0.5, 0.5);
+        let starts: Vec<f64> = half.notes().map(|n| n.start).collect();
+        for (got, want) in starts.iter().zip([0.01, 0.515, 0.985, 1.49]) {
+            assert!((got - want).abs() < 1e-9, "{starts:?}");
+        }
+
+        // Full strength lands exactly on the 8th-note grid
+        clip.quantize(0.5, 1.0);
+        let starts: Vec<f64> = clip.notes().map(|n| n.start).collect();
+        assert_eq!(starts, [0.0, 0.5, 1.0, 1.5]);
+        assert_eq!(clip.audio_regions().next().unwrap().start, 0.1);
+
+        // Durations round to whole steps, never below one
+        clip.quantize_durations(0.5, 1.0);
+        let durations: Vec<f64> = clip.notes().map(|n| n.duration).collect();
+        assert_eq!(durations, [0.5, 0.5, 0.5, 0.5]);
+    }
 }
@@ -3,7 +3,13 @@
 // Commands are the ONLY way the UI can mutate engine state.
 // They are queued and processed on the appropriate thread.
 
-use super::{ClipId, ConnectionDef, NodeDef, NodeId, NodeTypeId, PortId, SceneId, TrackId};
+use crate::arpeggiator::ArpSettings;
+use crate::voice_allocator::{VoiceMode, VoiceStealMode};
+
+use super::{
+    AuxId, CcMapping, ClipId, ConnectionDef, KeyZone, ModRouteDef, NodeDef, NodeId, NodeTypeId,
+    PortId, SceneId, TrackId, TuningTable,
+};
 
 /// A command from the UI to the engine.
 ///
@@ -28,6 +34,14 @@ pub enum Command {
     /// Remove a node and its connections.
     RemoveNode { node_id: NodeId },
 
+    /// Put a node back under its original ID, with the given connections
+    /// and modulation routes (used by undo/redo).
+    RestoreNode {
+        node: NodeDef,
+        connections: Vec<ConnectionDef>,
+        mod_routes: Vec<ModRouteDef>,
+    },
+
     /// Connect two ports.
     Connect {
         source_node: NodeId,
@@ -47,6 +61,21 @@ pub enum Command {
     /// Set the graph output node.
     SetOutputNode { node_id: NodeId },
 
+    /// Modulate a node parameter from another node's output.
+    AddModRoute {
+        source_node: NodeId,
+        dest_node: NodeId,
+        param_id: u32,
+        depth: f32,
+    },
+
+    /// Remove a modulation route.
+    RemoveModRoute {
+        source_node: NodeId,
+        dest_node: NodeId,
+        param_id: u32,
+    },
+
     /// Move a node in the UI.
     MoveNode {
         node_id: NodeId,
@@ -63,6 +92,16 @@ pub enum Command {
         value: f32,
     },
 
+    /// Scale a parameter by note velocity (0.0 = off, 1.0 = fully proportional).
+    SetVelocitySensitivity {
+        node_id: NodeId,
+        param_id: u32,
+        amount: f32,
+    },
+
+    /// Pass a node's input straight to its output, or restore it.
+    SetNodeBypass { node_id: NodeId, bypassed: bool },
+
     /// Begin a parameter gesture (for automation recording).
     BeginParamGesture { node_id: NodeId, param_id: u32 },
 
@@ -81,9 +120,25 @@ pub enum Command {
     /// Set tempo in BPM.
     SetTempo { bpm: f64 },
 
+    /// Glide to a new tempo over a number of beats.
+    RampTempo { target_bpm: f64, over_beats: f64 },
+
     /// Seek to a position in beats.
     Seek { beat: f64 },
 
+    /// Loop playback between two beat positions.
+    SetLoop { enabled: bool, start: f64, end: f64 },
+
+    /// Click on every beat, accenting the first beat of each bar.
+    SetMetronome { enabled: bool, beats_per_bar: u32 },
+
+    /// Tune note frequencies to a reference pitch for A4 in Hz.
+    SetTuning { a4: f64 },
+
+    /// Map notes through a microtonal tuning table, or back to equal
+    /// temperament with `None`.
+    SetTuningTable { table: Option<TuningTable> },
+
     // ═══════════════════════════════════════════
     // MIDI
     // ═══════════════════════════════════════════
@@ -93,6 +148,40 @@ pub enum Command {
     /// MIDI note off.
     NoteOff { note: u8 },
 
+    /// Channel pitch bend in semitones (applies to all voices).
+    PitchBend { semitones: f32 },
+
+    /// Switch between polyphonic and monophonic voice allocation.
+    SetVoiceMode { mode: VoiceMode },
+
+    /// Portamento time in seconds between mono notes.
+    SetGlideTime { seconds: f32 },
+
+    /// Which voice to steal when polyphony is exhausted.
+    SetVoiceStealMode { mode: VoiceStealMode },
+
+    /// Start `voices` detuned voices per note, spread across the stereo field.
+    SetUnison {
+        voices: usize,
+        detune_cents: f32,
+        spread: f32,
+    },
+
+    /// Pan voices by note number across the stereo field (0..1).
+    SetVoicePanSpread { spread: f32 },
+
+    /// Arpeggiate held live notes, or play them directly with `None`.
+    SetArpeggiator { settings: Option<ArpSettings> },
+
+    /// MIDI control change, value normalized to 0..1.
+    ControlChange { cc: u8, value: f32 },
+
+    /// Route a CC number to a node parameter.
+    MapControlChange { cc: u8, mapping: CcMapping },
+
+    /// Remove the mapping for a CC number.
+    UnmapControlChange { cc: u8 },
+
     // ═══════════════════════════════════════════
     // Session
     // ═══════════════════════════════════════════
@@ -132,6 +221,28 @@ pub enum Command {
     /// Set clip looping.
     SetClipLooping { clip_id: ClipId, looping: bool },
 
+    /// Transpose a clip's notes on playback, in semitones.
+    SetClipTranspose { clip_id: ClipId, semitones: i8 },
+
+    /// Scale a clip's note velocities on playback.
+    SetClipVelocityScale { clip_id: ClipId, scale: f32 },
+
+    /// Randomly delay a clip's notes by up to `timing` beats and move their
+    /// velocities by up to `velocity`, repeatably for a given `seed`.
+    SetClipHumanize {
+        clip_id: ClipId,
+        timing: f64,
+        velocity: f32,
+        seed: u32,
+    },
+
+    /// Move note starts toward a grid of `grid` beats (strength 0-1).
+    QuantizeClip {
+        clip_id: ClipId,
+        grid: f64,
+        strength: f32,
+    },
+
     // ═══════════════════════════════════════════
     // Tracks
     // ═══════════════════════════════════════════
@@ -162,6 +273,31 @@ pub enum Command {
         node_id: Option<u32>,
     },
 
+    /// Add a key zone routing a note range to its own target.
+    AddTrackKeyZone { track_id: TrackId, zone: KeyZone },
+
+    /// Remove all of a track's key zones.
+    ClearTrackKeyZones { track_id: TrackId },
+
+    /// Route a track into a group track (None = master bus).
+    SetTrackParent {
+        track_id: TrackId,
+        parent: Option<TrackId>,
+    },
+
+    /// Set a track's send level to an aux bus (0.0 removes the send).
+    SetTrackSend {
+        track_id: TrackId,
+        aux_id: AuxId,
+        level: f32,
+    },
+
+    /// Set or clear the effect node on an aux return.
+    SetAuxEffect {
+        aux_id: AuxId,
+        node_id: Option<NodeId>,
+    },
+
     /// Assign a clip to a track's clip slot.
     SetClipSlot {
         track_id: TrackId,
@@ -203,6 +339,15 @@ pub enum Command {
     /// Remove a clip placement from the timeline.
     RemoveClipPlacement { track_id: TrackId, start_beat: f64 },
 
+    // ═══════════════════════════════════════════
+    // Recording
+    // ═══════════════════════════════════════════
+    /// Start capturing input for a take on an armed track.
+    StartRecording { track_id: TrackId },
+
+    /// Stop capturing and hand the take back to the UI.
+    StopRecording,
+
     // ═══════════════════════════════════════════
     // Compilation
     // ═══════════════════════════════════════════
@@ -230,6 +375,14 @@ pub enum CommandResult {
     /// Command succeeded and created a node.
     NodeCreated { node_id: NodeId },
 
+    /// A parameter value was outside its declared range and was clamped.
+    ParamClamped {
+        node_id: NodeId,
+        param_id: u32,
+        requested: f32,
+        value: f32,
+    },
+
     /// Command failed.
     Error { message: String },
 }
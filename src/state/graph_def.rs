@@ -3,7 +3,7 @@
 // This is the "document" representation of the audio graph.
 // It can be serialized, edited by the UI, and compiled to a runtime Graph.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use super::ParamInfo;
 
@@ -39,6 +39,8 @@ pub struct PortInfo {
     pub name: String,
     pub direction: PortDirection,
     pub port_type: PortType,
+    /// Channel count of the port. Sources connected to an input of a
+    /// different width are mixed to match when the graph compiles.
     pub channels: usize,
 }
 
@@ -63,6 +65,16 @@ impl PortInfo {
         }
     }
 
+    pub fn control_input(id: PortId, name: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            direction: PortDirection::Input,
+            port_type: PortType::Control,
+            channels: 1,
+        }
+    }
+
     pub fn stereo(mut self) -> Self {
         self.channels = 2;
         self
@@ -119,6 +131,7 @@ impl NodeTypeInfo {
 
 /// A connection between two ports.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConnectionDef {
     pub source_node: NodeId,
     pub source_port: PortId,
@@ -126,8 +139,23 @@ pub struct ConnectionDef {
     pub dest_port: PortId,
 }
 
+/// A modulation route from a node's output to another node's parameter.
+///
+/// Each block the source output is sampled at control rate, scaled by
+/// `depth` (in the destination parameter's units) and added to the
+/// parameter's base value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModRouteDef {
+    pub source_node: NodeId,
+    pub dest_node: NodeId,
+    pub param_id: u32,
+    pub depth: f32,
+}
+
 /// An instance of a node in the graph.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeDef {
     /// Unique instance ID
     pub id: NodeId,
@@ -141,8 +169,15 @@ pub struct NodeDef {
     /// Current parameter values (sparse - only non-default values)
     pub param_values: HashMap<u32, f32>,
 
+    /// Velocity sensitivity per parameter (0.0 - 1.0, sparse)
+    pub velocity_sensitivity: HashMap<u32, f32>,
+
     /// User-defined label
     pub label: Option<String>,
+
+    /// Pass input straight to output (or stay silent, for sources)
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub bypassed: bool,
 }
 
 impl NodeDef {
@@ -152,7 +187,9 @@ impl NodeDef {
             type_id,
             position: (0.0, 0.0),
             param_values: HashMap::new(),
+            velocity_sensitivity: HashMap::new(),
             label: None,
+            bypassed: false,
         }
     }
 
@@ -172,11 +209,51 @@ impl NodeDef {
     }
 }
 
+/// A problem found by `GraphDef::validate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphIssue {
+    /// No output node is set, or it names a node that doesn't exist.
+    MissingOutput,
+
+    /// A node whose audio can never reach the output.
+    UnreachableNode { node_id: NodeId },
+
+    /// A connection to or from a node that doesn't exist.
+    DanglingConnection { source: NodeId, dest: NodeId },
+}
+
+impl GraphIssue {
+    /// Whether the issue keeps the graph from compiling or making sound,
+    /// rather than just leaving nodes unused.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, GraphIssue::UnreachableNode { .. })
+    }
+}
+
+impl std::fmt::Display for GraphIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphIssue::MissingOutput => write!(f, "No output node is set"),
+            GraphIssue::UnreachableNode { node_id } => {
+                write!(f, "Node {} is not connected to the output", node_id)
+            }
+            GraphIssue::DanglingConnection { source, dest } => {
+                write!(
+                    f,
+                    "Connection from {} to {} uses a missing node",
+                    source, dest
+                )
+            }
+        }
+    }
+}
+
 /// The complete declarative graph definition.
 ///
 /// This is the "document" that the UI edits.
 /// It gets compiled to a runtime `Graph` by the bridge.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct GraphDef {
     /// All nodes in the graph
     pub nodes: HashMap<NodeId, NodeDef>,
@@ -184,6 +261,9 @@ pub struct GraphDef {
     /// All connections
     pub connections: Vec<ConnectionDef>,
 
+    /// Modulation routes (source output -> destination parameter)
+    pub mod_routes: Vec<ModRouteDef>,
+
     /// The output node (final audio destination)
     pub output_node: Option<NodeId>,
 
@@ -217,6 +297,8 @@ impl GraphDef {
     pub fn remove_node(&mut self, id: NodeId) -> Option<NodeDef> {
         self.connections
             .retain(|c| c.source_node != id && c.dest_node != id);
+        self.mod_routes
+            .retain(|r| r.source_node != id && r.dest_node != id);
 
         if self.output_node == Some(id) {
             self.output_node = None;
@@ -225,6 +307,17 @@ impl GraphDef {
         self.nodes.remove(&id)
     }
 
+    /// Put a node back under its own ID, replacing any node with that ID.
+    pub fn restore_node(&mut self, node: NodeDef) {
+        self.next_id = self.next_id.max(node.id + 1);
+        self.nodes.insert(node.id, node);
+    }
+
+    /// The ID the next added node will get.
+    pub fn next_node_id(&self) -> NodeId {
+        self.next_id
+    }
+
     /// Connect two nodes.
     pub fn connect(
         &mut self,
@@ -245,6 +338,22 @@ impl GraphDef {
         }
     }
 
+    /// Whether two ports are connected.
+    pub fn is_connected(
+        &self,
+        source_node: NodeId,
+        source_port: PortId,
+        dest_node: NodeId,
+        dest_port: PortId,
+    ) -> bool {
+        self.connections.contains(&ConnectionDef {
+            source_node,
+            source_port,
+            dest_node,
+            dest_port,
+        })
+    }
+
     /// Disconnect two nodes.
     pub fn disconnect(
         &mut self,
@@ -261,6 +370,50 @@ impl GraphDef {
         });
     }
 
+    /// Route a node's output to another node's parameter.
+    ///
+    /// Re-adding an existing source/dest/param route updates its depth.
+    pub fn add_mod_route(
+        &mut self,
+        source_node: NodeId,
+        dest_node: NodeId,
+        param_id: u32,
+        depth: f32,
+    ) {
+        if let Some(route) = self.mod_routes.iter_mut().find(|r| {
+            r.source_node == source_node && r.dest_node == dest_node && r.param_id == param_id
+        }) {
+            route.depth = depth;
+            return;
+        }
+
+        self.mod_routes.push(ModRouteDef {
+            source_node,
+            dest_node,
+            param_id,
+            depth,
+        });
+    }
+
+    /// Find the modulation route into a parameter from a source node.
+    pub fn find_mod_route(
+        &self,
+        source_node: NodeId,
+        dest_node: NodeId,
+        param_id: u32,
+    ) -> Option<&ModRouteDef> {
+        self.mod_routes.iter().find(|r| {
+            r.source_node == source_node && r.dest_node == dest_node && r.param_id == param_id
+        })
+    }
+
+    /// Remove a modulation route.
+    pub fn remove_mod_route(&mut self, source_node: NodeId, dest_node: NodeId, param_id: u32) {
+        self.mod_routes.retain(|r| {
+            !(r.source_node == source_node && r.dest_node == dest_node && r.param_id == param_id)
+        });
+    }
+
     /// Set a parameter value on a node.
     pub fn set_param(&mut self, node_id: NodeId, param_id: u32, value: f32) {
         if let Some(node) = self.nodes.get_mut(&node_id) {
@@ -268,6 +421,28 @@ impl GraphDef {
         }
     }
 
+    /// Scale a parameter by note velocity when a voice triggers.
+    ///
+    /// `amount` of 0 disables scaling; 1 makes the value fully proportional
+    /// to velocity. Only affects per-voice nodes.
+    pub fn set_velocity_sensitivity(&mut self, node_id: NodeId, param_id: u32, amount: f32) {
+        if let Some(node) = self.nodes.get_mut(&node_id) {
+            let amount = amount.clamp(0.0, 1.0);
+            if amount > 0.0 {
+                node.velocity_sensitivity.insert(param_id, amount);
+            } else {
+                node.velocity_sensitivity.remove(&param_id);
+            }
+        }
+    }
+
+    /// Bypass a node, or bring it back into the signal path.
+    pub fn set_bypass(&mut self, node_id: NodeId, bypassed: bool) {
+        if let Some(node) = self.nodes.get_mut(&node_id) {
+            node.bypassed = bypassed;
+        }
+    }
+
     /// Get a node by ID.
     pub fn get_node(&self, id: NodeId) -> Option<&NodeDef> {
         self.nodes.get(&id)
@@ -296,4 +471,144 @@ impl GraphDef {
             .iter()
             .filter(move |c| c.source_node == node_id)
     }
+
+    /// Whether audio from `from` reaches `to` by following connections.
+    ///
+    /// Connecting `to` into `from` would create a cycle exactly when this
+    /// holds (a node always reaches itself).
+    pub fn reaches(&self, from: NodeId, to: NodeId) -> bool {
+        let mut stack = vec![from];
+        let mut seen = HashSet::new();
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            if seen.insert(node) {
+                stack.extend(self.connections_from(node).map(|c| c.dest_node));
+            }
+        }
+        false
+    }
+
+    /// Nodes whose audio reaches `output`, including `output` itself.
+    ///
+    /// Walks back from `output` along connections and modulation routes, so
+    /// a node modulating a contributing node contributes too.
+    pub fn contributing_nodes(&self, output: NodeId) -> HashSet<NodeId> {
+        let mut stack = vec![output];
+        let mut seen = HashSet::new();
+        while let Some(node) = stack.pop() {
+            if seen.insert(node) {
+                stack.extend(self.connections_to(node).map(|c| c.source_node));
+                stack.extend(
+                    self.mod_routes
+                        .iter()
+                        .filter(|r| r.dest_node == node)
+                        .map(|r| r.source_node),
+                );
+            }
+        }
+        seen
+    }
+
+    /// Check the graph for problems without changing it.
+    ///
+    /// Issues come in order: a missing output, then dangling connections
+    /// in the order they were made, then unreachable nodes by ID. A node
+    /// reaches the output through connections or by modulating a node that
+    /// does. With no output set, only nodes with no connections or
+    /// modulation routes at all are reported as unreachable.
+    pub fn validate(&self) -> Vec<GraphIssue> {
+        let mut issues = Vec::new();
+
+        let output = self.output_node.filter(|id| self.nodes.contains_key(id));
+        if output.is_none() {
+            issues.push(GraphIssue::MissingOutput);
+        }
+
+        for c in &self.connections {
+            if !self.nodes.contains_key(&c.source_node) || !self.nodes.contains_key(&c.dest_node) {
+                issues.push(GraphIssue::DanglingConnection {
+                    source: c.source_node,
+                    dest: c.dest_node,
+                });
+            }
+        }
+
+        let reached: HashSet<NodeId> = match output {
+            Some(output) => self.contributing_nodes(output),
+            None => self
+                .connections
+                .iter()
+                .flat_map(|c| [c.source_node, c.dest_node])
+                .chain(
+                    self.mod_routes
+                        .iter()
+                        .flat_map(|r| [r.source_node, r.dest_node]),
+                )
+                .collect(),
+        };
+
+        let mut unreachable: Vec<NodeId> = self
+            .nodes
+            .keys()
+            .copied()
+            .filter(|id| !reached.contains(id))
+            .collect();
+        unreachable.sort_unstable();
+        issues.extend(
+            unreachable
+                .into_iter()
+                .map(|node_id| GraphIssue::UnreachableNode { node_id }),
+        );
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_reports_orphan_and_missing_output() {
+        let mut graph = GraphDef::new();
+        let osc = graph.add_node(1);
+        let lfo = graph.add_node(50);
+        let out = graph.add_node(100);
+        let orphan = graph.add_node(1);
+        graph.connect(osc, 0, out, 0);
+        graph.add_mod_route(lfo, osc, 0, 1.0);
+        graph.output_node = Some(out);
+
+        // Modulating a node that reaches the output counts as reaching it
+        assert_eq!(
+            graph.validate(),
+            vec![GraphIssue::UnreachableNode { node_id: orphan }]
+        );
+
+        graph.output_node = None;
+        graph.connections.push(ConnectionDef {
+            source_node: osc,
+            source_port: 0,
+            dest_node: 99,
+            dest_port: 0,
+        });
+        let issues = graph.validate();
+        assert_eq!(
+            issues,
+            vec![
+                GraphIssue::MissingOutput,
+                GraphIssue::DanglingConnection {
+                    source: osc,
+                    dest: 99
+                },
+                GraphIssue::UnreachableNode { node_id: orphan },
+            ]
+        );
+
+        // Only the unused node is a warning
+        let errors: Vec<bool> = issues.iter().map(GraphIssue::is_error).collect();
+        assert_eq!(errors, [true, true, false]);
+    }
 }
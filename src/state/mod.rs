@@ -15,13 +15,19 @@ mod clip;
 mod command;
 mod graph_def;
 mod param_info;
+mod preset;
 mod runtime_graph;
 mod session;
+mod tuning;
+mod undo;
 
 pub use arrangement::*;
 pub use clip::*;
 pub use command::*;
 pub use graph_def::*;
 pub use param_info::*;
+pub use preset::*;
 pub use runtime_graph::*;
 pub use session::*;
+pub use tuning::*;
+pub use undo::*;
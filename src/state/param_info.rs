@@ -97,6 +97,9 @@ pub struct ParamInfo {
 
     /// Step size for discrete parameters (0 = continuous)
     pub step: f32,
+
+    /// Time the engine takes to glide to a new value (0 = instant)
+    pub smooth_ms: f32,
 }
 
 impl ParamInfo {
@@ -113,6 +116,7 @@ impl ParamInfo {
             unit: ParamUnit::None,
             curve: DisplayCurve::Linear,
             step: 0.0,
+            smooth_ms: 0.0,
         }
     }
 
@@ -137,6 +141,12 @@ impl ParamInfo {
         self
     }
 
+    /// Glide to runtime changes over `ms` instead of jumping.
+    pub fn smooth_ms(mut self, ms: f32) -> Self {
+        self.smooth_ms = ms.max(0.0);
+        self
+    }
+
     /// Clamp a value to the valid range.
     #[inline]
     pub fn clamp(&self, value: f32) -> f32 {
@@ -0,0 +1,62 @@
+// Node presets.
+//
+// A preset is a snapshot of one node's parameter values, tagged with the
+// node type it came from so it can't be applied to an incompatible node.
+
+use std::collections::HashMap;
+
+use super::{NodeDef, NodeId, NodeTypeId, ParamId};
+
+/// A reusable parameter set for a single node type.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodePreset {
+    /// Node type this preset was captured from.
+    pub type_id: NodeTypeId,
+
+    /// Parameter values (sparse - only values set on the node).
+    pub param_values: HashMap<ParamId, f32>,
+}
+
+impl NodePreset {
+    /// Capture the current parameter values of a node.
+    pub fn capture(node: &NodeDef) -> Self {
+        Self {
+            type_id: node.type_id,
+            param_values: node.param_values.clone(),
+        }
+    }
+}
+
+/// Error applying a preset to a node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PresetError {
+    /// The target node does not exist.
+    NodeNotFound { node_id: NodeId },
+
+    /// The preset was captured from a different node type.
+    TypeMismatch {
+        node_id: NodeId,
+        expected: NodeTypeId,
+        found: NodeTypeId,
+    },
+}
+
+impl std::fmt::Display for PresetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresetError::NodeNotFound { node_id } => write!(f, "Node {} not found", node_id),
+            PresetError::TypeMismatch {
+                node_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Preset for node type {} cannot be applied to node {} of type {}",
+                expected, node_id, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
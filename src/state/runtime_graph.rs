@@ -8,9 +8,10 @@
 // - User nodes (instruments, effects) come from GraphDef
 // - Track mixer nodes are auto-generated from Arrangement
 // - Master bus node receives all track outputs
+// - Aux return buses receive track sends and feed the master bus
 // - Node IDs are partitioned to avoid collisions
 
-use super::{Arrangement, ConnectionDef, GraphDef, NodeDef, NodeId, Session, TrackId};
+use super::{Arrangement, AuxId, ConnectionDef, GraphDef, NodeDef, NodeId, Session, TrackId};
 use crate::nodes::{node_types, params};
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -30,12 +31,27 @@ pub const TRACK_VOLUME_OFFSET: NodeId = 0;
 pub const TRACK_PAN_OFFSET: NodeId = 1;
 pub const TRACK_MUTE_OFFSET: NodeId = 2; // Reserved for future mute node
 
+/// First send node offset; a track's send to aux `n` is at this + n.
+pub const TRACK_SEND_OFFSET: NodeId = 8;
+
+/// Number of aux buses, limited by the send slots in a track's range.
+pub const MAX_AUX_BUSES: AuxId = TRACK_NODE_STRIDE - TRACK_SEND_OFFSET;
+
 /// Master bus node ID.
 pub const MASTER_BUS_ID: NodeId = 0x2000_0000;
 
 /// Master output node ID.
 pub const MASTER_OUTPUT_ID: NodeId = 0x2000_0001;
 
+/// Master limiter node ID (only present when the limiter is enabled).
+pub const MASTER_LIMITER_ID: NodeId = 0x2000_0002;
+
+/// Master DC blocker node ID (only present when the blocker is enabled).
+pub const MASTER_DC_BLOCKER_ID: NodeId = 0x2000_0003;
+
+/// Aux return nodes: 0x3000_0000 + aux_id
+pub const AUX_RETURN_BASE: NodeId = 0x3000_0000;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Node ID Helpers
 // ═══════════════════════════════════════════════════════════════════════════
@@ -50,6 +66,16 @@ pub fn track_pan_node(track_id: TrackId) -> NodeId {
     TRACK_NODE_BASE + (track_id * TRACK_NODE_STRIDE) + TRACK_PAN_OFFSET
 }
 
+/// Get the send node ID for a track's send to an aux bus.
+pub fn track_send_node(track_id: TrackId, aux_id: AuxId) -> NodeId {
+    TRACK_NODE_BASE + (track_id * TRACK_NODE_STRIDE) + TRACK_SEND_OFFSET + aux_id
+}
+
+/// Get the return node ID for an aux bus.
+pub fn aux_return_node(aux_id: AuxId) -> NodeId {
+    AUX_RETURN_BASE + aux_id
+}
+
 /// Check if a node ID is in the user range.
 pub fn is_user_node(id: NodeId) -> bool {
     id <= USER_NODE_MAX
@@ -57,7 +83,7 @@ pub fn is_user_node(id: NodeId) -> bool {
 
 /// Check if a node ID is a track mixer node.
 pub fn is_track_node(id: NodeId) -> bool {
-    id >= TRACK_NODE_BASE && id < MASTER_BUS_ID
+    (TRACK_NODE_BASE..MASTER_BUS_ID).contains(&id)
 }
 
 /// Extract track ID from a track node ID.
@@ -79,7 +105,8 @@ impl Session {
     /// This combines:
     /// 1. User-created nodes from `self.graph` (instruments, effects)
     /// 2. Auto-generated track mixer nodes from `self.arrangement`
-    /// 3. Master bus and output routing
+    /// 3. Aux return buses for track sends
+    /// 4. Master bus and output routing (with optional DC blocker and limiter)
     ///
     /// The resulting graph is ready for compilation to the runtime engine.
     pub fn build_runtime_graph(&self) -> GraphDef {
@@ -101,13 +128,38 @@ impl Session {
                 .labeled("Output"),
         );
 
-        // Connect master bus to output
-        graph.connections.push(ConnectionDef {
-            source_node: MASTER_BUS_ID,
-            source_port: 0,
-            dest_node: MASTER_OUTPUT_ID,
-            dest_port: 0,
-        });
+        // Connect master bus to output, through the DC blocker and limiter
+        // if enabled. The limiter sits after the bus so its lookahead delays
+        // every track equally, and after the blocker so it limits the
+        // centred signal.
+        let mut chain = vec![MASTER_BUS_ID];
+        if self.master_dc_blocker_enabled {
+            graph.nodes.insert(
+                MASTER_DC_BLOCKER_ID,
+                NodeDef::new(MASTER_DC_BLOCKER_ID, node_types::DC_BLOCKER)
+                    .at(850.0, 300.0)
+                    .labeled("DC Blocker"),
+            );
+            chain.push(MASTER_DC_BLOCKER_ID);
+        }
+        if self.master_limiter_enabled {
+            graph.nodes.insert(
+                MASTER_LIMITER_ID,
+                NodeDef::new(MASTER_LIMITER_ID, node_types::LIMITER)
+                    .at(900.0, 300.0)
+                    .labeled("Limiter"),
+            );
+            chain.push(MASTER_LIMITER_ID);
+        }
+        chain.push(MASTER_OUTPUT_ID);
+        for pair in chain.windows(2) {
+            graph.connections.push(ConnectionDef {
+                source_node: pair[0],
+                source_port: 0,
+                dest_node: pair[1],
+                dest_port: 0,
+            });
+        }
 
         // Set the output node
         graph.output_node = Some(MASTER_OUTPUT_ID);
@@ -115,6 +167,9 @@ impl Session {
         // Add mixer chain for each track
         self.build_track_mixers(&mut graph);
 
+        // Add a return bus for every aux that receives a send
+        self.build_aux_returns(&mut graph);
+
         graph
     }
 
@@ -128,6 +183,10 @@ impl Session {
     /// Build the mixer chain for a single track.
     ///
     /// Chain: [instrument] -> Volume -> Pan -> [Master Bus]
+    ///
+    /// A track in a group feeds the group's Volume instead of the master bus.
+    ///
+    /// Each send taps the pan output: Pan -> Send -> [Aux Return]
     fn build_track_mixer(&self, graph: &mut GraphDef, track_id: TrackId) {
         let track = match self.arrangement.get_track(track_id) {
             Some(t) => t,
@@ -166,16 +225,27 @@ impl Session {
             dest_port: 0,
         });
 
-        // Wire: Pan -> Master Bus
+        // Wire: Pan -> Group Volume, or Pan -> Master Bus
+        let (dest_node, dest_port) = match self.arrangement.group_parent(track_id) {
+            Some(parent) => (track_volume_node(parent), 0),
+            None => (MASTER_BUS_ID, track_id), // Each track feeds a different input
+        };
         graph.connections.push(ConnectionDef {
             source_node: pan_id,
             source_port: 0,
-            dest_node: MASTER_BUS_ID,
-            dest_port: track_id, // Each track feeds a different input
+            dest_node,
+            dest_port,
         });
 
-        // Wire: Instrument -> Volume (if track has a target node)
-        if let Some(target_node) = track.target_node {
+        // Wire: Instrument -> Volume (if track has a target node), plus
+        // any other instruments its key zones play
+        let mut instruments: Vec<NodeId> = track.target_node.into_iter().collect();
+        for zone in &track.key_zones {
+            if !instruments.contains(&zone.target_node) {
+                instruments.push(zone.target_node);
+            }
+        }
+        for target_node in instruments {
             graph.connections.push(ConnectionDef {
                 source_node: target_node,
                 source_port: 0,
@@ -183,6 +253,65 @@ impl Session {
                 dest_port: 0,
             });
         }
+
+        // Post-fader sends: Pan -> Send -> Aux Return
+        for send in &track.sends {
+            let send_id = track_send_node(track_id, send.aux_id);
+            graph.nodes.insert(
+                send_id,
+                NodeDef::new(send_id, node_types::GAIN)
+                    .at(650.0, 100.0 + (track_id as f32 * 80.0))
+                    .with_param(params::GAIN, send.level)
+                    .labeled(format!("{} Send {}", track.name, send.aux_id)),
+            );
+            graph.connections.push(ConnectionDef {
+                source_node: pan_id,
+                source_port: 0,
+                dest_node: send_id,
+                dest_port: 0,
+            });
+            graph.connections.push(ConnectionDef {
+                source_node: send_id,
+                source_port: 0,
+                dest_node: aux_return_node(send.aux_id),
+                dest_port: track_id, // Each track feeds a different input
+            });
+        }
+    }
+
+    /// Build return buses for all aux buses that receive a send.
+    ///
+    /// Chain: [sends] -> Aux Return -> [effect] -> [Master Bus]
+    fn build_aux_returns(&self, graph: &mut GraphDef) {
+        for aux_id in self.arrangement.active_aux_buses() {
+            let return_id = aux_return_node(aux_id);
+            graph.nodes.insert(
+                return_id,
+                NodeDef::new(return_id, node_types::MIXER)
+                    .at(700.0, 500.0 + (aux_id as f32 * 80.0))
+                    .labeled(format!("Aux {aux_id} Return")),
+            );
+
+            // The return reaches the master bus through its effect, if any
+            let bus_source = match self.arrangement.aux_effects.get(&aux_id) {
+                Some(&effect_id) if graph.nodes.contains_key(&effect_id) => {
+                    graph.connections.push(ConnectionDef {
+                        source_node: return_id,
+                        source_port: 0,
+                        dest_node: effect_id,
+                        dest_port: 0,
+                    });
+                    effect_id
+                }
+                _ => return_id,
+            };
+            graph.connections.push(ConnectionDef {
+                source_node: bus_source,
+                source_port: 0,
+                dest_node: MASTER_BUS_ID,
+                dest_port: 0,
+            });
+        }
     }
 
     /// Update track mixer parameters in an existing runtime graph.
@@ -196,12 +325,21 @@ impl Session {
             let volume_id = track_volume_node(track_id);
             let pan_id = track_pan_node(track_id);
 
-            // Volume (incorporating mute state)
-            let effective_volume = if track.mute { 0.0 } else { track.volume };
+            // Volume (incorporating mute and solo state)
+            let effective_volume = self.arrangement.effective_volume(track_id);
             changes.push((volume_id, params::GAIN, effective_volume));
 
             // Pan
             changes.push((pan_id, params::PAN, track.pan));
+
+            // Send levels
+            for send in &track.sends {
+                changes.push((
+                    track_send_node(track_id, send.aux_id),
+                    params::GAIN,
+                    send.level,
+                ));
+            }
         }
 
         changes
@@ -286,4 +424,170 @@ mod tests {
         assert!(graph.nodes.contains_key(&track_volume_node(1)));
         assert!(graph.nodes.contains_key(&track_pan_node(1)));
     }
+
+    #[test]
+    fn test_solo_silences_other_tracks() {
+        let mut session = Session::new("Test");
+        let lead = session.arrangement.create_track("Lead");
+        let bass = session.arrangement.create_track("Bass");
+        session.arrangement.set_track_volume(lead, 0.8);
+        session.arrangement.set_track_volume(bass, 0.6);
+
+        let volume = |session: &Session, track_id| {
+            let volume_id = track_volume_node(track_id);
+            session
+                .sync_all_track_params()
+                .into_iter()
+                .find(|&(node_id, param_id, _)| node_id == volume_id && param_id == params::GAIN)
+                .map(|(_, _, value)| value)
+                .unwrap()
+        };
+        assert_eq!(volume(&session, lead), 0.8);
+        assert_eq!(volume(&session, bass), 0.6);
+
+        session.arrangement.set_track_solo(lead, true);
+        assert_eq!(volume(&session, lead), 0.8);
+        assert_eq!(volume(&session, bass), 0.0);
+
+        // Unsoloing brings the other track back
+        session.arrangement.set_track_solo(lead, false);
+        assert_eq!(volume(&session, bass), 0.6);
+    }
+
+    #[test]
+    fn test_master_limiter_insertion() {
+        let mut session = Session::new("Test");
+        let graph = session.build_runtime_graph();
+        assert!(!graph.nodes.contains_key(&MASTER_LIMITER_ID));
+
+        session.master_limiter_enabled = true;
+        let graph = session.build_runtime_graph();
+        assert!(graph.nodes.contains_key(&MASTER_LIMITER_ID));
+        assert!(graph.connections.iter().any(|c| {
+            c.source_node == MASTER_BUS_ID && c.dest_node == MASTER_LIMITER_ID
+        }));
+        assert!(graph.connections.iter().any(|c| {
+            c.source_node == MASTER_LIMITER_ID && c.dest_node == MASTER_OUTPUT_ID
+        }));
+        assert!(!graph.connections.iter().any(|c| {
+            c.source_node == MASTER_BUS_ID && c.dest_node == MASTER_OUTPUT_ID
+        }));
+    }
+
+    #[test]
+    fn test_master_dc_blocker_insertion() {
+        let mut session = Session::new("Test");
+        let graph = session.build_runtime_graph();
+        assert!(!graph.nodes.contains_key(&MASTER_DC_BLOCKER_ID));
+
+        // Bus -> DC blocker -> limiter -> output
+        session.master_dc_blocker_enabled = true;
+        session.master_limiter_enabled = true;
+        let graph = session.build_runtime_graph();
+        let links: Vec<(NodeId, NodeId)> = graph
+            .connections
+            .iter()
+            .filter(|c| (MASTER_BUS_ID..AUX_RETURN_BASE).contains(&c.source_node))
+            .map(|c| (c.source_node, c.dest_node))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (MASTER_BUS_ID, MASTER_DC_BLOCKER_ID),
+                (MASTER_DC_BLOCKER_ID, MASTER_LIMITER_ID),
+                (MASTER_LIMITER_ID, MASTER_OUTPUT_ID),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aux_send_return() {
+        let mut session = Session::new("Test");
+        let reverb = session.graph.add_node(node_types::REVERB);
+        let drums = session.arrangement.create_track("Drums");
+        let keys = session.arrangement.create_track("Keys");
+        session.arrangement.set_track_send(drums, 0, 0.3);
+        session.arrangement.set_track_send(keys, 0, 0.6);
+        session.arrangement.set_aux_effect(0, Some(reverb));
+
+        let graph = session.build_runtime_graph();
+        let connected = |source, dest| {
+            graph
+                .connections
+                .iter()
+                .any(|c| c.source_node == source && c.dest_node == dest)
+        };
+
+        // One return for the shared aux, reaching the master through the reverb
+        let return_id = aux_return_node(0);
+        assert_eq!(graph.nodes[&return_id].type_id, node_types::MIXER);
+        assert!(!graph.nodes.contains_key(&aux_return_node(1)));
+        assert!(connected(return_id, reverb));
+        assert!(connected(reverb, MASTER_BUS_ID));
+        assert!(!connected(return_id, MASTER_BUS_ID));
+
+        // Each track feeds both the master bus and its send
+        for (track, level) in [(drums, 0.3), (keys, 0.6)] {
+            let send_id = track_send_node(track, 0);
+            assert_eq!(track_id_from_node(send_id), Some(track));
+            assert_eq!(graph.nodes[&send_id].param_values[&params::GAIN], level);
+            assert!(connected(track_pan_node(track), MASTER_BUS_ID));
+            assert!(connected(track_pan_node(track), send_id));
+            assert!(connected(send_id, return_id));
+        }
+
+        // A zero level removes the send; the return goes with the last one
+        session.arrangement.set_track_send(drums, 0, 0.0);
+        session.arrangement.set_track_send(keys, 0, 0.0);
+        let graph = session.build_runtime_graph();
+        assert!(!graph.nodes.contains_key(&track_send_node(drums, 0)));
+        assert!(!graph.nodes.contains_key(&return_id));
+    }
+
+    #[test]
+    fn test_group_tracks() {
+        let mut session = Session::new("Test");
+        let group = session.arrangement.create_track("Drums");
+        let kick = session.arrangement.create_track("Kick");
+        let snare = session.arrangement.create_track("Snare");
+        assert!(session.arrangement.set_track_parent(kick, Some(group)));
+        assert!(session.arrangement.set_track_parent(snare, Some(group)));
+
+        // A group can't be routed into its own child, or itself
+        assert!(!session.arrangement.set_track_parent(group, Some(kick)));
+        assert!(!session.arrangement.set_track_parent(group, Some(group)));
+
+        let graph = session.build_runtime_graph();
+        let into_master: Vec<NodeId> = graph
+            .connections
+            .iter()
+            .filter(|c| c.dest_node == MASTER_BUS_ID)
+            .map(|c| c.source_node)
+            .collect();
+        assert_eq!(into_master, vec![track_pan_node(group)]);
+        for child in [kick, snare] {
+            assert!(graph.connections.iter().any(|c| {
+                c.source_node == track_pan_node(child) && c.dest_node == track_volume_node(group)
+            }));
+        }
+
+        // Tracks in a cycle slipped in directly fall back to the master bus
+        let group_track = session.arrangement.get_track_mut(group).unwrap();
+        group_track.parent_track = Some(kick);
+        assert_eq!(session.arrangement.group_parent(group), None);
+        assert_eq!(session.arrangement.group_parent(kick), None);
+        assert_eq!(session.arrangement.group_parent(snare), Some(group));
+        let graph = session.build_runtime_graph();
+        for track in [group, kick] {
+            assert!(graph.connections.iter().any(|c| {
+                c.source_node == track_pan_node(track) && c.dest_node == MASTER_BUS_ID
+            }));
+        }
+        let group_track = session.arrangement.get_track_mut(group).unwrap();
+        group_track.parent_track = None;
+
+        // Deleting the group sends its children back to the master bus
+        session.arrangement.delete_track(group);
+        assert_eq!(session.arrangement.group_parent(snare), None);
+    }
 }
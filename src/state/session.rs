@@ -3,10 +3,16 @@
 // The Session represents the complete state of a project.
 // It can be serialized for save/load.
 
-use super::{Arrangement, GraphDef};
+use std::collections::HashMap;
+
+use crate::arpeggiator::ArpSettings;
+use crate::voice_allocator::{VoiceMode, VoiceStealMode};
+
+use super::{Arrangement, GraphDef, NodeId, ParamId, ParamInfo, TuningTable};
 
 /// Transport state visible to the UI.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct TransportState {
     /// Whether the transport is playing.
     pub playing: bool,
@@ -28,6 +34,20 @@ pub struct TransportState {
 
     /// Loop end in beats.
     pub loop_end: f64,
+
+    /// Time signature numerator; the metronome accents each bar's first beat.
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default = "TransportState::default_beats_per_bar")
+    )]
+    pub beats_per_bar: u32,
+
+    /// Reference pitch of A4 in Hz.
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default = "TransportState::default_tuning")
+    )]
+    pub tuning: f64,
 }
 
 impl TransportState {
@@ -40,8 +60,49 @@ impl TransportState {
             loop_enabled: false,
             loop_start: 0.0,
             loop_end: 4.0,
+            beats_per_bar: Self::default_beats_per_bar(),
+            tuning: Self::default_tuning(),
         }
     }
+
+    fn default_beats_per_bar() -> u32 {
+        4
+    }
+
+    fn default_tuning() -> f64 {
+        crate::node::DEFAULT_TUNING
+    }
+}
+
+/// Routes a MIDI CC to a node parameter.
+///
+/// The range is captured from the parameter's `ParamInfo` when the
+/// mapping is made, so incoming 0..1 values land inside it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CcMapping {
+    pub node_id: NodeId,
+    pub param_id: ParamId,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl CcMapping {
+    /// Map to the full declared range of a parameter.
+    pub fn new(node_id: NodeId, info: &ParamInfo) -> Self {
+        Self {
+            node_id,
+            param_id: info.id,
+            min: info.min,
+            max: info.max,
+        }
+    }
+
+    /// Scale a normalized CC value (0..1) into the parameter range.
+    #[inline]
+    pub fn scale(&self, value: f32) -> f32 {
+        self.min + value.clamp(0.0, 1.0) * (self.max - self.min)
+    }
 }
 
 /// Complete session state.
@@ -50,6 +111,7 @@ impl TransportState {
 /// The UI owns this and the bridge synchronizes relevant parts
 /// to the real-time engine.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Session {
     /// Project name.
     pub name: String,
@@ -69,8 +131,52 @@ pub struct Session {
     /// Maximum voices for polyphony.
     pub max_voices: usize,
 
+    /// Polyphonic or monophonic voice allocation.
+    pub voice_mode: VoiceMode,
+
+    /// Portamento time in seconds between mono notes.
+    pub glide_time: f32,
+
+    /// Which voice to steal when polyphony is exhausted.
+    pub voice_steal_mode: VoiceStealMode,
+
+    /// Unison voices per note (1 = off).
+    pub unison_voices: usize,
+
+    /// Detune of the outermost unison voices, in cents.
+    pub unison_detune: f32,
+
+    /// Stereo width of the unison stack (0..1).
+    pub unison_spread: f32,
+
+    /// Pan voices by note number, low left to high right (0..1).
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub voice_pan_spread: f32,
+
+    /// Arpeggiator playing held live notes; notes play directly when `None`.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub arpeggiator: Option<ArpSettings>,
+
+    /// Microtonal tuning table; equal temperament when `None`.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub tuning_table: Option<TuningTable>,
+
     /// Maximum block size.
     pub max_block_size: usize,
+
+    /// Insert a brickwall limiter between the master bus and output.
+    pub master_limiter_enabled: bool,
+
+    /// Insert a DC blocker between the master bus and output.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub master_dc_blocker_enabled: bool,
+
+    /// Mix a click on every beat into the master output.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub metronome_enabled: bool,
+
+    /// MIDI CC number to parameter mappings.
+    pub cc_mappings: HashMap<u8, CcMapping>,
 }
 
 impl Session {
@@ -82,9 +188,43 @@ impl Session {
             transport: TransportState::new(),
             sample_rate: 48_000.0,
             max_voices: 8,
+            voice_mode: VoiceMode::Poly,
+            glide_time: 0.0,
+            voice_steal_mode: VoiceStealMode::Oldest,
+            unison_voices: 1,
+            unison_detune: 0.0,
+            unison_spread: 0.0,
+            voice_pan_spread: 0.0,
+            arpeggiator: None,
+            tuning_table: None,
             max_block_size: 512,
+            master_limiter_enabled: false,
+            master_dc_blocker_enabled: false,
+            metronome_enabled: false,
+            cc_mappings: HashMap::new(),
         }
     }
+
+    /// Resolve a CC value to its `(node, param, value)` target, if mapped.
+    pub fn control_change(&self, cc: u8, value: f32) -> Option<(NodeId, ParamId, f32)> {
+        self.cc_mappings
+            .get(&cc)
+            .map(|m| (m.node_id, m.param_id, m.scale(value)))
+    }
+
+    /// Serialize the project to JSON.
+    ///
+    /// Audio pool entries keep their metadata but not their samples.
+    #[cfg(feature = "serialize")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Load a project saved with `to_json`.
+    #[cfg(feature = "serialize")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
 }
 
 impl Default for Session {
@@ -117,3 +257,60 @@ pub struct EngineReadback {
     /// Whether the engine is currently processing.
     pub running: bool,
 }
+
+#[cfg(all(test, feature = "serialize"))]
+mod tests {
+    use super::*;
+    use crate::state::{AudioRegionDef, NoteDef};
+
+    #[test]
+    fn test_session_json_round_trip() {
+        let mut session = Session::new("Song");
+        let osc = session.graph.add_node(1);
+        let out = session.graph.add_node(2);
+        session.graph.connect(osc, 0, out, 0);
+        session.graph.set_param(osc, 0, 220.0);
+        session.graph.output_node = Some(out);
+        session.unison_voices = 3;
+        session.cc_mappings.insert(
+            74,
+            CcMapping {
+                node_id: osc,
+                param_id: 0,
+                min: 20.0,
+                max: 2000.0,
+            },
+        );
+
+        let arrangement = &mut session.arrangement;
+        let audio = arrangement.add_audio_to_pool("kick.wav", 44_100.0, 1, vec![0.5; 100]);
+        let track = arrangement.create_track("Lead");
+        arrangement.set_track_target(track, Some(osc));
+        arrangement.create_scene("Verse");
+        let clip = arrangement.create_clip("Riff", 4.0);
+        arrangement.add_note_to_clip(clip, NoteDef::new(0.0, 1.0, 60, 0.8));
+        arrangement.add_audio_to_clip(clip, AudioRegionDef::new(2.0, 1.0, audio).with_pitch(-3.0));
+        arrangement.schedule_clip(track, clip, 8.0);
+
+        let json = session.to_json().unwrap();
+        let mut loaded = Session::from_json(&json).unwrap();
+
+        // Compare as JSON values so hash map ordering doesn't matter
+        let value = |json: &str| serde_json::from_str::<serde_json::Value>(json).unwrap();
+        assert_eq!(value(&loaded.to_json().unwrap()), value(&json));
+        assert_eq!(loaded.arrangement.get_clip(clip).unwrap().events.len(), 2);
+        assert_eq!(loaded.control_change(74, 1.0), Some((osc, 0, 2000.0)));
+
+        // PCM is not inlined; the host reattaches it by ID
+        assert!(!json.contains("samples"));
+        let entry = loaded.arrangement.get_audio(audio).unwrap();
+        assert_eq!(entry.frames, 100);
+        assert!(entry.samples.is_empty());
+        let pool = &mut loaded.arrangement.audio_pool;
+        assert!(pool.set_samples(audio, vec![0.5; 100]));
+        assert_eq!(pool.get(audio).unwrap().samples.len(), 100);
+
+        // ID counters survive, so new objects don't collide
+        assert_ne!(loaded.arrangement.create_clip("New", 1.0), clip);
+    }
+}
@@ -0,0 +1,138 @@
+// Tuning tables for microtonal scales.
+//
+// A table is a repeating scale given in cents, in the style of a Scala
+// file: each entry is a scale degree above the root and the last entry is
+// the period the scale repeats at (1200 cents for an octave). The root
+// note keeps its equal-tempered pitch, so the A4 reference still applies.
+
+/// Number of MIDI notes a table maps.
+pub const NOTE_COUNT: usize = 128;
+
+/// A repeating scale mapping MIDI notes to frequencies.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TuningTable {
+    /// Scale degrees in cents above the root; the last one is the period.
+    pub cents: Vec<f64>,
+
+    /// MIDI note the scale starts on.
+    pub root_note: u8,
+}
+
+impl TuningTable {
+    /// Build a table from scale degrees in cents, ending with the period.
+    pub fn new(cents: Vec<f64>, root_note: u8) -> Result<Self, TuningError> {
+        match cents.last() {
+            None => Err(TuningError::Empty),
+            Some(&period) if period <= 0.0 => Err(TuningError::InvalidPeriod { cents: period }),
+            Some(_) => Ok(Self {
+                cents,
+                root_note: root_note.min(NOTE_COUNT as u8 - 1),
+            }),
+        }
+    }
+
+    /// Parse a list of cents values, one per line.
+    ///
+    /// Blank lines and lines starting with `!` (Scala comments) are skipped.
+    pub fn from_cents_list(text: &str, root_note: u8) -> Result<Self, TuningError> {
+        let mut cents = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('!') {
+                continue;
+            }
+            let value = line
+                .split_whitespace()
+                .next()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| v.is_finite())
+                .ok_or(TuningError::InvalidLine { line: index + 1 })?;
+            cents.push(value);
+        }
+        Self::new(cents, root_note)
+    }
+
+    /// Cents of `note` above the root (negative below it).
+    pub fn cents_above_root(&self, note: u8) -> f64 {
+        let steps = self.cents.len() as i32;
+        let period = self.cents[self.cents.len() - 1];
+        let degree = note as i32 - self.root_note as i32;
+        let repeats = degree.div_euclid(steps) as f64;
+        let offset = match degree.rem_euclid(steps) {
+            0 => 0.0,
+            d => self.cents[d as usize - 1],
+        };
+        repeats * period + offset
+    }
+
+    /// Frequency ratio of `note` to A4.
+    pub fn ratio(&self, note: u8) -> f64 {
+        let root = (self.root_note as f64 - 69.0) / 12.0;
+        2.0_f64.powf(root + self.cents_above_root(note) / 1200.0)
+    }
+
+    /// Frequency ratio to A4 of every MIDI note.
+    pub fn note_ratios(&self) -> [f32; NOTE_COUNT] {
+        std::array::from_fn(|note| self.ratio(note as u8) as f32)
+    }
+}
+
+/// Error building a tuning table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TuningError {
+    /// The scale has no degrees.
+    Empty,
+
+    /// A line of a cents list isn't a number.
+    InvalidLine { line: usize },
+
+    /// The scale doesn't rise over its period.
+    InvalidPeriod { cents: f64 },
+}
+
+impl std::fmt::Display for TuningError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TuningError::Empty => write!(f, "Tuning table has no scale degrees"),
+            TuningError::InvalidLine { line } => {
+                write!(f, "Line {} of the cents list is not a number", line)
+            }
+            TuningError::InvalidPeriod { cents } => {
+                write!(f, "Scale period of {} cents must be positive", cents)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TuningError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarter_tone_scale() {
+        let text: String = (1..=24).map(|step| format!("{}\n", step * 50)).collect();
+        let table = TuningTable::from_cents_list(&format!("! 24-EDO\n{text}"), 60).unwrap();
+        let ratios = table.note_ratios();
+
+        // The root keeps its equal-tempered pitch...
+        let equal = |note: f64| 2.0_f64.powf((note - 69.0) / 12.0);
+        assert!((ratios[60] as f64 - equal(60.0)).abs() < 1e-6);
+
+        // ...and the next note sits halfway to the equal-tempered C#
+        assert!((ratios[61] as f64 - equal(60.5)).abs() < 1e-6);
+        let cents = 1200.0 * (ratios[61] as f64 / equal(61.0)).log2();
+        assert!((cents + 50.0).abs() < 1e-3, "cents = {cents}");
+
+        // A period of 24 notes spans an octave, below the root too
+        assert!((ratios[84] / ratios[60] - 2.0).abs() < 1e-5);
+        assert!((ratios[36] / ratios[60] - 0.5).abs() < 1e-5);
+
+        assert_eq!(
+            TuningTable::from_cents_list("100\nfoo\n", 60),
+            Err(TuningError::InvalidLine { line: 2 })
+        );
+    }
+}
@@ -0,0 +1,364 @@
+// Undo/redo history for graph edits.
+//
+// Each undoable command is recorded as a step holding the commands that
+// redo it and the commands that revert it. Both are ordinary Commands, so
+// undoing goes through the same path as any other edit and the engine
+// recompiles or updates parameters exactly as it would for the original.
+//
+// Only graph edits are recorded; transport, MIDI and arrangement commands
+// pass through untouched.
+
+use super::{Command, GraphDef, NodeId, NodeTypeId, ParamId};
+
+/// Maximum number of undo steps kept.
+const MAX_UNDO_STEPS: usize = 256;
+
+/// One undoable edit.
+#[derive(Debug, Clone, Default)]
+pub struct UndoStep {
+    /// Commands that re-apply the edit, in order.
+    pub redo: Vec<Command>,
+
+    /// Commands that revert the edit, in order.
+    pub undo: Vec<Command>,
+}
+
+/// Undo and redo stacks, with parameter gestures coalesced into one step.
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    undo_stack: Vec<UndoStep>,
+    redo_stack: Vec<UndoStep>,
+
+    /// Parameter gesture in progress, if any.
+    gesture: Option<(NodeId, u32)>,
+
+    /// Whether a group of edits is being coalesced (see `begin_group`).
+    group: bool,
+
+    /// Whether the open gesture or group already has a step to merge into.
+    gesture_recorded: bool,
+}
+
+impl UndoHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Start coalescing edits into a single step until `end_gesture`.
+    pub fn begin_gesture(&mut self, node_id: NodeId, param_id: u32) {
+        self.gesture = Some((node_id, param_id));
+        self.gesture_recorded = false;
+    }
+
+    /// Close the gesture started with `begin_gesture`.
+    pub fn end_gesture(&mut self, node_id: NodeId, param_id: u32) {
+        if self.gesture == Some((node_id, param_id)) {
+            self.gesture = None;
+            self.gesture_recorded = false;
+        }
+    }
+
+    /// Coalesce every edit until `end_group` into a single step, e.g. the
+    /// parameter changes of applying a preset.
+    pub fn begin_group(&mut self) {
+        self.group = true;
+        self.gesture_recorded = false;
+    }
+
+    /// Close the group started with `begin_group`.
+    pub fn end_group(&mut self) {
+        self.group = false;
+        self.gesture_recorded = false;
+    }
+
+    /// Record a new edit. Clears the redo stack.
+    pub fn record(&mut self, step: UndoStep) {
+        self.redo_stack.clear();
+
+        let coalescing = self.gesture.is_some() || self.group;
+        let merging = coalescing && self.gesture_recorded;
+        match self.undo_stack.last_mut() {
+            Some(last) if merging => merge(last, step),
+            _ => {
+                self.gesture_recorded = coalescing;
+                self.undo_stack.push(step);
+                if self.undo_stack.len() > MAX_UNDO_STEPS {
+                    self.undo_stack.remove(0);
+                }
+            }
+        }
+    }
+
+    /// Pop the most recent step to undo; it moves to the redo stack.
+    pub fn take_undo(&mut self) -> Option<UndoStep> {
+        let step = self.undo_stack.pop()?;
+        self.gesture_recorded = false;
+        self.redo_stack.push(step.clone());
+        Some(step)
+    }
+
+    /// Pop the most recently undone step; it moves back to the undo stack.
+    pub fn take_redo(&mut self) -> Option<UndoStep> {
+        let step = self.redo_stack.pop()?;
+        self.undo_stack.push(step.clone());
+        Some(step)
+    }
+
+    /// Forget all history.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.gesture = None;
+        self.group = false;
+        self.gesture_recorded = false;
+    }
+}
+
+/// Fold a later edit into `last`.
+///
+/// Repeated values for the same parameter collapse to the newest one, and
+/// the oldest inverse for a node is kept since it is applied last.
+fn merge(last: &mut UndoStep, step: UndoStep) {
+    for cmd in step.redo {
+        match (last.redo.last_mut(), &cmd) {
+            (
+                Some(Command::SetParam {
+                    node_id,
+                    param_id,
+                    value,
+                }),
+                Command::SetParam {
+                    node_id: new_node,
+                    param_id: new_param,
+                    value: new_value,
+                },
+            ) if node_id == new_node && param_id == new_param => *value = *new_value,
+            _ => last.redo.push(cmd),
+        }
+    }
+
+    let mut undo: Vec<Command> = step
+        .undo
+        .into_iter()
+        .filter(|cmd| !last.undo.iter().any(|old| reverts_same_param(old, cmd)))
+        .collect();
+    undo.append(&mut last.undo);
+    last.undo = undo;
+}
+
+/// Whether reverting with `old` makes reverting with `new` redundant.
+fn reverts_same_param(old: &Command, new: &Command) -> bool {
+    let Command::SetParam {
+        node_id, param_id, ..
+    } = new
+    else {
+        return false;
+    };
+    match old {
+        Command::SetParam {
+            node_id: old_node,
+            param_id: old_param,
+            ..
+        } => old_node == node_id && old_param == param_id,
+        Command::RestoreNode { node, .. } => node.id == *node_id,
+        _ => false,
+    }
+}
+
+/// Commands that revert `cmd`, computed from the graph before it applies.
+///
+/// `param_default` gives the registry default of a node type's parameter,
+/// for reverting parameters that were never set.
+///
+/// Returns `None` for commands that aren't recorded or would change nothing.
+pub(crate) fn invert_command(
+    graph: &GraphDef,
+    cmd: &Command,
+    param_default: impl Fn(NodeTypeId, ParamId) -> Option<f32>,
+) -> Option<Vec<Command>> {
+    let inverse = match cmd {
+        Command::AddNode { .. } | Command::AddNodeDef { .. } => vec![Command::RemoveNode {
+            node_id: graph.next_node_id(),
+        }],
+
+        Command::RemoveNode { node_id } => {
+            let node = graph.get_node(*node_id)?;
+            let mut inverse = vec![Command::RestoreNode {
+                node: node.clone(),
+                connections: graph
+                    .connections
+                    .iter()
+                    .filter(|c| c.source_node == *node_id || c.dest_node == *node_id)
+                    .cloned()
+                    .collect(),
+                mod_routes: graph
+                    .mod_routes
+                    .iter()
+                    .filter(|r| r.source_node == *node_id || r.dest_node == *node_id)
+                    .cloned()
+                    .collect(),
+            }];
+            if graph.output_node == Some(*node_id) {
+                inverse.push(Command::SetOutputNode { node_id: *node_id });
+            }
+            inverse
+        }
+
+        Command::Connect {
+            source_node,
+            source_port,
+            dest_node,
+            dest_port,
+        } => {
+            if graph.is_connected(*source_node, *source_port, *dest_node, *dest_port) {
+                return None;
+            }
+            vec![Command::Disconnect {
+                source_node: *source_node,
+                source_port: *source_port,
+                dest_node: *dest_node,
+                dest_port: *dest_port,
+            }]
+        }
+
+        Command::Disconnect {
+            source_node,
+            source_port,
+            dest_node,
+            dest_port,
+        } => {
+            if !graph.is_connected(*source_node, *source_port, *dest_node, *dest_port) {
+                return None;
+            }
+            vec![Command::Connect {
+                source_node: *source_node,
+                source_port: *source_port,
+                dest_node: *dest_node,
+                dest_port: *dest_port,
+            }]
+        }
+
+        // There is no command to clear the output, so only a change from
+        // one output node to another can be reverted
+        Command::SetOutputNode { .. } => vec![Command::SetOutputNode {
+            node_id: graph.output_node?,
+        }],
+
+        Command::AddModRoute {
+            source_node,
+            dest_node,
+            param_id,
+            ..
+        } => match graph.find_mod_route(*source_node, *dest_node, *param_id) {
+            Some(route) => vec![Command::AddModRoute {
+                source_node: *source_node,
+                dest_node: *dest_node,
+                param_id: *param_id,
+                depth: route.depth,
+            }],
+            None => vec![Command::RemoveModRoute {
+                source_node: *source_node,
+                dest_node: *dest_node,
+                param_id: *param_id,
+            }],
+        },
+
+        Command::RemoveModRoute {
+            source_node,
+            dest_node,
+            param_id,
+        } => {
+            let route = graph.find_mod_route(*source_node, *dest_node, *param_id)?;
+            vec![Command::AddModRoute {
+                source_node: *source_node,
+                dest_node: *dest_node,
+                param_id: *param_id,
+                depth: route.depth,
+            }]
+        }
+
+        Command::MoveNode { node_id, .. } => vec![Command::MoveNode {
+            node_id: *node_id,
+            position: graph.get_node(*node_id)?.position,
+        }],
+
+        Command::SetParam {
+            node_id, param_id, ..
+        } => {
+            let node = graph.get_node(*node_id)?;
+            let value = node
+                .param_values
+                .get(param_id)
+                .copied()
+                .or_else(|| param_default(node.type_id, *param_id));
+            match value {
+                Some(value) => vec![Command::SetParam {
+                    node_id: *node_id,
+                    param_id: *param_id,
+                    value,
+                }],
+                // A parameter the registry doesn't know; put the node
+                // definition back as it was instead
+                None => vec![Command::RestoreNode {
+                    node: node.clone(),
+                    connections: Vec::new(),
+                    mod_routes: Vec::new(),
+                }],
+            }
+        }
+
+        Command::SetVelocitySensitivity {
+            node_id, param_id, ..
+        } => {
+            let node = graph.get_node(*node_id)?;
+            vec![Command::SetVelocitySensitivity {
+                node_id: *node_id,
+                param_id: *param_id,
+                amount: node
+                    .velocity_sensitivity
+                    .get(param_id)
+                    .copied()
+                    .unwrap_or(0.0),
+            }]
+        }
+
+        Command::SetNodeBypass { node_id, bypassed } => {
+            let node = graph.get_node(*node_id)?;
+            if node.bypassed == *bypassed {
+                return None;
+            }
+            vec![Command::SetNodeBypass {
+                node_id: *node_id,
+                bypassed: node.bypassed,
+            }]
+        }
+
+        _ => return None,
+    };
+    Some(inverse)
+}
+
+/// The command that redoes `cmd`, computed from the graph after it applied.
+///
+/// Added nodes are redone by restoring them, so they keep their IDs.
+pub(crate) fn redo_command(graph: &GraphDef, cmd: &Command, added: NodeId) -> Command {
+    match cmd {
+        Command::AddNode { .. } | Command::AddNodeDef { .. } => match graph.get_node(added) {
+            Some(node) => Command::RestoreNode {
+                node: node.clone(),
+                connections: Vec::new(),
+                mod_routes: Vec::new(),
+            },
+            None => cmd.clone(),
+        },
+        _ => cmd.clone(),
+    }
+}
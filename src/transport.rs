@@ -61,6 +61,43 @@ impl Transport {
 // ===================================
 //
 
+/// A tempo change spread over a number of beats.
+///
+/// Tempo moves linearly per beat, so across the ramp it rises (or falls)
+/// exponentially in time; both directions have closed forms.
+#[derive(Debug, Clone, Copy)]
+struct TempoRamp {
+    /// Tempo once the ramp completes
+    target_bpm: f64,
+
+    /// Tempo change per beat
+    rate: f64,
+
+    /// Beats left until the ramp completes
+    remaining: f64,
+}
+
+/// Seconds to play `beats` starting at `bpm`, with tempo rising `rate`
+/// BPM per beat.
+#[inline]
+fn ramp_seconds(bpm: f64, rate: f64, beats: f64) -> f64 {
+    if rate.abs() < 1e-12 {
+        beats * 60.0 / bpm
+    } else {
+        60.0 / rate * (1.0 + rate * beats / bpm).ln()
+    }
+}
+
+/// Beats played in `seconds` starting at `bpm`; inverse of `ramp_seconds`.
+#[inline]
+fn ramp_beats(bpm: f64, rate: f64, seconds: f64) -> f64 {
+    if rate.abs() < 1e-12 {
+        seconds * bpm / 60.0
+    } else {
+        bpm / rate * ((rate * seconds / 60.0).exp() - 1.0)
+    }
+}
+
 /// Musical-time transport.
 ///
 /// This struct:
@@ -80,6 +117,18 @@ pub struct MusicalTransport {
 
     /// Musical position in beats
     beat_pos: f64,
+
+    /// Whether playback wraps from `loop_end` back to `loop_start`
+    loop_enabled: bool,
+
+    /// Loop start in beats
+    loop_start: f64,
+
+    /// Loop end in beats
+    loop_end: f64,
+
+    /// Tempo ramp in progress, starting from `bpm`
+    ramp: Option<TempoRamp>,
 }
 
 impl MusicalTransport {
@@ -89,6 +138,10 @@ impl MusicalTransport {
             sample_rate,
             sample_pos: 0,
             beat_pos: 0.0,
+            loop_enabled: false,
+            loop_start: 0.0,
+            loop_end: 0.0,
+            ramp: None,
         }
     }
 
@@ -113,13 +166,32 @@ impl MusicalTransport {
 
     /// Advance musical time by a number of samples.
     ///
-    /// Called once per compiled audio block.
+    /// Called once per compiled audio block. If the block crosses the loop
+    /// end, the beat position wraps back into the loop. The sample position
+    /// is a running clock and never wraps.
     pub fn advance_samples(&mut self, frames: usize) {
         let seconds = frames as f64 / self.sample_rate;
-        let beats = seconds * (self.bpm / 60.0);
+        let beats = self.beats_in(seconds);
+        let active_loop = self.active_loop();
 
         self.sample_pos += frames as u64;
         self.beat_pos += beats;
+
+        if let Some(ramp) = &mut self.ramp {
+            if beats >= ramp.remaining {
+                self.bpm = ramp.target_bpm;
+                self.ramp = None;
+            } else {
+                self.bpm += ramp.rate * beats;
+                ramp.remaining -= beats;
+            }
+        }
+
+        if let Some((start, end)) = active_loop
+            && self.beat_pos >= end
+        {
+            self.beat_pos = start + (self.beat_pos - end) % (end - start);
+        }
     }
 
     // -------------------------------
@@ -136,6 +208,7 @@ impl MusicalTransport {
         self.beat_pos
     }
 
+    /// Tempo at the current position (mid-ramp, the instantaneous tempo).
     #[inline]
     pub fn bpm(&self) -> f64 {
         self.bpm
@@ -146,12 +219,114 @@ impl MusicalTransport {
         self.sample_rate
     }
 
+    /// The loop region as `(start, end)` in beats, if looping is enabled.
+    #[inline]
+    pub fn loop_region(&self) -> Option<(f64, f64)> {
+        (self.loop_enabled && self.loop_end > self.loop_start)
+            .then_some((self.loop_start, self.loop_end))
+    }
+
+    /// The loop region, if the playhead will reach its end.
+    ///
+    /// A playhead already past the loop end plays on without wrapping.
+    #[inline]
+    fn active_loop(&self) -> Option<(f64, f64)> {
+        self.loop_region().filter(|&(_, end)| self.beat_pos < end)
+    }
+
+    /// Frames from now until the loop wraps, if it wraps in `block_frames`.
+    ///
+    /// Frames before the returned offset play the end of the loop; the
+    /// frame at the offset is the first one back at `loop_start`.
+    pub fn loop_wrap_offset(&self, block_frames: usize) -> Option<usize> {
+        let (_, end) = self.active_loop()?;
+        let frames = self.frames_until(end);
+        (frames < block_frames).then_some(frames)
+    }
+
+    /// Index of the first frame at or past `beat`.
+    #[inline]
+    fn frames_until(&self, beat: f64) -> usize {
+        (self.seconds_for(beat - self.beat_pos) * self.sample_rate).ceil() as usize
+    }
+
+    /// Seconds from now until `beats` more beats have played.
+    fn seconds_for(&self, beats: f64) -> f64 {
+        match self.ramp {
+            None => beats * 60.0 / self.bpm,
+            Some(ramp) => {
+                let ramp_beats = beats.min(ramp.remaining);
+                ramp_seconds(self.bpm, ramp.rate, ramp_beats)
+                    + (beats - ramp_beats) * 60.0 / ramp.target_bpm
+            }
+        }
+    }
+
+    /// Beats played in the next `seconds`.
+    fn beats_in(&self, seconds: f64) -> f64 {
+        match self.ramp {
+            None => seconds * self.bpm / 60.0,
+            Some(ramp) => {
+                let ramp_time = ramp_seconds(self.bpm, ramp.rate, ramp.remaining);
+                if seconds <= ramp_time {
+                    ramp_beats(self.bpm, ramp.rate, seconds)
+                } else {
+                    ramp.remaining + (seconds - ramp_time) * ramp.target_bpm / 60.0
+                }
+            }
+        }
+    }
+
     // -------------------------------
     // MARK: Mutators (scheduler-controlled)
     // -------------------------------
 
+    /// Change tempo immediately, cancelling any ramp.
     pub fn set_bpm(&mut self, bpm: f64) {
         self.bpm = bpm;
+        self.ramp = None;
+    }
+
+    /// Glide from the current tempo to `target_bpm` over the next
+    /// `over_beats` beats. A zero-length ramp is an immediate change.
+    pub fn ramp_tempo(&mut self, target_bpm: f64, over_beats: f64) {
+        if over_beats <= 0.0 {
+            self.set_bpm(target_bpm);
+            return;
+        }
+        self.ramp = Some(TempoRamp {
+            target_bpm,
+            rate: (target_bpm - self.bpm) / over_beats,
+            remaining: over_beats,
+        });
+    }
+
+    /// Set the loop region in beats. `None` disables looping.
+    pub fn set_loop(&mut self, region: Option<(f64, f64)>) {
+        match region {
+            Some((start, end)) => {
+                self.loop_enabled = true;
+                self.loop_start = start.max(0.0);
+                self.loop_end = end;
+            }
+            None => self.loop_enabled = false,
+        }
+    }
+
+    /// Jump to an absolute beat position.
+    ///
+    /// A tempo ramp in progress ends at its target tempo (the tempo the
+    /// engine already reports), and the sample position is recomputed from
+    /// the resulting tempo.
+    pub fn seek(&mut self, beat: f64) {
+        if let Some(ramp) = self.ramp.take() {
+            self.bpm = ramp.target_bpm;
+        }
+        let beat = beat.max(0.0);
+        let seconds = beat * 60.0 / self.bpm;
+
+        self.beat_pos = beat;
+        self.sample_pos = (seconds * self.sample_rate).round() as u64;
     }
 
     // -------------------------------
@@ -161,22 +336,25 @@ impl MusicalTransport {
     /// Convert a beat offset (relative to now) into a sample offset.
     #[inline]
     pub fn beat_offset_to_sample_offset(&self, beats: f64) -> usize {
-        let seconds = beats * 60.0 / self.bpm;
-        (seconds * self.sample_rate) as usize
+        (self.seconds_for(beats) * self.sample_rate) as usize
     }
 
     /// Compute the sample offset of a musical event within the current block.
     ///
+    /// While looping, events at or past the loop end never happen, and
+    /// events inside the loop but behind the playhead fall after the wrap.
     /// Returns None if the event occurs before the current position.
     pub fn event_sample_offset(&self, event: &MusicalEvent) -> Option<usize> {
         let event_beat = event.beat();
 
-        if event_beat < self.beat_pos {
-            return None;
+        match self.active_loop() {
+            Some((_, end)) if event_beat >= end => None,
+            Some((start, end)) if event_beat < self.beat_pos && event_beat >= start => {
+                Some(self.frames_until(end) + self.beat_offset_to_sample_offset(event_beat - start))
+            }
+            _ if event_beat < self.beat_pos => None,
+            _ => Some(self.beat_offset_to_sample_offset(event_beat - self.beat_pos)),
         }
-
-        let delta_beats = event_beat - self.beat_pos;
-        Some(self.beat_offset_to_sample_offset(delta_beats))
     }
 
     pub fn event_sample_position(&self, event: &MusicalEvent) -> Option<u64> {
@@ -184,3 +362,79 @@ impl MusicalTransport {
             .map(|offset| self.sample_pos + offset as u64)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tempo_ramp_integrates_beats() {
+        let sample_rate = 48_000.0;
+        let mut transport = MusicalTransport::new(sample_rate);
+        transport.set_bpm(120.0);
+        transport.ramp_tempo(140.0, 4.0);
+
+        // Tempo rises 5 BPM per beat: d(beat)/dt = (120 + 5 * beat) / 60,
+        // so the ramp ends after 60 / 5 * ln(140 / 120) seconds
+        let ramp_seconds = 12.0 * (140.0f64 / 120.0).ln();
+
+        // Run 3 seconds in uneven blocks, straddling the end of the ramp
+        let mut frames_left = 3 * 48_000;
+        for block in [512, 1000, 37].iter().cycle() {
+            let frames = frames_left.min(*block);
+            transport.advance_samples(frames);
+            frames_left -= frames;
+            if frames_left == 0 {
+                break;
+            }
+        }
+
+        let expected = 4.0 + (3.0 - ramp_seconds) * 140.0 / 60.0;
+        assert!(
+            (transport.beat_position() - expected).abs() < 1e-9,
+            "{} vs {expected}",
+            transport.beat_position()
+        );
+        assert_eq!(transport.bpm(), 140.0);
+        assert_eq!(transport.sample_position(), 3 * 48_000);
+    }
+
+    #[test]
+    fn test_seek_ends_tempo_ramp() {
+        let mut transport = MusicalTransport::new(48_000.0);
+        transport.set_bpm(120.0);
+        transport.ramp_tempo(60.0, 8.0);
+        transport.advance_samples(24_000);
+
+        transport.seek(4.0);
+        assert_eq!(transport.bpm(), 60.0);
+        assert_eq!(transport.sample_position(), 4 * 48_000);
+
+        // One more beat at the target tempo, no ramp left to integrate
+        transport.advance_samples(48_000);
+        assert!((transport.beat_position() - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tempo_ramp_event_offsets() {
+        let sample_rate = 48_000.0;
+        let mut transport = MusicalTransport::new(sample_rate);
+        transport.set_bpm(120.0);
+        transport.ramp_tempo(140.0, 4.0);
+
+        // An event on beat 2 lands where the transport reaches beat 2
+        let event = MusicalEvent::NoteOn {
+            beat: 2.0,
+            note: 60,
+            velocity: 1.0,
+        };
+        let offset = transport.event_sample_offset(&event).unwrap();
+        transport.advance_samples(offset);
+        assert!(transport.beat_position() <= 2.0);
+        transport.advance_samples(1);
+        assert!(transport.beat_position() > 2.0);
+
+        // Halfway through, the tempo has risen halfway
+        assert!((transport.bpm() - 130.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,85 @@
+//! Unit conversions for pitch, level and musical time.
+//!
+//! Pitch helpers take the A4 reference explicitly so they agree with the
+//! session's tuning; pass [`DEFAULT_A4`] for standard concert pitch.
+
+/// Standard concert pitch of A4 in Hz.
+pub const DEFAULT_A4: f32 = 440.0;
+
+/// MIDI note number of A4.
+const A4_NOTE: f32 = 69.0;
+
+/// Frequency in Hz of a (possibly fractional) MIDI note, with A4 at `a4` Hz.
+#[inline]
+pub fn note_to_freq(note: f32, a4: f32) -> f32 {
+    a4 * 2.0_f32.powf((note - A4_NOTE) / 12.0)
+}
+
+/// Fractional MIDI note of a frequency in Hz, with A4 at `a4` Hz.
+#[inline]
+pub fn freq_to_note(freq: f32, a4: f32) -> f32 {
+    A4_NOTE + 12.0 * (freq / a4).log2()
+}
+
+/// Linear gain of a level in decibels.
+#[inline]
+pub fn db_to_linear(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
+
+/// Level in decibels of a linear gain; silence is negative infinity.
+#[inline]
+pub fn linear_to_db(linear: f32) -> f32 {
+    20.0 * linear.log10()
+}
+
+/// Number of samples spanning `beats` at a steady tempo.
+#[inline]
+pub fn beats_to_samples(beats: f64, bpm: f64, sample_rate: f64) -> f64 {
+    beats * 60.0 / bpm * sample_rate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pitch_round_trip() {
+        assert_eq!(note_to_freq(69.0, DEFAULT_A4), 440.0);
+        assert!((note_to_freq(60.0, DEFAULT_A4) - 261.6256).abs() < 1e-3);
+        assert!((note_to_freq(69.0, 432.0) - 432.0).abs() < 1e-4);
+
+        for note in [0.0, 21.0, 60.0, 60.5, 69.0, 108.0, 127.0] {
+            for a4 in [DEFAULT_A4, 432.0] {
+                let back = freq_to_note(note_to_freq(note, a4), a4);
+                assert!(
+                    (back - note).abs() < 1e-4,
+                    "note {note} came back as {back}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_level_round_trip() {
+        assert_eq!(db_to_linear(0.0), 1.0);
+        assert!((db_to_linear(-6.0) - 0.501_187).abs() < 1e-5);
+        assert_eq!(linear_to_db(0.0), f32::NEG_INFINITY);
+
+        for db in [-96.0, -60.0, -6.0, 0.0, 6.0, 24.0] {
+            let back = linear_to_db(db_to_linear(db));
+            assert!((back - db).abs() < 1e-4, "{db} dB came back as {back}");
+        }
+    }
+
+    #[test]
+    fn test_beats_to_samples() {
+        // One beat at 120 BPM is half a second
+        assert_eq!(beats_to_samples(1.0, 120.0, 48_000.0), 24_000.0);
+        assert_eq!(beats_to_samples(4.0, 90.0, 44_100.0), 117_600.0);
+
+        let samples = beats_to_samples(3.25, 137.0, 48_000.0);
+        let back = samples / 48_000.0 * 137.0 / 60.0;
+        assert!((back - 3.25).abs() < 1e-12);
+    }
+}
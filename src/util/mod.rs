@@ -0,0 +1,5 @@
+// Shared helpers.
+//
+// Small pure functions used by the nodes, the bindings and UI code alike.
+
+pub mod convert;
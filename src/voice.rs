@@ -11,6 +11,24 @@ pub struct Voice {
     pub note: u8,
     pub velocity: f32,
 
+    /// Pitch bend in semitones
+    pub pitch_bend: f32,
+
+    /// Portamento time in seconds from the previous note (0 = jump)
+    pub glide_time: f32,
+
+    /// Unison detune in cents
+    pub detune: f32,
+
+    /// Stereo position (-1 = left, +1 = right) applied when voices are mixed
+    pub pan: f32,
+
+    /// Loudest envelope level, updated after each block (velocity until then)
+    pub level: f32,
+
+    /// Note-on order, used to find the oldest voice when stealing
+    pub serial: u64,
+
     /// Gate is high while note is held
     pub gate: bool,
 
@@ -29,6 +47,12 @@ impl Voice {
             active: false,
             note: 0,
             velocity: 0.0,
+            pitch_bend: 0.0,
+            glide_time: 0.0,
+            detune: 0.0,
+            pan: 0.0,
+            level: 0.0,
+            serial: 0,
             gate: false,
             trigger: false,
             release: false,
@@ -48,6 +72,10 @@ impl Voice {
         self.active = true;
         self.note = note;
         self.velocity = velocity;
+        self.glide_time = 0.0;
+        self.detune = 0.0;
+        self.pan = 0.0;
+        self.level = velocity;
         self.gate = true;
         self.trigger = true;
         self.release = false;
@@ -78,6 +106,10 @@ pub struct VoiceContext {
     pub id: VoiceId,
     pub note: u8,
     pub velocity: f32,
+    pub pitch_bend: f32,
+    pub glide_time: f32,
+    pub detune: f32,
+    pub pan: f32,
     pub gate: bool,
     pub trigger: bool,
     pub release: bool,
@@ -89,6 +121,10 @@ impl From<&Voice> for VoiceContext {
             id: v.id,
             note: v.note,
             velocity: v.velocity,
+            pitch_bend: v.pitch_bend,
+            glide_time: v.glide_time,
+            detune: v.detune,
+            pan: v.pan,
             gate: v.gate,
             trigger: v.trigger,
             release: v.release,
@@ -5,6 +5,67 @@
 
 use crate::voice::{Voice, VoiceContext, VoiceId};
 
+/// Note panned to the center by key pan spread (middle C).
+const PAN_SPREAD_CENTER_NOTE: f32 = 60.0;
+
+/// Semitones from the center note to a hard-panned side at full spread,
+/// so note 36 sits hard left and note 84 hard right.
+const PAN_SPREAD_RANGE: f32 = 24.0;
+
+/// How note-ons are assigned to voices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoiceMode {
+    /// Each note gets its own voice.
+    #[default]
+    Poly,
+    /// A single voice is reused, gliding between notes.
+    Mono,
+    /// Like `Mono`, but a note played while another is held takes over
+    /// the voice without retriggering its envelopes.
+    Legato,
+}
+
+impl VoiceMode {
+    /// Look up a mode by its binding index (0 = Poly, 1 = Mono, 2 = Legato).
+    pub fn from_index(index: u32) -> Option<Self> {
+        match index {
+            0 => Some(Self::Poly),
+            1 => Some(Self::Mono),
+            2 => Some(Self::Legato),
+            _ => None,
+        }
+    }
+}
+
+/// Which voice to give up when a note-on finds every voice busy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoiceStealMode {
+    /// Steal the voice that started first.
+    #[default]
+    Oldest,
+    /// Steal the voice with the lowest envelope level.
+    Quietest,
+    /// Steal the voice playing the lowest note.
+    LowestNote,
+    /// Keep all sounding voices and drop the new note.
+    SkipNewNote,
+}
+
+impl VoiceStealMode {
+    /// Look up a mode by its binding index (0 = Oldest .. 3 = SkipNewNote).
+    pub fn from_index(index: u32) -> Option<Self> {
+        match index {
+            0 => Some(Self::Oldest),
+            1 => Some(Self::Quietest),
+            2 => Some(Self::LowestNote),
+            3 => Some(Self::SkipNewNote),
+            _ => None,
+        }
+    }
+}
+
 /// Allocates and manages polyphonic voices.
 ///
 /// Responsibilities:
@@ -17,12 +78,93 @@ use crate::voice::{Voice, VoiceContext, VoiceId};
 /// - allocate during processing
 pub struct VoiceAllocator {
     voices: Vec<Voice>,
+
+    /// Channel pitch bend in semitones, shared by all voices
+    pitch_bend: f32,
+
+    mode: VoiceMode,
+
+    /// Portamento time in seconds for mono and legato modes
+    glide_time: f32,
+
+    steal_mode: VoiceStealMode,
+
+    /// Voices started per note-on in poly mode
+    unison_voices: usize,
+
+    /// Detune of the outermost unison voices, in cents
+    unison_detune: f32,
+
+    /// Stereo width of the unison stack (0 = centered, 1 = hard left/right)
+    unison_spread: f32,
+
+    /// How far voices are panned by note number (0 = centered, 1 = full)
+    pan_spread: f32,
+
+    /// Serial handed to the next note-on (shared by its unison voices)
+    next_serial: u64,
 }
 
 impl VoiceAllocator {
     pub fn new(max_voices: usize) -> Self {
         let voices = (0..max_voices).map(Voice::new).collect();
-        Self { voices }
+        Self {
+            voices,
+            pitch_bend: 0.0,
+            mode: VoiceMode::Poly,
+            glide_time: 0.0,
+            steal_mode: VoiceStealMode::Oldest,
+            unison_voices: 1,
+            unison_detune: 0.0,
+            unison_spread: 0.0,
+            pan_spread: 0.0,
+            next_serial: 1,
+        }
+    }
+
+    /// Configure unison: each poly note-on starts `voices` voices, detuned
+    /// evenly across ±`detune_cents` and panned across ±`spread`.
+    pub fn set_unison(&mut self, voices: usize, detune_cents: f32, spread: f32) {
+        self.unison_voices = voices.clamp(1, self.voices.len().max(1));
+        self.unison_detune = detune_cents.max(0.0);
+        self.unison_spread = spread.clamp(0.0, 1.0);
+    }
+
+    /// Pan voices by note number: low notes to the left, high notes to the
+    /// right. `spread` scales the offset (0 = off, 1 = notes 36 and 84 hard
+    /// left and right).
+    pub fn set_pan_spread(&mut self, spread: f32) {
+        self.pan_spread = spread.clamp(0.0, 1.0);
+    }
+
+    /// Choose which voice is stolen when polyphony is exhausted.
+    pub fn set_steal_mode(&mut self, mode: VoiceStealMode) {
+        self.steal_mode = mode;
+    }
+
+    /// Record a voice's current envelope level (for quietest-voice stealing).
+    pub fn set_level(&mut self, voice_id: VoiceId, level: f32) {
+        if let Some(v) = self.voices.get_mut(voice_id) {
+            v.level = level;
+        }
+    }
+
+    /// Switch between polyphonic and monophonic allocation.
+    pub fn set_mode(&mut self, mode: VoiceMode) {
+        self.mode = mode;
+    }
+
+    /// Set the portamento time (seconds) used when a mono voice changes note.
+    pub fn set_glide_time(&mut self, seconds: f32) {
+        self.glide_time = seconds.max(0.0);
+    }
+
+    /// Set the channel pitch bend (semitones) for all current and future voices.
+    pub fn set_pitch_bend(&mut self, semitones: f32) {
+        self.pitch_bend = semitones;
+        for v in &mut self.voices {
+            v.pitch_bend = semitones;
+        }
     }
 
     /// Clear one-shot trigger flags at start of each block.
@@ -32,35 +174,105 @@ impl VoiceAllocator {
         }
     }
 
-    /// Allocate a voice for a note-on event.
+    /// Allocate voices for a note-on event.
     ///
-    /// Returns the allocated voice id.
+    /// Returns the first allocated voice id (unison stacks start several).
     pub fn note_on(&mut self, note: u8, velocity: f32) -> Option<VoiceId> {
-        // First, try to find an inactive voice
-        if let Some(v) = self.voices.iter_mut().find(|v| !v.active) {
-            v.note_on(note, velocity);
-            return Some(v.id);
+        let serial = self.next_serial;
+        self.next_serial += 1;
+
+        // Mono: reuse the sounding voice so its oscillators glide to the new note
+        if matches!(self.mode, VoiceMode::Mono | VoiceMode::Legato) {
+            let idx = self.voices.iter().position(|v| v.active).unwrap_or(0);
+            let voice = self.voices.get(idx)?;
+            let sounding = voice.active;
+
+            // Legato: a held note hands over without a new trigger, so
+            // envelopes carry on from where they are
+            if self.mode == VoiceMode::Legato && voice.gate {
+                let v = &mut self.voices[idx];
+                v.note = note;
+                v.velocity = velocity;
+                v.serial = serial;
+                v.glide_time = self.glide_time;
+                return Some(idx);
+            }
+
+            self.start_voice(idx, note, velocity, serial);
+            if sounding {
+                self.voices[idx].glide_time = self.glide_time;
+            }
+            return Some(idx);
+        }
+
+        let count = self.unison_voices;
+        let mut first = None;
+        for i in 0..count {
+            let Some(idx) = self.free_voice(serial) else {
+                break;
+            };
+            self.start_voice(idx, note, velocity, serial);
+
+            // Spread the stack evenly from -1 to +1
+            let position = if count > 1 {
+                2.0 * i as f32 / (count - 1) as f32 - 1.0
+            } else {
+                0.0
+            };
+            let v = &mut self.voices[idx];
+            v.detune = position * self.unison_detune;
+            v.pan = (v.pan + position * self.unison_spread).clamp(-1.0, 1.0);
+            first.get_or_insert(idx);
         }
+        first
+    }
 
-        // Voice stealing: when all voices are active, steal the oldest one.
-        // A more sophisticated policy could steal voices in release phase first,
-        // or prioritize stealing quieter voices.
-        if let Some(v) = self.voices.first_mut() {
-            v.note_on(note, velocity);
+    /// Find a voice for note-on `serial`: an inactive one if possible,
+    /// otherwise a victim chosen by the steal policy (never a voice the
+    /// same note-on just started).
+    fn free_voice(&self, serial: u64) -> Option<VoiceId> {
+        if let Some(v) = self.voices.iter().find(|v| !v.active) {
             return Some(v.id);
         }
 
-        None
+        let candidates = self.voices.iter().filter(|v| v.serial != serial);
+        let victim = match self.steal_mode {
+            VoiceStealMode::Oldest => candidates.min_by_key(|v| v.serial),
+            VoiceStealMode::Quietest => candidates.min_by(|a, b| a.level.total_cmp(&b.level)),
+            VoiceStealMode::LowestNote => candidates.min_by_key(|v| v.note),
+            VoiceStealMode::SkipNewNote => None,
+        };
+        victim.map(|v| v.id)
     }
 
-    /// Release the voice associated with a note-off event.
+    /// Start `note` on the voice at `idx`.
+    fn start_voice(&mut self, idx: VoiceId, note: u8, velocity: f32, serial: u64) {
+        let v = &mut self.voices[idx];
+        v.note_on(note, velocity);
+        v.pitch_bend = self.pitch_bend;
+        v.serial = serial;
+        v.pan = ((note as f32 - PAN_SPREAD_CENTER_NOTE) / PAN_SPREAD_RANGE * self.pan_spread)
+            .clamp(-1.0, 1.0);
+    }
+
+    /// Release the voices associated with a note-off event.
+    ///
+    /// Releases every voice of the earliest held note-on for `note`, so a
+    /// whole unison stack stops together.
     pub fn note_off(&mut self, note: u8) {
-        if let Some(v) = self
+        let Some(serial) = self
             .voices
-            .iter_mut()
-            .find(|v| v.active && v.gate && v.note == note)
-        {
-            v.note_off();
+            .iter()
+            .filter(|v| v.active && v.gate && v.note == note)
+            .map(|v| v.serial)
+            .min()
+        else {
+            return;
+        };
+        for v in &mut self.voices {
+            if v.active && v.gate && v.serial == serial {
+                v.note_off();
+            }
         }
     }
 
@@ -71,6 +283,13 @@ impl VoiceAllocator {
         }
     }
 
+    /// Immediately silence every voice (used on transport stop/seek).
+    pub fn reset(&mut self) {
+        for v in &mut self.voices {
+            v.deactivate();
+        }
+    }
+
     /// Iterate over active voices.
     pub fn active_voices(&self) -> impl Iterator<Item = VoiceContext> + '_ {
         self.voices
@@ -79,6 +298,29 @@ impl VoiceAllocator {
             .map(VoiceContext::from)
     }
 
+    /// Iterate over active voices that can still make sound.
+    ///
+    /// Skips voices that are fully released (see `is_released`).
+    pub fn sounding_voices(&self) -> impl Iterator<Item = VoiceContext> + '_ {
+        self.voices
+            .iter()
+            .filter(|v| v.active && !Self::fully_released(v))
+            .map(VoiceContext::from)
+    }
+
+    /// Whether an active voice has been released and its envelopes have
+    /// decayed to nothing, so it only waits to be deactivated.
+    pub fn is_released(&self, voice_id: VoiceId) -> bool {
+        self.voices
+            .get(voice_id)
+            .is_some_and(|v| v.active && Self::fully_released(v))
+    }
+
+    #[inline]
+    fn fully_released(v: &Voice) -> bool {
+        !v.gate && v.level <= 0.0
+    }
+
     /// Get a specific voice's context.
     pub fn get_voice(&self, id: VoiceId) -> Option<VoiceContext> {
         self.voices.get(id).map(VoiceContext::from)
@@ -88,4 +330,131 @@ impl VoiceAllocator {
     pub fn active_count(&self) -> usize {
         self.voices.iter().filter(|v| v.active).count()
     }
+
+    /// Bitmask of the notes held by active voices; bit `n % 64` of word
+    /// `n / 64` is MIDI note `n`.
+    pub fn active_note_mask(&self) -> [u64; 2] {
+        let mut mask = [0; 2];
+        for v in self.voices.iter().filter(|v| v.active && v.gate) {
+            let note = v.note as usize & 127;
+            mask[note / 64] |= 1 << (note % 64);
+        }
+        mask
+    }
+
+    /// Notes held by active voices, ascending and without duplicates.
+    pub fn active_notes(&self) -> Vec<u8> {
+        notes_in_mask(self.active_note_mask())
+    }
+}
+
+/// The notes set in a mask from `VoiceAllocator::active_note_mask`.
+pub fn notes_in_mask(mask: [u64; 2]) -> Vec<u8> {
+    mask_notes(mask).collect()
+}
+
+/// Iterate the notes set in a note mask, lowest first, without allocating.
+pub fn mask_notes(mask: [u64; 2]) -> impl Iterator<Item = u8> + Clone {
+    (0..128u8).filter(move |&note| mask[note as usize / 64] & (1 << (note % 64)) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fill four voices so each policy picks a different victim: voice 1 is
+    /// oldest (voice 0 is freed and reused), voice 2 has the lowest note and
+    /// voice 3 the lowest velocity.
+    fn saturated(mode: VoiceStealMode) -> VoiceAllocator {
+        let mut voices = VoiceAllocator::new(4);
+        voices.set_steal_mode(mode);
+        for (note, velocity) in [(64, 0.5), (67, 0.8), (60, 0.9), (62, 0.3)] {
+            voices.note_on(note, velocity);
+        }
+        voices.deactivate(0);
+        assert_eq!(voices.note_on(65, 0.6), Some(0));
+        assert_eq!(voices.active_count(), 4);
+        voices
+    }
+
+    fn notes(voices: &VoiceAllocator) -> Vec<u8> {
+        (0..4).map(|id| voices.get_voice(id).unwrap().note).collect()
+    }
+
+    #[test]
+    fn test_steal_oldest() {
+        let mut voices = saturated(VoiceStealMode::Oldest);
+        assert_eq!(voices.note_on(72, 1.0), Some(1));
+        assert_eq!(notes(&voices), [65, 72, 60, 62]);
+    }
+
+    #[test]
+    fn test_steal_quietest() {
+        let mut voices = saturated(VoiceStealMode::Quietest);
+        // Before any envelope reports, velocity stands in for level
+        assert_eq!(voices.note_on(72, 1.0), Some(3));
+
+        // Envelope levels reported by the graph take over
+        voices.set_level(1, 0.05);
+        assert_eq!(voices.note_on(74, 1.0), Some(1));
+        assert_eq!(notes(&voices), [65, 74, 60, 72]);
+    }
+
+    #[test]
+    fn test_steal_lowest_note() {
+        let mut voices = saturated(VoiceStealMode::LowestNote);
+        assert_eq!(voices.note_on(72, 1.0), Some(2));
+        assert_eq!(notes(&voices), [65, 67, 72, 62]);
+    }
+
+    #[test]
+    fn test_steal_skip_new_note() {
+        let mut voices = saturated(VoiceStealMode::SkipNewNote);
+        assert_eq!(voices.note_on(72, 1.0), None);
+        assert_eq!(notes(&voices), [65, 67, 60, 62]);
+    }
+
+    #[test]
+    fn test_legato_keeps_envelope_running() {
+        use crate::audio_buffer::OutputBuffer;
+        use crate::node::{Node, ProcessContext};
+        use crate::nodes::{AdsrEnvelope, params};
+
+        // Envelope output just before and just after a second, overlapping note
+        let overlap = |mode: VoiceMode| {
+            let mut voices = VoiceAllocator::new(4);
+            voices.set_mode(mode);
+            let mut env = AdsrEnvelope::new();
+            env.prepare(48_000.0, 256);
+            env.set_param(params::ATTACK, 0.05);
+            let mut render = |voices: &mut VoiceAllocator| {
+                let voice = voices.get_voice(0).unwrap();
+                let ctx = ProcessContext::new(256, 48_000.0, 0, 120.0).with_voice(voice);
+                let mut data = vec![0.0f32; 256];
+                env.process(&ctx, &[], &mut OutputBuffer::new(&mut data, 1));
+                voices.clear_triggers();
+                data
+            };
+
+            assert_eq!(voices.note_on(60, 1.0), Some(0));
+            let first = render(&mut voices);
+            assert_eq!(voices.note_on(63, 1.0), Some(0));
+            assert_eq!(voices.active_count(), 1);
+            assert_eq!(voices.get_voice(0).unwrap().note, 63);
+            let retriggered = voices.get_voice(0).unwrap().trigger;
+            let second = render(&mut voices);
+            (retriggered, first[255], second)
+        };
+
+        // Legato reuses the voice and the attack carries on rising
+        let (retriggered, before, after) = overlap(VoiceMode::Legato);
+        assert!(!retriggered);
+        assert!(after[0] > before);
+        assert!(after.windows(2).all(|w| w[1] >= w[0]));
+
+        // Mono retriggers, restarting the attack from zero
+        let (retriggered, before, after) = overlap(VoiceMode::Mono);
+        assert!(retriggered);
+        assert!(after[0] < before * 0.1);
+    }
 }
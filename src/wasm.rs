@@ -39,7 +39,7 @@ use crate::node_factory::NodeRegistry;
 use crate::nodes::register_standard_nodes;
 use crate::plan_handoff::PlanHandoff;
 use crate::scheduler::Scheduler;
-use crate::state::{Command, EngineReadback, Session};
+use crate::state::{Command, DisplayCurve, EngineReadback, GraphIssue, KeyZone, Session};
 use crate::voice_allocator::VoiceAllocator;
 
 
@@ -116,7 +116,7 @@ pub struct HyasynthReadback {
     pub sample_position: u64,
     /// Current beat position in the timeline.
     pub beat_position: f64,
-    /// CPU load estimate (0.0 - 1.0).
+    /// CPU load estimate (0.0 - 1.0); not measured in the browser, so always 0.
     pub cpu_load: f32,
     /// Number of currently active voices.
     pub active_voices: u32,
@@ -152,6 +152,12 @@ pub struct HyasynthRegistry {
     inner: NodeRegistry,
 }
 
+impl Default for HyasynthRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl HyasynthRegistry {
     /// Create a new registry with all standard nodes registered.
@@ -166,6 +172,57 @@ impl HyasynthRegistry {
     pub fn count(&self) -> u32 {
         self.inner.iter().count() as u32
     }
+
+    /// Describe every registered node type as a JSON array, sorted by type ID.
+    ///
+    /// Each entry has `type_id`, `name`, `category`, `num_inputs`,
+    /// `num_outputs` and `params`; each param has `id`, `name`, `short_name`,
+    /// `min`, `max`, `default`, `unit`, `curve` and `step`.
+    pub fn node_types_json(&self) -> String {
+        let mut types: Vec<_> = self.inner.iter().collect();
+        types.sort_unstable_by_key(|info| info.type_id);
+
+        let types: Vec<_> = types
+            .into_iter()
+            .map(|info| {
+                let params: Vec<_> = info
+                    .parameters
+                    .iter()
+                    .map(|param| {
+                        serde_json::json!({
+                            "id": param.id,
+                            "name": param.name,
+                            "short_name": param.short_name,
+                            "min": param.min,
+                            "max": param.max,
+                            "default": param.default,
+                            "unit": param.unit.to_string(),
+                            "curve": curve_name(param.curve),
+                            "step": param.step,
+                        })
+                    })
+                    .collect();
+                serde_json::json!({
+                    "type_id": info.type_id,
+                    "name": info.name,
+                    "category": info.category,
+                    "num_inputs": info.inputs.len(),
+                    "num_outputs": info.outputs.len(),
+                    "params": params,
+                })
+            })
+            .collect();
+        serde_json::Value::Array(types).to_string()
+    }
+}
+
+fn curve_name(curve: DisplayCurve) -> &'static str {
+    match curve {
+        DisplayCurve::Linear => "linear",
+        DisplayCurve::Logarithmic => "logarithmic",
+        DisplayCurve::Exponential => "exponential",
+        DisplayCurve::Symmetric => "symmetric",
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -268,6 +325,27 @@ impl HyasynthSession {
         });
     }
 
+    /// Modulate a node parameter from another node's output.
+    ///
+    /// `depth` is in the destination parameter's units per unit of source signal.
+    pub fn add_mod_route(&mut self, source_node: u32, dest_node: u32, param_id: u32, depth: f32) {
+        self.inner.send(Command::AddModRoute {
+            source_node,
+            dest_node,
+            param_id,
+            depth,
+        });
+    }
+
+    /// Remove a modulation route.
+    pub fn remove_mod_route(&mut self, source_node: u32, dest_node: u32, param_id: u32) {
+        self.inner.send(Command::RemoveModRoute {
+            source_node,
+            dest_node,
+            param_id,
+        });
+    }
+
     /// Set the output node.
     pub fn set_output(&mut self, node_id: u32) {
         self.inner.send(Command::SetOutputNode { node_id });
@@ -287,6 +365,28 @@ impl HyasynthSession {
         self.inner.set_param(node_id, param_id, value);
     }
 
+    /// Clamp parameter values set from now on to the ranges in the registry.
+    ///
+    /// Sessions start with the standard nodes' ranges.
+    pub fn set_param_ranges(&mut self, registry: &HyasynthRegistry) {
+        self.inner.set_param_ranges(&registry.inner);
+    }
+
+    /// Scale a parameter by note velocity (0.0 = off, 1.0 = fully proportional).
+    pub fn set_velocity_sensitivity(&mut self, node_id: u32, param_id: u32, amount: f32) {
+        self.inner.send(Command::SetVelocitySensitivity {
+            node_id,
+            param_id,
+            amount,
+        });
+    }
+
+    /// Pass a node's input straight to its output without unwiring it, or
+    /// restore it. Bypassed sources fall silent.
+    pub fn set_node_bypass(&mut self, node_id: u32, bypassed: bool) {
+        self.inner.set_node_bypass(node_id, bypassed);
+    }
+
     /// Begin a parameter gesture (for automation recording).
     pub fn begin_gesture(&mut self, node_id: u32, param_id: u32) {
         self.inner
@@ -318,11 +418,46 @@ impl HyasynthSession {
         self.inner.send(Command::SetTempo { bpm });
     }
 
+    /// Glide from the current tempo to `target_bpm` over `over_beats` beats.
+    pub fn ramp_tempo(&mut self, target_bpm: f64, over_beats: f64) {
+        self.inner.ramp_tempo(target_bpm, over_beats);
+    }
+
     /// Seek to a position in beats.
+    ///
+    /// Realtime rendering doesn't play clips (the host sends their notes),
+    /// so only the transport moves; clips follow a seek in offline renders.
     pub fn seek(&mut self, beat: f64) {
         self.inner.send(Command::Seek { beat });
     }
 
+    /// Loop playback between two beat positions.
+    pub fn set_loop(&mut self, enabled: bool, start_beat: f64, end_beat: f64) {
+        self.inner.set_loop(enabled, start_beat, end_beat);
+    }
+
+    /// Click on every beat, accenting the first beat of each bar.
+    pub fn set_metronome(&mut self, enabled: bool, beats_per_bar: u32) {
+        self.inner.set_metronome(enabled, beats_per_bar);
+    }
+
+    /// Tune note frequencies so A4 sounds at `a4` Hz (440 by default).
+    pub fn set_tuning(&mut self, a4: f64) {
+        self.inner.set_tuning(a4);
+    }
+
+    /// Load a microtonal tuning table from a list of cents, one scale
+    /// degree per line with the period last, starting on `root_note`.
+    /// Returns false if the list can't be parsed.
+    pub fn load_tuning_cents(&mut self, text: &str, root_note: u8) -> bool {
+        self.inner.load_tuning_cents(text, root_note).is_ok()
+    }
+
+    /// Return to equal temperament.
+    pub fn clear_tuning_table(&mut self) {
+        self.inner.set_tuning_table(None);
+    }
+
     /// Check if the transport is playing.
     pub fn is_playing(&self) -> bool {
         self.inner.session().transport.playing
@@ -347,6 +482,117 @@ impl HyasynthSession {
         self.inner.note_off(note);
     }
 
+    /// Set the channel pitch bend in semitones (applies to all voices).
+    pub fn pitch_bend(&mut self, semitones: f32) {
+        self.inner.pitch_bend(semitones);
+    }
+
+    /// Switch between polyphonic (false) and monophonic (true) voice allocation.
+    pub fn set_mono(&mut self, mono: bool) {
+        let mode = if mono {
+            crate::VoiceMode::Mono
+        } else {
+            crate::VoiceMode::Poly
+        };
+        self.inner.set_voice_mode(mode);
+    }
+
+    /// Choose how notes are assigned to voices.
+    ///
+    /// `mode`: 0 = poly, 1 = mono, 2 = legato.
+    pub fn set_voice_mode(&mut self, mode: u32) {
+        if let Some(mode) = crate::VoiceMode::from_index(mode) {
+            self.inner.set_voice_mode(mode);
+        }
+    }
+
+    /// Set the portamento time in seconds between mono notes.
+    pub fn set_glide_time(&mut self, seconds: f32) {
+        self.inner.set_glide_time(seconds);
+    }
+
+    /// Choose which voice is stolen when polyphony is exhausted.
+    ///
+    /// `mode`: 0 = oldest, 1 = quietest, 2 = lowest note, 3 = skip new note.
+    pub fn set_voice_steal_mode(&mut self, mode: u32) {
+        if let Some(mode) = crate::VoiceStealMode::from_index(mode) {
+            self.inner.set_voice_steal_mode(mode);
+        }
+    }
+
+    /// Configure unison: voices per note, detune of the outer voices in
+    /// cents, and stereo spread (0..1).
+    pub fn set_unison(&mut self, voices: u32, detune_cents: f32, spread: f32) {
+        self.inner.set_unison(voices as usize, detune_cents, spread);
+    }
+
+    /// Pan voices by note number, low notes left and high notes right (0..1).
+    pub fn set_voice_pan_spread(&mut self, spread: f32) {
+        self.inner.set_voice_pan_spread(spread);
+    }
+
+    /// Arpeggiate held notes into `target_node`, or play them directly when
+    /// `enabled` is false.
+    ///
+    /// `mode`: 0 = up, 1 = down, 2 = up-down, 3 = random. `rate` is the step
+    /// length in beats and `gate` the fraction of a step each note sounds for.
+    pub fn set_arpeggiator(
+        &mut self,
+        enabled: bool,
+        mode: u32,
+        rate: f64,
+        octaves: u8,
+        gate: f32,
+        target_node: u32,
+    ) {
+        let Some(mode) = crate::ArpMode::from_index(mode) else {
+            return;
+        };
+        let settings = enabled.then_some(crate::ArpSettings {
+            mode,
+            rate,
+            octaves,
+            gate,
+            target_node,
+        });
+        self.inner.set_arpeggiator(settings);
+    }
+
+    /// Send a MIDI control change (value normalized to 0..1).
+    pub fn control_change(&mut self, cc: u8, value: f32) {
+        self.inner.control_change(cc, value);
+    }
+
+    /// Route a CC number to the full range of a node parameter.
+    ///
+    /// Returns false if the node or parameter doesn't exist.
+    pub fn map_control_change(
+        &mut self,
+        registry: &HyasynthRegistry,
+        cc: u8,
+        node_id: u32,
+        param_id: u32,
+    ) -> bool {
+        let Some(info) = self
+            .inner
+            .session()
+            .graph
+            .get_node(node_id)
+            .and_then(|node| registry.inner.get_info(node.type_id))
+            .and_then(|info| info.find_param(param_id))
+            .cloned()
+        else {
+            return false;
+        };
+        self.inner.map_control_change(cc, node_id, &info);
+        true
+    }
+
+    /// Remove the mapping for a CC number.
+    pub fn unmap_control_change(&mut self, cc: u8) {
+        self.inner.unmap_control_change(cc);
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Readback
     // ─────────────────────────────────────────────────────────────────────────
@@ -356,6 +602,11 @@ impl HyasynthSession {
         self.inner.readback().into()
     }
 
+    /// Get the notes held by the engine's voices, ascending.
+    pub fn get_active_notes(&self) -> Vec<u8> {
+        self.inner.active_notes()
+    }
+
     /// Get the number of nodes in the graph.
     pub fn node_count(&self) -> u32 {
         self.inner.session().graph.nodes.len() as u32
@@ -366,6 +617,39 @@ impl HyasynthSession {
         self.inner.session().graph.output_node.unwrap_or(u32::MAX)
     }
 
+    /// Get a node's type ID, or u32::MAX if the node doesn't exist.
+    pub fn get_node_type(&self, node_id: u32) -> u32 {
+        self.inner.node_type_of(node_id).unwrap_or(u32::MAX)
+    }
+
+    /// Get all connections as a flat array, four values per connection:
+    /// `[source_node, source_port, dest_node, dest_port, ...]`.
+    pub fn get_connections(&self) -> Vec<u32> {
+        self.inner
+            .iter_connections()
+            .iter()
+            .flat_map(|c| [c.source_node, c.source_port, c.dest_node, c.dest_port])
+            .collect()
+    }
+
+    /// Check the graph for problems without compiling it, as a flat array
+    /// of three values per issue: `[kind, node_id, dest_node, ...]`.
+    ///
+    /// `kind` is 0 for a missing output, 1 for a node that can't reach the
+    /// output and 2 for a connection to or from a missing node. Unused IDs
+    /// are u32::MAX.
+    pub fn validate_graph(&self) -> Vec<u32> {
+        self.inner
+            .validate_graph()
+            .into_iter()
+            .flat_map(|issue| match issue {
+                GraphIssue::MissingOutput => [0, u32::MAX, u32::MAX],
+                GraphIssue::UnreachableNode { node_id } => [1, node_id, u32::MAX],
+                GraphIssue::DanglingConnection { source, dest } => [2, source, dest],
+            })
+            .collect()
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Clips
     // ─────────────────────────────────────────────────────────────────────────
@@ -409,6 +693,39 @@ impl HyasynthSession {
         }
     }
 
+    /// Move a clip's note starts toward a grid of `grid` beats.
+    ///
+    /// `strength`: 0 = untouched, 1 = exactly on the grid.
+    pub fn quantize_clip(&mut self, clip_id: u32, grid: f64, strength: f32) {
+        self.inner.send(Command::QuantizeClip {
+            clip_id,
+            grid,
+            strength,
+        });
+    }
+
+    /// Transpose a clip's notes on playback, in semitones.
+    pub fn set_clip_transpose(&mut self, clip_id: u32, semitones: i8) {
+        self.inner.send(Command::SetClipTranspose { clip_id, semitones });
+    }
+
+    /// Scale a clip's note velocities on playback (1.0 = unchanged).
+    pub fn set_clip_velocity_scale(&mut self, clip_id: u32, scale: f32) {
+        self.inner.send(Command::SetClipVelocityScale { clip_id, scale });
+    }
+
+    /// Randomly delay a clip's notes by up to `timing` beats and move their
+    /// velocities by up to `velocity` on playback. The same `seed` always
+    /// gives the same offsets.
+    pub fn set_clip_humanize(&mut self, clip_id: u32, timing: f64, velocity: f32, seed: u32) {
+        self.inner.send(Command::SetClipHumanize {
+            clip_id,
+            timing,
+            velocity,
+            seed,
+        });
+    }
+
     /// Get the number of notes in a clip.
     pub fn get_clip_note_count(&self, clip_id: u32) -> u32 {
         self.inner
@@ -465,10 +782,12 @@ impl HyasynthSession {
 
     /// Set track solo.
     pub fn set_track_solo(&mut self, track_id: u32, solo: bool) {
-        self.inner
-            .session_mut()
-            .arrangement
-            .set_track_solo(track_id, solo);
+        self.inner.set_track_solo(track_id, solo);
+    }
+
+    /// Arm a track for recording, or disarm it.
+    pub fn set_track_armed(&mut self, track_id: u32, armed: bool) {
+        self.inner.set_track_armed(track_id, armed);
     }
 
     /// Set track target node (the node this track sends MIDI to).
@@ -485,6 +804,42 @@ impl HyasynthSession {
             .set_track_target(track_id, target);
     }
 
+    /// Route a note range on a track to its own target, transposed.
+    /// Overlapping zones layer.
+    pub fn add_track_key_zone(
+        &mut self,
+        track_id: u32,
+        low_note: u8,
+        high_note: u8,
+        target_node: u32,
+        transpose: i8,
+    ) {
+        self.inner.add_track_key_zone(
+            track_id,
+            KeyZone {
+                low_note,
+                high_note,
+                target_node,
+                transpose,
+            },
+        );
+    }
+
+    /// Remove a track's key zones.
+    pub fn clear_track_key_zones(&mut self, track_id: u32) {
+        self.inner.clear_track_key_zones(track_id);
+    }
+
+    /// Play a note through a track's key zones.
+    pub fn track_note_on(&mut self, track_id: u32, note: u8, velocity: f32) {
+        self.inner.track_note_on(track_id, note, velocity);
+    }
+
+    /// Release a note played with `track_note_on`.
+    pub fn track_note_off(&mut self, track_id: u32, note: u8) {
+        self.inner.track_note_off(track_id, note);
+    }
+
     /// Get the number of tracks.
     pub fn get_track_count(&self) -> u32 {
         self.inner.session().arrangement.tracks.len() as u32
@@ -539,6 +894,21 @@ impl HyasynthSession {
         self.inner.session_mut().arrangement.stop_all();
     }
 
+    /// Set the grid that clip and scene launches wait for.
+    ///
+    /// `quantization`: 0 = off, 1 = beat, 2 = half bar, 3 = bar,
+    /// 4 = two bars, 5 = four bars.
+    pub fn set_launch_quantization(&mut self, quantization: u32) {
+        if let Some(quantization) = crate::state::LaunchQuantization::from_index(quantization) {
+            self.inner.session_mut().arrangement.launch_quantization = quantization;
+        }
+    }
+
+    /// Set the swing applied to off-beat 8th notes in clips (0..1).
+    pub fn set_swing(&mut self, amount: f32) {
+        self.inner.session_mut().arrangement.swing = amount.clamp(0.0, 1.0);
+    }
+
     /// Get the number of scenes.
     pub fn get_scene_count(&self) -> u32 {
         self.inner.session().arrangement.scenes.len() as u32
@@ -577,6 +947,27 @@ impl HyasynthSession {
             .arrangement
             .remove_clip_placement(track_id, start_beat);
     }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Recording
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Start recording input onto an armed track at the playhead.
+    /// Returns false if the track isn't armed or a take is already running.
+    pub fn start_recording(&mut self, track_id: u32) -> bool {
+        self.inner.start_recording(track_id)
+    }
+
+    /// Stop recording; the take is committed by `poll_recording`.
+    pub fn stop_recording(&mut self) {
+        self.inner.stop_recording();
+    }
+
+    /// Commit a finished take to the audio pool as a clip on its track.
+    /// Returns the clip ID, or u32::MAX if no take has come back yet.
+    pub fn poll_recording(&mut self) -> u32 {
+        self.inner.poll_recording().unwrap_or(u32::MAX)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -604,58 +995,59 @@ impl HyasynthEngine {
     /// Render audio frames to the provided output buffer (interleaved stereo).
     /// Output format: [L0, R0, L1, R1, L2, R2, ...]
     ///
-    /// The output slice must have length >= frames * 2.
+    /// The output slice must have length >= frames * 2. A mono graph output
+    /// is copied to both channels.
     pub fn render(&mut self, frames: u32, output: &mut [f32]) {
         let total_frames = frames as usize;
-        let max_block = self.inner.engine().graph().max_block;
-
         if output.len() < total_frames * 2 {
             output.fill(0.0);
             return;
         }
 
-        let mut offset = 0;
-        while offset < total_frames {
-            let chunk_frames = (total_frames - offset).min(max_block);
-
-            // Compile execution plan
-            self.scheduler.compile_block(&mut self.handoff, chunk_frames, &[]);
-
-            // Process pending commands
-            self.inner.process_commands();
-
-            // Read and process the plan
-            let plan = self.handoff.read_plan();
-            self.inner.process_plan(plan);
-
+        self.render_blocks(total_frames, |offset, chunk_frames, planar, channels| {
             let out_chunk = &mut output[offset * 2..(offset + chunk_frames) * 2];
-
-            // Convert planar to interleaved
-            if let Some(engine_output) = self.inner.output_buffer(chunk_frames) {
-                if engine_output.len() >= chunk_frames * 2 {
-                    for i in 0..chunk_frames {
-                        out_chunk[i * 2] = engine_output[i];
-                        out_chunk[i * 2 + 1] = engine_output[chunk_frames + i];
-                    }
-                } else if engine_output.len() >= chunk_frames {
-                    for i in 0..chunk_frames {
-                        out_chunk[i * 2] = engine_output[i];
-                        out_chunk[i * 2 + 1] = engine_output[i];
-                    }
-                } else {
-                    out_chunk.fill(0.0);
-                }
-            } else {
+            if channels == 0 {
                 out_chunk.fill(0.0);
+                return;
+            }
+            let right = 1.min(channels - 1) * chunk_frames;
+            for (i, frame) in out_chunk.chunks_exact_mut(2).enumerate() {
+                frame[0] = planar[i];
+                frame[1] = planar[right + i];
             }
+        });
+    }
 
-            offset += chunk_frames;
+    /// Render audio frames to the provided output buffer as a single channel.
+    ///
+    /// Every channel of the graph output is averaged, so a stereo output
+    /// renders as `(L + R) / 2`. The output slice must have length >= frames.
+    pub fn render_mono(&mut self, frames: u32, output: &mut [f32]) {
+        let total_frames = frames as usize;
+        if output.len() < total_frames {
+            output.fill(0.0);
+            return;
         }
 
-        // Sync readback
-        self.inner.update_sample_position(self.scheduler.sample_position());
-        self.inner.update_beat_position(self.scheduler.beat_position());
-        self.inner.sync_readback();
+        self.render_blocks(total_frames, |offset, chunk_frames, planar, channels| {
+            let out_chunk = &mut output[offset..offset + chunk_frames];
+            out_chunk.fill(0.0);
+            if channels == 0 {
+                return;
+            }
+            let scale = 1.0 / channels as f32;
+            for channel in planar.chunks_exact(chunk_frames) {
+                for (o, s) in out_chunk.iter_mut().zip(channel) {
+                    *o += s * scale;
+                }
+            }
+        });
+    }
+
+    /// Capture interleaved stereo input for the take being recorded.
+    /// Input format: [L0, R0, L1, R1, L2, R2, ...]
+    pub fn push_input(&mut self, input: &[f32]) {
+        self.inner.push_input(input);
     }
 
     /// Compile the session's graph and load it into the engine.
@@ -718,6 +1110,61 @@ impl HyasynthEngine {
     }
 }
 
+impl HyasynthEngine {
+    /// Run `total_frames` through the engine in blocks of at most `max_block`.
+    ///
+    /// `write` receives each block's frame offset, frame count, planar
+    /// output and its channel count (0 with no output node, in which case
+    /// the output is empty).
+    fn render_blocks(
+        &mut self,
+        total_frames: usize,
+        mut write: impl FnMut(usize, usize, &[f32], usize),
+    ) {
+        let max_block = self.inner.engine().graph().max_block;
+
+        let mut offset = 0;
+        while offset < total_frames {
+            let chunk_frames = (total_frames - offset).min(max_block);
+
+            // Process pending commands before compiling so a seek lands here;
+            // clips aren't played here, so it only moves the transport
+            self.inner.process_commands();
+            if let Some(beat) = self.inner.take_pending_seek() {
+                self.scheduler.seek(beat);
+            }
+            if let Some((bpm, over_beats)) = self.inner.take_pending_tempo() {
+                self.scheduler.ramp_tempo(bpm, over_beats);
+            }
+            self.scheduler.set_loop(self.inner.loop_region());
+            self.scheduler.set_metronome(self.inner.metronome());
+            let (held, velocity) = self.inner.held_notes();
+            self.scheduler
+                .set_arpeggiator(self.inner.arpeggiator(), held, velocity);
+
+            // Compile execution plan
+            self.scheduler.compile_block(&mut self.handoff, chunk_frames, &[]);
+
+            // Read and process the plan
+            let plan = self.handoff.read_plan();
+            self.inner.process_plan(plan);
+            self.inner.update_output_peaks(chunk_frames);
+
+            match self.inner.output_buffer(chunk_frames) {
+                Some(planar) => write(offset, chunk_frames, planar, self.inner.output_channels()),
+                None => write(offset, chunk_frames, &[], 0),
+            }
+
+            offset += chunk_frames;
+        }
+
+        // Sync readback
+        self.inner.update_sample_position(self.scheduler.sample_position());
+        self.inner.update_beat_position(self.scheduler.beat_position());
+        self.inner.sync_readback();
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Node Type Constants
 // ═══════════════════════════════════════════════════════════════════════════
@@ -752,6 +1199,18 @@ pub fn node_adsr_env() -> u32 {
     crate::nodes::node_types::ADSR_ENV
 }
 
+/// DAHDSR envelope node type.
+#[wasm_bindgen]
+pub fn node_dahdsr_env() -> u32 {
+    crate::nodes::node_types::DAHDSR_ENV
+}
+
+/// Filter envelope node type.
+#[wasm_bindgen]
+pub fn node_filter_env() -> u32 {
+    crate::nodes::node_types::FILTER_ENV
+}
+
 /// Gain node type.
 #[wasm_bindgen]
 pub fn node_gain() -> u32 {
@@ -770,6 +1229,18 @@ pub fn node_output() -> u32 {
     crate::nodes::node_types::OUTPUT
 }
 
+/// Slew limiter node type.
+#[wasm_bindgen]
+pub fn node_slew() -> u32 {
+    crate::nodes::node_types::SLEW
+}
+
+/// DC blocker node type.
+#[wasm_bindgen]
+pub fn node_dc_blocker() -> u32 {
+    crate::nodes::node_types::DC_BLOCKER
+}
+
 /// Lowpass filter node type.
 #[wasm_bindgen]
 pub fn node_lowpass() -> u32 {
@@ -794,6 +1265,24 @@ pub fn node_notch() -> u32 {
     crate::nodes::node_types::NOTCH
 }
 
+/// Ladder filter node type.
+#[wasm_bindgen]
+pub fn node_ladder() -> u32 {
+    crate::nodes::node_types::LADDER
+}
+
+/// Allpass filter node type.
+#[wasm_bindgen]
+pub fn node_allpass() -> u32 {
+    crate::nodes::node_types::ALLPASS
+}
+
+/// Comb filter node type.
+#[wasm_bindgen]
+pub fn node_comb() -> u32 {
+    crate::nodes::node_types::COMB
+}
+
 /// LFO node type.
 #[wasm_bindgen]
 pub fn node_lfo() -> u32 {
@@ -812,6 +1301,42 @@ pub fn node_reverb() -> u32 {
     crate::nodes::node_types::REVERB
 }
 
+/// Distortion (waveshaper) node type.
+#[wasm_bindgen]
+pub fn node_distortion() -> u32 {
+    crate::nodes::node_types::DISTORTION
+}
+
+/// Limiter node type.
+#[wasm_bindgen]
+pub fn node_limiter() -> u32 {
+    crate::nodes::node_types::LIMITER
+}
+
+/// Parametric EQ node type.
+#[wasm_bindgen]
+pub fn node_eq() -> u32 {
+    crate::nodes::node_types::EQ
+}
+
+/// Flanger node type.
+#[wasm_bindgen]
+pub fn node_flanger() -> u32 {
+    crate::nodes::node_types::FLANGER
+}
+
+/// Stereo width (mid/side) node type.
+#[wasm_bindgen]
+pub fn node_width() -> u32 {
+    crate::nodes::node_types::WIDTH
+}
+
+/// Ring modulator node type.
+#[wasm_bindgen]
+pub fn node_ringmod() -> u32 {
+    crate::nodes::node_types::RINGMOD
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Parameter ID Constants
 // ═══════════════════════════════════════════════════════════════════════════
@@ -852,6 +1377,36 @@ pub fn param_release() -> u32 {
     crate::nodes::params::RELEASE
 }
 
+/// Envelope curve parameter ID (0 = linear, 1 = exponential).
+#[wasm_bindgen]
+pub fn param_curve() -> u32 {
+    crate::nodes::params::CURVE
+}
+
+/// Envelope delay parameter ID (DAHDSR).
+#[wasm_bindgen]
+pub fn param_env_delay() -> u32 {
+    crate::nodes::params::ENV_DELAY
+}
+
+/// Envelope hold parameter ID (DAHDSR).
+#[wasm_bindgen]
+pub fn param_hold() -> u32 {
+    crate::nodes::params::HOLD
+}
+
+/// Envelope velocity sensitivity parameter ID (0 = none, 1 = full).
+#[wasm_bindgen]
+pub fn param_velocity() -> u32 {
+    crate::nodes::params::VELOCITY
+}
+
+/// Filter envelope amount parameter ID, in semitones at full level.
+#[wasm_bindgen]
+pub fn param_env_amount() -> u32 {
+    crate::nodes::params::ENV_AMOUNT
+}
+
 /// Gain parameter ID.
 #[wasm_bindgen]
 pub fn param_gain() -> u32 {
@@ -864,6 +1419,12 @@ pub fn param_pan() -> u32 {
     crate::nodes::params::PAN
 }
 
+/// Mixer auto gain toggle parameter ID.
+#[wasm_bindgen]
+pub fn param_auto_gain() -> u32 {
+    crate::nodes::params::AUTO_GAIN
+}
+
 /// Cutoff parameter ID.
 #[wasm_bindgen]
 pub fn param_cutoff() -> u32 {
@@ -876,6 +1437,12 @@ pub fn param_resonance() -> u32 {
     crate::nodes::params::RESONANCE
 }
 
+/// Filter keyboard tracking parameter ID.
+#[wasm_bindgen]
+pub fn param_keytrack() -> u32 {
+    crate::nodes::params::KEYTRACK
+}
+
 /// Rate parameter ID.
 #[wasm_bindgen]
 pub fn param_rate() -> u32 {
@@ -888,6 +1455,18 @@ pub fn param_depth() -> u32 {
     crate::nodes::params::DEPTH
 }
 
+/// LFO phase offset parameter ID.
+#[wasm_bindgen]
+pub fn param_phase_offset() -> u32 {
+    crate::nodes::params::PHASE_OFFSET
+}
+
+/// LFO retrigger toggle parameter ID.
+#[wasm_bindgen]
+pub fn param_retrigger() -> u32 {
+    crate::nodes::params::RETRIGGER
+}
+
 /// Time parameter ID.
 #[wasm_bindgen]
 pub fn param_time() -> u32 {
@@ -906,8 +1485,202 @@ pub fn param_mix() -> u32 {
     crate::nodes::params::MIX
 }
 
+/// Delay tempo sync toggle parameter ID.
+#[wasm_bindgen]
+pub fn param_sync_on() -> u32 {
+    crate::nodes::params::SYNC_ON
+}
+
+/// Delay sync division parameter ID.
+#[wasm_bindgen]
+pub fn param_sync_div() -> u32 {
+    crate::nodes::params::SYNC_DIV
+}
+
+/// Delay ping-pong toggle parameter ID.
+#[wasm_bindgen]
+pub fn param_pingpong() -> u32 {
+    crate::nodes::params::PINGPONG
+}
+
+/// Delay feedback damping parameter ID.
+#[wasm_bindgen]
+pub fn param_delay_damping() -> u32 {
+    crate::nodes::params::DELAY_DAMPING
+}
+
 /// Damping parameter ID.
 #[wasm_bindgen]
 pub fn param_damping() -> u32 {
     crate::nodes::params::DAMPING
 }
+
+/// Reverb pre-delay parameter ID.
+#[wasm_bindgen]
+pub fn param_predelay() -> u32 {
+    crate::nodes::params::PREDELAY
+}
+
+/// Reverb stereo width parameter ID.
+#[wasm_bindgen]
+pub fn param_width() -> u32 {
+    crate::nodes::params::WIDTH
+}
+
+/// Reverb freeze toggle parameter ID.
+#[wasm_bindgen]
+pub fn param_freeze() -> u32 {
+    crate::nodes::params::FREEZE
+}
+
+/// Distortion drive parameter ID.
+#[wasm_bindgen]
+pub fn param_drive() -> u32 {
+    crate::nodes::params::DRIVE
+}
+
+/// Distortion shape parameter ID.
+#[wasm_bindgen]
+pub fn param_shape() -> u32 {
+    crate::nodes::params::SHAPE
+}
+
+/// Limiter ceiling parameter ID.
+#[wasm_bindgen]
+pub fn param_ceiling() -> u32 {
+    crate::nodes::params::CEILING
+}
+
+/// Flanger feedback parameter ID.
+#[wasm_bindgen]
+pub fn param_flanger_feedback() -> u32 {
+    crate::nodes::params::FLANGER_FEEDBACK
+}
+
+/// Ring modulator mode parameter ID (0 = ring, 1 = AM).
+#[wasm_bindgen]
+pub fn param_ringmod_mode() -> u32 {
+    crate::nodes::params::RINGMOD_MODE
+}
+
+/// Slew limiter rise rate parameter ID (units per second).
+#[wasm_bindgen]
+pub fn param_rise() -> u32 {
+    crate::nodes::params::RISE
+}
+
+/// Slew limiter fall rate parameter ID (units per second).
+#[wasm_bindgen]
+pub fn param_fall() -> u32 {
+    crate::nodes::params::FALL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::{node_types, params};
+
+    /// A session and an engine linked to it (`create_engine` makes a fresh
+    /// bridge, so notes sent from the session wouldn't reach it).
+    fn linked_pair() -> (HyasynthSession, HyasynthEngine) {
+        let config = HyasynthConfig::default();
+        let mut graph = Graph::new(config.max_block_size as usize, config.max_voices as usize);
+        graph.prepare(config.sample_rate);
+        let engine = Engine::new(graph, VoiceAllocator::new(config.max_voices as usize));
+        let (session_handle, engine_handle) = create_bridge(Session::new("Mono"), engine);
+
+        let session = HyasynthSession {
+            inner: session_handle,
+            config,
+        };
+        let engine = HyasynthEngine {
+            inner: engine_handle,
+            scheduler: Scheduler::new(config.sample_rate),
+            handoff: PlanHandoff::new(
+                ExecutionPlan::new(config.sample_rate),
+                ExecutionPlan::new(config.sample_rate),
+            ),
+        };
+        (session, engine)
+    }
+
+    #[test]
+    fn test_render_mono_averages_stereo() {
+        let registry = HyasynthRegistry::new();
+
+        // Two identical setups, one per render path: a sine panned right
+        let setup = || {
+            let (mut session, mut engine) = linked_pair();
+            let osc = session.add_node(node_types::SINE_OSC, 0.0, 0.0);
+            let pan = session.add_node(node_types::PAN, 0.0, 0.0);
+            let out = session.add_node(node_types::OUTPUT, 0.0, 0.0);
+            session.connect(osc, 0, pan, 0);
+            session.connect(pan, 0, out, 0);
+            session.set_output(out);
+            session.set_param(pan, params::PAN, 0.6);
+            assert!(engine.compile_graph(&session, &registry, DEFAULT_SAMPLE_RATE));
+            session.note_on(69, 1.0);
+            engine
+        };
+        let mut stereo_engine = setup();
+        let mut mono_engine = setup();
+
+        // Spans several blocks, ending on a partial one
+        let frames = 1_200;
+        let mut stereo = vec![0.0f32; frames * 2];
+        let mut mono = vec![0.0f32; frames];
+        stereo_engine.render(frames as u32, &mut stereo);
+        mono_engine.render_mono(frames as u32, &mut mono);
+
+        // Panned off center, so the channels differ
+        assert!(stereo.chunks_exact(2).any(|f| (f[0] - f[1]).abs() > 0.1));
+        for (frame, m) in stereo.chunks_exact(2).zip(&mono) {
+            assert!((m - (frame[0] + frame[1]) * 0.5).abs() < 1e-6);
+        }
+
+        // Short buffers are silenced rather than partially written
+        let mut short = vec![1.0f32; frames - 1];
+        mono_engine.render_mono(frames as u32, &mut short);
+        assert!(short.iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_node_types_json_lists_lowpass() {
+        let registry = HyasynthRegistry::new();
+        let json: serde_json::Value = serde_json::from_str(&registry.node_types_json()).unwrap();
+        let types = json.as_array().unwrap();
+        assert_eq!(types.len(), registry.count() as usize);
+
+        let lowpass = types
+            .iter()
+            .find(|t| t["type_id"] == node_types::LOWPASS)
+            .expect("lowpass not listed");
+        assert_eq!(lowpass["name"], "Lowpass");
+        assert_eq!(lowpass["category"], "Filters");
+        assert_eq!(lowpass["num_outputs"], 1);
+
+        let cutoff = lowpass["params"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["id"] == params::CUTOFF)
+            .expect("cutoff not listed");
+        assert_eq!(cutoff["min"], 20.0);
+        assert_eq!(cutoff["max"], 20_000.0);
+        assert_eq!(cutoff["unit"], "Hz");
+    }
+
+    #[test]
+    fn test_get_connections_is_flat() {
+        let (mut session, _engine) = linked_pair();
+        let osc = session.add_node(node_types::SAW_OSC, 0.0, 0.0);
+        let filter = session.add_node(node_types::LOWPASS, 0.0, 0.0);
+        let out = session.add_node(node_types::OUTPUT, 0.0, 0.0);
+        session.connect(osc, 0, filter, 0);
+        session.connect(filter, 0, out, 0);
+
+        assert_eq!(session.get_connections(), [osc, 0, filter, 0, filter, 0, out, 0]);
+        assert_eq!(session.get_node_type(filter), node_types::LOWPASS);
+        assert_eq!(session.get_node_type(u32::MAX), u32::MAX);
+    }
+}
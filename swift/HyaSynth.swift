@@ -1,6 +1,7 @@
 // Swift wrapper for the Hyasynth audio engine.
 // This provides a more ergonomic Swift API over the C FFI.
 
+import Foundation
 import HyasynthC
 
 // MARK: - Node Types
@@ -14,18 +15,27 @@ public enum NodeType: UInt32 {
     
     // Envelopes
     case adsrEnv = 10
+    case dahdsrEnv = 11
     
     // Effects
     case gain = 20
     case pan = 21
     case delay = 23
     case reverb = 24
+    case distortion = 25
+    case limiter = 26
+    case eq = 27
+    case flanger = 28
+    case width = 29
     
     // Filters
     case lowpass = 40
     case highpass = 41
     case bandpass = 42
     case notch = 43
+    case ladder = 44
+    case allpass = 45
+    case comb = 46
     
     // Modulators
     case lfo = 50
@@ -47,6 +57,9 @@ public enum EnvelopeParam: UInt32 {
     case decay = 1
     case sustain = 2
     case release = 3
+    case delay = 4
+    case hold = 5
+    case curve = 6
 }
 
 public enum GainParam: UInt32 {
@@ -57,24 +70,87 @@ public enum GainParam: UInt32 {
 public enum FilterParam: UInt32 {
     case cutoff = 0
     case resonance = 1
+    case keytrack = 2
+}
+
+public enum VoiceStealMode: UInt32 {
+    case oldest = 0
+    case quietest = 1
+    case lowestNote = 2
+    case skipNewNote = 3
+}
+
+public enum LaunchQuantization: UInt32 {
+    case off = 0
+    case beat = 1
+    case halfBar = 2
+    case bar = 3
+    case twoBars = 4
+    case fourBars = 5
 }
 
 public enum LfoParam: UInt32 {
     case rate = 0
     case depth = 1
     case waveform = 2
+    case retrigger = 5
+    case phaseOffset = 6
 }
 
 public enum DelayParam: UInt32 {
     case time = 0
     case feedback = 1
     case mix = 2
+    case syncOn = 3
+    case syncDiv = 4
+    case pingPong = 5
 }
 
 public enum ReverbParam: UInt32 {
     case decay = 0
     case damping = 1
     case mix = 2
+    case preDelay = 3
+    case width = 4
+    case freeze = 5
+}
+
+public enum FlangerParam: UInt32 {
+    case rate = 0
+    case depth = 1
+    case mix = 2
+    case feedback = 3
+    case width = 4
+}
+
+public enum StereoWidthParam: UInt32 {
+    case width = 4
+}
+
+public enum DistortionParam: UInt32 {
+    case drive = 0
+    case shape = 1
+    case mix = 2
+}
+
+public enum LimiterParam: UInt32 {
+    case ceiling = 0
+    case release = 3
+}
+
+public enum EqParam: UInt32 {
+    case lowFreq = 0
+    case lowGain = 1
+    case lowQ = 2
+    case mid1Freq = 3
+    case mid1Gain = 4
+    case mid1Q = 5
+    case mid2Freq = 6
+    case mid2Gain = 7
+    case mid2Q = 8
+    case highFreq = 9
+    case highGain = 10
+    case highQ = 11
 }
 
 // MARK: - Configuration
@@ -151,6 +227,23 @@ public final class HyasynthSession {
         engineHandle = engine
     }
     
+    /// Load a session saved with `serialize()`.
+    ///
+    /// Returns nil if the data isn't a valid project.
+    public init?(data: Data, configuration: HyasynthConfiguration = .default) {
+        self.configuration = configuration
+        var engine: OpaquePointer?
+        var config = configuration.cConfig
+        let handle = data.withUnsafeBytes { buffer in
+            session_deserialize(
+                buffer.bindMemory(to: UInt8.self).baseAddress, buffer.count, &config, &engine
+            )
+        }
+        guard let handle else { return nil }
+        sessionHandle = handle
+        engineHandle = engine
+    }
+    
     deinit {
         if let engine = engineHandle {
             engine_destroy(engine)
@@ -160,6 +253,18 @@ public final class HyasynthSession {
         }
     }
     
+    // MARK: - Persistence
+    
+    /// Serialize the session for saving; load it again with `init?(data:)`.
+    public func serialize() -> Data? {
+        guard let h = sessionHandle else { return nil }
+        var ptr: UnsafeMutablePointer<UInt8>?
+        var len = 0
+        guard session_serialize(h, &ptr, &len), let ptr else { return nil }
+        defer { hya_bytes_free(ptr, len) }
+        return Data(bytes: ptr, count: len)
+    }
+    
     // MARK: - Graph Mutations
     
     @discardableResult
@@ -168,26 +273,45 @@ public final class HyasynthSession {
         return session_add_node(h, type.rawValue, position.x, position.y)
     }
     
-    public func removeNode(_ nodeId: UInt32) {
-        guard let h = sessionHandle else { return }
-        session_remove_node(h, nodeId)
+    @discardableResult
+    public func removeNode(_ nodeId: UInt32) -> HyaResult {
+        guard let h = sessionHandle else { return HYA_RESULT_NULL_HANDLE }
+        return session_remove_node(h, nodeId)
     }
     
+    /// Connect two nodes. Pass a registry to have the ports validated.
+    @discardableResult
     public func connect(from sourceNode: UInt32, port sourcePort: UInt32 = 0,
-                        to destNode: UInt32, port destPort: UInt32 = 0) {
-        guard let h = sessionHandle else { return }
-        session_connect(h, sourceNode, sourcePort, destNode, destPort)
+                        to destNode: UInt32, port destPort: UInt32 = 0,
+                        registry: HyasynthRegistry? = nil) -> HyaResult {
+        guard let h = sessionHandle else { return HYA_RESULT_NULL_HANDLE }
+        return session_connect(h, registry?.unsafeHandle, sourceNode, sourcePort, destNode, destPort)
     }
     
+    @discardableResult
     public func disconnect(from sourceNode: UInt32, port sourcePort: UInt32 = 0,
-                           to destNode: UInt32, port destPort: UInt32 = 0) {
-        guard let h = sessionHandle else { return }
-        session_disconnect(h, sourceNode, sourcePort, destNode, destPort)
+                           to destNode: UInt32, port destPort: UInt32 = 0) -> HyaResult {
+        guard let h = sessionHandle else { return HYA_RESULT_NULL_HANDLE }
+        return session_disconnect(h, sourceNode, sourcePort, destNode, destPort)
+    }
+    
+    @discardableResult
+    public func addModRoute(from sourceNode: UInt32, to destNode: UInt32,
+                            param paramId: UInt32, depth: Float) -> HyaResult {
+        guard let h = sessionHandle else { return HYA_RESULT_NULL_HANDLE }
+        return session_add_mod_route(h, sourceNode, destNode, paramId, depth)
     }
     
-    public func setOutputNode(_ nodeId: UInt32) {
+    public func removeModRoute(from sourceNode: UInt32, to destNode: UInt32,
+                               param paramId: UInt32) {
         guard let h = sessionHandle else { return }
-        session_set_output(h, nodeId)
+        session_remove_mod_route(h, sourceNode, destNode, paramId)
+    }
+    
+    @discardableResult
+    public func setOutputNode(_ nodeId: UInt32) -> HyaResult {
+        guard let h = sessionHandle else { return HYA_RESULT_NULL_HANDLE }
+        return session_set_output(h, nodeId)
     }
     
     public func clearGraph() {
@@ -202,6 +326,19 @@ public final class HyasynthSession {
         session_set_param(h, nodeId, param, value)
     }
     
+    /// Current value of a parameter, or the registry default if never set.
+    /// Returns nil if the node or parameter doesn't exist.
+    public func getParam(_ nodeId: UInt32, param: UInt32, registry: HyasynthRegistry) -> Float? {
+        guard let h = sessionHandle else { return nil }
+        let value = session_get_param(h, registry.unsafeHandle, nodeId, param)
+        return value.isNaN ? nil : value
+    }
+    
+    public func setVelocitySensitivity(_ nodeId: UInt32, param: UInt32, amount: Float) {
+        guard let h = sessionHandle else { return }
+        session_set_velocity_sensitivity(h, nodeId, param, amount)
+    }
+    
     public func setOscParam(_ nodeId: UInt32, _ param: OscParam, value: Float) {
         setParam(nodeId, param: param.rawValue, value: value)
     }
@@ -252,11 +389,26 @@ public final class HyasynthSession {
         }
     }
     
+    public func rampTempo(to targetBpm: Double, overBeats: Double) {
+        guard let h = sessionHandle else { return }
+        session_ramp_tempo(h, targetBpm, overBeats)
+    }
+    
     public func seek(toBeat beat: Double) {
         guard let h = sessionHandle else { return }
         session_seek(h, beat)
     }
     
+    public func setLoop(enabled: Bool, startBeat: Double, endBeat: Double) {
+        guard let h = sessionHandle else { return }
+        session_set_loop(h, enabled, startBeat, endBeat)
+    }
+    
+    public func setMetronome(enabled: Bool, beatsPerBar: UInt32 = 4) {
+        guard let h = sessionHandle else { return }
+        session_set_metronome(h, enabled, beatsPerBar)
+    }
+    
     // MARK: - MIDI
     
     public func noteOn(_ note: UInt8, velocity: Float = 0.8) {
@@ -269,6 +421,52 @@ public final class HyasynthSession {
         session_note_off(h, note)
     }
     
+    public func pitchBend(_ semitones: Float) {
+        guard let h = sessionHandle else { return }
+        session_pitch_bend(h, semitones)
+    }
+    
+    public func setMono(_ mono: Bool) {
+        guard let h = sessionHandle else { return }
+        session_set_mono(h, mono)
+    }
+    
+    public func setGlideTime(_ seconds: Float) {
+        guard let h = sessionHandle else { return }
+        session_set_glide_time(h, seconds)
+    }
+    
+    public func setVoiceStealMode(_ mode: VoiceStealMode) {
+        guard let h = sessionHandle else { return }
+        session_set_voice_steal_mode(h, mode.rawValue)
+    }
+    
+    public func setUnison(voices: UInt32, detuneCents: Float, spread: Float) {
+        guard let h = sessionHandle else { return }
+        session_set_unison(h, voices, detuneCents, spread)
+    }
+    
+    public func setVoicePanSpread(_ spread: Float) {
+        guard let h = sessionHandle else { return }
+        session_set_voice_pan_spread(h, spread)
+    }
+    
+    public func controlChange(_ cc: UInt8, value: Float) {
+        guard let h = sessionHandle else { return }
+        session_control_change(h, cc, value)
+    }
+    
+    @discardableResult
+    public func mapControlChange(_ cc: UInt8, registry: HyasynthRegistry, nodeId: UInt32, paramId: UInt32) -> Bool {
+        guard let h = sessionHandle, let reg = registry.unsafeHandle else { return false }
+        return session_map_control_change(h, reg, cc, nodeId, paramId)
+    }
+    
+    public func unmapControlChange(_ cc: UInt8) {
+        guard let h = sessionHandle else { return }
+        session_unmap_control_change(h, cc)
+    }
+    
     // MARK: - Readback
     
     public var engineState: EngineState {
@@ -463,6 +661,16 @@ public final class HyasynthSession {
         session_stop_all_clips(h)
     }
     
+    public func setLaunchQuantization(_ quantization: LaunchQuantization) {
+        guard let h = sessionHandle else { return }
+        session_set_launch_quantization(h, quantization.rawValue)
+    }
+    
+    public func setSwing(_ amount: Float) {
+        guard let h = sessionHandle else { return }
+        session_set_swing(h, amount)
+    }
+    
     public var sceneCount: UInt32 {
         guard let h = sessionHandle else { return 0 }
         return session_get_scene_count(h)
@@ -524,6 +732,69 @@ public final class HyasynthRegistry {
         return registry_count(h)
     }
 
+    /// Metadata for one node type.
+    public struct NodeType {
+        public let typeId: UInt32
+        public let name: String
+        public let category: String
+        public let numInputs: UInt32
+        public let numOutputs: UInt32
+        public let numParams: UInt32
+    }
+
+    /// Metadata for one node parameter.
+    public struct Param {
+        public let id: UInt32
+        public let name: String
+        public let minValue: Float
+        public let maxValue: Float
+        public let defaultValue: Float
+    }
+
+    /// All node types, ordered by type ID.
+    public var nodeTypes: [NodeType] {
+        (0..<count).compactMap { nodeType(at: $0) }
+    }
+
+    /// Node type at `index` (0..<count), ordered by type ID.
+    public func nodeType(at index: UInt32) -> NodeType? {
+        guard let h = handle else { return nil }
+        var info = HyaNodeTypeInfo()
+        guard registry_get_node_type(h, index, &info) else { return nil }
+        defer {
+            hya_string_free(info.name)
+            hya_string_free(info.category)
+        }
+        return NodeType(
+            typeId: info.type_id,
+            name: String(cString: info.name),
+            category: String(cString: info.category),
+            numInputs: info.num_inputs,
+            numOutputs: info.num_outputs,
+            numParams: info.num_params
+        )
+    }
+
+    /// Parameters of a node type, in display order.
+    public func params(of typeId: UInt32) -> [Param] {
+        guard let h = handle else { return [] }
+        var params: [Param] = []
+        var info = HyaParamInfo()
+        var index: UInt32 = 0
+        while registry_get_param(h, typeId, index, &info) {
+            params.append(Param(
+                id: info.id,
+                name: String(cString: info.name),
+                minValue: info.min_value,
+                maxValue: info.max_value,
+                defaultValue: info.default_value
+            ))
+            hya_string_free(info.name)
+            index += 1
+        }
+        return params
+    }
+
     internal var unsafeHandle: OpaquePointer? { handle }
 }
 